@@ -4,15 +4,58 @@ macro_rules! __fourcc_code {
     )
 }
 
+pub const DRM_FORMAT_BIG_ENDIAN	:u32 = 1 << 31; /* format is big endian instead of little endian */
+
+/* Reserved, used where no format was specified */
+pub const DRM_FORMAT_INVALID	:u32 = 0;
+
 /* color index */
+pub const DRM_FORMAT_C1	:u32 = __fourcc_code!('C', '1', ' ', ' '); /* [7:0] C0:C1:C2:C3:C4:C5:C6:C7 1:1:1:1:1:1:1:1 eight pixels/byte */
+pub const DRM_FORMAT_C2	:u32 = __fourcc_code!('C', '2', ' ', ' '); /* [7:0] C0:C1:C2:C3 2:2:2:2 four pixels/byte */
+pub const DRM_FORMAT_C4	:u32 = __fourcc_code!('C', '4', ' ', ' '); /* [7:0] C0:C1 4:4 two pixels/byte */
 pub const DRM_FORMAT_C8 	:u32 = __fourcc_code!('C', '8', ' ', ' '); /* [7:0] C */
 
+/* 1 bpp Darkness (inverse relationship between channel value and brightness) */
+pub const DRM_FORMAT_D1	:u32 = __fourcc_code!('D', '1', ' ', ' '); /* [7:0] D0:D1:D2:D3:D4:D5:D6:D7 1:1:1:1:1:1:1:1 eight pixels/byte */
+
+/* 2 bpp Darkness (inverse relationship between channel value and brightness) */
+pub const DRM_FORMAT_D2	:u32 = __fourcc_code!('D', '2', ' ', ' '); /* [7:0] D0:D1:D2:D3 2:2:2:2 four pixels/byte */
+
+/* 4 bpp Darkness (inverse relationship between channel value and brightness) */
+pub const DRM_FORMAT_D4	:u32 = __fourcc_code!('D', '4', ' ', ' '); /* [7:0] D0:D1 4:4 two pixels/byte */
+
+/* 8 bpp Darkness (inverse relationship between channel value and brightness) */
+pub const DRM_FORMAT_D8	:u32 = __fourcc_code!('D', '8', ' ', ' '); /* [7:0] D */
+
+/* 1 bpp Red (direct relationship between channel value and brightness) */
+pub const DRM_FORMAT_R1	:u32 = __fourcc_code!('R', '1', ' ', ' '); /* [7:0] R0:R1:R2:R3:R4:R5:R6:R7 1:1:1:1:1:1:1:1 eight pixels/byte */
+
+/* 2 bpp Red (direct relationship between channel value and brightness) */
+pub const DRM_FORMAT_R2	:u32 = __fourcc_code!('R', '2', ' ', ' '); /* [7:0] R0:R1:R2:R3 2:2:2:2 four pixels/byte */
+
+/* 4 bpp Red (direct relationship between channel value and brightness) */
+pub const DRM_FORMAT_R4	:u32 = __fourcc_code!('R', '4', ' ', ' '); /* [7:0] R0:R1 4:4 two pixels/byte */
+
 /* 8 bpp Red */
 pub const DRM_FORMAT_R8		:u32 = __fourcc_code!('R', '8', ' ', ' '); /* [7:0] R */
 
+/* 10 bpp Red (direct relationship between channel value and brightness) */
+pub const DRM_FORMAT_R10	:u32 = __fourcc_code!('R', '1', '0', ' '); /* [15:0] x:R 6:10 little endian */
+
+/* 12 bpp Red (direct relationship between channel value and brightness) */
+pub const DRM_FORMAT_R12	:u32 = __fourcc_code!('R', '1', '2', ' '); /* [15:0] x:R 4:12 little endian */
+
+/* 16 bpp Red */
+pub const DRM_FORMAT_R16	:u32 = __fourcc_code!('R', '1', '6', ' '); /* [15:0] R little endian */
+
 /* 16 bpp RG */
+pub const DRM_FORMAT_RG88		:u32 = __fourcc_code!('R', 'G', '8', '8'); /* [15:0] R:G 8:8 little endian */
 pub const DRM_FORMAT_GR88		:u32 = __fourcc_code!('G', 'R', '8', '8'); /* [15:0] G:R 8:8 little endian */
 
+/* 32 bpp RG */
+pub const DRM_FORMAT_RG1616	:u32 = __fourcc_code!('R', 'G', '3', '2'); /* [31:0] R:G 16:16 little endian */
+pub const DRM_FORMAT_GR1616	:u32 = __fourcc_code!('G', 'R', '3', '2'); /* [31:0] G:R 16:16 little endian */
+
 /* 8 bpp RGB */
 pub const DRM_FORMAT_RGB332	:u32 = __fourcc_code!('R', 'G', 'B', '8'); /* [7:0] R:G:B 3:3:2 */
 pub const DRM_FORMAT_BGR233	:u32 = __fourcc_code!('B', 'G', 'R', '8'); /* [7:0] B:G:R 2:3:3 */
@@ -66,6 +109,30 @@ pub const DRM_FORMAT_ABGR2101010	:u32 = __fourcc_code!('A', 'B', '3', '0'); /* [
 pub const DRM_FORMAT_RGBA1010102	:u32 = __fourcc_code!('R', 'A', '3', '0'); /* [31:0] R:G:B:A 10:10:10:2 little endian */
 pub const DRM_FORMAT_BGRA1010102	:u32 = __fourcc_code!('B', 'A', '3', '0'); /* [31:0] B:G:R:A 10:10:10:2 little endian */
 
+/*
+ * Floating point 64bpp RGB
+ * IEEE 754-2008 binary16 half-precision float
+ * [15:0] sign:exponent:mantissa 1:5:10
+ */
+pub const DRM_FORMAT_XRGB16161616F	:u32 = __fourcc_code!('X', 'R', '4', 'H'); /* [63:0] x:R:G:B 16:16:16:16 little endian */
+pub const DRM_FORMAT_XBGR16161616F	:u32 = __fourcc_code!('X', 'B', '4', 'H'); /* [63:0] x:B:G:R 16:16:16:16 little endian */
+
+pub const DRM_FORMAT_ARGB16161616F	:u32 = __fourcc_code!('A', 'R', '4', 'H'); /* [63:0] A:R:G:B 16:16:16:16 little endian */
+pub const DRM_FORMAT_ABGR16161616F	:u32 = __fourcc_code!('A', 'B', '4', 'H'); /* [63:0] A:B:G:R 16:16:16:16 little endian */
+
+/* 64 bpp RGB */
+pub const DRM_FORMAT_XRGB16161616	:u32 = __fourcc_code!('X', 'R', '4', '8'); /* [63:0] x:R:G:B 16:16:16:16 little endian */
+pub const DRM_FORMAT_XBGR16161616	:u32 = __fourcc_code!('X', 'B', '4', '8'); /* [63:0] x:B:G:R 16:16:16:16 little endian */
+
+pub const DRM_FORMAT_ARGB16161616	:u32 = __fourcc_code!('A', 'R', '4', '8'); /* [63:0] A:R:G:B 16:16:16:16 little endian */
+pub const DRM_FORMAT_ABGR16161616	:u32 = __fourcc_code!('A', 'B', '4', '8'); /* [63:0] A:B:G:R 16:16:16:16 little endian */
+
+/*
+ * RGBA format with 10-bit components packed in 64-bit per pixel, with 6 bits
+ * of unused padding per component:
+ */
+pub const DRM_FORMAT_AXBXGXRX106106106106	:u32 = __fourcc_code!('A', 'B', '1', '0'); /* [63:0] A:x:B:x:G:x:R:x 10:6:10:6:10:6:10:6 little endian */
+
 /* packed YCbCr */
 pub const DRM_FORMAT_YUYV		:u32 = __fourcc_code!('Y', 'U', 'Y', 'V'); /* [31:0] Cr0:Y1:Cb0:Y0 8:8:8:8 little endian */
 pub const DRM_FORMAT_YVYU		:u32 = __fourcc_code!('Y', 'V', 'Y', 'U'); /* [31:0] Cb0:Y1:Cr0:Y0 8:8:8:8 little endian */
@@ -73,3 +140,356 @@ pub const DRM_FORMAT_UYVY		:u32 = __fourcc_code!('U', 'Y', 'V', 'Y'); /* [31:0]
 pub const DRM_FORMAT_VYUY		:u32 = __fourcc_code!('V', 'Y', 'U', 'Y'); /* [31:0] Y1:Cb0:Y0:Cr0 8:8:8:8 little endian */
 
 pub const DRM_FORMAT_AYUV		:u32 = __fourcc_code!('A', 'Y', 'U', 'V'); /* [31:0] A:Y:Cb:Cr 8:8:8:8 little endian */
+pub const DRM_FORMAT_AVUY8888	:u32 = __fourcc_code!('A', 'V', 'U', 'Y'); /* [31:0] A:Cr:Cb:Y 8:8:8:8 little endian */
+pub const DRM_FORMAT_XYUV8888	:u32 = __fourcc_code!('X', 'Y', 'U', 'V'); /* [31:0] X:Y:Cb:Cr 8:8:8:8 little endian */
+pub const DRM_FORMAT_XVUY8888	:u32 = __fourcc_code!('X', 'V', 'U', 'Y'); /* [31:0] X:Cr:Cb:Y 8:8:8:8 little endian */
+pub const DRM_FORMAT_VUY888	:u32 = __fourcc_code!('V', 'U', '2', '4'); /* [23:0] Cr:Cb:Y 8:8:8 little endian */
+pub const DRM_FORMAT_VUY101010	:u32 = __fourcc_code!('V', 'U', '3', '0'); /* Y followed by U then V, 10:10:10. Non-linear modifier only */
+
+/*
+ * packed Y2xx indicate for each component, xx valid data occupy msb
+ * 16-xx padding occupy lsb
+ */
+pub const DRM_FORMAT_Y210	:u32 = __fourcc_code!('Y', '2', '1', '0'); /* [63:0] Cr0:0:Y1:0:Cb0:0:Y0:0 10:6:10:6:10:6:10:6 little endian per 2 Y pixels */
+pub const DRM_FORMAT_Y212	:u32 = __fourcc_code!('Y', '2', '1', '2'); /* [63:0] Cr0:0:Y1:0:Cb0:0:Y0:0 12:4:12:4:12:4:12:4 little endian per 2 Y pixels */
+pub const DRM_FORMAT_Y216	:u32 = __fourcc_code!('Y', '2', '1', '6'); /* [63:0] Cr0:Y1:Cb0:Y0 16:16:16:16 little endian per 2 Y pixels */
+
+/*
+ * packed Y4xx indicate for each component, xx valid data occupy msb
+ * 16-xx padding occupy lsb except Y410
+ */
+pub const DRM_FORMAT_Y410	:u32 = __fourcc_code!('Y', '4', '1', '0'); /* [31:0] A:Cr:Y:Cb 2:10:10:10 little endian */
+pub const DRM_FORMAT_Y412	:u32 = __fourcc_code!('Y', '4', '1', '2'); /* [63:0] A:0:Cr:0:Y:0:Cb:0 12:4:12:4:12:4:12:4 little endian */
+pub const DRM_FORMAT_Y416	:u32 = __fourcc_code!('Y', '4', '1', '6'); /* [63:0] A:Cr:Y:Cb 16:16:16:16 little endian */
+
+pub const DRM_FORMAT_XVYU2101010	:u32 = __fourcc_code!('X', 'V', '3', '0'); /* [31:0] X:Cr:Y:Cb 2:10:10:10 little endian */
+pub const DRM_FORMAT_XVYU12_16161616	:u32 = __fourcc_code!('X', 'V', '3', '6'); /* [63:0] X:0:Cr:0:Y:0:Cb:0 12:4:12:4:12:4:12:4 little endian */
+pub const DRM_FORMAT_XVYU16161616	:u32 = __fourcc_code!('X', 'V', '4', '8'); /* [63:0] X:Cr:Y:Cb 16:16:16:16 little endian */
+
+/*
+ * packed YCbCr420 2x2 tiled formats
+ * first 64 bits will contain Y,Cb,Cr components for a 2x2 tile
+ */
+pub const DRM_FORMAT_Y0L0	:u32 = __fourcc_code!('Y', '0', 'L', '0'); /* [63:0]   A3:A2:Y3:0:Cr0:0:Y2:0:A1:A0:Y1:0:Cb0:0:Y0:0  1:1:8:2:8:2:8:2:1:1:8:2:8:2:8:2 little endian */
+pub const DRM_FORMAT_X0L0	:u32 = __fourcc_code!('X', '0', 'L', '0'); /* [63:0]   X3:X2:Y3:0:Cr0:0:Y2:0:X1:X0:Y1:0:Cb0:0:Y0:0  1:1:8:2:8:2:8:2:1:1:8:2:8:2:8:2 little endian */
+pub const DRM_FORMAT_Y0L2	:u32 = __fourcc_code!('Y', '0', 'L', '2'); /* [63:0]   A3:A2:Y3:Cr0:Y2:A1:A0:Y1:Cb0:Y0  1:1:10:10:10:1:1:10:10:10 little endian */
+pub const DRM_FORMAT_X0L2	:u32 = __fourcc_code!('X', '0', 'L', '2'); /* [63:0]   X3:X2:Y3:Cr0:Y2:X1:X0:Y1:Cb0:Y0  1:1:10:10:10:1:1:10:10:10 little endian */
+
+/*
+ * 1-plane YUV 4:2:0
+ * In these formats, the component ordering is specified (Y, followed by U
+ * then V), but the exact Linear layout is undefined.
+ * These formats can only be used with a non-Linear modifier.
+ */
+pub const DRM_FORMAT_YUV420_8BIT	:u32 = __fourcc_code!('Y', 'U', '0', '8');
+pub const DRM_FORMAT_YUV420_10BIT	:u32 = __fourcc_code!('Y', 'U', '1', '0');
+
+/*
+ * 2 plane RGB + A
+ * index 0 = RGB plane, same format as the corresponding non _A8 format has
+ * index 1 = A plane, [7:0] A
+ */
+pub const DRM_FORMAT_XRGB8888_A8	:u32 = __fourcc_code!('X', 'R', 'A', '8');
+pub const DRM_FORMAT_XBGR8888_A8	:u32 = __fourcc_code!('X', 'B', 'A', '8');
+pub const DRM_FORMAT_RGBX8888_A8	:u32 = __fourcc_code!('R', 'X', 'A', '8');
+pub const DRM_FORMAT_BGRX8888_A8	:u32 = __fourcc_code!('B', 'X', 'A', '8');
+pub const DRM_FORMAT_RGB888_A8	:u32 = __fourcc_code!('R', '8', 'A', '8');
+pub const DRM_FORMAT_BGR888_A8	:u32 = __fourcc_code!('B', '8', 'A', '8');
+pub const DRM_FORMAT_RGB565_A8	:u32 = __fourcc_code!('R', '5', 'A', '8');
+pub const DRM_FORMAT_BGR565_A8	:u32 = __fourcc_code!('B', '5', 'A', '8');
+
+/*
+ * 2 plane YCbCr
+ * index 0 = Y plane, [7:0] Y
+ * index 1 = Cr:Cb plane, [15:0] Cr:Cb little endian
+ * or
+ * index 1 = Cb:Cr plane, [15:0] Cb:Cr little endian
+ */
+pub const DRM_FORMAT_NV12	:u32 = __fourcc_code!('N', 'V', '1', '2'); /* 2x2 subsampled Cr:Cb plane */
+pub const DRM_FORMAT_NV21	:u32 = __fourcc_code!('N', 'V', '2', '1'); /* 2x2 subsampled Cb:Cr plane */
+pub const DRM_FORMAT_NV16	:u32 = __fourcc_code!('N', 'V', '1', '6'); /* 2x1 subsampled Cr:Cb plane */
+pub const DRM_FORMAT_NV61	:u32 = __fourcc_code!('N', 'V', '6', '1'); /* 2x1 subsampled Cb:Cr plane */
+pub const DRM_FORMAT_NV24	:u32 = __fourcc_code!('N', 'V', '2', '4'); /* non-subsampled Cr:Cb plane */
+pub const DRM_FORMAT_NV42	:u32 = __fourcc_code!('N', 'V', '4', '2'); /* non-subsampled Cb:Cr plane */
+pub const DRM_FORMAT_NV15	:u32 = __fourcc_code!('N', 'V', '1', '5'); /* 2x2 subsampled Cr:Cb plane, 10 bit per component */
+pub const DRM_FORMAT_NV20	:u32 = __fourcc_code!('N', 'V', '2', '0'); /* 2x1 subsampled Cr:Cb plane, 10 bit per component */
+pub const DRM_FORMAT_NV30	:u32 = __fourcc_code!('N', 'V', '3', '0'); /* non-subsampled Cr:Cb plane, 10 bit per component */
+
+/*
+ * 2 plane YCbCr MSB aligned
+ * index 0 = Y plane, [15:0] Y:x [10:6] little endian
+ * index 1 = Cr:Cb plane, [31:0] Cr:x:Cb:x [10:6:10:6] little endian
+ */
+pub const DRM_FORMAT_P210	:u32 = __fourcc_code!('P', '2', '1', '0'); /* 2x1 subsampled Cr:Cb plane, 10 bit per channel */
+pub const DRM_FORMAT_P010	:u32 = __fourcc_code!('P', '0', '1', '0'); /* 2x2 subsampled Cr:Cb plane 10 bits per channel */
+pub const DRM_FORMAT_P012	:u32 = __fourcc_code!('P', '0', '1', '2'); /* 2x2 subsampled Cr:Cb plane 12 bits per channel */
+pub const DRM_FORMAT_P016	:u32 = __fourcc_code!('P', '0', '1', '6'); /* 2x2 subsampled Cr:Cb plane 16 bits per channel */
+pub const DRM_FORMAT_P030	:u32 = __fourcc_code!('P', '0', '3', '0'); /* 2x2 subsampled Cr:Cb plane 10 bits per channel packed */
+
+/*
+ * 3 plane non-subsampled (444) YCbCr
+ * 16 bits per component, but only 10 bits are used and 6 bits are padded
+ * index 0: Y plane, [15:0] Y:x [10:6] little endian
+ * index 1: Cb plane, [15:0] Cb:x [10:6] little endian
+ * index 2: Cr plane, [15:0] Cr:x [10:6] little endian
+ */
+pub const DRM_FORMAT_Q410	:u32 = __fourcc_code!('Q', '4', '1', '0');
+
+/*
+ * 3 plane non-subsampled (444) YCrCb
+ * 16 bits per component, but only 10 bits are used and 6 bits are padded
+ * index 0: Y plane, [15:0] Y:x [10:6] little endian
+ * index 1: Cr plane, [15:0] Cr:x [10:6] little endian
+ * index 2: Cb plane, [15:0] Cb:x [10:6] little endian
+ */
+pub const DRM_FORMAT_Q401	:u32 = __fourcc_code!('Q', '4', '0', '1');
+
+/*
+ * 3 plane YCbCr
+ * index 0: Y plane, [7:0] Y
+ * index 1: Cb plane, [7:0] Cb
+ * index 2: Cr plane, [7:0] Cr
+ * or
+ * index 1: Cr plane, [7:0] Cr
+ * index 2: Cb plane, [7:0] Cb
+ */
+pub const DRM_FORMAT_YUV410	:u32 = __fourcc_code!('Y', 'U', 'V', '9'); /* 4x4 subsampled Cb (1) and Cr (2) planes */
+pub const DRM_FORMAT_YVU410	:u32 = __fourcc_code!('Y', 'V', 'U', '9'); /* 4x4 subsampled Cr (1) and Cb (2) planes */
+pub const DRM_FORMAT_YUV411	:u32 = __fourcc_code!('Y', 'U', '1', '1'); /* 4x1 subsampled Cb (1) and Cr (2) planes */
+pub const DRM_FORMAT_YVU411	:u32 = __fourcc_code!('Y', 'V', '1', '1'); /* 4x1 subsampled Cr (1) and Cb (2) planes */
+pub const DRM_FORMAT_YUV420	:u32 = __fourcc_code!('Y', 'U', '1', '2'); /* 2x2 subsampled Cb (1) and Cr (2) planes */
+pub const DRM_FORMAT_YVU420	:u32 = __fourcc_code!('Y', 'V', '1', '2'); /* 2x2 subsampled Cr (1) and Cb (2) planes */
+pub const DRM_FORMAT_YUV422	:u32 = __fourcc_code!('Y', 'U', '1', '6'); /* 2x1 subsampled Cb (1) and Cr (2) planes */
+pub const DRM_FORMAT_YVU422	:u32 = __fourcc_code!('Y', 'V', '1', '6'); /* 2x1 subsampled Cr (1) and Cb (2) planes */
+pub const DRM_FORMAT_YUV444	:u32 = __fourcc_code!('Y', 'U', '2', '4'); /* non-subsampled Cb (1) and Cr (2) planes */
+pub const DRM_FORMAT_YVU444	:u32 = __fourcc_code!('Y', 'V', '2', '4'); /* non-subsampled Cr (1) and Cb (2) planes */
+
+/*
+ * Format Modifiers:
+ *
+ * Format modifiers describe, typically, a re-ordering or modification
+ * of the data in a plane of an FB. This can be used to express tiled/
+ * swizzled formats, or compression, or a combination of the two.
+ *
+ * The upper 8 bits of the format modifier are a vendor-id as assigned
+ * below. The lower 56 bits are assigned as vendor sees fit.
+ */
+
+/* Vendor Ids: */
+pub const DRM_FORMAT_MOD_VENDOR_NONE	:u64 = 0;
+pub const DRM_FORMAT_MOD_VENDOR_INTEL	:u64 = 0x01;
+pub const DRM_FORMAT_MOD_VENDOR_AMD	:u64 = 0x02;
+pub const DRM_FORMAT_MOD_VENDOR_NVIDIA	:u64 = 0x03;
+pub const DRM_FORMAT_MOD_VENDOR_SAMSUNG	:u64 = 0x04;
+pub const DRM_FORMAT_MOD_VENDOR_QCOM	:u64 = 0x05;
+pub const DRM_FORMAT_MOD_VENDOR_VIVANTE	:u64 = 0x06;
+pub const DRM_FORMAT_MOD_VENDOR_BROADCOM	:u64 = 0x07;
+pub const DRM_FORMAT_MOD_VENDOR_ARM	:u64 = 0x08;
+pub const DRM_FORMAT_MOD_VENDOR_ALLWINNER	:u64 = 0x09;
+pub const DRM_FORMAT_MOD_VENDOR_AMLOGIC	:u64 = 0x0a;
+
+/* add more to the end as needed */
+
+pub const DRM_FORMAT_RESERVED	:u64 = (1 << 56) - 1;
+
+macro_rules! __fourcc_mod_code {
+    ($vendor:expr, $val:expr) => (
+        (($vendor as u64) << 56) | (($val as u64) & 0x00ff_ffff_ffff_ffff)
+    )
+}
+
+/* Invalid Modifier, used where no modifier was specified */
+pub const DRM_FORMAT_MOD_INVALID	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_NONE, DRM_FORMAT_RESERVED);
+
+/* Linear Layout, the default without any modifier */
+pub const DRM_FORMAT_MOD_LINEAR	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_NONE, 0);
+
+/* Intel framebuffer modifiers */
+pub const I915_FORMAT_MOD_X_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 1);
+pub const I915_FORMAT_MOD_Y_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 2);
+pub const I915_FORMAT_MOD_Yf_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 3);
+pub const I915_FORMAT_MOD_Y_TILED_CCS	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 4);
+pub const I915_FORMAT_MOD_Yf_TILED_CCS	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 5);
+pub const I915_FORMAT_MOD_Y_TILED_GEN12_RC_CCS	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 6);
+pub const I915_FORMAT_MOD_Y_TILED_GEN12_MC_CCS	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 7);
+pub const I915_FORMAT_MOD_Y_TILED_GEN12_RC_CCS_CC	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 8);
+pub const I915_FORMAT_MOD_4_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 9);
+pub const I915_FORMAT_MOD_4_TILED_DG2_RC_CCS	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 10);
+pub const I915_FORMAT_MOD_4_TILED_DG2_MC_CCS	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 11);
+pub const I915_FORMAT_MOD_4_TILED_DG2_RC_CCS_CC	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 12);
+pub const I915_FORMAT_MOD_4_TILED_MTL_RC_CCS	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 13);
+pub const I915_FORMAT_MOD_4_TILED_MTL_MC_CCS	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 14);
+pub const I915_FORMAT_MOD_4_TILED_MTL_RC_CCS_CC	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 15);
+pub const I915_FORMAT_MOD_4_TILED_LNL_CCS	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 16);
+pub const I915_FORMAT_MOD_4_TILED_BMG_CCS	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_INTEL, 17);
+
+/* Samsung tiled formats, used by Exynos */
+pub const DRM_FORMAT_MOD_SAMSUNG_64_32_TILE	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_SAMSUNG, 1);
+pub const DRM_FORMAT_MOD_SAMSUNG_16_16_TILE	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_SAMSUNG, 2);
+
+/* Qualcomm compressed and tiled formats */
+pub const DRM_FORMAT_MOD_QCOM_COMPRESSED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_QCOM, 1);
+pub const DRM_FORMAT_MOD_QCOM_TILED2	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_QCOM, 2);
+pub const DRM_FORMAT_MOD_QCOM_TILED3	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_QCOM, 3);
+
+/* Vivante 4x4 tiling layouts */
+pub const DRM_FORMAT_MOD_VIVANTE_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_VIVANTE, 1);
+pub const DRM_FORMAT_MOD_VIVANTE_SUPER_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_VIVANTE, 2);
+pub const DRM_FORMAT_MOD_VIVANTE_SPLIT_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_VIVANTE, 3);
+pub const DRM_FORMAT_MOD_VIVANTE_SPLIT_SUPER_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_VIVANTE, 4);
+
+/* Vivante TS (tile-status) buffer modifiers, combined with a tiling layout */
+pub const VIVANTE_MOD_TS_64_4	:u64 = 1 << 48;
+pub const VIVANTE_MOD_TS_64_2	:u64 = 2 << 48;
+pub const VIVANTE_MOD_TS_128_4	:u64 = 3 << 48;
+pub const VIVANTE_MOD_TS_256_4	:u64 = 4 << 48;
+pub const VIVANTE_MOD_TS_MASK	:u64 = 0xf << 48;
+
+/* Vivante compression modifiers */
+pub const VIVANTE_MOD_COMP_DEC400	:u64 = 1 << 52;
+pub const VIVANTE_MOD_COMP_MASK	:u64 = 0xf << 52;
+
+pub const VIVANTE_MOD_EXT_MASK	:u64 = VIVANTE_MOD_TS_MASK | VIVANTE_MOD_COMP_MASK;
+
+/* NVIDIA frame buffer modifiers */
+pub const DRM_FORMAT_MOD_NVIDIA_TEGRA_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_NVIDIA, 1);
+
+/*
+ * Generalized Block Linear layout, used by desktop GPUs starting with NV50/G80,
+ * and Tegra GPUs starting with Tegra K1.
+ *
+ * Bits  Parameter                Description
+ * ----  ---------------------    -----------------------------------------
+ *  3:0  h                        log2(height) of each block, in GOBs
+ *   4   -                        Must be 1, to indicate block-linear layout
+ * 11:5  -                        Reserved (To support 3D-surfaces with variable
+ *                                log2(depth) block dimensions.)
+ * 19:12 k                        Page Kind
+ * 21:20 g                        GOB Height and Page Kind Generation
+ *  22   s                        Sector layout
+ * 25:23 c                        Lossless Framebuffer Compression type
+ * 55:25 -                        Reserved for future use.
+ */
+pub const DRM_FORMAT_MOD_NVIDIA_BLOCK_LINEAR	:u64 = 0x10;
+
+/* Broadcom modifiers */
+pub const DRM_FORMAT_MOD_BROADCOM_VC4_T_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_BROADCOM, 1);
+pub const DRM_FORMAT_MOD_BROADCOM_SAND32	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_BROADCOM, 2);
+pub const DRM_FORMAT_MOD_BROADCOM_SAND64	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_BROADCOM, 3);
+pub const DRM_FORMAT_MOD_BROADCOM_SAND128	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_BROADCOM, 4);
+pub const DRM_FORMAT_MOD_BROADCOM_SAND256	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_BROADCOM, 5);
+pub const DRM_FORMAT_MOD_BROADCOM_UIF	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_BROADCOM, 6);
+
+/* Arm modifier types, stored in bits 52 to 55 */
+pub const DRM_FORMAT_MOD_ARM_TYPE_AFBC	:u64 = 0x00;
+pub const DRM_FORMAT_MOD_ARM_TYPE_MISC	:u64 = 0x01;
+pub const DRM_FORMAT_MOD_ARM_TYPE_AFRC	:u64 = 0x02;
+pub const DRM_FORMAT_MOD_ARM_TYPE_MASK	:u64 = 0xf;
+
+/* Arm Framebuffer Compression (AFBC) modes */
+pub const AFBC_FORMAT_MOD_BLOCK_SIZE_MASK	:u64 = 0xf;
+pub const AFBC_FORMAT_MOD_BLOCK_SIZE_16x16	:u64 = 1;
+pub const AFBC_FORMAT_MOD_BLOCK_SIZE_32x8	:u64 = 2;
+pub const AFBC_FORMAT_MOD_BLOCK_SIZE_64x4	:u64 = 3;
+pub const AFBC_FORMAT_MOD_BLOCK_SIZE_32x8_64x4	:u64 = 4;
+pub const AFBC_FORMAT_MOD_YTR	:u64 = 1 << 4;
+pub const AFBC_FORMAT_MOD_SPLIT	:u64 = 1 << 5;
+pub const AFBC_FORMAT_MOD_SPARSE	:u64 = 1 << 6;
+pub const AFBC_FORMAT_MOD_CBR	:u64 = 1 << 7;
+pub const AFBC_FORMAT_MOD_TILED	:u64 = 1 << 8;
+pub const AFBC_FORMAT_MOD_SC	:u64 = 1 << 9;
+pub const AFBC_FORMAT_MOD_DB	:u64 = 1 << 10;
+pub const AFBC_FORMAT_MOD_BCH	:u64 = 1 << 11;
+pub const AFBC_FORMAT_MOD_USM	:u64 = 1 << 12;
+
+/* Arm Fixed-Rate Compression (AFRC) modes */
+pub const AFRC_FORMAT_MOD_CU_SIZE_MASK	:u64 = 0xf;
+pub const AFRC_FORMAT_MOD_CU_SIZE_16	:u64 = 1;
+pub const AFRC_FORMAT_MOD_CU_SIZE_24	:u64 = 2;
+pub const AFRC_FORMAT_MOD_CU_SIZE_32	:u64 = 3;
+pub const AFRC_FORMAT_MOD_LAYOUT_SCAN	:u64 = 1 << 8;
+
+/* Arm 16x16 Block U-Interleaved modifier */
+pub const DRM_FORMAT_MOD_ARM_16X16_BLOCK_U_INTERLEAVED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_ARM, (DRM_FORMAT_MOD_ARM_TYPE_MISC << 52) | 1);
+
+/* Allwinner tiled modifier */
+pub const DRM_FORMAT_MOD_ALLWINNER_TILED	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_ALLWINNER, 1);
+
+/* AMD modifiers, made of the fields below */
+pub const AMD_FMT_MOD	:u64 = __fourcc_mod_code!(DRM_FORMAT_MOD_VENDOR_AMD, 0);
+
+pub const AMD_FMT_MOD_TILE_VER_GFX9	:u64 = 1;
+pub const AMD_FMT_MOD_TILE_VER_GFX10	:u64 = 2;
+pub const AMD_FMT_MOD_TILE_VER_GFX10_RBPLUS	:u64 = 3;
+pub const AMD_FMT_MOD_TILE_VER_GFX11	:u64 = 4;
+pub const AMD_FMT_MOD_TILE_VER_GFX12	:u64 = 5;
+
+pub const AMD_FMT_MOD_TILE_GFX9_64K_S	:u64 = 9;
+pub const AMD_FMT_MOD_TILE_GFX9_64K_D	:u64 = 10;
+pub const AMD_FMT_MOD_TILE_GFX9_64K_S_X	:u64 = 25;
+pub const AMD_FMT_MOD_TILE_GFX9_64K_D_X	:u64 = 26;
+pub const AMD_FMT_MOD_TILE_GFX9_64K_R_X	:u64 = 27;
+pub const AMD_FMT_MOD_TILE_GFX11_256K_R_X	:u64 = 31;
+
+pub const AMD_FMT_MOD_TILE_GFX12_256B_2D	:u64 = 1;
+pub const AMD_FMT_MOD_TILE_GFX12_4K_2D	:u64 = 2;
+pub const AMD_FMT_MOD_TILE_GFX12_64K_2D	:u64 = 3;
+pub const AMD_FMT_MOD_TILE_GFX12_256K_2D	:u64 = 4;
+
+pub const AMD_FMT_MOD_DCC_BLOCK_64B	:u64 = 0;
+pub const AMD_FMT_MOD_DCC_BLOCK_128B	:u64 = 1;
+pub const AMD_FMT_MOD_DCC_BLOCK_256B	:u64 = 2;
+
+pub const AMD_FMT_MOD_TILE_VERSION_SHIFT	:u64 = 0;
+pub const AMD_FMT_MOD_TILE_VERSION_MASK	:u64 = 0xFF;
+pub const AMD_FMT_MOD_TILE_SHIFT	:u64 = 8;
+pub const AMD_FMT_MOD_TILE_MASK	:u64 = 0x1F;
+
+/* Whether DCC compression is enabled. */
+pub const AMD_FMT_MOD_DCC_SHIFT	:u64 = 13;
+pub const AMD_FMT_MOD_DCC_MASK	:u64 = 0x1;
+
+/*
+ * Whether to include two DCC surfaces, one which is rb & pipe aligned, and
+ * one which is not-aligned.
+ */
+pub const AMD_FMT_MOD_DCC_RETILE_SHIFT	:u64 = 14;
+pub const AMD_FMT_MOD_DCC_RETILE_MASK	:u64 = 0x1;
+
+/* Only set if DCC_RETILE = false */
+pub const AMD_FMT_MOD_DCC_PIPE_ALIGN_SHIFT	:u64 = 15;
+pub const AMD_FMT_MOD_DCC_PIPE_ALIGN_MASK	:u64 = 0x1;
+
+pub const AMD_FMT_MOD_DCC_INDEPENDENT_64B_SHIFT	:u64 = 16;
+pub const AMD_FMT_MOD_DCC_INDEPENDENT_64B_MASK	:u64 = 0x1;
+pub const AMD_FMT_MOD_DCC_INDEPENDENT_128B_SHIFT	:u64 = 17;
+pub const AMD_FMT_MOD_DCC_INDEPENDENT_128B_MASK	:u64 = 0x1;
+pub const AMD_FMT_MOD_DCC_MAX_COMPRESSED_BLOCK_SHIFT	:u64 = 18;
+pub const AMD_FMT_MOD_DCC_MAX_COMPRESSED_BLOCK_MASK	:u64 = 0x3;
+
+/*
+ * DCC supports embedding some clear colors directly in the DCC surface.
+ * However, on older GPUs the rendering HW ignores the embedded clear color
+ * and prefers the driver provided color. This necessitates doing a fastclear
+ * eliminate operation before a process transfers control.
+ *
+ * If this bit is set that means the fastclear eliminate is not needed for these
+ * embeddable colors.
+ */
+pub const AMD_FMT_MOD_DCC_CONSTANT_ENCODE_SHIFT	:u64 = 20;
+pub const AMD_FMT_MOD_DCC_CONSTANT_ENCODE_MASK	:u64 = 0x1;
+
+/*
+ * The below fields are for accounting for per GPU differences. These are only
+ * relevant for GFX9 and later and if the tile field is *_X/_T.
+ */
+pub const AMD_FMT_MOD_PIPE_XOR_BITS_SHIFT	:u64 = 21;
+pub const AMD_FMT_MOD_PIPE_XOR_BITS_MASK	:u64 = 0x7;
+pub const AMD_FMT_MOD_BANK_XOR_BITS_SHIFT	:u64 = 24;
+pub const AMD_FMT_MOD_BANK_XOR_BITS_MASK	:u64 = 0x7;
+pub const AMD_FMT_MOD_PACKERS_SHIFT	:u64 = 27;
+pub const AMD_FMT_MOD_PACKERS_MASK	:u64 = 0x7;
+pub const AMD_FMT_MOD_RB_SHIFT	:u64 = 30;
+pub const AMD_FMT_MOD_RB_MASK	:u64 = 0x7;
+pub const AMD_FMT_MOD_PIPE_SHIFT	:u64 = 33;
+pub const AMD_FMT_MOD_PIPE_MASK	:u64 = 0x7;
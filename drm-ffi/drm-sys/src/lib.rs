@@ -22,4 +22,102 @@ pub mod fourcc;
 
 pub const DRM_PLANE_TYPE_OVERLAY: u32 = 0;
 pub const DRM_PLANE_TYPE_PRIMARY: u32 = 1;
-pub const DRM_PLANE_TYPE_CURSOR:  u32 = 2;
\ No newline at end of file
+pub const DRM_PLANE_TYPE_CURSOR:  u32 = 2;
+
+#[cfg(all(not(feature = "use_bindgen"),
+          target_os="freebsd",
+          target_arch="x86_64"))]
+pub const DRM_MODE_CONNECTOR_WRITEBACK: u32 = 18;
+#[cfg(all(not(feature = "use_bindgen"),
+          target_os="freebsd",
+          target_arch="x86_64"))]
+pub const DRM_MODE_CONNECTOR_SPI: u32 = 19;
+#[cfg(all(not(feature = "use_bindgen"),
+          target_os="freebsd",
+          target_arch="x86_64"))]
+pub const DRM_MODE_CONNECTOR_USB: u32 = 20;
+
+#[cfg(all(not(feature = "use_bindgen"),
+          target_os="freebsd",
+          target_arch="x86_64"))]
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct drm_mode_rect {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+pub const DRM_MODE_HDCP_CONTENT_TYPE0: u32 = 0;
+pub const DRM_MODE_HDCP_CONTENT_TYPE1: u32 = 1;
+
+/* HDMI static metadata (linux/hdmi.h) */
+pub const HDMI_STATIC_METADATA_TYPE1: u32 = 0;
+
+pub const HDMI_EOTF_TRADITIONAL_GAMMA_SDR: u8 = 0;
+pub const HDMI_EOTF_TRADITIONAL_GAMMA_HDR: u8 = 1;
+pub const HDMI_EOTF_SMPTE_ST2084: u8 = 2;
+pub const HDMI_EOTF_BT_2100_HLG: u8 = 3;
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct hdr_metadata_chromaticity {
+    pub x: u16,
+    pub y: u16,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct hdr_metadata_infoframe {
+    pub eotf: u8,
+    pub metadata_type: u8,
+    pub display_primaries: [hdr_metadata_chromaticity; 3],
+    pub white_point: hdr_metadata_chromaticity,
+    pub max_display_mastering_luminance: u16,
+    pub min_display_mastering_luminance: u16,
+    pub max_cll: u16,
+    pub max_fall: u16,
+}
+
+/* The kernel wraps the infoframe in a union that has no other members */
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct hdr_output_metadata {
+    pub metadata_type: u32,
+    pub hdmi_metadata_type1: hdr_metadata_infoframe,
+}
+
+/* Flags of DRM_IOCTL_PRIME_HANDLE_TO_FD */
+pub const DRM_CLOEXEC: u32 = libc::O_CLOEXEC as u32;
+pub const DRM_RDWR: u32 = libc::O_RDWR as u32;
+
+/* dma-buf (linux/dma-buf.h) */
+pub const DMA_BUF_BASE: u8 = b'b';
+pub const DMA_BUF_NAME_LEN: usize = 32;
+
+pub const DMA_BUF_SYNC_READ: u32 = 1 << 0;
+pub const DMA_BUF_SYNC_WRITE: u32 = 2 << 0;
+pub const DMA_BUF_SYNC_RW: u32 = DMA_BUF_SYNC_READ | DMA_BUF_SYNC_WRITE;
+pub const DMA_BUF_SYNC_START: u32 = 0 << 2;
+pub const DMA_BUF_SYNC_END: u32 = 1 << 2;
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct dma_buf_sync {
+    pub flags: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct dma_buf_export_sync_file {
+    pub flags: u32,
+    pub fd: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct dma_buf_import_sync_file {
+    pub flags: u32,
+    pub fd: i32,
+}
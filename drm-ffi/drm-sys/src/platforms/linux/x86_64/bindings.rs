@@ -143,6 +143,8 @@ pub const DRM_MODE_CONNECTOR_VIRTUAL: u32 = 15;
 pub const DRM_MODE_CONNECTOR_DSI: u32 = 16;
 pub const DRM_MODE_CONNECTOR_DPI: u32 = 17;
 pub const DRM_MODE_CONNECTOR_WRITEBACK: u32 = 18;
+pub const DRM_MODE_CONNECTOR_SPI: u32 = 19;
+pub const DRM_MODE_CONNECTOR_USB: u32 = 20;
 pub const DRM_MODE_PROP_PENDING: u32 = 1;
 pub const DRM_MODE_PROP_RANGE: u32 = 2;
 pub const DRM_MODE_PROP_IMMUTABLE: u32 = 4;
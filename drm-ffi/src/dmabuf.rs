@@ -0,0 +1,70 @@
+//!
+//! Bindings to the dma-buf interface
+//!
+
+use drm_sys::*;
+use ioctl;
+
+use nix::errno::Errno;
+use nix::Error as NixError;
+use result::SystemError as Error;
+
+use std::ffi::CStr;
+use std::os::unix::io::RawFd;
+
+/// Begins or ends CPU access to a dma-buf.
+///
+/// Interrupted calls are restarted, as waiting for the device may take a
+/// while.
+pub fn sync(fd: RawFd, flags: u32) -> Result<dma_buf_sync, Error> {
+    let sync = dma_buf_sync {
+        flags: flags as u64,
+    };
+
+    loop {
+        match unsafe { ioctl::dmabuf::sync(fd, &sync) } {
+            Err(NixError::Sys(Errno::EINTR)) | Err(NixError::Sys(Errno::EAGAIN)) => continue,
+            res => {
+                res?;
+                return Ok(sync);
+            }
+        }
+    }
+}
+
+/// Sets the name of a dma-buf, as shown in its `fdinfo`.
+pub fn set_name(fd: RawFd, name: &CStr) -> Result<(), Error> {
+    unsafe {
+        ioctl::dmabuf::set_name(fd, name.as_ptr() as *const u64)?;
+    }
+
+    Ok(())
+}
+
+/// Exports the implicit fences of a dma-buf as a sync_file.
+pub fn export_sync_file(fd: RawFd, flags: u32) -> Result<dma_buf_export_sync_file, Error> {
+    let mut export = dma_buf_export_sync_file {
+        flags: flags,
+        fd: -1,
+    };
+
+    unsafe {
+        ioctl::dmabuf::export_sync_file(fd, &mut export)?;
+    }
+
+    Ok(export)
+}
+
+/// Adds the fence of a sync_file to the implicit fences of a dma-buf.
+pub fn import_sync_file(fd: RawFd, flags: u32, sync_file: RawFd) -> Result<dma_buf_import_sync_file, Error> {
+    let import = dma_buf_import_sync_file {
+        flags: flags,
+        fd: sync_file,
+    };
+
+    unsafe {
+        ioctl::dmabuf::import_sync_file(fd, &import)?;
+    }
+
+    Ok(import)
+}
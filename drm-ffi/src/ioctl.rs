@@ -122,6 +122,7 @@ pub(crate) mod mode {
     ioctl_readwrite!(get_fb, DRM_IOCTL_BASE, 0xAD, drm_mode_fb_cmd);
     ioctl_readwrite!(add_fb, DRM_IOCTL_BASE, 0xAE, drm_mode_fb_cmd);
     ioctl_readwrite!(add_fb2, DRM_IOCTL_BASE, 0xB8, drm_mode_fb_cmd2);
+    ioctl_readwrite!(get_fb2, DRM_IOCTL_BASE, 0xCE, drm_mode_fb_cmd2);
     ioctl_readwrite!(rm_fb, DRM_IOCTL_BASE, 0xAF, c_uint);
 
     /// Plane related functions
@@ -179,3 +180,22 @@ pub(crate) mod gem {
     /// Converts a dma-buf file descriptor into a buffer handle.
     ioctl_readwrite!(prime_fd_to_handle, DRM_IOCTL_BASE, 0x2e, drm_prime_handle);
 }
+
+pub(crate) mod dmabuf {
+    use drm_sys::*;
+
+    /// Begins or ends CPU access to a dma-buf.
+    ioctl_write_ptr!(sync, DMA_BUF_BASE, 0, dma_buf_sync);
+
+    /// Names a dma-buf.
+    ///
+    /// The argument is the string itself, the size encoded in the request
+    /// is the one of a pointer.
+    ioctl_write_ptr!(set_name, DMA_BUF_BASE, 1, u64);
+
+    /// Exports the implicit fences of a dma-buf as a sync_file.
+    ioctl_readwrite!(export_sync_file, DMA_BUF_BASE, 2, dma_buf_export_sync_file);
+
+    /// Adds a sync_file to the implicit fences of a dma-buf.
+    ioctl_write_ptr!(import_sync_file, DMA_BUF_BASE, 3, dma_buf_import_sync_file);
+}
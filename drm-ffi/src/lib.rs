@@ -17,6 +17,7 @@ extern crate nix;
 pub(crate) mod utils;
 
 use result::SystemError as Error;
+pub mod dmabuf;
 pub mod gem;
 pub mod ioctl;
 pub mod result;
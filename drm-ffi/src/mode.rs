@@ -75,6 +75,20 @@ pub fn get_framebuffer(fd: RawFd, id: u32) -> Result<drm_mode_fb_cmd, Error> {
     Ok(info)
 }
 
+/// Get info about a framebuffer (with modifiers).
+pub fn get_framebuffer2(fd: RawFd, id: u32) -> Result<drm_mode_fb_cmd2, Error> {
+    let mut info = drm_mode_fb_cmd2 {
+        fb_id: id,
+        ..Default::default()
+    };
+
+    unsafe {
+        ioctl::mode::get_fb2(fd, &mut info)?;
+    }
+
+    Ok(info)
+}
+
 /// Add a new framebuffer.
 pub fn add_fb(
     fd: RawFd,
@@ -331,62 +345,128 @@ pub fn move_cursor(fd: RawFd, id: u32, x: i32, y: i32) -> Result<drm_mode_cursor
 }
 
 /// Get info about a connector
+///
+/// Each buffer is resized to fit all of the connector's entries. Properties
+/// are only returned if buffers for both their IDs and values are given.
+///
+/// The kernel probes the connector (e.g. reads the EDID over DDC) when it is
+/// asked for its mode list without any room for modes. Unless `force_probe`
+/// is set, room for at least one mode is always passed, so the kernel returns
+/// its cached state instead.
 pub fn get_connector(
     fd: RawFd,
     id: u32,
-    props: Option<&mut &mut [u32]>,
-    prop_values: Option<&mut &mut [u64]>,
+    mut props: Option<&mut Vec<u32>>,
+    mut prop_values: Option<&mut Vec<u64>>,
     mut modes: Option<&mut Vec<drm_mode_modeinfo>>,
-    encoders: Option<&mut &mut [u32]>,
+    mut encoders: Option<&mut Vec<u32>>,
+    force_probe: bool,
 ) -> Result<drm_mode_get_connector, Error> {
+    let mut dummy = drm_mode_modeinfo::default();
+
+    let mut counts = drm_mode_get_connector {
+        connector_id: id,
+        ..Default::default()
+    };
+
+    if !force_probe {
+        counts.modes_ptr = &mut dummy as *mut _ as _;
+        counts.count_modes = 1;
+    }
+
+    unsafe {
+        ioctl::mode::get_connector(fd, &mut counts)?;
+    }
+
+    if props.is_none() && prop_values.is_none() && modes.is_none() && encoders.is_none() {
+        return Ok(counts);
+    }
+
+    loop {
+        // Buffers that are not given must be passed with a count of zero.
+        let count_props = match (&props, &prop_values) {
+            (&Some(_), &Some(_)) => counts.count_props,
+            _ => 0,
+        };
+        let (modes_ptr, count_modes) = reserve_modes(&mut modes, counts.count_modes, &mut dummy);
+        let count_encoders = if encoders.is_some() { counts.count_encoders } else { 0 };
 
-    let modes_count = if modes.is_some() {
         let mut info = drm_mode_get_connector {
             connector_id: id,
+            props_ptr: reserve(&mut props, count_props),
+            prop_values_ptr: reserve(&mut prop_values, count_props),
+            modes_ptr: modes_ptr,
+            encoders_ptr: reserve(&mut encoders, count_encoders),
+            count_props: count_props,
+            count_modes: count_modes,
+            count_encoders: count_encoders,
             ..Default::default()
         };
 
         unsafe {
             ioctl::mode::get_connector(fd, &mut info)?;
         }
- 
-        info.count_modes
-    } else { 0 };
- 
-    let mut info = drm_mode_get_connector {
-        connector_id: id,
-        props_ptr: map_ptr!(&props),
-        prop_values_ptr: map_ptr!(&prop_values),
-        modes_ptr: match modes.as_mut() {
-            Some(modes) => {
-                modes.clear();
-                modes.reserve_exact(modes_count as usize);
-                modes.as_ptr() as _
-            },
-            None => 0 as _,
-        },
-        encoders_ptr: map_ptr!(&encoders),
-        count_props: map_len!(&props),
-        count_modes: modes_count,
-        count_encoders: map_len!(&encoders),
-        ..Default::default()
-    };
 
-    unsafe {
-        ioctl::mode::get_connector(fd, &mut info)?;
-    }
+        // The kernel copies nothing into buffers that are too small, which
+        // happens if the connector changed in the meantime.
+        if (props.is_some() && prop_values.is_some() && info.count_props > count_props)
+            || (modes.is_some() && info.count_modes > count_modes)
+            || (encoders.is_some() && info.count_encoders > count_encoders)
+        {
+            counts = info;
+            continue;
+        }
+
+        let count_props = ::std::cmp::min(info.count_props, count_props);
 
-    if let Some(modes) = modes {
         unsafe {
-            modes.set_len(info.count_modes as usize);
+            set_len(&mut props, count_props);
+            set_len(&mut prop_values, count_props);
+            set_len(&mut modes, info.count_modes);
+            set_len(&mut encoders, info.count_encoders);
         }
+
+        return Ok(info);
     }
+}
 
-    map_shrink!(props, info.count_props as usize);
-    map_shrink!(prop_values, info.count_props as usize);
-    map_shrink!(encoders, info.count_encoders as usize);
+/// Clears a buffer and makes room for `count` entries, returning its pointer.
+fn reserve<T>(buffer: &mut Option<&mut Vec<T>>, count: u32) -> u64 {
+    match *buffer {
+        Some(ref mut buffer) => {
+            buffer.clear();
+            buffer.reserve_exact(count as usize);
+            buffer.as_mut_ptr() as u64
+        },
+        None => 0,
+    }
+}
 
-    Ok(info)
+/// Makes room for the modes of a connector, returning the pointer and count
+/// to pass.
+///
+/// Without a buffer, the single `dummy` slot is passed, so the kernel does not
+/// probe the connector. The count must then stay at one, as the kernel would
+/// otherwise write every mode past `dummy`.
+fn reserve_modes(
+    modes: &mut Option<&mut Vec<drm_mode_modeinfo>>,
+    count: u32,
+    dummy: &mut drm_mode_modeinfo,
+) -> (u64, u32) {
+    match *modes {
+        Some(_) => {
+            let count = ::std::cmp::max(count, 1);
+            (reserve(modes, count), count)
+        }
+        None => (dummy as *mut _ as u64, 1),
+    }
+}
+
+/// Sets the length of a buffer filled by the kernel.
+unsafe fn set_len<T>(buffer: &mut Option<&mut Vec<T>>, count: u32) {
+    if let Some(ref mut buffer) = *buffer {
+        buffer.set_len(count as usize);
+    }
 }
 
 /// Get info about an encoder
@@ -510,7 +590,10 @@ pub fn set_connector_property(
 }
 
 /// Get the value of a property blob
-pub fn get_property_blob(fd: RawFd, id: u32, data: Option<&mut &mut [u64]>) -> Result<drm_mode_get_blob, Error> {
+///
+/// If the buffer is too small, this will load the maximum bytes in the buffer.
+/// If the buffer is too big, this will coerce the buffer to the proper size.
+pub fn get_property_blob(fd: RawFd, id: u32, data: Option<&mut &mut [u8]>) -> Result<drm_mode_get_blob, Error> {
     let mut blob = drm_mode_get_blob {
         blob_id: id,
         length: map_len!(&data),
@@ -527,7 +610,7 @@ pub fn get_property_blob(fd: RawFd, id: u32, data: Option<&mut &mut [u64]>) -> R
 }
 
 /// Create a property blob
-pub fn create_property_blob(fd: RawFd, data: &mut [u64]) -> Result<drm_mode_create_blob, Error> {
+pub fn create_property_blob(fd: RawFd, data: &[u8]) -> Result<drm_mode_create_blob, Error> {
     let mut blob = drm_mode_create_blob {
         length: data.len() as _,
         data: data.as_ptr() as _,
@@ -708,3 +791,41 @@ pub mod dumbbuffer {
         Ok(map)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connector_modes_without_buffer_use_one_dummy_slot() {
+        let mut dummy = drm_mode_modeinfo::default();
+        let dummy_ptr = &mut dummy as *mut _ as u64;
+
+        for &count in &[0, 1, 5, 100] {
+            let (ptr, passed) = reserve_modes(&mut None, count, &mut dummy);
+            assert_eq!(ptr, dummy_ptr);
+            assert_eq!(passed, 1);
+        }
+    }
+
+    #[test]
+    fn connector_modes_with_buffer_fit_every_mode() {
+        let mut dummy = drm_mode_modeinfo::default();
+        let mut buffer = vec![drm_mode_modeinfo::default(); 2];
+
+        {
+            let mut modes = Some(&mut buffer);
+            let (ptr, passed) = reserve_modes(&mut modes, 5, &mut dummy);
+            assert_eq!(passed, 5);
+            assert_ne!(ptr, &mut dummy as *mut _ as u64);
+        }
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 5);
+
+        // Room for one mode keeps the kernel from probing the connector.
+        let mut modes = Some(&mut buffer);
+        let (ptr, passed) = reserve_modes(&mut modes, 0, &mut dummy);
+        assert_eq!(passed, 1);
+        assert_eq!(ptr, modes.unwrap().as_ptr() as u64);
+    }
+}
@@ -11,6 +11,7 @@ use drm::buffer::format::PixelFormat;
 
 use drm::control::ResourceHandle;
 use drm::control::{self, atomic, connector, crtc, dumbbuffer, framebuffer, property, AtomicCommitFlags};
+use drm::control::owned::{OwnedBlob, OwnedDumbBuffer, OwnedFramebuffer};
 
 fn find_prop_id<T: ResourceHandle>(card: &Card, handle: T, name: &'static str) -> Option<property::Handle> {
     let props = card.get_properties(handle).expect("Could not get props of connector");
@@ -31,7 +32,7 @@ pub fn main() {
     let res = card
         .resource_handles()
         .expect("Could not load normal resource ids.");
-    let coninfo: Vec<connector::Info> = res.connectors().iter().flat_map(|con| card.get_connector(*con)).collect();
+    let coninfo: Vec<connector::Info> = res.connectors().iter().flat_map(|con| card.get_connector(*con, false)).collect();
     let crtcinfo: Vec<crtc::Info> = res.crtcs().iter().flat_map(|crtc| card.get_crtc(*crtc)).collect();
 
     // Filter each connector until we find one that's connected.
@@ -57,19 +58,19 @@ pub fn main() {
     //let fmt = PixelFormat::ARGB4444;
 
     // Create a DB
-    let mut db = card.create_dumb_buffer((1920, 1080), fmt)
+    let mut db = OwnedDumbBuffer::create(&card, (1920, 1080), fmt)
         .expect("Could not create dumb buffer");
 
     // Map it and grey it out.
     {
-        let mut map = card.map_dumb_buffer(&mut db).expect("Could not map dumbbuffer");
+        let mut map = db.map().expect("Could not map dumbbuffer");
         for mut b in map.as_mut() {
             *b = 128;
         }
     }
 
     // Create an FB:
-    let fb = card.add_framebuffer(&db).expect("Could not create FB");
+    let fb = OwnedFramebuffer::add(&card, &db).expect("Could not create FB");
 
     let planes = card.plane_handles().expect("Could not list planes");
     let (better_planes, compatible_planes): (Vec<control::plane::Handle>, Vec<control::plane::Handle>) =
@@ -98,8 +99,8 @@ pub fn main() {
     let plane = *better_planes.get(0).unwrap_or(&compatible_planes[0]);
 
     println!("{:#?}", mode);
-    println!("{:#?}", fb);
-    println!("{:#?}", db);
+    println!("{:#?}", fb.handle());
+    println!("{:#?}", db.buffer());
     println!("{:#?}", plane);
 
     let mut atomic_req = atomic::AtomicModeReq::new();
@@ -108,11 +109,11 @@ pub fn main() {
         find_prop_id(&card, con.handle(), "CRTC_ID").expect("Could not get CRTC_ID"),
         property::Value::CRTC(Some(crtc.handle()))
     );
-    let blob = card.create_property_blob(mode).expect("Failed to create blob");
+    let blob = OwnedBlob::create(&card, &mode).expect("Failed to create blob");
     atomic_req.add_property(
         crtc.handle(),
         find_prop_id(&card, crtc.handle(), "MODE_ID").expect("Could not get MODE_ID"),
-        blob
+        property::Value::Blob(blob.id())
     );
     atomic_req.add_property(
         crtc.handle(),
@@ -122,7 +123,7 @@ pub fn main() {
     atomic_req.add_property(
         plane,
         find_prop_id(&card, plane, "FB_ID").expect("Could not get FB_ID"),
-        property::Value::Framebuffer(Some(fb)),
+        property::Value::Framebuffer(Some(fb.handle())),
     );
     atomic_req.add_property(
         plane,
@@ -178,6 +179,5 @@ pub fn main() {
     let five_seconds = ::std::time::Duration::from_millis(5000);
     ::std::thread::sleep(five_seconds);
 
-    card.destroy_framebuffer(fb).unwrap();
-    card.destroy_dumb_buffer(db).unwrap();
+    // The framebuffer, dumb buffer and mode blob are destroyed on drop.
 }
@@ -19,7 +19,7 @@ pub fn main() {
     let res = card
         .resource_handles()
         .expect("Could not load normal resource ids.");
-    let coninfo: Vec<connector::Info> = res.connectors().iter().flat_map(|con| card.get_connector(*con)).collect();
+    let coninfo: Vec<connector::Info> = res.connectors().iter().flat_map(|con| card.get_connector(*con, false)).collect();
     let crtcinfo: Vec<crtc::Info> = res.crtcs().iter().flat_map(|crtc| card.get_crtc(*crtc)).collect();
 
     // Filter each connector until we find one that's connected.
@@ -24,7 +24,7 @@ pub fn main() {
     println!("Planes:\t\t{:?}", plane_res.planes());
 
     for &handle in resources.connectors() {
-        let info = card.get_connector(handle).unwrap();
+        let info = card.get_connector(handle, false).unwrap();
         println!("Connector: {:?}", handle);
         println!("\t{:?}-{}", info.interface(), info.interface_id());
         println!("\t{:?}", info.state());
@@ -32,7 +32,7 @@ pub fn main() {
         println!("\t{:?}", info.encoders());
         println!("\t{:?}", info.current_encoder());
 
-        for &mode in card.get_modes(handle).unwrap().modes() {
+        for &mode in card.get_modes(handle, false).unwrap().modes() {
             //println!("{:?}", mode);
         }
     }
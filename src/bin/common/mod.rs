@@ -0,0 +1,232 @@
+//! Helpers shared by the command-line tools.
+
+#![allow(dead_code)]
+
+use drm::buffer::format::PixelFormat;
+use drm::control::{connector, crtc, encoder, framebuffer, plane, property, RawResourceHandle, ResourceHandle};
+use drm::control::Device as ControlDevice;
+use drm::Device;
+
+use std::fs::{self, File, OpenOptions};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
+
+/// An open device node.
+#[derive(Debug)]
+pub struct Card(File);
+
+impl AsRawFd for Card {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl Device for Card {}
+impl ControlDevice for Card {}
+
+impl Card {
+    /// Opens a device node for reading and writing, falling back to
+    /// read-only access.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Card, String> {
+        let path = path.as_ref();
+
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .or_else(|_| File::open(path))
+            .map(Card)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Returns the primary nodes found in `/dev/dri`, sorted by name.
+pub fn cards() -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir("/dev/dri").map_err(|e| format!("/dev/dri: {}", e))?;
+
+    let mut cards: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("card"))
+        .map(|entry| entry.path())
+        .collect();
+
+    cards.sort();
+    Ok(cards)
+}
+
+/// Returns the raw id of a handle.
+pub fn id<T: Into<u32>>(handle: T) -> u32 {
+    handle.into()
+}
+
+/// Returns the properties of an object along with their values.
+pub fn properties<T: ResourceHandle>(
+    card: &Card,
+    handle: T,
+) -> Result<Vec<(property::Info, property::RawValue)>, String> {
+    let set = card
+        .get_properties(handle)
+        .map_err(|e| format!("failed to get properties: {}", e))?;
+    let (ids, values) = set.as_props_and_values();
+
+    ids.iter()
+        .zip(values)
+        .map(|(&id, &value)| {
+            card.get_property(id)
+                .map(|info| (info, value))
+                .map_err(|e| format!("failed to get property {}: {}", self::id(id), e))
+        })
+        .collect()
+}
+
+/// Returns the name of the value of an enum property.
+pub fn enum_name(info: &property::Info, value: property::RawValue) -> Option<String> {
+    match info.value_type() {
+        property::ValueType::Enum(values) => {
+            let (_, enums) = values.values();
+            enums
+                .iter()
+                .find(|e| e.value() == value)
+                .map(|e| e.name().to_string_lossy().into_owned())
+        }
+        _ => None,
+    }
+}
+
+/// Parses a fourcc code such as `XR24` into a pixel format.
+pub fn parse_format(s: &str) -> Result<PixelFormat, String> {
+    s.parse().map_err(|_| format!("unknown format: {}", s))
+}
+
+/// A modesetting object of a device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Object {
+    Connector(connector::Handle),
+    Encoder(encoder::Handle),
+    Crtc(crtc::Handle),
+    Framebuffer(framebuffer::Handle),
+    Plane(plane::Handle),
+}
+
+impl Object {
+    /// Returns the raw id of this object.
+    pub fn id(&self) -> u32 {
+        match *self {
+            Object::Connector(h) => id(h),
+            Object::Encoder(h) => id(h),
+            Object::Crtc(h) => id(h),
+            Object::Framebuffer(h) => id(h),
+            Object::Plane(h) => id(h),
+        }
+    }
+
+    /// Returns the raw handle of this object.
+    pub fn raw(&self) -> RawResourceHandle {
+        match *self {
+            Object::Connector(h) => h.into(),
+            Object::Encoder(h) => h.into(),
+            Object::Crtc(h) => h.into(),
+            Object::Framebuffer(h) => h.into(),
+            Object::Plane(h) => h.into(),
+        }
+    }
+
+    /// Returns the properties of this object along with their values.
+    pub fn properties(&self, card: &Card) -> Result<Vec<(property::Info, property::RawValue)>, String> {
+        match *self {
+            Object::Connector(h) => properties(card, h),
+            Object::Encoder(h) => properties(card, h),
+            Object::Crtc(h) => properties(card, h),
+            Object::Framebuffer(h) => properties(card, h),
+            Object::Plane(h) => properties(card, h),
+        }
+    }
+
+    /// Sets a property of this object through the legacy interface.
+    pub fn set_property(&self, card: &Card, prop: property::Handle, value: property::RawValue) -> Result<(), String> {
+        match *self {
+            Object::Connector(h) => card.set_property(h, prop, value),
+            Object::Encoder(h) => card.set_property(h, prop, value),
+            Object::Crtc(h) => card.set_property(h, prop, value),
+            Object::Framebuffer(h) => card.set_property(h, prop, value),
+            Object::Plane(h) => card.set_property(h, prop, value),
+        }
+        .map_err(|e| format!("failed to set property of object {}: {}", self.id(), e))
+    }
+}
+
+impl ::std::fmt::Display for Object {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        let kind = match *self {
+            Object::Connector(_) => "connector",
+            Object::Encoder(_) => "encoder",
+            Object::Crtc(_) => "CRTC",
+            Object::Framebuffer(_) => "framebuffer",
+            Object::Plane(_) => "plane",
+        };
+        write!(f, "{} {}", kind, self.id())
+    }
+}
+
+/// Returns every modesetting object of a device.
+pub fn objects(card: &Card) -> Result<Vec<Object>, String> {
+    let res = card
+        .resource_handles()
+        .map_err(|e| format!("failed to get resources: {}", e))?;
+
+    let mut objects: Vec<Object> = Vec::new();
+    objects.extend(res.connectors().iter().map(|&h| Object::Connector(h)));
+    objects.extend(res.encoders().iter().map(|&h| Object::Encoder(h)));
+    objects.extend(res.crtcs().iter().map(|&h| Object::Crtc(h)));
+    objects.extend(res.framebuffers().iter().map(|&h| Object::Framebuffer(h)));
+
+    if let Ok(planes) = card.plane_handles() {
+        objects.extend(planes.planes().iter().map(|&h| Object::Plane(h)));
+    }
+
+    Ok(objects)
+}
+
+/// Finds the object with the given id.
+pub fn find_object(card: &Card, id: u32) -> Result<Object, String> {
+    objects(card)?
+        .into_iter()
+        .find(|o| o.id() == id)
+        .ok_or_else(|| format!("no object with id {}", id))
+}
+
+/// Finds a property of an object by name.
+pub fn find_property(
+    card: &Card,
+    object: Object,
+    name: &str,
+) -> Result<(property::Info, property::RawValue), String> {
+    object
+        .properties(card)?
+        .into_iter()
+        .find(|(info, _)| info.name().to_bytes() == name.as_bytes())
+        .ok_or_else(|| format!("{} has no property \"{}\"", object, name))
+}
+
+/// Parses the value of a property, given as a number or by enum name.
+pub fn parse_value(info: &property::Info, s: &str) -> Result<property::RawValue, String> {
+    if let property::ValueType::Enum(values) = info.value_type() {
+        if let Some(value) = values.get_value_from_name(s) {
+            return Ok(value.value());
+        }
+    }
+
+    let parsed = if let Some(hex) = s.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16).ok()
+    } else if s.starts_with('-') {
+        s.parse::<i64>().ok().map(|v| v as u64)
+    } else {
+        match s {
+            "true" => Some(1),
+            "false" => Some(0),
+            _ => s.parse().ok(),
+        }
+    };
+
+    parsed.ok_or_else(|| format!("invalid value for \"{}\": {}", info.name().to_string_lossy(), s))
+}
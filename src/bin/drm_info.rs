@@ -0,0 +1,676 @@
+//! Prints the modesetting state of DRM devices.
+//!
+//! ```text
+//! drm_info [-j] [DEVICE...]
+//! ```
+//!
+//! Without any device, every primary node in `/dev/dri` is inspected. The
+//! state is printed as a tree, or as JSON with `-j`.
+
+extern crate drm;
+extern crate drm_ffi;
+#[macro_use]
+extern crate serde;
+extern crate serde_json;
+
+mod common;
+
+use common::*;
+
+use drm::buffer::format::Fourcc;
+use drm::buffer::modifier::Modifier;
+use drm::control::{connector, crtc, edid, encoder, plane, property, Mode};
+use drm::control::Device as ControlDevice;
+use drm::{ClientCapability, Device, DriverCapability};
+
+use std::mem;
+use std::process;
+
+const CAPABILITIES: &[DriverCapability] = &[
+    DriverCapability::DumbBuffer,
+    DriverCapability::VBlankHighCRTC,
+    DriverCapability::DumbPreferredDepth,
+    DriverCapability::DumbPreferShadow,
+    DriverCapability::Prime,
+    DriverCapability::MonotonicTimestamp,
+    DriverCapability::ASyncPageFlip,
+    DriverCapability::CursorWidth,
+    DriverCapability::CursorHeight,
+    DriverCapability::AddFB2Modifiers,
+    DriverCapability::PageFlipTarget,
+    DriverCapability::CRTCInVBlankEvent,
+    DriverCapability::SyncObj,
+];
+
+/// Everything known about a device.
+#[derive(Serialize)]
+struct Node {
+    path: String,
+    driver: drm::Driver,
+    capabilities: Vec<Capability>,
+    connectors: Vec<Connector>,
+    encoders: Vec<encoder::Info>,
+    crtcs: Vec<Crtc>,
+    planes: Vec<Plane>,
+}
+
+/// The value of a driver capability, `None` if the driver does not know it.
+#[derive(Serialize)]
+struct Capability {
+    capability: DriverCapability,
+    value: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct Connector {
+    name: String,
+    info: connector::Info,
+    properties: Vec<Property>,
+}
+
+#[derive(Serialize)]
+struct Crtc {
+    info: crtc::Info,
+    properties: Vec<Property>,
+}
+
+#[derive(Serialize)]
+struct Plane {
+    info: plane::Info,
+    framebuffer: Option<Framebuffer>,
+    properties: Vec<Property>,
+}
+
+#[derive(Serialize)]
+struct Framebuffer {
+    id: u32,
+    size: (u32, u32),
+    format: Option<String>,
+    modifier: Option<u64>,
+    pitches: Vec<u32>,
+    offsets: Vec<u32>,
+}
+
+#[derive(Serialize)]
+struct Property {
+    info: property::Info,
+    raw_value: property::RawValue,
+    value: Value,
+}
+
+/// The value of a property, decoded according to its type.
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Value {
+    Unknown,
+    Boolean(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Enum(Option<String>),
+    Bitmask(u64),
+    Object(u32),
+    Blob(Option<Blob>),
+}
+
+/// The contents of a blob property, decoded according to the property name.
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Blob {
+    Mode(Mode),
+    InFormats(Vec<FormatModifier>),
+    Edid(Edid),
+    Path(String),
+    Tile(connector::Tile),
+    Data(usize),
+}
+
+/// A format modifier along with the formats it supports.
+#[derive(Serialize)]
+struct FormatModifier {
+    modifier: u64,
+    formats: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Edid {
+    #[serde(flatten)]
+    identity: edid::Identity,
+    hdr_eotfs: Vec<&'static str>,
+}
+
+fn main() {
+    let mut json = false;
+    let mut paths = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-j" | "--json" => json = true,
+            "-h" | "--help" => {
+                println!("usage: drm_info [-j] [DEVICE...]");
+                return;
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("unknown option: {}", arg);
+                process::exit(1);
+            }
+            _ => paths.push(arg.into()),
+        }
+    }
+
+    if paths.is_empty() {
+        paths = cards().unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1)
+        });
+    }
+
+    let mut nodes = Vec::new();
+    let mut failed = false;
+
+    for path in &paths {
+        match Card::open(path).and_then(|card| node(&card, &path.display().to_string())) {
+            Ok(node) => nodes.push(node),
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                failed = true;
+            }
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&nodes).expect("failed to serialize"));
+    } else {
+        for node in &nodes {
+            print_node(node);
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn node(card: &Card, path: &str) -> Result<Node, String> {
+    // Both are needed to see every plane and the atomic properties.
+    let _ = card.set_client_capability(ClientCapability::UniversalPlanes, true);
+    let _ = card.set_client_capability(ClientCapability::Atomic, true);
+
+    let driver = card.get_driver().map_err(|e| format!("failed to get driver: {}", e))?;
+    let resources = card
+        .resource_handles()
+        .map_err(|e| format!("failed to get resources: {}", e))?;
+
+    let capabilities = CAPABILITIES
+        .iter()
+        .map(|&cap| Capability {
+            capability: cap,
+            value: card.get_driver_capability(cap).ok(),
+        })
+        .collect();
+
+    let mut connectors = Vec::new();
+    for &handle in resources.connectors() {
+        let info = card
+            .get_connector(handle, false)
+            .map_err(|e| format!("failed to get connector {}: {}", id(handle), e))?;
+
+        let mut properties = Vec::new();
+        for &(prop, value) in info.properties() {
+            let prop = card
+                .get_property(prop)
+                .map_err(|e| format!("failed to get property {}: {}", id(prop), e))?;
+            properties.push(property(card, prop, value));
+        }
+
+        connectors.push(Connector {
+            name: info.name().to_string(),
+            info: info,
+            properties: properties,
+        });
+    }
+
+    let mut encoders = Vec::new();
+    for &handle in resources.encoders() {
+        encoders.push(
+            card.get_encoder(handle)
+                .map_err(|e| format!("failed to get encoder {}: {}", id(handle), e))?,
+        );
+    }
+
+    let mut crtcs = Vec::new();
+    for &handle in resources.crtcs() {
+        crtcs.push(Crtc {
+            info: card
+                .get_crtc(handle)
+                .map_err(|e| format!("failed to get CRTC {}: {}", id(handle), e))?,
+            properties: properties(card, handle)?,
+        });
+    }
+
+    let mut planes = Vec::new();
+    if let Ok(plane_resources) = card.plane_handles() {
+        for &handle in plane_resources.planes() {
+            let info = card
+                .get_plane(handle)
+                .map_err(|e| format!("failed to get plane {}: {}", id(handle), e))?;
+
+            planes.push(Plane {
+                framebuffer: info.framebuffer().and_then(|fb| framebuffer(card, fb)),
+                info: info,
+                properties: properties(card, handle)?,
+            });
+        }
+    }
+
+    Ok(Node {
+        path: path.to_string(),
+        driver: driver,
+        capabilities: capabilities,
+        connectors: connectors,
+        encoders: encoders,
+        crtcs: crtcs,
+        planes: planes,
+    })
+}
+
+fn properties<T: drm::control::ResourceHandle>(card: &Card, handle: T) -> Result<Vec<Property>, String> {
+    Ok(common::properties(card, handle)?
+        .into_iter()
+        .map(|(info, value)| property(card, info, value))
+        .collect())
+}
+
+fn property(card: &Card, info: property::Info, raw: property::RawValue) -> Property {
+    let value = match info.value_type() {
+        property::ValueType::Unknown => Value::Unknown,
+        property::ValueType::Boolean => Value::Boolean(raw != 0),
+        property::ValueType::UnsignedRange(..) => Value::Unsigned(raw),
+        property::ValueType::SignedRange(..) => Value::Signed(raw as i64),
+        property::ValueType::Enum(_) => Value::Enum(enum_name(&info, raw)),
+        property::ValueType::Bitmask => Value::Bitmask(raw),
+        property::ValueType::Blob => Value::Blob(match raw {
+            0 => None,
+            blob => card
+                .get_property_blob(blob)
+                .ok()
+                .map(|data| decode_blob(&info.name().to_string_lossy(), &data)),
+        }),
+        _ => Value::Object(raw as u32),
+    };
+
+    Property {
+        info: info,
+        raw_value: raw,
+        value: value,
+    }
+}
+
+fn decode_blob(name: &str, data: &[u8]) -> Blob {
+    let decoded = match name {
+        "MODE_ID" if data.len() == mem::size_of::<drm_ffi::drm_mode_modeinfo>() => {
+            let mode: drm_ffi::drm_mode_modeinfo =
+                unsafe { std::ptr::read_unaligned(data.as_ptr() as *const _) };
+            Some(Blob::Mode(mode.into()))
+        }
+        "IN_FORMATS" => in_formats(data).map(Blob::InFormats),
+        "EDID" => decode_edid(data).map(Blob::Edid),
+        "PATH" => connector::Path::from_blob(data).map(|path| {
+            let ports: Vec<String> = path.ports().iter().map(|p| p.to_string()).collect();
+            Blob::Path(format!("mst:{}-{}", path.parent(), ports.join("-")))
+        }),
+        "TILE" => connector::Tile::from_blob(data).map(Blob::Tile),
+        _ => None,
+    };
+
+    decoded.unwrap_or(Blob::Data(data.len()))
+}
+
+/// Decodes a `drm_format_modifier_blob`.
+fn in_formats(data: &[u8]) -> Option<Vec<FormatModifier>> {
+    let u32_at = |offset: usize| -> Option<u32> {
+        data.get(offset..offset + 4)
+            .map(|b| u32::from(b[0]) | u32::from(b[1]) << 8 | u32::from(b[2]) << 16 | u32::from(b[3]) << 24)
+    };
+    let u64_at = |offset: usize| -> Option<u64> {
+        Some(u64::from(u32_at(offset)?) | u64::from(u32_at(offset + 4)?) << 32)
+    };
+
+    let count_formats = u32_at(8)? as usize;
+    let formats_offset = u32_at(12)? as usize;
+    let count_modifiers = u32_at(16)? as usize;
+    let modifiers_offset = u32_at(20)? as usize;
+
+    let formats = (0..count_formats)
+        .map(|i| u32_at(formats_offset + i * 4))
+        .collect::<Option<Vec<u32>>>()?;
+
+    (0..count_modifiers)
+        .map(|i| {
+            // struct drm_format_modifier { u64 formats; u32 offset; u32 pad; u64 modifier; }
+            let base = modifiers_offset + i * 24;
+            let mask = u64_at(base)?;
+            let offset = u32_at(base + 8)? as usize;
+            let modifier = u64_at(base + 16)?;
+
+            Some(FormatModifier {
+                modifier: modifier,
+                formats: (0..64)
+                    .filter(|bit| mask & (1 << bit) != 0)
+                    .filter_map(|bit| formats.get(offset + bit))
+                    .map(|&format| Fourcc::from(format).to_string())
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+fn decode_edid(data: &[u8]) -> Option<Edid> {
+    let identity = edid::identity(data)?;
+
+    let hdr_eotfs = match edid::hdr_static_metadata(data) {
+        Some(hdr) => [
+            (hdr.supports_traditional_sdr(), "SDR"),
+            (hdr.supports_traditional_hdr(), "HDR"),
+            (hdr.supports_pq(), "PQ"),
+            (hdr.supports_hlg(), "HLG"),
+        ]
+        .iter()
+        .filter(|e| e.0)
+        .map(|e| e.1)
+        .collect(),
+        None => Vec::new(),
+    };
+
+    Some(Edid {
+        identity: identity,
+        hdr_eotfs: hdr_eotfs,
+    })
+}
+
+fn framebuffer(card: &Card, handle: drm::control::framebuffer::Handle) -> Option<Framebuffer> {
+    match card.get_planar_framebuffer(handle) {
+        Ok(info) => {
+            let planes = info.pitches().iter().take_while(|&&pitch| pitch != 0).count();
+            let fb = Framebuffer {
+                id: id(handle),
+                size: info.size(),
+                format: Some(Fourcc::from(info.raw_format()).to_string()),
+                modifier: info.modifier(),
+                pitches: info.pitches()[..planes].to_vec(),
+                offsets: info.offsets()[..planes].to_vec(),
+            };
+            Some(fb)
+        }
+        // Kernels without GETFB2 only describe single-planar framebuffers.
+        Err(_) => card.get_framebuffer(handle).ok().map(|info| Framebuffer {
+            id: id(handle),
+            size: info.size(),
+            format: None,
+            modifier: None,
+            pitches: vec![info.pitch()],
+            offsets: vec![0],
+        }),
+    }
+}
+
+/// A line of output along with the lines nested below it.
+struct Tree {
+    text: String,
+    children: Vec<Tree>,
+}
+
+impl Tree {
+    fn new<S: Into<String>>(text: S) -> Tree {
+        Tree {
+            text: text.into(),
+            children: Vec::new(),
+        }
+    }
+
+    fn leaf<S: Into<String>>(&mut self, text: S) -> &mut Tree {
+        self.children.push(Tree::new(text));
+        self.children.last_mut().unwrap()
+    }
+
+    fn print(&self) {
+        println!("{}", self.text);
+        self.print_children("");
+    }
+
+    fn print_children(&self, prefix: &str) {
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == self.children.len();
+            println!("{}{}{}", prefix, if last { "└───" } else { "├───" }, child.text);
+            child.print_children(&format!("{}{}", prefix, if last { "    " } else { "│   " }));
+        }
+    }
+}
+
+fn print_node(node: &Node) {
+    let mut tree = Tree::new(node.path.as_str());
+
+    {
+        let driver = &node.driver;
+        let (major, minor, patch) = driver.version();
+        let caps = tree.leaf(format!(
+            "Driver: {} ({}) version {}.{}.{} ({})",
+            driver.name().to_string_lossy(),
+            driver.description().to_string_lossy(),
+            major,
+            minor,
+            patch,
+            driver.date().to_string_lossy()
+        ));
+
+        for cap in &node.capabilities {
+            match cap.value {
+                Some(value) => caps.leaf(format!("{:?} = {}", cap.capability, value)),
+                None => caps.leaf(format!("{:?} unsupported", cap.capability)),
+            };
+        }
+    }
+
+    {
+        let connectors = tree.leaf("Connectors");
+        for conn in &node.connectors {
+            let info = &conn.info;
+            let t = connectors.leaf(format!("Connector {} {}", id(info.handle()), conn.name));
+            t.leaf(format!("Status: {:?}", info.state()));
+            if let Some((w, h)) = info.size() {
+                t.leaf(format!("Physical size: {}x{} mm", w, h));
+            }
+            t.leaf(format!("Subpixel: {:?}", info.subpixel()));
+            t.leaf(format!("Encoders: {:?}", info.encoders().iter().map(|&e| id(e)).collect::<Vec<_>>()));
+
+            if !info.modes().is_empty() {
+                let modes = t.leaf("Modes");
+                for mode in info.modes() {
+                    modes.leaf(mode_line(mode));
+                }
+            }
+
+            tree_properties(t, &conn.properties);
+        }
+    }
+
+    {
+        let encoders = tree.leaf("Encoders");
+        for info in &node.encoders {
+            let t = encoders.leaf(format!("Encoder {}", id(info.handle())));
+            t.leaf(format!("Type: {:?}", info.kind()));
+            t.leaf(format!("CRTC: {}", info.crtc().map_or(0, id)));
+        }
+    }
+
+    {
+        let crtcs = tree.leaf("CRTCs");
+        for crtc in &node.crtcs {
+            let info = &crtc.info;
+            let t = crtcs.leaf(format!("CRTC {}", id(info.handle())));
+            match info.mode() {
+                Some(ref mode) => t.leaf(format!("Mode: {}", mode_line(mode))),
+                None => t.leaf("Mode: none"),
+            };
+            t.leaf(format!("Position: {:?}", info.position()));
+            t.leaf(format!("Gamma size: {}", info.gamma_length()));
+            tree_properties(t, &crtc.properties);
+        }
+    }
+
+    {
+        let planes = tree.leaf("Planes");
+        for plane in &node.planes {
+            let info = &plane.info;
+            let t = planes.leaf(format!("Plane {}", id(info.handle())));
+            t.leaf(format!("CRTC: {}", info.crtc().map_or(0, id)));
+            t.leaf(format!(
+                "Formats: {}",
+                info.formats().iter().map(|&f| Fourcc::from(f).to_string()).collect::<Vec<_>>().join(" ")
+            ));
+
+            match plane.framebuffer {
+                Some(ref fb) => {
+                    let f = t.leaf(format!("FB: {}", fb.id));
+                    f.leaf(format!("Size: {}x{}", fb.size.0, fb.size.1));
+                    if let Some(ref format) = fb.format {
+                        f.leaf(format!("Format: {}", format));
+                    }
+                    if let Some(modifier) = fb.modifier {
+                        f.leaf(format!("Modifier: {}", Modifier::from(modifier)));
+                    }
+                    for (i, (pitch, offset)) in fb.pitches.iter().zip(&fb.offsets).enumerate() {
+                        f.leaf(format!("Plane {}: pitch {}, offset {}", i, pitch, offset));
+                    }
+                }
+                None => {
+                    t.leaf("FB: none");
+                }
+            }
+
+            tree_properties(t, &plane.properties);
+        }
+    }
+
+    tree.print();
+}
+
+fn tree_properties(tree: &mut Tree, properties: &[Property]) {
+    if properties.is_empty() {
+        return;
+    }
+
+    let tree = tree.leaf("Properties");
+    for prop in properties {
+        let info = &prop.info;
+
+        let mut flags = Vec::new();
+        if info.atomic() {
+            flags.push("atomic");
+        }
+        if !info.mutable() {
+            flags.push("immutable");
+        }
+        let flags = if flags.is_empty() { String::new() } else { format!(" ({})", flags.join(", ")) };
+
+        let kind = match info.value_type() {
+            property::ValueType::UnsignedRange(min, max) => format!("range [{}, {}]", min, max),
+            property::ValueType::SignedRange(min, max) => format!("signed range [{}, {}]", min, max),
+            property::ValueType::Enum(values) => {
+                let (_, enums) = values.values();
+                let names: Vec<_> = enums.iter().map(|e| e.name().to_string_lossy().into_owned()).collect();
+                format!("enum {{{}}}", names.join(", "))
+            }
+            value_type => format!("{:?}", value_type).to_lowercase(),
+        };
+
+        let value = match prop.value {
+            Value::Unknown => prop.raw_value.to_string(),
+            Value::Boolean(v) => v.to_string(),
+            Value::Unsigned(v) => v.to_string(),
+            Value::Signed(v) => v.to_string(),
+            Value::Enum(ref name) => name.clone().unwrap_or_else(|| prop.raw_value.to_string()),
+            Value::Bitmask(v) => format!("0x{:x}", v),
+            Value::Object(v) => v.to_string(),
+            Value::Blob(_) => prop.raw_value.to_string(),
+        };
+
+        let t = tree.leaf(format!(
+            "\"{}\"{}: {} = {}",
+            info.name().to_string_lossy(),
+            flags,
+            kind,
+            value
+        ));
+
+        if let Value::Blob(Some(ref blob)) = prop.value {
+            tree_blob(t, blob);
+        }
+    }
+}
+
+fn tree_blob(tree: &mut Tree, blob: &Blob) {
+    match *blob {
+        Blob::Mode(ref mode) => {
+            tree.leaf(mode_line(mode));
+        }
+        Blob::InFormats(ref modifiers) => {
+            for m in modifiers {
+                tree.leaf(format!("{}: {}", Modifier::from(m.modifier), m.formats.join(" ")));
+            }
+        }
+        Blob::Edid(ref edid) => {
+            let identity = &edid.identity;
+            tree.leaf(format!(
+                "{} {:04x} {}, {}",
+                identity.manufacturer(),
+                identity.product(),
+                identity.name().unwrap_or(""),
+                identity.year()
+            ));
+            if identity.serial() != 0 {
+                tree.leaf(format!("Serial: {}", identity.serial()));
+            }
+            if !edid.hdr_eotfs.is_empty() {
+                tree.leaf(format!("EOTFs: {}", edid.hdr_eotfs.join(" ")));
+            }
+        }
+        Blob::Path(ref path) => {
+            tree.leaf(path.as_str());
+        }
+        Blob::Tile(tile) => {
+            tree.leaf(format!(
+                "Group {}: tile {:?} of {:?}, size {:?}",
+                tile.group_id(),
+                tile.location(),
+                tile.num_tiles(),
+                tile.size()
+            ));
+        }
+        Blob::Data(len) => {
+            tree.leaf(format!("{} bytes", len));
+        }
+    }
+}
+
+fn mode_line(mode: &Mode) -> String {
+    let raw: drm_ffi::drm_mode_modeinfo = (*mode).into();
+    let (w, h) = mode.size();
+
+    let mut line = format!(
+        "{}x{}@{} {} kHz, {:?}",
+        w,
+        h,
+        mode.vrefresh(),
+        mode.clock(),
+        mode.name()
+    );
+
+    if raw.type_ & drm_ffi::DRM_MODE_TYPE_PREFERRED != 0 {
+        line.push_str(" preferred");
+    }
+    if raw.flags & drm_ffi::DRM_MODE_FLAG_INTERLACE != 0 {
+        line.push_str(" interlace");
+    }
+
+    line
+}
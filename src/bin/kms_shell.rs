@@ -0,0 +1,989 @@
+//! An interactive shell for inspecting and driving kernel modesetting.
+//!
+//! ```text
+//! kms_shell [DEVICE]
+//! ```
+//!
+//! Objects are referred to by id and properties by name, both of which are
+//! completed with tab. Property values may be numbers or enum names. Names
+//! containing spaces are quoted, e.g. `set 95 "Content Protection" Desired`.
+//!
+//! Atomic requests are built up with `add`, inspected with `request`, and
+//! checked with `test` before being applied with `commit`. Framebuffers can
+//! be created from PNG files with `load`, and `watch` prints the events of
+//! the device. Type `help` for the list of commands.
+
+extern crate drm;
+extern crate image;
+extern crate nix;
+extern crate rustyline;
+
+mod common;
+
+use common::*;
+
+use drm::buffer::convert::{self, Converter};
+use drm::buffer::format::PixelFormat;
+use drm::control::atomic::AtomicModeReq;
+use drm::control::damage::Rect;
+use drm::control::dumbbuffer::DumbBuffer;
+use drm::control::event::Event;
+use drm::control::hotplug::{self, HotplugMonitor};
+use drm::control::{connector, framebuffer, property, AtomicCommitFlags, Mode};
+use drm::control::Device as ControlDevice;
+use drm::{ClientCapability, Device};
+
+use nix::libc;
+
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, Instant};
+
+/// The kind of an argument, used for completion.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Arg {
+    /// Any object with properties
+    Object,
+    /// A connector
+    Connector,
+    /// A framebuffer
+    Framebuffer,
+    /// A property of the preceding object
+    Property,
+    /// A value of the preceding property
+    Value,
+    /// A mode of the preceding connector
+    Mode,
+    /// A path to a file
+    File,
+    /// Any number of the given flags
+    Flags(&'static [&'static str]),
+    /// Anything that cannot be completed
+    Other,
+}
+
+/// A command of the shell.
+struct Command {
+    name: &'static str,
+    args: &'static [Arg],
+    usage: &'static str,
+    help: &'static str,
+}
+
+const COMMIT_FLAGS: &[&str] = &["allow-modeset", "nonblock", "event"];
+
+const COMMANDS: &[Command] = &[
+    Command {
+        name: "help",
+        args: &[],
+        usage: "help",
+        help: "list the commands",
+    },
+    Command {
+        name: "objects",
+        args: &[],
+        usage: "objects",
+        help: "list the modesetting objects",
+    },
+    Command {
+        name: "props",
+        args: &[Arg::Object],
+        usage: "props OBJECT",
+        help: "list the properties of an object with their values",
+    },
+    Command {
+        name: "prop",
+        args: &[Arg::Object, Arg::Property],
+        usage: "prop OBJECT PROPERTY",
+        help: "describe a property of an object",
+    },
+    Command {
+        name: "set",
+        args: &[Arg::Object, Arg::Property, Arg::Value],
+        usage: "set OBJECT PROPERTY VALUE",
+        help: "set a property through the legacy interface",
+    },
+    Command {
+        name: "modes",
+        args: &[Arg::Connector],
+        usage: "modes CONNECTOR",
+        help: "list the modes of a connector",
+    },
+    Command {
+        name: "modeblob",
+        args: &[Arg::Connector, Arg::Mode],
+        usage: "modeblob CONNECTOR MODE",
+        help: "create a blob for MODE_ID from a mode given by name or #index",
+    },
+    Command {
+        name: "load",
+        args: &[Arg::File],
+        usage: "load FILE",
+        help: "create a framebuffer from a PNG file",
+    },
+    Command {
+        name: "rmfb",
+        args: &[Arg::Framebuffer],
+        usage: "rmfb FRAMEBUFFER",
+        help: "destroy a framebuffer",
+    },
+    Command {
+        name: "add",
+        args: &[Arg::Object, Arg::Property, Arg::Value],
+        usage: "add OBJECT PROPERTY VALUE",
+        help: "add a property to the atomic request",
+    },
+    Command {
+        name: "request",
+        args: &[],
+        usage: "request",
+        help: "show the atomic request",
+    },
+    Command {
+        name: "clear",
+        args: &[],
+        usage: "clear",
+        help: "clear the atomic request",
+    },
+    Command {
+        name: "test",
+        args: &[Arg::Flags(COMMIT_FLAGS)],
+        usage: "test [allow-modeset]",
+        help: "check the atomic request without applying it",
+    },
+    Command {
+        name: "commit",
+        args: &[Arg::Flags(COMMIT_FLAGS)],
+        usage: "commit [allow-modeset] [nonblock] [event]",
+        help: "apply the atomic request and clear it",
+    },
+    Command {
+        name: "watch",
+        args: &[Arg::Other],
+        usage: "watch [SECONDS]",
+        help: "print the events of the device, for 10 seconds by default",
+    },
+    Command {
+        name: "quit",
+        args: &[],
+        usage: "quit",
+        help: "leave the shell",
+    },
+];
+
+/// A framebuffer created by the shell, backed by a dumb buffer.
+struct Image {
+    buffer: DumbBuffer,
+    fb: framebuffer::Handle,
+}
+
+/// A property to set in the atomic request.
+struct Assignment {
+    object: Object,
+    info: property::Info,
+    value: property::RawValue,
+}
+
+/// The state of the shell.
+struct Shell<'a> {
+    card: &'a Card,
+    atomic: bool,
+    request: Vec<Assignment>,
+    images: Vec<Image>,
+    blobs: Vec<u64>,
+}
+
+/// Completes commands, object ids, property names and values.
+struct ShellHelper<'a> {
+    card: &'a Card,
+    files: FilenameCompleter,
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path: Result<PathBuf, String> = match (args.next(), args.next()) {
+        (Some(ref arg), _) if arg == "-h" || arg == "--help" => {
+            println!("usage: kms_shell [DEVICE]");
+            return;
+        }
+        (Some(path), None) => Ok(path.into()),
+        (None, None) => cards().and_then(|cards| cards.into_iter().next().ok_or_else(|| "no DRM device found".to_string())),
+        _ => Err("usage: kms_shell [DEVICE]".to_string()),
+    };
+
+    if let Err(e) = path.and_then(Card::open).and_then(|card| run(&card)) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn run(card: &Card) -> Result<(), String> {
+    card.set_client_capability(ClientCapability::UniversalPlanes, true)
+        .map_err(|e| format!("failed to enable universal planes: {}", e))?;
+    let atomic = card.set_client_capability(ClientCapability::Atomic, true).is_ok();
+    if !atomic {
+        println!("atomic modesetting is not supported, atomic requests are disabled");
+    }
+
+    let config = Config::builder()
+        .max_history_size(256)
+        .map_err(|e| e.to_string())?
+        .completion_type(CompletionType::List)
+        .auto_add_history(true)
+        .build();
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::with_config(config).map_err(|e| e.to_string())?;
+    editor.set_helper(Some(ShellHelper {
+        card: card,
+        files: FilenameCompleter::new(),
+    }));
+
+    let mut shell = Shell {
+        card: card,
+        atomic: atomic,
+        request: Vec::new(),
+        images: Vec::new(),
+        blobs: Vec::new(),
+    };
+
+    let result = loop {
+        let line = match editor.readline("kms> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break Ok(()),
+            Err(e) => break Err(e.to_string()),
+        };
+
+        let (words, open_quote) = split_words(&line);
+        if open_quote {
+            println!("error: unterminated quote");
+            continue;
+        }
+
+        let args: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        match args.first() {
+            Some(&"quit") => break Ok(()),
+            Some(_) => {
+                if let Err(e) = shell.execute(&args) {
+                    println!("error: {}", e);
+                }
+            }
+            None => (),
+        }
+    };
+
+    shell.cleanup();
+    result
+}
+
+impl<'a> Shell<'a> {
+    fn execute(&mut self, args: &[&str]) -> Result<(), String> {
+        let command = COMMANDS
+            .iter()
+            .find(|c| c.name == args[0])
+            .ok_or_else(|| format!("unknown command: {}, see help", args[0]))?;
+
+        let max = match command.args.last() {
+            Some(&Arg::Flags(_)) => usize::MAX,
+            _ => command.args.len(),
+        };
+        let min = command.args.iter().filter(|a| !matches!(**a, Arg::Flags(_) | Arg::Other)).count();
+        let args = &args[1..];
+        if args.len() < min || args.len() > max {
+            return Err(format!("usage: {}", command.usage));
+        }
+
+        match command.name {
+            "help" => self.help(),
+            "objects" => self.objects(),
+            "props" => self.props(args[0]),
+            "prop" => self.prop(args[0], args[1]),
+            "set" => self.set(args[0], args[1], args[2]),
+            "modes" => self.modes(args[0]),
+            "modeblob" => self.mode_blob(args[0], args[1]),
+            "load" => self.load(args[0]),
+            "rmfb" => self.remove_framebuffer(args[0]),
+            "add" => self.add(args[0], args[1], args[2]),
+            "request" => self.show_request(),
+            "clear" => {
+                self.request.clear();
+                Ok(())
+            }
+            "test" => self.commit(args, true),
+            "commit" => self.commit(args, false),
+            "watch" => self.watch(args.first().cloned()),
+            _ => unreachable!(),
+        }
+    }
+
+    fn help(&self) -> Result<(), String> {
+        for command in COMMANDS {
+            println!("  {:<42} {}", command.usage, command.help);
+        }
+        Ok(())
+    }
+
+    fn objects(&self) -> Result<(), String> {
+        for object in objects(self.card)? {
+            match object {
+                Object::Connector(handle) => {
+                    let info = self
+                        .card
+                        .get_connector(handle, false)
+                        .map_err(|e| format!("failed to get {}: {}", object, e))?;
+                    println!("  {} ({}, {:?})", object, info.name(), info.state());
+                }
+                _ => println!("  {}", object),
+            }
+        }
+        Ok(())
+    }
+
+    fn props(&self, object: &str) -> Result<(), String> {
+        let object = parse_object(self.card, object)?;
+
+        for (info, value) in object.properties(self.card)? {
+            println!(
+                "  {} ({}) = {}",
+                info.name().to_string_lossy(),
+                id(info.handle()),
+                format_value(&info, value)
+            );
+        }
+        Ok(())
+    }
+
+    fn prop(&self, object: &str, name: &str) -> Result<(), String> {
+        let object = parse_object(self.card, object)?;
+        let (info, value) = find_property(self.card, object, name)?;
+
+        println!("  id: {}", id(info.handle()));
+        println!("  type: {}", describe_type(&info.value_type()));
+        println!("  mutable: {}, atomic: {}", info.mutable(), info.atomic());
+        println!("  value: {}", format_value(&info, value));
+        Ok(())
+    }
+
+    fn set(&self, object: &str, name: &str, value: &str) -> Result<(), String> {
+        let object = parse_object(self.card, object)?;
+        let (info, _) = find_property(self.card, object, name)?;
+        let value = parse_value(&info, value)?;
+
+        object.set_property(self.card, info.handle(), value)
+    }
+
+    fn modes(&self, connector: &str) -> Result<(), String> {
+        let info = self.connector(connector)?;
+
+        for (i, mode) in info.modes().iter().enumerate() {
+            let (w, h) = mode.size();
+            println!(
+                "  #{:<3} {} {}x{}@{} {} kHz",
+                i,
+                mode.name().to_string_lossy(),
+                w,
+                h,
+                mode.vrefresh(),
+                mode.clock()
+            );
+        }
+        Ok(())
+    }
+
+    fn mode_blob(&mut self, connector: &str, mode: &str) -> Result<(), String> {
+        let info = self.connector(connector)?;
+        let mode = find_mode(&info, mode)?;
+        let blob: u64 = self
+            .card
+            .create_property_blob(&mode)
+            .map_err(|e| format!("failed to create mode blob: {}", e))?
+            .into();
+
+        self.blobs.push(blob);
+        println!("  created blob {}", blob);
+        Ok(())
+    }
+
+    fn load(&mut self, path: &str) -> Result<(), String> {
+        let image = image::open(path)
+            .map_err(|e| format!("failed to load {}: {}", path, e))?
+            .to_rgba8();
+        let size = image.dimensions();
+
+        let mut buffer = self
+            .card
+            .create_dumb_buffer(size, PixelFormat::ARGB8888)
+            .map_err(|e| format!("failed to create {}x{} buffer: {}", size.0, size.1, e))?;
+
+        // Decoded images are RGBA bytes, which is ABGR8888 in DRM terms.
+        let image_view = convert::Image::new(image.as_raw(), size, size.0 * 4, PixelFormat::ABGR8888);
+        let copied = self
+            .card
+            .map_dumb_buffer(&mut buffer)
+            .map_err(|e| format!("failed to map buffer: {}", e))
+            .and_then(|mut map| {
+                Converter::new(PixelFormat::ABGR8888, PixelFormat::ARGB8888)
+                    .and_then(|c| c.blit(&image_view, Rect::new(0, 0, size.0, size.1), &mut map, (0, 0)))
+                    .map_err(|e| format!("failed to copy image: {}", e))
+            });
+
+        let fb = copied.and_then(|_| {
+            self.card
+                .add_planar_framebuffer(&buffer, &[0; 4], 0)
+                .map_err(|e| format!("failed to create framebuffer: {}", e))
+        });
+
+        match fb {
+            Ok(fb) => {
+                println!("  created framebuffer {} ({}x{})", id(fb), size.0, size.1);
+                self.images.push(Image { buffer: buffer, fb: fb });
+                Ok(())
+            }
+            Err(e) => {
+                let _ = self.card.destroy_dumb_buffer(buffer);
+                Err(e)
+            }
+        }
+    }
+
+    fn remove_framebuffer(&mut self, fb: &str) -> Result<(), String> {
+        let fb = match parse_object(self.card, fb)? {
+            Object::Framebuffer(fb) => fb,
+            object => return Err(format!("{} is not a framebuffer", object)),
+        };
+
+        self.card
+            .destroy_framebuffer(fb)
+            .map_err(|e| format!("failed to destroy framebuffer {}: {}", id(fb), e))?;
+
+        if let Some(i) = self.images.iter().position(|image| image.fb == fb) {
+            let image = self.images.remove(i);
+            let _ = self.card.destroy_dumb_buffer(image.buffer);
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, object: &str, name: &str, value: &str) -> Result<(), String> {
+        if !self.atomic {
+            return Err("atomic modesetting is not supported".to_string());
+        }
+
+        let object = parse_object(self.card, object)?;
+        let (info, _) = find_property(self.card, object, name)?;
+        let value = parse_value(&info, value)?;
+
+        if !info.atomic() && !info.mutable() {
+            return Err(format!("property {} is immutable", name));
+        }
+
+        let existing = self
+            .request
+            .iter_mut()
+            .find(|a| a.object == object && a.info.handle() == info.handle());
+        match existing {
+            Some(assignment) => assignment.value = value,
+            None => self.request.push(Assignment {
+                object: object,
+                info: info,
+                value: value,
+            }),
+        }
+        Ok(())
+    }
+
+    fn show_request(&self) -> Result<(), String> {
+        if self.request.is_empty() {
+            println!("  the atomic request is empty");
+        }
+
+        let mut last = None;
+        for assignment in &self.request {
+            if last != Some(assignment.object) {
+                println!("  {}:", assignment.object);
+                last = Some(assignment.object);
+            }
+            println!(
+                "    {} = {}",
+                assignment.info.name().to_string_lossy(),
+                format_value(&assignment.info, assignment.value)
+            );
+        }
+        Ok(())
+    }
+
+    fn commit(&mut self, args: &[&str], test: bool) -> Result<(), String> {
+        if !self.atomic {
+            return Err("atomic modesetting is not supported".to_string());
+        }
+
+        let mut flags = Vec::new();
+        if test {
+            flags.push(AtomicCommitFlags::TestOnly);
+        }
+        for &arg in args {
+            flags.push(match arg {
+                "allow-modeset" => AtomicCommitFlags::AllowModeset,
+                "nonblock" if !test => AtomicCommitFlags::Nonblock,
+                "event" if !test => AtomicCommitFlags::PageFlipEvent,
+                _ => return Err(format!("invalid flag: {}", arg)),
+            });
+        }
+
+        let mut req = AtomicModeReq::new();
+        for assignment in &self.request {
+            req.add_raw_property(assignment.object.raw(), assignment.info.handle(), assignment.value);
+        }
+
+        self.card
+            .atomic_commit(&flags, req)
+            .map_err(|e| format!("atomic {} failed: {}", if test { "test" } else { "commit" }, e))?;
+
+        if test {
+            println!("  the atomic request is valid");
+        } else {
+            self.request.clear();
+        }
+        Ok(())
+    }
+
+    fn watch(&self, seconds: Option<&str>) -> Result<(), String> {
+        let seconds = match seconds {
+            Some(s) => s.parse().map_err(|_| format!("invalid duration: {}", s))?,
+            None => 10,
+        };
+        let deadline = Instant::now() + Duration::from_secs(seconds);
+        let monitor = HotplugMonitor::new(self.card).map_err(|e| format!("failed to monitor hotplug events: {}", e))?;
+
+        println!("  watching events for {} seconds", seconds);
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+
+            let timeout = deadline - now;
+            let mut fds = [
+                libc::pollfd {
+                    fd: self.card.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                libc::pollfd {
+                    fd: monitor.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+            ];
+            let timeout = (timeout.as_secs() * 1000 + u64::from(timeout.subsec_millis()) + 1) as libc::c_int;
+
+            if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) } < 0 {
+                let errno = nix::errno::Errno::last();
+                if errno == nix::errno::Errno::EINTR {
+                    continue;
+                }
+                return Err(format!("failed to wait for events: {}", errno));
+            }
+
+            if fds[0].revents & libc::POLLIN != 0 {
+                let events = self
+                    .card
+                    .receive_events()
+                    .map_err(|e| format!("failed to read events: {}", e))?;
+                for event in events {
+                    print_event(&event);
+                }
+            }
+
+            if fds[1].revents & libc::POLLIN != 0 {
+                while let Some(event) = monitor
+                    .next_event()
+                    .map_err(|e| format!("failed to read hotplug events: {}", e))?
+                {
+                    match event {
+                        hotplug::Event::Hotplug => println!("  hotplug"),
+                        hotplug::Event::Connector(c) => println!("  connector {} changed", id(c)),
+                        hotplug::Event::Property(c, p) => println!("  property {} of connector {} changed", id(p), id(c)),
+                        hotplug::Event::Lease => println!("  lease changed"),
+                    }
+                }
+            }
+        }
+    }
+
+    fn connector(&self, connector: &str) -> Result<connector::Info, String> {
+        let handle = match parse_object(self.card, connector)? {
+            Object::Connector(handle) => handle,
+            object => return Err(format!("{} is not a connector", object)),
+        };
+
+        self.card
+            .get_connector(handle, false)
+            .map_err(|e| format!("failed to get connector {}: {}", connector, e))
+    }
+
+    /// Destroys everything the shell created.
+    fn cleanup(&mut self) {
+        for image in self.images.drain(..) {
+            let _ = self.card.destroy_framebuffer(image.fb);
+            let _ = self.card.destroy_dumb_buffer(image.buffer);
+        }
+        for blob in self.blobs.drain(..) {
+            let _ = self.card.destroy_property_blob(blob);
+        }
+    }
+}
+
+/// Parses an object id and looks up the object.
+fn parse_object(card: &Card, s: &str) -> Result<Object, String> {
+    let id = s.parse().map_err(|_| format!("invalid object id: {}", s))?;
+    find_object(card, id)
+}
+
+/// Finds a mode of a connector by name or `#index`.
+fn find_mode(info: &connector::Info, name: &str) -> Result<Mode, String> {
+    if let Some(index) = name.strip_prefix('#') {
+        let index: usize = index.parse().map_err(|_| format!("invalid mode index: {}", name))?;
+        return info
+            .modes()
+            .get(index)
+            .cloned()
+            .ok_or_else(|| format!("connector {} has no mode #{}", info.name(), index));
+    }
+
+    info.modes()
+        .iter()
+        .find(|mode| mode.name().to_bytes() == name.as_bytes())
+        .cloned()
+        .ok_or_else(|| format!("connector {} has no mode {}", info.name(), name))
+}
+
+fn format_value(info: &property::Info, value: property::RawValue) -> String {
+    match info.value_type() {
+        property::ValueType::Enum(_) => enum_name(info, value).unwrap_or_else(|| format!("{} (unknown)", value)),
+        property::ValueType::Boolean => (value != 0).to_string(),
+        property::ValueType::SignedRange(..) => (value as i64).to_string(),
+        property::ValueType::Bitmask => format!("{:#x}", value),
+        _ => value.to_string(),
+    }
+}
+
+fn describe_type(kind: &property::ValueType) -> String {
+    match *kind {
+        property::ValueType::Unknown => "unknown".to_string(),
+        property::ValueType::Boolean => "boolean".to_string(),
+        property::ValueType::UnsignedRange(min, max) => format!("range [{}, {}]", min, max),
+        property::ValueType::SignedRange(min, max) => format!("signed range [{}, {}]", min, max),
+        property::ValueType::Enum(ref values) => {
+            let (_, enums) = values.values();
+            let names: Vec<String> = enums
+                .iter()
+                .map(|e| format!("{}={}", e.name().to_string_lossy(), e.value()))
+                .collect();
+            format!("enum {{{}}}", names.join(", "))
+        }
+        property::ValueType::Bitmask => "bitmask".to_string(),
+        property::ValueType::Blob => "blob".to_string(),
+        property::ValueType::Object => "object".to_string(),
+        property::ValueType::CRTC => "CRTC".to_string(),
+        property::ValueType::Connector => "connector".to_string(),
+        property::ValueType::Encoder => "encoder".to_string(),
+        property::ValueType::Framebuffer => "framebuffer".to_string(),
+        property::ValueType::Plane => "plane".to_string(),
+        property::ValueType::Property => "property".to_string(),
+    }
+}
+
+fn print_event(event: &Event) {
+    let crtc = |crtc: Option<drm::control::crtc::Handle>| crtc.map_or("?".to_string(), |c| id(c).to_string());
+
+    match *event {
+        Event::Vblank(ref e) => println!(
+            "  vblank on CRTC {}: frame {} at {:?}",
+            crtc(e.crtc()),
+            e.frame(),
+            e.time()
+        ),
+        Event::PageFlip(ref e) => println!(
+            "  page flip on CRTC {}: frame {} at {:?}",
+            crtc(e.crtc()),
+            e.frame(),
+            e.time()
+        ),
+        Event::Unknown(ref data) => println!("  unknown event of {} bytes", data.len()),
+    }
+}
+
+/// A word of a command line, with its quotes and escapes removed.
+#[derive(Debug, PartialEq, Eq)]
+struct Word {
+    text: String,
+    /// Byte offsets of the word in the line, including its quotes
+    start: usize,
+    end: usize,
+}
+
+/// Splits a command line into words, the way a shell does.
+///
+/// Words are separated by whitespace, which can be quoted with `"` or `'`.
+/// Outside of single quotes, a backslash escapes the following character.
+/// Also returns whether the line ends within a quote.
+fn split_words(line: &str) -> (Vec<Word>, bool) {
+    let mut words = Vec::new();
+    let mut word: Option<Word> = None;
+    let mut quote = None;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if quote.is_none() && c.is_whitespace() {
+            words.extend(word.take());
+            continue;
+        }
+
+        let current = word.get_or_insert_with(|| Word {
+            text: String::new(),
+            start: i,
+            end: i,
+        });
+
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some('\''), c) => current.text.push(c),
+            (_, '\\') => {
+                if let Some((_, escaped)) = chars.next() {
+                    current.text.push(escaped);
+                }
+            }
+            (_, c) => current.text.push(c),
+        }
+
+        current.end = chars.peek().map_or(line.len(), |&(next, _)| next);
+    }
+
+    words.extend(word);
+    (words, quote.is_some())
+}
+
+/// Quotes a completion candidate, if it is not a single word as it is.
+fn quote_word(word: &str) -> String {
+    let plain = !word.is_empty() && !word.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == '\\');
+    if plain {
+        return word.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for c in word.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl<'a> ShellHelper<'a> {
+    /// Returns the candidates for an argument of a command, given the
+    /// preceding arguments.
+    fn candidates(&self, kind: Arg, args: &[&str]) -> Vec<String> {
+        let object = |i: usize| args.get(i).and_then(|s| parse_object(self.card, s).ok());
+
+        match kind {
+            Arg::Object | Arg::Connector | Arg::Framebuffer => objects(self.card)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|o| {
+                    matches!(
+                        (kind, *o),
+                        (Arg::Connector, Object::Connector(_)) | (Arg::Framebuffer, Object::Framebuffer(_)) | (Arg::Object, _)
+                    )
+                })
+                .map(|o| o.id().to_string())
+                .collect(),
+            Arg::Property => object(0)
+                .and_then(|o| o.properties(self.card).ok())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(info, _)| info.name().to_string_lossy().into_owned())
+                .collect(),
+            Arg::Value => {
+                let info = match (object(0), args.get(1)) {
+                    (Some(o), Some(name)) => find_property(self.card, o, name).ok(),
+                    _ => None,
+                };
+
+                match info.map(|(info, _)| info.value_type()) {
+                    Some(property::ValueType::Enum(values)) => values
+                        .values()
+                        .1
+                        .iter()
+                        .map(|e| e.name().to_string_lossy().into_owned())
+                        .collect(),
+                    Some(property::ValueType::Boolean) => vec!["false".to_string(), "true".to_string()],
+                    Some(property::ValueType::CRTC) => self.ids(|o| matches!(o, Object::Crtc(_))),
+                    Some(property::ValueType::Framebuffer) => self.ids(|o| matches!(o, Object::Framebuffer(_))),
+                    _ => Vec::new(),
+                }
+            }
+            Arg::Mode => match object(0) {
+                Some(Object::Connector(handle)) => self
+                    .card
+                    .get_connector(handle, false)
+                    .map(|info| {
+                        let mut names: Vec<String> = info
+                            .modes()
+                            .iter()
+                            .map(|m| m.name().to_string_lossy().into_owned())
+                            .collect();
+                        names.dedup();
+                        names
+                    })
+                    .unwrap_or_default(),
+                _ => Vec::new(),
+            },
+            Arg::Flags(flags) => flags
+                .iter()
+                .filter(|f| !args.contains(f))
+                .map(|f| f.to_string())
+                .collect(),
+            Arg::File | Arg::Other => Vec::new(),
+        }
+    }
+
+    /// Returns the ids of the objects matching a filter, plus `0` for none.
+    fn ids<F: Fn(Object) -> bool>(&self, filter: F) -> Vec<String> {
+        let mut ids = vec!["0".to_string()];
+        ids.extend(
+            objects(self.card)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&o| filter(o))
+                .map(|o| o.id().to_string()),
+        );
+        ids
+    }
+}
+
+impl<'a> Completer for ShellHelper<'a> {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, ctx: &Context) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let (mut words, _) = split_words(before);
+
+        // The word being completed, unless the cursor follows whitespace.
+        let (start, word) = match words.last().map(|w| w.end) {
+            Some(end) if end == before.len() => {
+                let word = words.pop().unwrap();
+                (word.start, word.text)
+            }
+            _ => (pos, String::new()),
+        };
+        let words: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+
+        let candidates = if words.is_empty() {
+            COMMANDS.iter().map(|c| c.name.to_string()).collect()
+        } else {
+            let command = match COMMANDS.iter().find(|c| c.name == words[0]) {
+                Some(command) => command,
+                None => return Ok((start, Vec::new())),
+            };
+            let args = &words[1..];
+            let kind = match command.args.get(args.len()).or_else(|| command.args.last()) {
+                Some(&Arg::Flags(flags)) => Arg::Flags(flags),
+                Some(&kind) if args.len() < command.args.len() => kind,
+                _ => return Ok((start, Vec::new())),
+            };
+
+            if kind == Arg::File {
+                return self.files.complete(line, pos, ctx);
+            }
+            self.candidates(kind, args)
+        };
+
+        let pairs = candidates
+            .into_iter()
+            .filter(|c| c.starts_with(&word))
+            .map(|c| Pair {
+                replacement: quote_word(&c),
+                display: c,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl<'a> Hinter for ShellHelper<'a> {
+    type Hint = String;
+}
+
+impl<'a> Highlighter for ShellHelper<'a> {}
+
+impl<'a> Validator for ShellHelper<'a> {}
+
+impl<'a> Helper for ShellHelper<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(line: &str) -> Vec<String> {
+        split_words(line).0.into_iter().map(|w| w.text).collect()
+    }
+
+    #[test]
+    fn split_plain_words() {
+        assert_eq!(texts("  set 95  DPMS\tOn "), vec!["set", "95", "DPMS", "On"]);
+        assert!(texts("   ").is_empty());
+    }
+
+    #[test]
+    fn split_quoted_words() {
+        assert_eq!(texts(r#"set 95 "Content Protection" Desired"#), vec!["set", "95", "Content Protection", "Desired"]);
+        assert_eq!(texts("set 95 'max bpc' 8"), vec!["set", "95", "max bpc", "8"]);
+        assert_eq!(texts(r#"set 95 "scaling mode" "Full aspect""#)[3], "Full aspect");
+        assert_eq!(texts(r#"a"b c"d"#), vec!["ab cd"]);
+        assert_eq!(texts(r#"x "" y"#), vec!["x", "", "y"]);
+    }
+
+    #[test]
+    fn split_escapes() {
+        assert_eq!(texts(r"set 95 HDCP\ Content\ Type 1"), vec!["set", "95", "HDCP Content Type", "1"]);
+        assert_eq!(texts(r#""a \"b\" \\""#), vec![r#"a "b" \"#]);
+        assert_eq!(texts(r"'a\b'"), vec![r"a\b"]);
+    }
+
+    #[test]
+    fn split_open_quote() {
+        let (words, open) = split_words(r#"prop 95 "max b"#);
+        assert!(open);
+        assert_eq!(words[2], Word { text: "max b".to_string(), start: 8, end: 14 });
+
+        assert!(!split_words(r#"prop 95 "max bpc""#).1);
+    }
+
+    #[test]
+    fn word_offsets() {
+        let (words, _) = split_words(r#"set "max bpc" "#);
+        assert_eq!(words[1], Word { text: "max bpc".to_string(), start: 4, end: 13 });
+    }
+
+    #[test]
+    fn quoted_candidates_split_back() {
+        for name in &["max bpc", "Content Protection", "DPMS", r#"odd "name""#, r"back\slash", ""] {
+            let quoted = quote_word(name);
+            assert_eq!(texts(&quoted), vec![name.to_string()], "{}", quoted);
+        }
+
+        assert_eq!(quote_word("DPMS"), "DPMS");
+        assert_eq!(quote_word("max bpc"), r#""max bpc""#);
+    }
+}
@@ -0,0 +1,630 @@
+//! Sets modes, planes and properties from the command line.
+//!
+//! ```text
+//! modetest [-D DEVICE] [-a] [-v]
+//!          [-s CONNECTOR[@CRTC]:MODE[-VREFRESH][@FORMAT]]...
+//!          [-P PLANE@CRTC:WxH[+X+Y][@FORMAT]]...
+//!          [-w OBJECT:PROPERTY:VALUE]...
+//! ```
+//!
+//! Connectors are given by id or name (`HDMI-A-1`), modes by name
+//! (`1920x1080`) or index (`#0`), and formats as fourcc codes (`XR24`).
+//! Property values may be numbers or enum names.
+//!
+//! Every mode and plane shows a test pattern until enter is pressed. With
+//! `-v`, the framebuffers of the modes are flipped continuously and the
+//! achieved refresh rate is reported.
+
+extern crate drm;
+
+mod common;
+
+use common::*;
+
+use drm::buffer::format::PixelFormat;
+use drm::buffer::Buffer;
+use drm::control::atomic::AtomicModeReq;
+use drm::control::dumbbuffer::DumbBuffer;
+use drm::control::event::Event;
+use drm::control::{connector, crtc, framebuffer, plane, property, AtomicCommitFlags, Mode, PageFlipFlags, PlaneType};
+use drm::control::Device as ControlDevice;
+use drm::{ClientCapability, Device};
+
+use std::io::{self, BufRead};
+use std::process;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "usage: modetest [-D DEVICE] [-a] [-v]
+                [-s CONNECTOR[@CRTC]:MODE[-VREFRESH][@FORMAT]]...
+                [-P PLANE@CRTC:WxH[+X+Y][@FORMAT]]...
+                [-w OBJECT:PROPERTY:VALUE]...";
+
+/// A mode to set, as given with `-s`.
+#[derive(Debug)]
+struct ModeArg {
+    connector: String,
+    crtc: Option<u32>,
+    mode: String,
+    vrefresh: Option<u32>,
+    format: PixelFormat,
+}
+
+/// A plane to enable, as given with `-P`.
+#[derive(Debug)]
+struct PlaneArg {
+    plane: u32,
+    crtc: u32,
+    size: (u32, u32),
+    position: (i32, i32),
+    format: PixelFormat,
+}
+
+/// A property to set, as given with `-w`.
+#[derive(Debug)]
+struct PropertyArg {
+    object: u32,
+    property: String,
+    value: String,
+}
+
+#[derive(Debug, Default)]
+struct Args {
+    device: Option<String>,
+    atomic: bool,
+    vsync_test: bool,
+    modes: Vec<ModeArg>,
+    planes: Vec<PlaneArg>,
+    properties: Vec<PropertyArg>,
+}
+
+/// A framebuffer backed by a dumb buffer.
+struct Image {
+    buffer: DumbBuffer,
+    fb: framebuffer::Handle,
+}
+
+/// A CRTC showing a mode.
+struct Pipe {
+    crtc: crtc::Handle,
+    primary: Option<plane::Handle>,
+    images: Vec<Image>,
+    front: usize,
+}
+
+fn main() {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(1);
+        }
+    };
+
+    if let Err(e) = run(&args) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut iter: I) -> Result<Args, String> {
+    let mut args = Args::default();
+
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| format!("missing argument for {}", arg));
+
+        match arg.as_str() {
+            "-D" => args.device = Some(value()?),
+            "-a" => args.atomic = true,
+            "-v" => args.vsync_test = true,
+            "-s" => args.modes.push(parse_mode_arg(&value()?)?),
+            "-P" => args.planes.push(parse_plane_arg(&value()?)?),
+            "-w" => args.properties.push(parse_property_arg(&value()?)?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    if args.modes.is_empty() && args.planes.is_empty() && args.properties.is_empty() {
+        return Err("nothing to do".to_string());
+    }
+
+    if args.vsync_test && args.modes.is_empty() {
+        return Err("-v requires a mode set with -s".to_string());
+    }
+
+    Ok(args)
+}
+
+/// Splits off an optional `@FORMAT` suffix.
+fn split_format(s: &str) -> Result<(&str, PixelFormat), String> {
+    match s.rfind('@') {
+        Some(i) => Ok((&s[..i], parse_format(&s[i + 1..])?)),
+        None => Ok((s, PixelFormat::XRGB8888)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str, what: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("invalid {}: {}", what, s))
+}
+
+fn parse_mode_arg(s: &str) -> Result<ModeArg, String> {
+    let colon = s.find(':').ok_or_else(|| format!("invalid mode: {}", s))?;
+    let (output, mode) = (&s[..colon], &s[colon + 1..]);
+
+    let (connector, crtc) = match output.find('@') {
+        Some(i) => (&output[..i], Some(parse_number(&output[i + 1..], "CRTC")?)),
+        None => (output, None),
+    };
+
+    let (mode, format) = split_format(mode)?;
+    let (mode, vrefresh) = match mode.rfind('-') {
+        Some(i) => (&mode[..i], Some(parse_number(&mode[i + 1..], "refresh rate")?)),
+        None => (mode, None),
+    };
+
+    Ok(ModeArg {
+        connector: connector.to_string(),
+        crtc: crtc,
+        mode: mode.to_string(),
+        vrefresh: vrefresh,
+        format: format,
+    })
+}
+
+fn parse_plane_arg(s: &str) -> Result<PlaneArg, String> {
+    let invalid = || format!("invalid plane: {}", s);
+
+    let colon = s.find(':').ok_or_else(invalid)?;
+    let (ids, geometry) = (&s[..colon], &s[colon + 1..]);
+
+    let at = ids.find('@').ok_or_else(invalid)?;
+    let plane = parse_number(&ids[..at], "plane")?;
+    let crtc = parse_number(&ids[at + 1..], "CRTC")?;
+
+    let (geometry, format) = split_format(geometry)?;
+    let (size, position) = match geometry.find(['+', '-']) {
+        Some(i) => (&geometry[..i], &geometry[i..]),
+        None => (geometry, ""),
+    };
+
+    let x = size.find('x').ok_or_else(invalid)?;
+    let size = (parse_number(&size[..x], "width")?, parse_number(&size[x + 1..], "height")?);
+
+    let position = if position.is_empty() {
+        (0, 0)
+    } else {
+        // Each coordinate carries its own sign, e.g. `+10-20`.
+        let split = position[1..].find(['+', '-']).ok_or_else(invalid)? + 1;
+        let coord = |s: &str| parse_number::<i32>(s.trim_start_matches('+'), "position");
+        (coord(&position[..split])?, coord(&position[split..])?)
+    };
+
+    Ok(PlaneArg {
+        plane: plane,
+        crtc: crtc,
+        size: size,
+        position: position,
+        format: format,
+    })
+}
+
+fn parse_property_arg(s: &str) -> Result<PropertyArg, String> {
+    let mut parts = s.splitn(3, ':');
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(object), Some(property), Some(value)) => Ok(PropertyArg {
+            object: parse_number(object, "object")?,
+            property: property.to_string(),
+            value: value.to_string(),
+        }),
+        _ => Err(format!("invalid property: {}", s)),
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let path = match args.device {
+        Some(ref path) => path.clone().into(),
+        None => cards()?.into_iter().next().ok_or("no DRM device found")?,
+    };
+    let card = Card::open(&path)?;
+
+    card.set_client_capability(ClientCapability::UniversalPlanes, true)
+        .map_err(|e| format!("failed to enable universal planes: {}", e))?;
+    if args.atomic {
+        card.set_client_capability(ClientCapability::Atomic, true)
+            .map_err(|e| format!("atomic modesetting is not supported: {}", e))?;
+    }
+
+    let mut req = AtomicModeReq::new();
+    let mut pipes = Vec::new();
+    let mut overlays = Vec::new();
+    let mut blobs = Vec::new();
+
+    let result = setup(&card, args, &mut req, &mut pipes, &mut overlays, &mut blobs).and_then(|_| {
+        if args.atomic {
+            card.atomic_commit(&[AtomicCommitFlags::AllowModeset], req)
+                .map_err(|e| format!("atomic commit failed: {}", e))?;
+        }
+
+        if args.vsync_test {
+            flip_loop(&card, args.atomic, &mut pipes)
+        } else {
+            println!("press enter to exit");
+            let mut line = String::new();
+            io::stdin().lock().read_line(&mut line).map(|_| ()).map_err(|e| e.to_string())
+        }
+    });
+
+    for pipe in pipes {
+        for image in pipe.images {
+            destroy_image(&card, image);
+        }
+    }
+    for image in overlays {
+        destroy_image(&card, image);
+    }
+    for blob in blobs {
+        let _ = card.destroy_property_blob(blob);
+    }
+
+    result
+}
+
+fn setup(
+    card: &Card,
+    args: &Args,
+    req: &mut AtomicModeReq,
+    pipes: &mut Vec<Pipe>,
+    overlays: &mut Vec<Image>,
+    blobs: &mut Vec<u64>,
+) -> Result<(), String> {
+    let res = card
+        .resource_handles()
+        .map_err(|e| format!("failed to get resources: {}", e))?;
+
+    for arg in &args.modes {
+        let connector = find_connector(card, &arg.connector)?;
+        let info = card
+            .get_connector(connector, false)
+            .map_err(|e| format!("failed to get connector {}: {}", arg.connector, e))?;
+        let mode = find_mode(&info, &arg.mode, arg.vrefresh)?;
+
+        let taken: Vec<crtc::Handle> = pipes.iter().map(|p| p.crtc).collect();
+        let crtc = match arg.crtc {
+            Some(id) => match find_object(card, id)? {
+                Object::Crtc(crtc) => crtc,
+                object => return Err(format!("{} is not a CRTC", object)),
+            },
+            None => find_crtc(card, &res, &info, &taken)?,
+        };
+
+        let (w, h) = mode.size();
+        let images = vec![
+            create_image(card, (w.into(), h.into()), arg.format, 0)?,
+            create_image(card, (w.into(), h.into()), arg.format, 1)?,
+        ];
+
+        let mut pipe = Pipe {
+            crtc: crtc,
+            primary: None,
+            images: images,
+            front: 0,
+        };
+
+        if args.atomic {
+            let primary = find_primary_plane(card, &res, crtc)?;
+            let blob = card
+                .create_property_blob(&mode)
+                .map_err(|e| format!("failed to create mode blob: {}", e))?;
+            blobs.push(blob.into());
+
+            add(card, req, Object::Connector(connector), "CRTC_ID", id(crtc).into())?;
+            add(card, req, Object::Crtc(crtc), "MODE_ID", blob.into())?;
+            add(card, req, Object::Crtc(crtc), "ACTIVE", 1)?;
+            add_plane(card, req, primary, crtc, pipe.images[0].fb, (0, 0), (w.into(), h.into()))?;
+            pipe.primary = Some(primary);
+        } else {
+            card.set_crtc(crtc, Some(pipe.images[0].fb), (0, 0), &[connector], Some(mode))
+                .map_err(|e| format!("failed to set mode on CRTC {}: {}", id(crtc), e))?;
+        }
+
+        println!(
+            "setting mode {} on connector {}, CRTC {}",
+            mode.name().to_string_lossy(),
+            info.name(),
+            id(crtc)
+        );
+        pipes.push(pipe);
+    }
+
+    for arg in &args.planes {
+        let plane = match find_object(card, arg.plane)? {
+            Object::Plane(plane) => plane,
+            object => return Err(format!("{} is not a plane", object)),
+        };
+        let crtc = match find_object(card, arg.crtc)? {
+            Object::Crtc(crtc) => crtc,
+            object => return Err(format!("{} is not a CRTC", object)),
+        };
+
+        let image = create_image(card, arg.size, arg.format, 0)?;
+
+        if args.atomic {
+            add_plane(card, req, plane, crtc, image.fb, arg.position, arg.size)?;
+        } else {
+            card.set_plane(
+                plane,
+                crtc,
+                Some(image.fb),
+                0,
+                (arg.position.0, arg.position.1, arg.size.0, arg.size.1),
+                (0, 0, arg.size.0 << 16, arg.size.1 << 16),
+            )
+            .map_err(|e| format!("failed to set plane {}: {}", arg.plane, e))?;
+        }
+
+        println!("enabling plane {} on CRTC {}", arg.plane, arg.crtc);
+        overlays.push(image);
+    }
+
+    for arg in &args.properties {
+        let object = find_object(card, arg.object)?;
+        let (info, _) = find_property(card, object, &arg.property)?;
+        let value = parse_value(&info, &arg.value)?;
+
+        if args.atomic {
+            req.add_raw_property(object.raw(), info.handle(), value);
+        } else {
+            object.set_property(card, info.handle(), value)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn find_connector(card: &Card, name: &str) -> Result<connector::Handle, String> {
+    if let Ok(id) = name.parse() {
+        return match find_object(card, id)? {
+            Object::Connector(connector) => Ok(connector),
+            object => Err(format!("{} is not a connector", object)),
+        };
+    }
+
+    card.find_connector_by_name(name)
+        .map_err(|e| format!("invalid connector {}: {}", name, e))?
+        .ok_or_else(|| format!("no connector named {}", name))
+}
+
+fn find_mode(info: &connector::Info, name: &str, vrefresh: Option<u32>) -> Result<Mode, String> {
+    if let Some(index) = name.strip_prefix('#') {
+        let index: usize = parse_number(index, "mode index")?;
+        return info
+            .modes()
+            .get(index)
+            .cloned()
+            .ok_or_else(|| format!("connector {} has no mode #{}", info.name(), index));
+    }
+
+    info.modes()
+        .iter()
+        .find(|mode| mode.name().to_bytes() == name.as_bytes() && vrefresh.is_none_or(|v| v == mode.vrefresh()))
+        .cloned()
+        .ok_or_else(|| format!("connector {} has no mode {}", info.name(), name))
+}
+
+/// Picks a CRTC for a connector, preferring the one it is driven by.
+fn find_crtc(
+    card: &Card,
+    res: &drm::control::ResourceHandles,
+    info: &connector::Info,
+    taken: &[crtc::Handle],
+) -> Result<crtc::Handle, String> {
+    let mut candidates = Vec::new();
+
+    for &encoder in info.encoders() {
+        let encoder = card
+            .get_encoder(encoder)
+            .map_err(|e| format!("failed to get encoder {}: {}", id(encoder), e))?;
+
+        if info.current_encoder() == Some(encoder.handle()) {
+            candidates.extend(encoder.crtc());
+        }
+        candidates.extend(res.filter_crtcs(encoder.possible_crtcs()));
+    }
+
+    candidates
+        .into_iter()
+        .find(|crtc| !taken.contains(crtc))
+        .ok_or_else(|| format!("no CRTC available for connector {}", info.name()))
+}
+
+fn find_primary_plane(
+    card: &Card,
+    res: &drm::control::ResourceHandles,
+    crtc: crtc::Handle,
+) -> Result<plane::Handle, String> {
+    let planes = card
+        .plane_handles()
+        .map_err(|e| format!("failed to get planes: {}", e))?;
+
+    for &plane in planes.planes() {
+        let info = card
+            .get_plane(plane)
+            .map_err(|e| format!("failed to get plane {}: {}", id(plane), e))?;
+        if !res.filter_crtcs(info.possible_crtcs()).contains(&crtc) {
+            continue;
+        }
+
+        let (_, kind) = find_property(card, Object::Plane(plane), "type")?;
+        if kind == PlaneType::Primary as u64 {
+            return Ok(plane);
+        }
+    }
+
+    Err(format!("no primary plane for CRTC {}", id(crtc)))
+}
+
+/// Adds a property to an atomic request.
+fn add(card: &Card, req: &mut AtomicModeReq, object: Object, name: &str, value: property::RawValue) -> Result<(), String> {
+    let (info, _) = find_property(card, object, name)?;
+    req.add_raw_property(object.raw(), info.handle(), value);
+    Ok(())
+}
+
+/// Adds the properties to show a framebuffer on a plane to an atomic request.
+fn add_plane(
+    card: &Card,
+    req: &mut AtomicModeReq,
+    plane: plane::Handle,
+    crtc: crtc::Handle,
+    fb: framebuffer::Handle,
+    position: (i32, i32),
+    size: (u32, u32),
+) -> Result<(), String> {
+    let object = Object::Plane(plane);
+
+    add(card, req, object, "FB_ID", id(fb).into())?;
+    add(card, req, object, "CRTC_ID", id(crtc).into())?;
+    add(card, req, object, "SRC_X", 0)?;
+    add(card, req, object, "SRC_Y", 0)?;
+    add(card, req, object, "SRC_W", u64::from(size.0) << 16)?;
+    add(card, req, object, "SRC_H", u64::from(size.1) << 16)?;
+    add(card, req, object, "CRTC_X", position.0 as u64)?;
+    add(card, req, object, "CRTC_Y", position.1 as u64)?;
+    add(card, req, object, "CRTC_W", size.0.into())?;
+    add(card, req, object, "CRTC_H", size.1.into())
+}
+
+fn create_image(card: &Card, size: (u32, u32), format: PixelFormat, variant: u32) -> Result<Image, String> {
+    let mut buffer = card
+        .create_dumb_buffer(size, format)
+        .map_err(|e| format!("failed to create {}x{} buffer: {}", size.0, size.1, e))?;
+    let pitch = buffer.pitch();
+
+    let drawn = card
+        .map_dumb_buffer(&mut buffer)
+        .map_err(|e| format!("failed to map buffer: {}", e))
+        .and_then(|mut map| draw_pattern(map.as_mut(), size, pitch, format, variant));
+
+    let fb = drawn.and_then(|_| {
+        card.add_planar_framebuffer(&buffer, &[0; 4], 0)
+            .map_err(|e| format!("failed to create framebuffer: {}", e))
+    });
+
+    match fb {
+        Ok(fb) => Ok(Image { buffer: buffer, fb: fb }),
+        Err(e) => {
+            let _ = card.destroy_dumb_buffer(buffer);
+            Err(e)
+        }
+    }
+}
+
+fn destroy_image(card: &Card, image: Image) {
+    let _ = card.destroy_framebuffer(image.fb);
+    let _ = card.destroy_dumb_buffer(image.buffer);
+}
+
+/// Draws color bars above a gray ramp. Each variant shifts the bars, so
+/// flipping between variants is visible.
+fn draw_pattern(data: &mut [u8], size: (u32, u32), pitch: u32, format: PixelFormat, variant: u32) -> Result<(), String> {
+    const BARS: [(u8, u8, u8); 7] = [
+        (192, 192, 192),
+        (192, 192, 0),
+        (0, 192, 192),
+        (0, 192, 0),
+        (192, 0, 192),
+        (192, 0, 0),
+        (0, 0, 192),
+    ];
+
+    let bpp = match format {
+        PixelFormat::XRGB8888 | PixelFormat::ARGB8888 | PixelFormat::XBGR8888 | PixelFormat::ABGR8888 => 4,
+        PixelFormat::RGB565 | PixelFormat::BGR565 => 2,
+        _ => return Err(format!("test patterns are not supported in {:?}", format)),
+    };
+
+    let (w, h) = (size.0 as usize, size.1 as usize);
+    let shift = variant as usize * w / 14;
+
+    for y in 0..h {
+        let row = &mut data[y * pitch as usize..];
+
+        for x in 0..w {
+            let (r, g, b) = if y < h * 2 / 3 {
+                BARS[(x + shift) % w * BARS.len() / w]
+            } else {
+                let v = (x * 255 / w) as u8;
+                (v, v, v)
+            };
+
+            let pixel = match format {
+                PixelFormat::XRGB8888 | PixelFormat::ARGB8888 => {
+                    0xff00_0000 | u32::from(r) << 16 | u32::from(g) << 8 | u32::from(b)
+                }
+                PixelFormat::XBGR8888 | PixelFormat::ABGR8888 => {
+                    0xff00_0000 | u32::from(b) << 16 | u32::from(g) << 8 | u32::from(r)
+                }
+                PixelFormat::RGB565 => u32::from(r >> 3) << 11 | u32::from(g >> 2) << 5 | u32::from(b >> 3),
+                _ => u32::from(b >> 3) << 11 | u32::from(g >> 2) << 5 | u32::from(r >> 3),
+            };
+
+            for i in 0..bpp {
+                row[x * bpp + i] = (pixel >> (i * 8)) as u8;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Flips every pipe between its framebuffers until interrupted, reporting the
+/// achieved refresh rate every five seconds.
+fn flip_loop(card: &Card, atomic: bool, pipes: &mut [Pipe]) -> Result<(), String> {
+    let interval = Duration::from_secs(5);
+    let mut start = Instant::now();
+    let mut frames = 0u32;
+
+    loop {
+        let mut req = AtomicModeReq::new();
+
+        for pipe in pipes.iter_mut() {
+            pipe.front = 1 - pipe.front;
+            let fb = pipe.images[pipe.front].fb;
+
+            match pipe.primary {
+                Some(plane) if atomic => add(card, &mut req, Object::Plane(plane), "FB_ID", id(fb).into())?,
+                _ => card
+                    .page_flip(pipe.crtc, fb, &[PageFlipFlags::PageFlipEvent])
+                    .map_err(|e| format!("failed to flip CRTC {}: {}", id(pipe.crtc), e))?,
+            }
+        }
+
+        if atomic {
+            card.atomic_commit(&[AtomicCommitFlags::PageFlipEvent, AtomicCommitFlags::Nonblock], req)
+                .map_err(|e| format!("atomic commit failed: {}", e))?;
+        }
+
+        // Wait until every CRTC flipped before queueing the next frame.
+        let mut pending = pipes.len();
+        while pending > 0 {
+            for event in card.receive_events().map_err(|e| format!("failed to read events: {}", e))? {
+                if let Event::PageFlip(_) = event {
+                    pending -= 1;
+                }
+            }
+        }
+
+        frames += 1;
+        let elapsed = start.elapsed();
+        if elapsed >= interval {
+            let secs = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9;
+            println!("freq: {:.2}Hz", f64::from(frames) / secs);
+            start = Instant::now();
+            frames = 0;
+        }
+    }
+}
@@ -0,0 +1,861 @@
+//! Software conversion of pixels between formats.
+//!
+//! A `Converter` translates rows of pixels from one `PixelFormat` into
+//! another. Every packed RGB format is supported, as well as `C8` through a
+//! palette and the packed YUV formats `YUYV`, `YVYU`, `UYVY`, `VYUY` and
+//! `AYUV`. YUV is converted with the BT.601 or BT.709 matrix, in limited or
+//! full range.
+//!
+//! `Converter::blit` copies a rectangle of pixels between any two pixel
+//! views, such as an `Image` in regular memory and a mapped dumb buffer.
+//!
+//! # Example
+//!
+//! ```
+//! use drm::buffer::convert::{Converter, Image};
+//! use drm::buffer::format::PixelFormat;
+//! use drm::control::damage::Rect;
+//! use drm::control::dumbbuffer::{PixelView, ShadowBuffer};
+//!
+//! // Two RGBA pixels as bytes, as decoded by most image libraries.
+//! let rgba = [255, 0, 0, 255, 0, 0, 255, 128];
+//! let image = Image::new(&rgba, (2, 1), 8, PixelFormat::ABGR8888);
+//!
+//! let mut target = ShadowBuffer::new((4, 2), 16, PixelFormat::ARGB8888);
+//! let converter = Converter::new(PixelFormat::ABGR8888, PixelFormat::ARGB8888).unwrap();
+//! converter.blit(&image, Rect::new(0, 0, 2, 1), &mut target, (2, 1)).unwrap();
+//!
+//! let row: Vec<&[u32]> = target.rows_as().unwrap().collect();
+//! assert_eq!(row[0], &[0, 0, 0, 0]);
+//! assert_eq!(row[1], &[0, 0, 0xffff_0000, 0x8000_00ff]);
+//! ```
+
+use buffer::format::PixelFormat;
+use control::damage::Rect;
+use control::dumbbuffer::{PixelView, PixelViewMut};
+
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+
+/// The matrix used to convert between YUV and RGB.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum YuvEncoding {
+    /// ITU-R BT.601, used for standard definition video
+    Bt601,
+    /// ITU-R BT.709, used for high definition video
+    Bt709,
+}
+
+impl YuvEncoding {
+    /// Returns the red and blue coefficients `(Kr, Kb)`.
+    fn coefficients(&self) -> (f32, f32) {
+        match *self {
+            YuvEncoding::Bt601 => (0.299, 0.114),
+            YuvEncoding::Bt709 => (0.2126, 0.0722),
+        }
+    }
+}
+
+/// The range of YUV values.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum YuvRange {
+    /// Luma from 16 to 235 and chroma from 16 to 240
+    Limited,
+    /// Luma and chroma from 0 to 255
+    Full,
+}
+
+/// Errors that can occur when converting pixels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The format can not be converted from or to.
+    UnsupportedFormat(PixelFormat),
+    /// A `C8` format was used without a palette.
+    MissingPalette,
+    /// The format of a pixel view differs from the one of the converter.
+    FormatMismatch,
+    /// A row is shorter than the number of pixels to convert.
+    BufferTooSmall,
+    /// A YUV 4:2:2 destination was written starting at an odd column or
+    /// with an odd width.
+    Unaligned,
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConvertError::UnsupportedFormat(format) => write!(f, "unsupported format {:?}", format),
+            ConvertError::MissingPalette => write!(f, "C8 requires a palette"),
+            ConvertError::FormatMismatch => write!(f, "pixel view format differs from the converter"),
+            ConvertError::BufferTooSmall => write!(f, "buffer too small"),
+            ConvertError::Unaligned => write!(f, "YUV 4:2:2 must be written in pairs of pixels"),
+        }
+    }
+}
+
+impl Error for ConvertError {}
+
+/// A pixel with 16 bits per channel, in the order red, green, blue, alpha.
+type Rgba = [u16; 4];
+
+const OPAQUE_BLACK: Rgba = [0, 0, 0, 0xffff];
+
+/// A channel of a packed pixel.
+#[derive(Debug, Copy, Clone)]
+struct Channel {
+    shift: u32,
+    bits: u32,
+}
+
+impl Channel {
+    fn read(&self, pixel: u32) -> u16 {
+        expand((pixel >> self.shift) & ((1 << self.bits) - 1), self.bits)
+    }
+
+    fn write(&self, value: u16) -> u32 {
+        reduce(value, self.bits) << self.shift
+    }
+}
+
+/// The memory layout of a format.
+#[derive(Debug, Copy, Clone)]
+enum Layout {
+    /// Little-endian words holding the red, green, blue and alpha channels
+    Packed { bytes: usize, channels: [Option<Channel>; 4] },
+    /// A byte indexing a palette
+    Indexed,
+    /// Pairs of pixels sharing chroma, given as byte offsets of
+    /// `(Y0, U, Y1, V)` within four bytes
+    Yuv422([usize; 4]),
+    /// Bytes of `V`, `U`, `Y` and alpha
+    Ayuv,
+}
+
+impl Layout {
+    fn of(format: PixelFormat) -> Option<Layout> {
+        use self::PixelFormat::*;
+
+        let layout = match format {
+            C8 => Layout::Indexed,
+            R8 => packed(1, "R", &[8]),
+            R16 => packed(2, "R", &[16]),
+            RG88 => packed(2, "RG", &[8, 8]),
+            GR88 => packed(2, "GR", &[8, 8]),
+
+            RGB332 => packed(1, "RGB", &[3, 3, 2]),
+            BGR233 => packed(1, "BGR", &[2, 3, 3]),
+
+            XRGB4444 => packed(2, "XRGB", &[4, 4, 4, 4]),
+            XBGR4444 => packed(2, "XBGR", &[4, 4, 4, 4]),
+            RGBX4444 => packed(2, "RGBX", &[4, 4, 4, 4]),
+            BGRX4444 => packed(2, "BGRX", &[4, 4, 4, 4]),
+            ARGB4444 => packed(2, "ARGB", &[4, 4, 4, 4]),
+            ABGR4444 => packed(2, "ABGR", &[4, 4, 4, 4]),
+            RGBA4444 => packed(2, "RGBA", &[4, 4, 4, 4]),
+            BGRA4444 => packed(2, "BGRA", &[4, 4, 4, 4]),
+
+            XRGB1555 => packed(2, "XRGB", &[1, 5, 5, 5]),
+            XBGR1555 => packed(2, "XBGR", &[1, 5, 5, 5]),
+            RGBX5551 => packed(2, "RGBX", &[5, 5, 5, 1]),
+            BGRX5551 => packed(2, "BGRX", &[5, 5, 5, 1]),
+            ARGB1555 => packed(2, "ARGB", &[1, 5, 5, 5]),
+            ABGR1555 => packed(2, "ABGR", &[1, 5, 5, 5]),
+            RGBA5551 => packed(2, "RGBA", &[5, 5, 5, 1]),
+            BGRA5551 => packed(2, "BGRA", &[5, 5, 5, 1]),
+
+            RGB565 => packed(2, "RGB", &[5, 6, 5]),
+            BGR565 => packed(2, "BGR", &[5, 6, 5]),
+
+            RGB888 => packed(3, "RGB", &[8, 8, 8]),
+            BGR888 => packed(3, "BGR", &[8, 8, 8]),
+
+            XRGB8888 => packed(4, "XRGB", &[8, 8, 8, 8]),
+            XBGR8888 => packed(4, "XBGR", &[8, 8, 8, 8]),
+            RGBX8888 => packed(4, "RGBX", &[8, 8, 8, 8]),
+            BGRX8888 => packed(4, "BGRX", &[8, 8, 8, 8]),
+            ARGB8888 => packed(4, "ARGB", &[8, 8, 8, 8]),
+            ABGR8888 => packed(4, "ABGR", &[8, 8, 8, 8]),
+            RGBA8888 => packed(4, "RGBA", &[8, 8, 8, 8]),
+            BGRA8888 => packed(4, "BGRA", &[8, 8, 8, 8]),
+
+            XRGB2101010 => packed(4, "XRGB", &[2, 10, 10, 10]),
+            XBGR2101010 => packed(4, "XBGR", &[2, 10, 10, 10]),
+            RGBX1010102 => packed(4, "RGBX", &[10, 10, 10, 2]),
+            BGRX1010102 => packed(4, "BGRX", &[10, 10, 10, 2]),
+            ARGB2101010 => packed(4, "ARGB", &[2, 10, 10, 10]),
+            ABGR2101010 => packed(4, "ABGR", &[2, 10, 10, 10]),
+            RGBA1010102 => packed(4, "RGBA", &[10, 10, 10, 2]),
+            BGRA1010102 => packed(4, "BGRA", &[10, 10, 10, 2]),
+
+            YUYV => Layout::Yuv422([0, 1, 2, 3]),
+            YVYU => Layout::Yuv422([0, 3, 2, 1]),
+            UYVY => Layout::Yuv422([1, 0, 3, 2]),
+            VYUY => Layout::Yuv422([1, 2, 3, 0]),
+
+            AYUV => Layout::Ayuv,
+
+            _ => return None,
+        };
+
+        Some(layout)
+    }
+
+    /// Returns the byte offset of a column.
+    fn offset(&self, x: usize) -> usize {
+        match *self {
+            Layout::Packed { bytes, .. } => x * bytes,
+            Layout::Indexed => x,
+            Layout::Yuv422(_) => x / 2 * 4,
+            Layout::Ayuv => x * 4,
+        }
+    }
+}
+
+/// Describes a packed format by its channels from the most significant bit
+/// down, e.g. `"XRGB"` with `[2, 10, 10, 10]`.
+fn packed(bytes: usize, order: &str, bits: &[u32]) -> Layout {
+    let mut channels = [None; 4];
+    let mut shift = bytes as u32 * 8;
+
+    for (name, &bits) in order.chars().zip(bits) {
+        shift -= bits;
+        let index = match name {
+            'R' => 0,
+            'G' => 1,
+            'B' => 2,
+            'A' => 3,
+            _ => continue,
+        };
+        channels[index] = Some(Channel {
+            shift: shift,
+            bits: bits,
+        });
+    }
+
+    Layout::Packed {
+        bytes: bytes,
+        channels: channels,
+    }
+}
+
+/// Scales a value of `bits` bits to 16 bits by repeating its bits.
+fn expand(value: u32, bits: u32) -> u16 {
+    let mut result = 0;
+    let mut shift = 16 - bits as i32;
+
+    while shift > -(bits as i32) {
+        result |= if shift >= 0 { value << shift } else { value >> -shift };
+        shift -= bits as i32;
+    }
+
+    result as u16
+}
+
+/// Scales a 16 bit value to `bits` bits, rounding to the nearest value.
+fn reduce(value: u16, bits: u32) -> u32 {
+    (u32::from(value) * ((1 << bits) - 1) + 0x7fff) / 0xffff
+}
+
+/// Converts rows of pixels from one format into another.
+///
+/// # Example
+///
+/// ```
+/// use drm::buffer::convert::{Converter, YuvRange};
+/// use drm::buffer::format::PixelFormat;
+///
+/// let converter = Converter::new(PixelFormat::XRGB8888, PixelFormat::RGB565).unwrap();
+/// let mut rgb565 = [0u8; 4];
+/// converter.convert_row(&[0xff, 0x00, 0xff, 0x00, 0x00, 0xff, 0x00, 0x00], &mut rgb565, 2).unwrap();
+/// assert_eq!(rgb565, [0x1f, 0xf8, 0xe0, 0x07]);
+///
+/// // Limited range black and white, as a pair of YUYV pixels.
+/// let converter = Converter::new(PixelFormat::YUYV, PixelFormat::XRGB8888)
+///     .unwrap()
+///     .range(YuvRange::Limited);
+/// let mut xrgb = [0u8; 8];
+/// converter.convert_row(&[16, 128, 235, 128], &mut xrgb, 2).unwrap();
+/// assert_eq!(xrgb, [0, 0, 0, 0, 0xff, 0xff, 0xff, 0]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Converter<'a> {
+    src: PixelFormat,
+    dst: PixelFormat,
+    src_layout: Layout,
+    dst_layout: Layout,
+    encoding: YuvEncoding,
+    range: YuvRange,
+    src_palette: Option<&'a [u32]>,
+    dst_palette: Option<&'a [u32]>,
+}
+
+impl<'a> Converter<'a> {
+    /// Creates a converter between two formats, using BT.601 in limited
+    /// range for YUV.
+    pub fn new(src: PixelFormat, dst: PixelFormat) -> Result<Converter<'a>, ConvertError> {
+        Ok(Converter {
+            src: src,
+            dst: dst,
+            src_layout: Layout::of(src).ok_or(ConvertError::UnsupportedFormat(src))?,
+            dst_layout: Layout::of(dst).ok_or(ConvertError::UnsupportedFormat(dst))?,
+            encoding: YuvEncoding::Bt601,
+            range: YuvRange::Limited,
+            src_palette: None,
+            dst_palette: None,
+        })
+    }
+
+    /// Sets the matrix used for YUV.
+    pub fn encoding(mut self, encoding: YuvEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets the range of YUV values.
+    pub fn range(mut self, range: YuvRange) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// Sets the ARGB8888 palette of a `C8` source.
+    pub fn src_palette(mut self, palette: &'a [u32]) -> Self {
+        self.src_palette = Some(palette);
+        self
+    }
+
+    /// Sets the ARGB8888 palette of a `C8` destination. Each pixel is mapped
+    /// to the closest color of the palette.
+    pub fn dst_palette(mut self, palette: &'a [u32]) -> Self {
+        self.dst_palette = Some(palette);
+        self
+    }
+
+    /// Returns the format converted from.
+    pub fn src_format(&self) -> PixelFormat {
+        self.src
+    }
+
+    /// Returns the format converted to.
+    pub fn dst_format(&self) -> PixelFormat {
+        self.dst
+    }
+
+    /// Converts `width` pixels from the start of `src` into `dst`.
+    ///
+    /// YUV 4:2:2 destinations with an odd width get the chroma of the last
+    /// pixel repeated.
+    pub fn convert_row(&self, src: &[u8], dst: &mut [u8], width: usize) -> Result<(), ConvertError> {
+        let mut pixels = vec![OPAQUE_BLACK; width];
+        self.decode(src, &mut pixels)?;
+        self.encode(&pixels, dst)
+    }
+
+    /// Copies a rectangle of `src` to `position` in `dst`, converting the
+    /// pixels.
+    ///
+    /// The rectangle is clipped to both views. Fails with
+    /// `ConvertError::FormatMismatch` if the formats of the views differ
+    /// from those of the converter.
+    pub fn blit<S, T>(&self, src: &S, rect: Rect, dst: &mut T, position: (i32, i32)) -> Result<(), ConvertError>
+    where
+        S: PixelView + ?Sized,
+        T: PixelViewMut + ?Sized,
+    {
+        if src.format() != self.src || dst.format() != self.dst {
+            return Err(ConvertError::FormatMismatch);
+        }
+
+        // Clip to the source, then to the destination moved onto the source.
+        let (sw, sh) = src.size();
+        let (dw, dh) = dst.size();
+        let (dx, dy) = (position.0 - rect.x1, position.1 - rect.y1);
+        let bounds = Rect::new(-dx, -dy, dw, dh);
+
+        let rect = match rect
+            .intersection(&Rect::new(0, 0, sw, sh))
+            .and_then(|rect| rect.intersection(&bounds))
+        {
+            Some(rect) => rect,
+            None => return Ok(()),
+        };
+
+        let (src_layout, dst_layout) = (self.src_layout, self.dst_layout);
+        let (x, width) = (rect.x1 as usize, (rect.x2 - rect.x1) as usize);
+        let target_x = (rect.x1 + dx) as usize;
+
+        if let Layout::Yuv422(_) = dst_layout {
+            if !target_x.is_multiple_of(2) || (!width.is_multiple_of(2) && target_x + width < dw as usize) {
+                return Err(ConvertError::Unaligned);
+            }
+        }
+
+        // 4:2:2 sources are read from the start of the pair.
+        let skip = match src_layout {
+            Layout::Yuv422(_) => x % 2,
+            _ => 0,
+        };
+
+        let (src_pitch, dst_pitch) = (src.pitch() as usize, dst.pitch() as usize);
+        let src_data = src.data();
+        let dst_data = dst.data_mut();
+        let mut pixels = vec![OPAQUE_BLACK; width + skip];
+
+        for y in rect.y1..rect.y2 {
+            let start = y as usize * src_pitch + src_layout.offset(x - skip);
+            let row = src_data.get(start..).ok_or(ConvertError::BufferTooSmall)?;
+            self.decode(row, &mut pixels)?;
+
+            let start = (y + dy) as usize * dst_pitch + dst_layout.offset(target_x);
+            let row = dst_data.get_mut(start..).ok_or(ConvertError::BufferTooSmall)?;
+            self.encode(&pixels[skip..], row)?;
+        }
+
+        Ok(())
+    }
+
+    /// Decodes as many pixels as fit `pixels`.
+    fn decode(&self, src: &[u8], pixels: &mut [Rgba]) -> Result<(), ConvertError> {
+        let width = pixels.len();
+
+        match self.src_layout {
+            Layout::Packed { bytes, channels } => {
+                let src = src.get(..width * bytes).ok_or(ConvertError::BufferTooSmall)?;
+
+                for (pixel, data) in pixels.iter_mut().zip(src.chunks(bytes)) {
+                    let word = data.iter().rev().fold(0u32, |acc, &b| acc << 8 | u32::from(b));
+                    for (value, channel) in pixel.iter_mut().zip(&channels) {
+                        if let Some(ref channel) = *channel {
+                            *value = channel.read(word);
+                        }
+                    }
+                }
+            }
+            Layout::Indexed => {
+                let palette = self.src_palette.ok_or(ConvertError::MissingPalette)?;
+                let src = src.get(..width).ok_or(ConvertError::BufferTooSmall)?;
+
+                for (pixel, &index) in pixels.iter_mut().zip(src) {
+                    *pixel = match palette.get(index as usize) {
+                        Some(&color) => argb_to_rgba(color),
+                        None => OPAQUE_BLACK,
+                    };
+                }
+            }
+            Layout::Yuv422(offsets) => {
+                let src = src.get(..width.div_ceil(2) * 4).ok_or(ConvertError::BufferTooSmall)?;
+
+                for (pair, data) in pixels.chunks_mut(2).zip(src.chunks(4)) {
+                    let (u, v) = (data[offsets[1]], data[offsets[3]]);
+                    pair[0] = self.yuv_to_rgba(data[offsets[0]], u, v, 0xff);
+                    if pair.len() > 1 {
+                        pair[1] = self.yuv_to_rgba(data[offsets[2]], u, v, 0xff);
+                    }
+                }
+            }
+            Layout::Ayuv => {
+                let src = src.get(..width * 4).ok_or(ConvertError::BufferTooSmall)?;
+
+                for (pixel, data) in pixels.iter_mut().zip(src.chunks(4)) {
+                    *pixel = self.yuv_to_rgba(data[2], data[1], data[0], data[3]);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Encodes every pixel of `pixels` into the start of `dst`.
+    fn encode(&self, pixels: &[Rgba], dst: &mut [u8]) -> Result<(), ConvertError> {
+        let width = pixels.len();
+
+        match self.dst_layout {
+            Layout::Packed { bytes, channels } => {
+                let dst = dst.get_mut(..width * bytes).ok_or(ConvertError::BufferTooSmall)?;
+
+                for (pixel, data) in pixels.iter().zip(dst.chunks_mut(bytes)) {
+                    let word = pixel
+                        .iter()
+                        .zip(&channels)
+                        .filter_map(|(&value, channel)| channel.map(|c| c.write(value)))
+                        .fold(0, |acc, bits| acc | bits);
+
+                    for (i, byte) in data.iter_mut().enumerate() {
+                        *byte = (word >> (i * 8)) as u8;
+                    }
+                }
+            }
+            Layout::Indexed => {
+                let palette = self.dst_palette.ok_or(ConvertError::MissingPalette)?;
+                let dst = dst.get_mut(..width).ok_or(ConvertError::BufferTooSmall)?;
+
+                for (pixel, index) in pixels.iter().zip(dst) {
+                    *index = closest(palette, pixel);
+                }
+            }
+            Layout::Yuv422(offsets) => {
+                let dst = dst.get_mut(..width.div_ceil(2) * 4).ok_or(ConvertError::BufferTooSmall)?;
+
+                for (pair, data) in pixels.chunks(2).zip(dst.chunks_mut(4)) {
+                    let first = self.rgba_to_yuv(&pair[0]);
+                    let second = pair.get(1).map_or(first, |p| self.rgba_to_yuv(p));
+
+                    data[offsets[0]] = first.0;
+                    data[offsets[2]] = second.0;
+                    data[offsets[1]] = (u16::from(first.1) + u16::from(second.1)).div_ceil(2) as u8;
+                    data[offsets[3]] = (u16::from(first.2) + u16::from(second.2)).div_ceil(2) as u8;
+                }
+            }
+            Layout::Ayuv => {
+                let dst = dst.get_mut(..width * 4).ok_or(ConvertError::BufferTooSmall)?;
+
+                for (pixel, data) in pixels.iter().zip(dst.chunks_mut(4)) {
+                    let (y, u, v) = self.rgba_to_yuv(pixel);
+                    data.copy_from_slice(&[v, u, y, (pixel[3] >> 8) as u8]);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn yuv_to_rgba(&self, y: u8, u: u8, v: u8, a: u8) -> Rgba {
+        let (kr, kb) = self.encoding.coefficients();
+        let kg = 1.0 - kr - kb;
+
+        let (y, u, v) = match self.range {
+            YuvRange::Limited => (
+                (f32::from(y) - 16.0) / 219.0,
+                (f32::from(u) - 128.0) / 224.0,
+                (f32::from(v) - 128.0) / 224.0,
+            ),
+            YuvRange::Full => (
+                f32::from(y) / 255.0,
+                (f32::from(u) - 128.0) / 255.0,
+                (f32::from(v) - 128.0) / 255.0,
+            ),
+        };
+
+        let r = y + 2.0 * (1.0 - kr) * v;
+        let b = y + 2.0 * (1.0 - kb) * u;
+        let g = (y - kr * r - kb * b) / kg;
+
+        [unorm16(r), unorm16(g), unorm16(b), expand(a.into(), 8)]
+    }
+
+    fn rgba_to_yuv(&self, pixel: &Rgba) -> (u8, u8, u8) {
+        let (kr, kb) = self.encoding.coefficients();
+        let kg = 1.0 - kr - kb;
+        let (r, g, b) = (
+            f32::from(pixel[0]) / 65535.0,
+            f32::from(pixel[1]) / 65535.0,
+            f32::from(pixel[2]) / 65535.0,
+        );
+
+        let y = kr * r + kg * g + kb * b;
+        let u = (b - y) / (2.0 * (1.0 - kb));
+        let v = (r - y) / (2.0 * (1.0 - kr));
+
+        let (y, u, v) = match self.range {
+            YuvRange::Limited => (16.0 + 219.0 * y, 128.0 + 224.0 * u, 128.0 + 224.0 * v),
+            YuvRange::Full => (255.0 * y, 128.0 + 255.0 * u, 128.0 + 255.0 * v),
+        };
+
+        (unorm8(y), unorm8(u), unorm8(v))
+    }
+}
+
+/// Converts a value from 0 to 1 into 16 bits, clamping it.
+fn unorm16(value: f32) -> u16 {
+    (value.clamp(0.0, 1.0) * 65535.0).round() as u16
+}
+
+/// Clamps a value to a byte.
+fn unorm8(value: f32) -> u8 {
+    value.clamp(0.0, 255.0).round() as u8
+}
+
+fn argb_to_rgba(color: u32) -> Rgba {
+    [
+        expand(color >> 16 & 0xff, 8),
+        expand(color >> 8 & 0xff, 8),
+        expand(color & 0xff, 8),
+        expand(color >> 24, 8),
+    ]
+}
+
+/// Returns the index of the palette color closest to a pixel.
+fn closest(palette: &[u32], pixel: &Rgba) -> u8 {
+    let distance = |color: u32| -> u64 {
+        let color = argb_to_rgba(color);
+        (0..3)
+            .map(|i| {
+                let d = i64::from(color[i] >> 8) - i64::from(pixel[i] >> 8);
+                (d * d) as u64
+            })
+            .sum()
+    };
+
+    (0..cmp::min(palette.len(), 256))
+        .min_by_key(|&i| distance(palette[i]))
+        .unwrap_or(0) as u8
+}
+
+/// Pixels in regular memory, e.g. a decoded image.
+#[derive(Debug, Copy, Clone)]
+pub struct Image<'a> {
+    data: &'a [u8],
+    size: (u32, u32),
+    pitch: u32,
+    format: PixelFormat,
+}
+
+impl<'a> Image<'a> {
+    /// Describes the pixels of `data`, with rows `pitch` bytes apart.
+    pub fn new(data: &'a [u8], size: (u32, u32), pitch: u32, format: PixelFormat) -> Image<'a> {
+        Image {
+            data: data,
+            size: size,
+            pitch: pitch,
+            format: format,
+        }
+    }
+}
+
+impl<'a> PixelView for Image<'a> {
+    fn data(&self) -> &[u8] {
+        self.data
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn pitch(&self) -> u32 {
+        self.pitch
+    }
+
+    fn format(&self) -> PixelFormat {
+        self.format
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use control::dumbbuffer::ShadowBuffer;
+
+    /// Converts a row of pixels with a fresh converter.
+    fn convert(src: PixelFormat, dst: PixelFormat, pixels: &[u8], width: usize, dst_len: usize) -> Vec<u8> {
+        let mut out = vec![0; dst_len];
+        Converter::new(src, dst).unwrap().convert_row(pixels, &mut out, width).unwrap();
+        out
+    }
+
+    fn assert_near(a: &[u8], b: &[u8]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!((i16::from(*x) - i16::from(*y)).abs() <= 1, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn expand_and_reduce_round_trip() {
+        for &bits in &[1, 2, 4, 5, 6, 10, 16] {
+            let max = (1u32 << bits) - 1;
+            for value in 0..=max {
+                assert_eq!(reduce(expand(value, bits), bits), value, "{} bits", bits);
+            }
+            assert_eq!(expand(0, bits), 0);
+            assert_eq!(expand(max, bits), 0xffff);
+        }
+
+        assert_eq!(expand(0b10, 2), 0xaaaa);
+        assert_eq!(expand(0x10, 5), 0x8421);
+        assert_eq!(expand(0x202, 10), 0x80a0);
+    }
+
+    #[test]
+    fn reduce_rounds_to_nearest() {
+        assert_eq!(reduce(0x7fff, 1), 0);
+        assert_eq!(reduce(0x8000, 1), 1);
+        assert_eq!(reduce(0x8080, 5), 0x10);
+        assert_eq!(reduce(0x80ff, 8), 0x80);
+        assert_eq!(reduce(0x8180, 8), 0x81);
+    }
+
+    #[test]
+    fn pack_2101010() {
+        // 0x80ff8000 as ARGB8888.
+        let argb = [0x00, 0x80, 0xff, 0x80];
+
+        assert_eq!(convert(PixelFormat::ARGB8888, PixelFormat::ARGB2101010, &argb, 1, 4), [0x00, 0x08, 0xf8, 0xbf]);
+        assert_eq!(convert(PixelFormat::ARGB8888, PixelFormat::ABGR2101010, &argb, 1, 4), [0xff, 0x0b, 0x08, 0x80]);
+        assert_eq!(convert(PixelFormat::ARGB8888, PixelFormat::RGBA1010102, &argb, 1, 4), [0x02, 0x20, 0xe0, 0xff]);
+        assert_eq!(convert(PixelFormat::ARGB2101010, PixelFormat::ARGB8888, &[0x00, 0x08, 0xf8, 0xbf], 1, 4), [0x00, 0x80, 0xff, 0xaa]);
+    }
+
+    #[test]
+    fn pack_1555() {
+        // 0x00ff8000 as XRGB8888, which is opaque.
+        let xrgb = [0x00, 0x80, 0xff, 0x00];
+
+        assert_eq!(convert(PixelFormat::XRGB8888, PixelFormat::ARGB1555, &xrgb, 1, 2), [0x00, 0xfe]);
+        assert_eq!(convert(PixelFormat::XRGB8888, PixelFormat::XRGB1555, &xrgb, 1, 2), [0x00, 0x7e]);
+        assert_eq!(convert(PixelFormat::XRGB8888, PixelFormat::RGBA5551, &xrgb, 1, 2), [0x01, 0xfc]);
+        assert_eq!(convert(PixelFormat::ARGB1555, PixelFormat::ARGB8888, &[0x00, 0x7e], 1, 4), [0x00, 0x84, 0xff, 0x00]);
+    }
+
+    #[test]
+    fn pack_4444() {
+        let argb = [0x00, 0x80, 0xff, 0x80];
+
+        assert_eq!(convert(PixelFormat::ARGB8888, PixelFormat::ARGB4444, &argb, 1, 2), [0x80, 0x8f]);
+        assert_eq!(convert(PixelFormat::ARGB8888, PixelFormat::BGRA4444, &argb, 1, 2), [0xf8, 0x08]);
+        assert_eq!(convert(PixelFormat::ARGB4444, PixelFormat::ARGB8888, &[0x80, 0x8f], 1, 4), [0x00, 0x88, 0xff, 0x88]);
+    }
+
+    #[test]
+    fn pack_332() {
+        let xrgb = [0x00, 0x80, 0xff, 0x00];
+
+        assert_eq!(convert(PixelFormat::XRGB8888, PixelFormat::RGB332, &xrgb, 1, 1), [0xf0]);
+        assert_eq!(convert(PixelFormat::XRGB8888, PixelFormat::BGR233, &xrgb, 1, 1), [0x27]);
+        assert_eq!(convert(PixelFormat::RGB332, PixelFormat::XRGB8888, &[0xf0], 1, 4), [0x00, 0x92, 0xff, 0x00]);
+    }
+
+    #[test]
+    fn yuv_encodings() {
+        let red = [0x00, 0x00, 0xff, 0x00];
+
+        // AYUV is stored as V, U, Y, A.
+        let bt601 = convert(PixelFormat::XRGB8888, PixelFormat::AYUV, &red, 1, 4);
+        assert_eq!(bt601, [240, 90, 81, 0xff]);
+
+        let bt709 = Converter::new(PixelFormat::XRGB8888, PixelFormat::AYUV)
+            .unwrap()
+            .encoding(YuvEncoding::Bt709);
+        let mut ayuv = [0; 4];
+        bt709.convert_row(&red, &mut ayuv, 1).unwrap();
+        assert_eq!(ayuv, [240, 102, 63, 0xff]);
+
+        let back = Converter::new(PixelFormat::AYUV, PixelFormat::XRGB8888)
+            .unwrap()
+            .encoding(YuvEncoding::Bt709);
+        let mut xrgb = [0; 4];
+        back.convert_row(&ayuv, &mut xrgb, 1).unwrap();
+        assert_near(&xrgb, &red);
+    }
+
+    #[test]
+    fn yuv_full_range() {
+        let encode = Converter::new(PixelFormat::XRGB8888, PixelFormat::AYUV)
+            .unwrap()
+            .range(YuvRange::Full);
+        let mut ayuv = [0; 12];
+        let black_white_red = [0, 0, 0, 0, 0xff, 0xff, 0xff, 0, 0, 0, 0xff, 0];
+        encode.convert_row(&black_white_red, &mut ayuv, 3).unwrap();
+        assert_eq!(ayuv, [128, 128, 0, 0xff, 128, 128, 255, 0xff, 255, 85, 76, 0xff]);
+
+        let decode = Converter::new(PixelFormat::YUYV, PixelFormat::XRGB8888)
+            .unwrap()
+            .range(YuvRange::Full);
+        let mut xrgb = [0; 8];
+        decode.convert_row(&[0, 128, 255, 128], &mut xrgb, 2).unwrap();
+        assert_eq!(xrgb, [0, 0, 0, 0, 0xff, 0xff, 0xff, 0]);
+    }
+
+    #[test]
+    fn c8_palettes() {
+        let palette = [0xff00_0000, 0xffff_0000, 0xff00_ff00];
+
+        let from_c8 = Converter::new(PixelFormat::C8, PixelFormat::XRGB8888)
+            .unwrap()
+            .src_palette(&palette);
+        let mut xrgb = [0xaa; 16];
+        from_c8.convert_row(&[1, 2, 0, 7], &mut xrgb, 4).unwrap();
+        // Indices past the end of the palette are black.
+        assert_eq!(xrgb, [0, 0, 0xff, 0, 0, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let to_c8 = Converter::new(PixelFormat::XRGB8888, PixelFormat::C8)
+            .unwrap()
+            .dst_palette(&palette);
+        let mut c8 = [0xaa; 3];
+        to_c8.convert_row(&[0x01, 0x01, 0xfe, 0, 0, 0, 0, 0, 0x10, 0xf0, 0x10, 0], &mut c8, 3).unwrap();
+        assert_eq!(c8, [1, 0, 2]);
+    }
+
+    #[test]
+    fn c8_without_palette() {
+        let mut out = [0; 4];
+
+        let from_c8 = Converter::new(PixelFormat::C8, PixelFormat::XRGB8888).unwrap();
+        assert_eq!(from_c8.convert_row(&[0], &mut out, 1), Err(ConvertError::MissingPalette));
+
+        let to_c8 = Converter::new(PixelFormat::XRGB8888, PixelFormat::C8).unwrap();
+        assert_eq!(to_c8.convert_row(&[0; 4], &mut out, 1), Err(ConvertError::MissingPalette));
+    }
+
+    #[test]
+    fn yuv422_source_at_odd_column() {
+        // Limited range black, white, gray and black.
+        let yuyv = [16, 128, 235, 128, 126, 128, 16, 128];
+        let image = Image::new(&yuyv, (4, 1), 8, PixelFormat::YUYV);
+        let converter = Converter::new(PixelFormat::YUYV, PixelFormat::XRGB8888).unwrap();
+
+        let mut target = ShadowBuffer::new((2, 1), 8, PixelFormat::XRGB8888);
+        converter.blit(&image, Rect::new(1, 0, 2, 1), &mut target, (0, 0)).unwrap();
+        assert_eq!(target.rows_as::<u32>().unwrap().next().unwrap(), &[0x00ff_ffff, 0x0080_8080]);
+
+        converter.blit(&image, Rect::new(3, 0, 1, 1), &mut target, (1, 0)).unwrap();
+        assert_eq!(target.rows_as::<u32>().unwrap().next().unwrap(), &[0x00ff_ffff, 0]);
+    }
+
+    #[test]
+    fn yuv422_destination_must_be_aligned() {
+        let xrgb = [0xff; 16];
+        let image = Image::new(&xrgb, (4, 1), 16, PixelFormat::XRGB8888);
+        let converter = Converter::new(PixelFormat::XRGB8888, PixelFormat::YUYV).unwrap();
+        let mut target = ShadowBuffer::new((4, 1), 8, PixelFormat::YUYV);
+
+        let odd_column = converter.blit(&image, Rect::new(0, 0, 2, 1), &mut target, (1, 0));
+        assert_eq!(odd_column, Err(ConvertError::Unaligned));
+        let odd_width = converter.blit(&image, Rect::new(0, 0, 3, 1), &mut target, (0, 0));
+        assert_eq!(odd_width, Err(ConvertError::Unaligned));
+
+        // An odd width is fine when it ends at the edge of the destination.
+        converter.blit(&image, Rect::new(0, 0, 2, 1), &mut target, (2, 0)).unwrap();
+        assert_eq!(target.data(), &[0, 0, 0, 0, 235, 128, 235, 128]);
+    }
+
+    /// Blits from a 4x4 R8 image numbered 1 to 16 into a 3x3 target.
+    fn blit_r8(rect: Rect, position: (i32, i32)) -> Vec<u8> {
+        let pixels: Vec<u8> = (1..17).collect();
+        let image = Image::new(&pixels, (4, 4), 4, PixelFormat::R8);
+        let mut target = ShadowBuffer::new((3, 3), 3, PixelFormat::R8);
+
+        let converter = Converter::new(PixelFormat::R8, PixelFormat::R8).unwrap();
+        converter.blit(&image, rect, &mut target, position).unwrap();
+        target.data().to_vec()
+    }
+
+    #[test]
+    fn blit_clips_to_source() {
+        assert_eq!(blit_r8(Rect::new(-1, -1, 3, 3), (0, 0)), [0, 0, 0, 0, 1, 2, 0, 5, 6]);
+        assert_eq!(blit_r8(Rect::new(3, 3, 4, 4), (0, 0)), [16, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn blit_clips_to_destination() {
+        assert_eq!(blit_r8(Rect::new(2, 2, 2, 2), (1, 1)), [0, 0, 0, 0, 11, 12, 0, 15, 16]);
+        assert_eq!(blit_r8(Rect::new(0, 0, 4, 4), (-2, -1)), [7, 8, 0, 11, 12, 0, 15, 16, 0]);
+        assert_eq!(blit_r8(Rect::new(0, 0, 4, 4), (1, 2)), [0, 0, 0, 0, 0, 0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn blit_outside_does_nothing() {
+        assert_eq!(blit_r8(Rect::new(0, 0, 4, 4), (3, 0)), [0; 9]);
+        assert_eq!(blit_r8(Rect::new(0, 0, 4, 4), (-4, 0)), [0; 9]);
+        assert_eq!(blit_r8(Rect::new(4, 0, 2, 2), (0, 0)), [0; 9]);
+    }
+}
@@ -0,0 +1,474 @@
+//! # dma-buf
+//!
+//! Buffers shared between devices and processes as file descriptors.
+//!
+//! A dma-buf is what `Device::buffer_to_dma_buf` exports a GEM buffer as,
+//! and what other drivers such as V4L2, Vulkan or `/dev/udmabuf` hand out.
+//! The buffer stays alive as long as any file descriptor or mapping of it
+//! does.
+//!
+//! The contents of a dma-buf can be mapped into memory. Reads and writes
+//! through a mapping must be bracketed by `DmaBuf::begin_cpu_access` and the
+//! end of the returned `CpuAccess`, so the exporter can flush its caches and
+//! wait for the device to finish rendering.
+//!
+//! Rendering to a buffer attaches fences to it, which are implicitly waited
+//! for by other users of the buffer. These can be exchanged with explicit
+//! `SyncFile` fences.
+//!
+//! # Example
+//!
+//! ```
+//! use drm::buffer::dmabuf::{Access, DmaBuf};
+//! use drm::SystemError;
+//!
+//! fn clear(buffer: &mut DmaBuf) -> Result<(), SystemError> {
+//!     let access = buffer.begin_cpu_access(Access::Write)?;
+//!
+//!     for byte in buffer.map()?.as_mut() {
+//!         *byte = 0;
+//!     }
+//!
+//!     access.end()
+//! }
+//! ```
+
+use control::dumbbuffer::Mmap;
+use drm_ffi as ffi;
+use drm_ffi::result::SystemError;
+
+use nix::errno::Errno;
+use nix::libc;
+
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+
+/// The kind of access to a dma-buf.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Access {
+    /// Reading from the buffer
+    Read = ffi::DMA_BUF_SYNC_READ,
+    /// Writing to the buffer
+    Write = ffi::DMA_BUF_SYNC_WRITE,
+    /// Reading from and writing to the buffer
+    ReadWrite = ffi::DMA_BUF_SYNC_RW,
+}
+
+/// A dma-buf file descriptor that is closed on drop.
+#[derive(Debug)]
+pub struct DmaBuf {
+    fd: RawFd,
+}
+
+impl DmaBuf {
+    /// Returns the size of the buffer in bytes.
+    pub fn size(&self) -> Result<usize, SystemError> {
+        let size = unsafe { libc::lseek(self.fd, 0, libc::SEEK_END) };
+        if size < 0 {
+            return Err(Errno::last().into());
+        }
+
+        Ok(size as usize)
+    }
+
+    /// Sets the name of the buffer, which shows up in its `fdinfo` and in
+    /// debugfs.
+    ///
+    /// Fails with `SystemError::InvalidArgument` if the name contains a nul
+    /// byte or is longer than 31 bytes. Fails with
+    /// `SystemError::Unknown { errno: EBUSY }` if the buffer is mapped.
+    pub fn set_name(&self, name: &str) -> Result<(), SystemError> {
+        if name.len() >= ffi::DMA_BUF_NAME_LEN {
+            return Err(SystemError::InvalidArgument);
+        }
+
+        let name = CString::new(name).map_err(|_| SystemError::InvalidArgument)?;
+        ffi::dmabuf::set_name(self.fd, &name)
+    }
+
+    /// Prepares the buffer for access by the CPU, waiting for the device to
+    /// finish with it.
+    ///
+    /// The access ends when the returned `CpuAccess` is dropped. It keeps a
+    /// file descriptor of its own, so the buffer can be mapped while it
+    /// lasts.
+    pub fn begin_cpu_access(&self, access: Access) -> Result<CpuAccess, SystemError> {
+        let fd = unsafe { libc::fcntl(self.fd, libc::F_DUPFD_CLOEXEC, 0) };
+        if fd < 0 {
+            return Err(Errno::last().into());
+        }
+
+        let access = CpuAccess {
+            fd: fd,
+            access: access,
+        };
+
+        ffi::dmabuf::sync(fd, ffi::DMA_BUF_SYNC_START | access.access as u32)?;
+        Ok(access)
+    }
+
+    /// Maps the buffer into memory for reading and writing.
+    ///
+    /// This requires the file descriptor to be opened for writing, e.g. by
+    /// exporting it with `PrimeFlags::ReadWrite`. The buffer is borrowed
+    /// mutably, so no other mapping of it can be made while this one lives.
+    pub fn map(&mut self) -> Result<DmaBufMapping<'_>, SystemError> {
+        let map = Mmap::new(self.fd, 0, self.size()?, true)?;
+
+        Ok(DmaBufMapping {
+            _phantom: PhantomData,
+            map: map,
+        })
+    }
+
+    /// Maps the buffer into memory for reading.
+    pub fn map_ref(&self) -> Result<DmaBufMappingRef<'_>, SystemError> {
+        let map = Mmap::new(self.fd, 0, self.size()?, false)?;
+
+        Ok(DmaBufMappingRef {
+            _phantom: PhantomData,
+            map: map,
+        })
+    }
+
+    /// Returns a sync_file signalling once all pending accesses of the given
+    /// kind are done.
+    ///
+    /// Waiting for `Access::Read` waits for the pending writes, which is
+    /// enough to read the buffer. `Access::Write` also waits for the pending
+    /// reads. Requires Linux 6.0.
+    pub fn export_sync_file(&self, access: Access) -> Result<SyncFile, SystemError> {
+        let export = ffi::dmabuf::export_sync_file(self.fd, access as u32)?;
+        Ok(SyncFile { fd: export.fd })
+    }
+
+    /// Adds the fence of a sync_file to the implicit fences of the buffer.
+    ///
+    /// Later users of the buffer wait for the fence as if the given kind of
+    /// access was done by the device. Requires Linux 6.0.
+    pub fn import_sync_file(&self, access: Access, sync_file: &SyncFile) -> Result<(), SystemError> {
+        ffi::dmabuf::import_sync_file(self.fd, access as u32, sync_file.fd)?;
+        Ok(())
+    }
+}
+
+impl AsRawFd for DmaBuf {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl FromRawFd for DmaBuf {
+    unsafe fn from_raw_fd(fd: RawFd) -> DmaBuf {
+        DmaBuf { fd: fd }
+    }
+}
+
+impl IntoRawFd for DmaBuf {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.fd;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl Drop for DmaBuf {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// CPU access to a dma-buf, which ends on drop.
+///
+/// Errors of ending the access on drop are ignored, use `end` to handle
+/// them.
+#[derive(Debug)]
+pub struct CpuAccess {
+    fd: RawFd,
+    access: Access,
+}
+
+impl CpuAccess {
+    /// Returns the kind of access.
+    pub fn access(&self) -> Access {
+        self.access
+    }
+
+    /// Ends the access, flushing the writes of the CPU to the device.
+    pub fn end(self) -> Result<(), SystemError> {
+        let res = self.sync_end();
+        unsafe {
+            libc::close(self.fd);
+        }
+        mem::forget(self);
+        res
+    }
+
+    fn sync_end(&self) -> Result<(), SystemError> {
+        ffi::dmabuf::sync(self.fd, ffi::DMA_BUF_SYNC_END | self.access as u32)?;
+        Ok(())
+    }
+}
+
+impl Drop for CpuAccess {
+    fn drop(&mut self) {
+        let _ = self.sync_end();
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Mapping of a dma-buf
+///
+/// The buffer is unmapped when the mapping is dropped. Errors of unmapping
+/// are only reported by `unmap`.
+#[derive(Debug)]
+pub struct DmaBufMapping<'a> {
+    _phantom: PhantomData<&'a mut DmaBuf>,
+    map: Mmap,
+}
+
+impl<'a> DmaBufMapping<'a> {
+    /// Unmaps the buffer.
+    pub fn unmap(mut self) -> Result<(), SystemError> {
+        self.map.unmap()
+    }
+}
+
+impl<'a> AsRef<[u8]> for DmaBufMapping<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.map.as_slice()
+    }
+}
+
+impl<'a> AsMut<[u8]> for DmaBufMapping<'a> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.map.as_mut_slice()
+    }
+}
+
+/// Read-only mapping of a dma-buf
+///
+/// Any number of read-only mappings of a buffer may exist at the same time.
+#[derive(Debug)]
+pub struct DmaBufMappingRef<'a> {
+    _phantom: PhantomData<&'a DmaBuf>,
+    map: Mmap,
+}
+
+impl<'a> DmaBufMappingRef<'a> {
+    /// Unmaps the buffer.
+    pub fn unmap(mut self) -> Result<(), SystemError> {
+        self.map.unmap()
+    }
+}
+
+impl<'a> AsRef<[u8]> for DmaBufMappingRef<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.map.as_slice()
+    }
+}
+
+/// A sync_file fence that is closed on drop.
+///
+/// The file descriptor becomes readable once the fence signalled, so it can
+/// be polled or passed to the `IN_FENCE_FD` property of a plane.
+#[derive(Debug)]
+pub struct SyncFile {
+    fd: RawFd,
+}
+
+impl AsRawFd for SyncFile {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl FromRawFd for SyncFile {
+    unsafe fn from_raw_fd(fd: RawFd) -> SyncFile {
+        SyncFile { fd: fd }
+    }
+}
+
+impl IntoRawFd for SyncFile {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.fd;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl Drop for SyncFile {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::OpenOptions;
+
+    const SIZE: usize = 4096;
+
+    // _IOW('u', 0x42, struct udmabuf_create)
+    const UDMABUF_CREATE: libc::c_ulong = 0x4018_7542;
+
+    #[repr(C)]
+    struct UdmabufCreate {
+        memfd: u32,
+        flags: u32,
+        offset: u64,
+        size: u64,
+    }
+
+    /// Returns a sealed memfd of one page, as `/dev/udmabuf` requires.
+    fn memfd() -> RawFd {
+        let name = CString::new("drm-rs-test").unwrap();
+        let fd = unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING) };
+        assert!(fd >= 0, "memfd_create failed: {}", Errno::last());
+
+        unsafe {
+            assert_eq!(libc::ftruncate(fd, SIZE as libc::off_t), 0);
+            assert_eq!(libc::fcntl(fd, libc::F_ADD_SEALS, libc::F_SEAL_SHRINK), 0);
+        }
+
+        fd
+    }
+
+    /// Returns a dma-buf of a memfd, and the memfd, if `/dev/udmabuf` is
+    /// available. Otherwise the test is skipped with a message.
+    fn udmabuf() -> Option<(DmaBuf, DmaBuf)> {
+        let dev = match OpenOptions::new().read(true).write(true).open("/dev/udmabuf") {
+            Ok(dev) => dev,
+            Err(err) => {
+                eprintln!("skipping test, /dev/udmabuf is not available: {}", err);
+                return None;
+            }
+        };
+
+        let memfd = memfd();
+        let create = UdmabufCreate {
+            memfd: memfd as u32,
+            flags: libc::O_CLOEXEC as u32,
+            offset: 0,
+            size: SIZE as u64,
+        };
+
+        let fd = unsafe { libc::ioctl(dev.as_raw_fd(), UDMABUF_CREATE, &create) };
+        assert!(fd >= 0, "UDMABUF_CREATE failed: {}", Errno::last());
+
+        unsafe { Some((DmaBuf::from_raw_fd(fd), DmaBuf::from_raw_fd(memfd))) }
+    }
+
+    #[test]
+    fn map_writes_are_shared() {
+        let mut buffer = unsafe { DmaBuf::from_raw_fd(memfd()) };
+        assert_eq!(buffer.size().unwrap(), SIZE);
+
+        {
+            let mut map = buffer.map().unwrap();
+            assert_eq!(map.as_ref().len(), SIZE);
+            map.as_mut()[..4].copy_from_slice(b"drm!");
+            map.unmap().unwrap();
+        }
+
+        let first = buffer.map_ref().unwrap();
+        let second = buffer.map_ref().unwrap();
+        assert_eq!(&first.as_ref()[..4], b"drm!");
+        assert_eq!(&second.as_ref()[..4], b"drm!");
+    }
+
+    #[test]
+    fn set_name_rejects_invalid_names() {
+        let buffer = unsafe { DmaBuf::from_raw_fd(memfd()) };
+
+        match buffer.set_name(&"x".repeat(32)) {
+            Err(SystemError::InvalidArgument) => (),
+            res => panic!("long name was not rejected: {:?}", res),
+        }
+        match buffer.set_name("a\0b") {
+            Err(SystemError::InvalidArgument) => (),
+            res => panic!("name with a nul byte was not rejected: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn udmabuf_set_name() {
+        let (buffer, _memfd) = match udmabuf() {
+            Some(buffers) => buffers,
+            None => return,
+        };
+
+        buffer.set_name("drm-rs-test").unwrap();
+        buffer.set_name(&"x".repeat(31)).unwrap();
+    }
+
+    #[test]
+    fn udmabuf_cpu_access() {
+        let (mut buffer, memfd) = match udmabuf() {
+            Some(buffers) => buffers,
+            None => return,
+        };
+
+        let access = buffer.begin_cpu_access(Access::Write).unwrap();
+        assert_eq!(access.access(), Access::Write);
+        {
+            let mut map = buffer.map().unwrap();
+            for byte in map.as_mut() {
+                *byte = 0xa5;
+            }
+        }
+        access.end().unwrap();
+
+        let access = buffer.begin_cpu_access(Access::Read).unwrap();
+        assert!(buffer.map_ref().unwrap().as_ref().iter().all(|&b| b == 0xa5));
+        drop(access);
+
+        assert!(memfd.map_ref().unwrap().as_ref().iter().all(|&b| b == 0xa5));
+    }
+
+    #[test]
+    fn udmabuf_sync_file_round_trip() {
+        let (buffer, _memfd) = match udmabuf() {
+            Some(buffers) => buffers,
+            None => return,
+        };
+
+        let fence = match buffer.export_sync_file(Access::Write) {
+            Ok(fence) => fence,
+            Err(SystemError::Unknown { errno: Errno::ENOTTY }) => {
+                eprintln!("skipping test, sync_file export requires Linux 6.0");
+                return;
+            }
+            Err(err) => panic!("export_sync_file failed: {:?}", err),
+        };
+
+        // Nothing accesses the buffer, so the fence has already signalled.
+        let mut poll = libc::pollfd {
+            fd: fence.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        assert_eq!(unsafe { libc::poll(&mut poll, 1, 0) }, 1);
+        assert_ne!(poll.revents & libc::POLLIN, 0);
+
+        buffer.import_sync_file(Access::Read, &fence).unwrap();
+        buffer.import_sync_file(Access::Write, &fence).unwrap();
+
+        // The imported fence is among the ones exported again.
+        let again = buffer.export_sync_file(Access::Write).unwrap();
+        poll.fd = again.as_raw_fd();
+        poll.revents = 0;
+        assert_eq!(unsafe { libc::poll(&mut poll, 1, 0) }, 1);
+    }
+}
@@ -1,320 +1,857 @@
 //! Color formats using standard FourCC.
+//!
+//! Every format is described by a `FormatInfo`, which tells how its pixels
+//! are laid out in memory: the number of planes, the size of a block of
+//! pixels in each plane, and the subsampling of the chroma planes. The table
+//! follows `drm_fourcc.h` and the format descriptions of the kernel.
+//!
+//! ```
+//! use drm::buffer::format::PixelFormat;
+//! use std::convert::TryFrom;
+//!
+//! let format = PixelFormat::try_from(0x3231564e).unwrap();
+//! assert_eq!(format, PixelFormat::NV12);
+//!
+//! let info = format.info();
+//! assert_eq!(info.planes(), 2);
+//! assert_eq!(info.subsampling(), (2, 2));
+//! assert_eq!(info.plane_size(1, (1920, 1080)), (960, 540));
+//! assert_eq!(info.min_pitch(1, 1920), 1920);
+//! ```
 
 use drm_ffi::fourcc::*;
 
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-#[allow(missing_docs)]
-/// Possible pixel formats of a buffer
-pub enum PixelFormat {
-    C8,
-    R8,
-    GR88,
-
-    RGB332,
-    BGR233,
-
-    XRGB4444,
-    XBGR4444,
-    RGBX4444,
-    BGRX4444,
-
-    ARGB4444,
-    ABGR4444,
-    RGBA4444,
-    BGRA4444,
-
-    XRGB1555,
-    XBGR1555,
-    RGBX5551,
-    BGRX5551,
-
-    ARGB1555,
-    ABGR1555,
-    RGBA5551,
-    BGRA5551,
-
-    RGB565,
-    BGR565,
-
-    XRGB8888,
-    XBGR8888,
-    RGBX8888,
-    BGRX8888,
-
-    ARGB8888,
-    ABGR8888,
-    RGBA8888,
-    BGRA8888,
-
-    XRGB2101010,
-    XBGR2101010,
-    RGBX1010102,
-    BGRX1010102,
-
-    ARGB2101010,
-    ABGR2101010,
-    RGBA1010102,
-    BGRA1010102,
-
-    YUYV,
-    YVYU,
-    UYVY,
-    VYUY,
-
-    AYUV,
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The description of a pixel format.
+///
+/// Pixels are stored in blocks, which are a single pixel for most formats.
+/// The block size of a plane is given in pixels of that plane, so chroma
+/// planes of subsampled formats cover more pixels of the image.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FormatInfo {
+    format: PixelFormat,
+    planes: usize,
+    block_bytes: [u32; 4],
+    block_size: [(u32, u32); 4],
+    subsampling: (u32, u32),
+    alpha: bool,
+    opaque: Option<PixelFormat>,
+    depth: Option<u32>,
+    yuv: bool,
+}
+
+impl FormatInfo {
+    /// Returns the format this describes.
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Returns the number of planes.
+    pub fn planes(&self) -> usize {
+        self.planes
+    }
+
+    /// Returns the number of bytes of a block of pixels in a plane.
+    ///
+    /// Returns `0` for planes the format does not have, and for formats that
+    /// have no linear layout and can only be used with a modifier.
+    pub fn block_bytes(&self, plane: usize) -> u32 {
+        self.block_bytes.get(plane).copied().unwrap_or(0)
+    }
+
+    /// Returns the width and height in pixels of a block in a plane.
+    pub fn block_size(&self, plane: usize) -> (u32, u32) {
+        self.block_size.get(plane).copied().unwrap_or((1, 1))
+    }
+
+    /// Returns the horizontal and vertical subsampling of the chroma planes.
+    ///
+    /// This is `(1, 1)` for formats without subsampled chroma.
+    pub fn subsampling(&self) -> (u32, u32) {
+        self.subsampling
+    }
+
+    /// Returns whether the format has an alpha channel.
+    pub fn has_alpha(&self) -> bool {
+        self.alpha
+    }
+
+    /// Returns the format with the alpha channel ignored, such as
+    /// `XRGB8888` for `ARGB8888`.
+    ///
+    /// Formats without alpha are their own opaque equivalent. Returns `None`
+    /// if there is no format that only ignores the alpha channel.
+    pub fn opaque(&self) -> Option<PixelFormat> {
+        if self.alpha {
+            self.opaque
+        } else {
+            Some(self.format)
+        }
+    }
+
+    /// Returns whether the format stores YUV rather than RGB.
+    pub fn is_yuv(&self) -> bool {
+        self.yuv
+    }
+
+    /// Returns the depth to add a framebuffer through the legacy interface.
+    ///
+    /// The kernel maps each pair of depth and `bpp` back to a single format,
+    /// so only C8, XRGB1555, RGB565, RGB888, XRGB8888, XRGB2101010 and
+    /// ARGB8888 have one.
+    pub fn depth(&self) -> Option<u32> {
+        self.depth
+    }
+
+    /// Returns the number of bits per pixel of a plane, rounded up.
+    pub fn bpp(&self, plane: usize) -> u32 {
+        let (width, height) = self.block_size(plane);
+        (self.block_bytes(plane) * 8).div_ceil(width * height)
+    }
+
+    /// Returns the width and height of a plane of an image of a size.
+    ///
+    /// Chroma planes of subsampled formats are rounded up, so that every
+    /// pixel of the image is covered.
+    pub fn plane_size(&self, plane: usize, size: (u32, u32)) -> (u32, u32) {
+        if plane == 0 {
+            return size;
+        }
+
+        let (h, v) = self.subsampling;
+        (size.0.div_ceil(h), size.1.div_ceil(v))
+    }
+
+    /// Returns the minimal number of bytes of a row of a plane of an image
+    /// of a width.
+    pub fn min_pitch(&self, plane: usize, width: u32) -> u32 {
+        let width = self.plane_size(plane, (width, 1)).0;
+        let block_width = self.block_size(plane).0;
+        width.div_ceil(block_width) * self.block_bytes(plane)
+    }
+}
+
+/// Describes a format with a single plane of pixels stored in `bytes`.
+fn packed(bytes: u32) -> FormatInfo {
+    FormatInfo {
+        // Replaced with the described format
+        format: PixelFormat::C8,
+        planes: 1,
+        block_bytes: [bytes, 0, 0, 0],
+        block_size: [(1, 1); 4],
+        subsampling: (1, 1),
+        alpha: false,
+        opaque: None,
+        depth: None,
+        yuv: false,
+    }
+}
+
+/// Describes a format packing a single plane of pixels of `bits` into bytes.
+fn sub_byte(bits: u32) -> FormatInfo {
+    FormatInfo {
+        block_bytes: [1, 0, 0, 0],
+        block_size: [(8 / bits, 1), (1, 1), (1, 1), (1, 1)],
+        ..packed(1)
+    }
+}
+
+/// Describes a format with a plane for each entry of `bytes`.
+fn planar(bytes: &[u32]) -> FormatInfo {
+    let mut block_bytes = [0; 4];
+    block_bytes[..bytes.len()].copy_from_slice(bytes);
+
+    FormatInfo {
+        planes: bytes.len(),
+        block_bytes: block_bytes,
+        ..packed(0)
+    }
+}
+
+impl FormatInfo {
+    fn depth_of(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    fn alpha_of(mut self, opaque: Option<PixelFormat>) -> Self {
+        self.alpha = true;
+        self.opaque = opaque;
+        self
+    }
+
+    fn yuv_of(mut self, subsampling: (u32, u32)) -> Self {
+        self.yuv = true;
+        self.subsampling = subsampling;
+        self
+    }
+
+    fn blocks_of(mut self, sizes: &[(u32, u32)]) -> Self {
+        self.block_size[..sizes.len()].copy_from_slice(sizes);
+        self
+    }
+}
+
+macro_rules! formats {
+    ($($name:ident = $raw:ident => $info:expr,)*) => {
+        #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+        #[allow(missing_docs)]
+        #[allow(non_camel_case_types)]
+        /// Possible pixel formats of a buffer
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum PixelFormat {
+            $($name,)*
+        }
+
+        impl PixelFormat {
+            /// Convert into the raw fourcc code
+            pub fn as_raw(&self) -> u32 {
+                match *self {
+                    $(PixelFormat::$name => $raw,)*
+                }
+            }
+
+            /// Convert from a raw fourcc code, if the format is known
+            pub fn from_raw(raw: u32) -> Option<PixelFormat> {
+                match raw {
+                    $($raw => Some(PixelFormat::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the name of the format in `drm_fourcc.h`, without the
+            /// `DRM_FORMAT_` prefix.
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    $(PixelFormat::$name => stringify!($name),)*
+                }
+            }
+
+            /// Every known format.
+            #[cfg(test)]
+            const ALL: &[PixelFormat] = &[$(PixelFormat::$name,)*];
+
+            fn from_name(name: &str) -> Option<PixelFormat> {
+                match name {
+                    $(stringify!($name) => Some(PixelFormat::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the description of the format.
+            pub fn info(&self) -> FormatInfo {
+                use self::PixelFormat::*;
+                let info = match *self {
+                    $($name => $info,)*
+                };
+
+                FormatInfo {
+                    format: *self,
+                    ..info
+                }
+            }
+        }
+    };
+}
+
+formats! {
+    C1 = DRM_FORMAT_C1 => sub_byte(1),
+    C2 = DRM_FORMAT_C2 => sub_byte(2),
+    C4 = DRM_FORMAT_C4 => sub_byte(4),
+    C8 = DRM_FORMAT_C8 => packed(1).depth_of(8),
+
+    D1 = DRM_FORMAT_D1 => sub_byte(1),
+    D2 = DRM_FORMAT_D2 => sub_byte(2),
+    D4 = DRM_FORMAT_D4 => sub_byte(4),
+    D8 = DRM_FORMAT_D8 => packed(1),
+
+    R1 = DRM_FORMAT_R1 => sub_byte(1),
+    R2 = DRM_FORMAT_R2 => sub_byte(2),
+    R4 = DRM_FORMAT_R4 => sub_byte(4),
+    R8 = DRM_FORMAT_R8 => packed(1),
+    R10 = DRM_FORMAT_R10 => packed(2),
+    R12 = DRM_FORMAT_R12 => packed(2),
+    R16 = DRM_FORMAT_R16 => packed(2),
+
+    RG88 = DRM_FORMAT_RG88 => packed(2),
+    GR88 = DRM_FORMAT_GR88 => packed(2),
+    RG1616 = DRM_FORMAT_RG1616 => packed(4),
+    GR1616 = DRM_FORMAT_GR1616 => packed(4),
+
+    RGB332 = DRM_FORMAT_RGB332 => packed(1),
+    BGR233 = DRM_FORMAT_BGR233 => packed(1),
+
+    XRGB4444 = DRM_FORMAT_XRGB4444 => packed(2),
+    XBGR4444 = DRM_FORMAT_XBGR4444 => packed(2),
+    RGBX4444 = DRM_FORMAT_RGBX4444 => packed(2),
+    BGRX4444 = DRM_FORMAT_BGRX4444 => packed(2),
+
+    ARGB4444 = DRM_FORMAT_ARGB4444 => packed(2).alpha_of(Some(XRGB4444)),
+    ABGR4444 = DRM_FORMAT_ABGR4444 => packed(2).alpha_of(Some(XBGR4444)),
+    RGBA4444 = DRM_FORMAT_RGBA4444 => packed(2).alpha_of(Some(RGBX4444)),
+    BGRA4444 = DRM_FORMAT_BGRA4444 => packed(2).alpha_of(Some(BGRX4444)),
+
+    XRGB1555 = DRM_FORMAT_XRGB1555 => packed(2).depth_of(15),
+    XBGR1555 = DRM_FORMAT_XBGR1555 => packed(2),
+    RGBX5551 = DRM_FORMAT_RGBX5551 => packed(2),
+    BGRX5551 = DRM_FORMAT_BGRX5551 => packed(2),
+
+    ARGB1555 = DRM_FORMAT_ARGB1555 => packed(2).alpha_of(Some(XRGB1555)),
+    ABGR1555 = DRM_FORMAT_ABGR1555 => packed(2).alpha_of(Some(XBGR1555)),
+    RGBA5551 = DRM_FORMAT_RGBA5551 => packed(2).alpha_of(Some(RGBX5551)),
+    BGRA5551 = DRM_FORMAT_BGRA5551 => packed(2).alpha_of(Some(BGRX5551)),
+
+    RGB565 = DRM_FORMAT_RGB565 => packed(2).depth_of(16),
+    BGR565 = DRM_FORMAT_BGR565 => packed(2),
+
+    RGB888 = DRM_FORMAT_RGB888 => packed(3).depth_of(24),
+    BGR888 = DRM_FORMAT_BGR888 => packed(3),
+
+    XRGB8888 = DRM_FORMAT_XRGB8888 => packed(4).depth_of(24),
+    XBGR8888 = DRM_FORMAT_XBGR8888 => packed(4),
+    RGBX8888 = DRM_FORMAT_RGBX8888 => packed(4),
+    BGRX8888 = DRM_FORMAT_BGRX8888 => packed(4),
+
+    ARGB8888 = DRM_FORMAT_ARGB8888 => packed(4).depth_of(32).alpha_of(Some(XRGB8888)),
+    ABGR8888 = DRM_FORMAT_ABGR8888 => packed(4).alpha_of(Some(XBGR8888)),
+    RGBA8888 = DRM_FORMAT_RGBA8888 => packed(4).alpha_of(Some(RGBX8888)),
+    BGRA8888 = DRM_FORMAT_BGRA8888 => packed(4).alpha_of(Some(BGRX8888)),
+
+    XRGB2101010 = DRM_FORMAT_XRGB2101010 => packed(4).depth_of(30),
+    XBGR2101010 = DRM_FORMAT_XBGR2101010 => packed(4),
+    RGBX1010102 = DRM_FORMAT_RGBX1010102 => packed(4),
+    BGRX1010102 = DRM_FORMAT_BGRX1010102 => packed(4),
+
+    ARGB2101010 = DRM_FORMAT_ARGB2101010 => packed(4).alpha_of(Some(XRGB2101010)),
+    ABGR2101010 = DRM_FORMAT_ABGR2101010 => packed(4).alpha_of(Some(XBGR2101010)),
+    RGBA1010102 = DRM_FORMAT_RGBA1010102 => packed(4).alpha_of(Some(RGBX1010102)),
+    BGRA1010102 = DRM_FORMAT_BGRA1010102 => packed(4).alpha_of(Some(BGRX1010102)),
+
+    XRGB16161616F = DRM_FORMAT_XRGB16161616F => packed(8),
+    XBGR16161616F = DRM_FORMAT_XBGR16161616F => packed(8),
+    ARGB16161616F = DRM_FORMAT_ARGB16161616F => packed(8).alpha_of(Some(XRGB16161616F)),
+    ABGR16161616F = DRM_FORMAT_ABGR16161616F => packed(8).alpha_of(Some(XBGR16161616F)),
+
+    XRGB16161616 = DRM_FORMAT_XRGB16161616 => packed(8),
+    XBGR16161616 = DRM_FORMAT_XBGR16161616 => packed(8),
+    ARGB16161616 = DRM_FORMAT_ARGB16161616 => packed(8).alpha_of(Some(XRGB16161616)),
+    ABGR16161616 = DRM_FORMAT_ABGR16161616 => packed(8).alpha_of(Some(XBGR16161616)),
+
+    AXBXGXRX106106106106 = DRM_FORMAT_AXBXGXRX106106106106 => packed(8).alpha_of(None),
+
+    XRGB8888_A8 = DRM_FORMAT_XRGB8888_A8 => planar(&[4, 1]).alpha_of(Some(XRGB8888)),
+    XBGR8888_A8 = DRM_FORMAT_XBGR8888_A8 => planar(&[4, 1]).alpha_of(Some(XBGR8888)),
+    RGBX8888_A8 = DRM_FORMAT_RGBX8888_A8 => planar(&[4, 1]).alpha_of(Some(RGBX8888)),
+    BGRX8888_A8 = DRM_FORMAT_BGRX8888_A8 => planar(&[4, 1]).alpha_of(Some(BGRX8888)),
+    RGB888_A8 = DRM_FORMAT_RGB888_A8 => planar(&[3, 1]).alpha_of(Some(RGB888)),
+    BGR888_A8 = DRM_FORMAT_BGR888_A8 => planar(&[3, 1]).alpha_of(Some(BGR888)),
+    RGB565_A8 = DRM_FORMAT_RGB565_A8 => planar(&[2, 1]).alpha_of(Some(RGB565)),
+    BGR565_A8 = DRM_FORMAT_BGR565_A8 => planar(&[2, 1]).alpha_of(Some(BGR565)),
+
+    YUYV = DRM_FORMAT_YUYV => packed(2).yuv_of((2, 1)),
+    YVYU = DRM_FORMAT_YVYU => packed(2).yuv_of((2, 1)),
+    UYVY = DRM_FORMAT_UYVY => packed(2).yuv_of((2, 1)),
+    VYUY = DRM_FORMAT_VYUY => packed(2).yuv_of((2, 1)),
+
+    AYUV = DRM_FORMAT_AYUV => packed(4).yuv_of((1, 1)).alpha_of(Some(XYUV8888)),
+    AVUY8888 = DRM_FORMAT_AVUY8888 => packed(4).yuv_of((1, 1)).alpha_of(Some(XVUY8888)),
+    XYUV8888 = DRM_FORMAT_XYUV8888 => packed(4).yuv_of((1, 1)),
+    XVUY8888 = DRM_FORMAT_XVUY8888 => packed(4).yuv_of((1, 1)),
+    VUY888 = DRM_FORMAT_VUY888 => packed(3).yuv_of((1, 1)),
+    VUY101010 = DRM_FORMAT_VUY101010 => planar(&[0]).yuv_of((1, 1)),
+
+    Y210 = DRM_FORMAT_Y210 => packed(4).yuv_of((2, 1)),
+    Y212 = DRM_FORMAT_Y212 => packed(4).yuv_of((2, 1)),
+    Y216 = DRM_FORMAT_Y216 => packed(4).yuv_of((2, 1)),
+
+    Y410 = DRM_FORMAT_Y410 => packed(4).yuv_of((1, 1)).alpha_of(Some(XVYU2101010)),
+    Y412 = DRM_FORMAT_Y412 => packed(8).yuv_of((1, 1)).alpha_of(Some(XVYU12_16161616)),
+    Y416 = DRM_FORMAT_Y416 => packed(8).yuv_of((1, 1)).alpha_of(Some(XVYU16161616)),
+
+    XVYU2101010 = DRM_FORMAT_XVYU2101010 => packed(4).yuv_of((1, 1)),
+    XVYU12_16161616 = DRM_FORMAT_XVYU12_16161616 => packed(8).yuv_of((1, 1)),
+    XVYU16161616 = DRM_FORMAT_XVYU16161616 => packed(8).yuv_of((1, 1)),
+
+    Y0L0 = DRM_FORMAT_Y0L0 => packed(8).blocks_of(&[(2, 2)]).yuv_of((2, 2)).alpha_of(Some(X0L0)),
+    X0L0 = DRM_FORMAT_X0L0 => packed(8).blocks_of(&[(2, 2)]).yuv_of((2, 2)),
+    Y0L2 = DRM_FORMAT_Y0L2 => packed(8).blocks_of(&[(2, 2)]).yuv_of((2, 2)).alpha_of(Some(X0L2)),
+    X0L2 = DRM_FORMAT_X0L2 => packed(8).blocks_of(&[(2, 2)]).yuv_of((2, 2)),
+
+    YUV420_8BIT = DRM_FORMAT_YUV420_8BIT => planar(&[0]).yuv_of((2, 2)),
+    YUV420_10BIT = DRM_FORMAT_YUV420_10BIT => planar(&[0]).yuv_of((2, 2)),
+
+    NV12 = DRM_FORMAT_NV12 => planar(&[1, 2]).yuv_of((2, 2)),
+    NV21 = DRM_FORMAT_NV21 => planar(&[1, 2]).yuv_of((2, 2)),
+    NV16 = DRM_FORMAT_NV16 => planar(&[1, 2]).yuv_of((2, 1)),
+    NV61 = DRM_FORMAT_NV61 => planar(&[1, 2]).yuv_of((2, 1)),
+    NV24 = DRM_FORMAT_NV24 => planar(&[1, 2]).yuv_of((1, 1)),
+    NV42 = DRM_FORMAT_NV42 => planar(&[1, 2]).yuv_of((1, 1)),
+    NV15 = DRM_FORMAT_NV15 => planar(&[5, 5]).blocks_of(&[(4, 1), (2, 1)]).yuv_of((2, 2)),
+    NV20 = DRM_FORMAT_NV20 => planar(&[5, 5]).blocks_of(&[(4, 1), (2, 1)]).yuv_of((2, 1)),
+    NV30 = DRM_FORMAT_NV30 => planar(&[5, 5]).blocks_of(&[(4, 1), (2, 1)]).yuv_of((1, 1)),
+
+    P210 = DRM_FORMAT_P210 => planar(&[2, 4]).yuv_of((2, 1)),
+    P010 = DRM_FORMAT_P010 => planar(&[2, 4]).yuv_of((2, 2)),
+    P012 = DRM_FORMAT_P012 => planar(&[2, 4]).yuv_of((2, 2)),
+    P016 = DRM_FORMAT_P016 => planar(&[2, 4]).yuv_of((2, 2)),
+    P030 = DRM_FORMAT_P030 => planar(&[4, 8]).blocks_of(&[(3, 1), (3, 1)]).yuv_of((2, 2)),
+
+    Q410 = DRM_FORMAT_Q410 => planar(&[2, 2, 2]).yuv_of((1, 1)),
+    Q401 = DRM_FORMAT_Q401 => planar(&[2, 2, 2]).yuv_of((1, 1)),
+
+    YUV410 = DRM_FORMAT_YUV410 => planar(&[1, 1, 1]).yuv_of((4, 4)),
+    YVU410 = DRM_FORMAT_YVU410 => planar(&[1, 1, 1]).yuv_of((4, 4)),
+    YUV411 = DRM_FORMAT_YUV411 => planar(&[1, 1, 1]).yuv_of((4, 1)),
+    YVU411 = DRM_FORMAT_YVU411 => planar(&[1, 1, 1]).yuv_of((4, 1)),
+    YUV420 = DRM_FORMAT_YUV420 => planar(&[1, 1, 1]).yuv_of((2, 2)),
+    YVU420 = DRM_FORMAT_YVU420 => planar(&[1, 1, 1]).yuv_of((2, 2)),
+    YUV422 = DRM_FORMAT_YUV422 => planar(&[1, 1, 1]).yuv_of((2, 1)),
+    YVU422 = DRM_FORMAT_YVU422 => planar(&[1, 1, 1]).yuv_of((2, 1)),
+    YUV444 = DRM_FORMAT_YUV444 => planar(&[1, 1, 1]).yuv_of((1, 1)),
+    YVU444 = DRM_FORMAT_YVU444 => planar(&[1, 1, 1]).yuv_of((1, 1)),
 }
 
 impl PixelFormat {
+    /// The depth in bits per pixel, as used by the legacy interface to add
+    /// framebuffers.
+    ///
+    /// Returns `None` for formats the legacy interface would mistake for
+    /// another format of the same depth and `bpp`, e.g. XBGR8888 for
+    /// XRGB8888, and for planar and YUV formats. Framebuffers of these have
+    /// to be added with `Device::add_planar_framebuffer`.
+    ///
+    /// ```
+    /// use drm::buffer::format::PixelFormat;
+    ///
+    /// assert_eq!(PixelFormat::XRGB8888.depth(), Some(24));
+    /// assert_eq!(PixelFormat::XBGR8888.depth(), None);
+    /// assert_eq!(PixelFormat::YUYV.depth(), None);
+    /// ```
+    pub fn depth(&self) -> Option<u32> {
+        self.info().depth()
+    }
+
+    /// Bits per pixel of the first plane of the used format
+    ///
+    /// ```
+    /// use drm::buffer::format::PixelFormat;
+    ///
+    /// assert_eq!(PixelFormat::XRGB8888.bpp(), 32);
+    /// assert_eq!(PixelFormat::NV12.bpp(), 8);
+    /// ```
+    pub fn bpp(&self) -> u32 {
+        self.info().bpp(0)
+    }
+}
+
+impl TryFrom<u32> for PixelFormat {
+    type Error = u32;
+
+    /// Converts a raw fourcc code, returning it back if it is unknown.
+    fn try_from(raw: u32) -> Result<PixelFormat, u32> {
+        PixelFormat::from_raw(raw).ok_or(raw)
+    }
+}
+
+impl fmt::Display for PixelFormat {
+    /// Writes the four-character code, or the name in `drm_fourcc.h` with
+    /// the `#` flag.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&Fourcc::from(*self), f)
+    }
+}
+
+impl FromStr for PixelFormat {
+    type Err = ParseFourccError;
+
+    /// Parses a format in any of the forms accepted by `Fourcc`, as long as
+    /// it is a known little-endian format.
+    fn from_str(s: &str) -> Result<PixelFormat, ParseFourccError> {
+        s.parse::<Fourcc>()?.format().ok_or(ParseFourccError)
+    }
+}
+
+/// A raw fourcc code, such as those of the formats a plane supports.
+///
+/// Unlike `PixelFormat`, this can hold codes that are not known to this crate
+/// and big-endian variants of formats. Codes are written as four characters
+/// with trailing spaces dropped, followed by `big-endian` if the flag is set.
+/// Codes that are not printable are written in hexadecimal. The `#` flag
+/// writes known formats by their name in `drm_fourcc.h` instead.
+///
+/// Any of these forms can be parsed back, as well as the names without the
+/// `DRM_FORMAT_` prefix.
+///
+/// ```
+/// use drm::buffer::format::{Fourcc, PixelFormat};
+///
+/// let fourcc = Fourcc::from(0x34325258);
+/// assert_eq!(fourcc.to_string(), "XR24");
+/// assert_eq!(format!("{:#}", fourcc), "DRM_FORMAT_XRGB8888");
+/// assert_eq!(fourcc.format(), Some(PixelFormat::XRGB8888));
+///
+/// assert_eq!("AR30".parse::<PixelFormat>(), Ok(PixelFormat::ARGB2101010));
+/// assert_eq!("DRM_FORMAT_NV12".parse::<PixelFormat>(), Ok(PixelFormat::NV12));
+/// assert_eq!("P010".parse::<PixelFormat>(), Ok(PixelFormat::P010));
+///
+/// let big_endian: Fourcc = "RG16 big-endian".parse().unwrap();
+/// assert!(big_endian.is_big_endian());
+/// assert_eq!(big_endian.format(), None);
+/// assert_eq!(Fourcc::from(0x00000001).to_string(), "0x00000001");
+/// ```
+#[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Fourcc(u32);
+
+impl Fourcc {
     /// Convert into the raw fourcc code
     pub fn as_raw(&self) -> u32 {
-        use self::PixelFormat::*;
-        match *self {
-            C8 => DRM_FORMAT_C8,
-            R8 => DRM_FORMAT_R8,
-            GR88 => DRM_FORMAT_GR88,
-
-            RGB332 => DRM_FORMAT_RGB332,
-            BGR233 => DRM_FORMAT_BGR233,
-
-            XRGB4444 => DRM_FORMAT_XRGB4444,
-            XBGR4444 => DRM_FORMAT_XBGR4444,
-            RGBX4444 => DRM_FORMAT_RGBX4444,
-            BGRX4444 => DRM_FORMAT_BGRX4444,
-
-            ARGB4444 => DRM_FORMAT_ARGB4444,
-            ABGR4444 => DRM_FORMAT_ABGR4444,
-            RGBA4444 => DRM_FORMAT_RGBA4444,
-            BGRA4444 => DRM_FORMAT_BGRA4444,
-
-            XRGB1555 => DRM_FORMAT_XRGB1555,
-            XBGR1555 => DRM_FORMAT_XBGR1555,
-            RGBX5551 => DRM_FORMAT_RGBX5551,
-            BGRX5551 => DRM_FORMAT_BGRX5551,
-
-            ARGB1555 => DRM_FORMAT_ARGB1555,
-            ABGR1555 => DRM_FORMAT_ABGR1555,
-            RGBA5551 => DRM_FORMAT_RGBA4444,
-            BGRA5551 => DRM_FORMAT_RGBA5551,
-
-            RGB565 => DRM_FORMAT_RGB565,
-            BGR565 => DRM_FORMAT_BGR565,
-
-            XRGB8888 => DRM_FORMAT_XRGB8888,
-            XBGR8888 => DRM_FORMAT_XBGR8888,
-            RGBX8888 => DRM_FORMAT_RGBX8888,
-            BGRX8888 => DRM_FORMAT_BGRX8888,
-
-            ARGB8888 => DRM_FORMAT_ARGB8888,
-            ABGR8888 => DRM_FORMAT_ABGR8888,
-            RGBA8888 => DRM_FORMAT_RGBA8888,
-            BGRA8888 => DRM_FORMAT_BGRA8888,
-
-            XRGB2101010 => DRM_FORMAT_XRGB2101010,
-            XBGR2101010 => DRM_FORMAT_XBGR2101010,
-            RGBX1010102 => DRM_FORMAT_RGBX1010102,
-            BGRX1010102 => DRM_FORMAT_BGRX1010102,
-
-            ARGB2101010 => DRM_FORMAT_ARGB2101010,
-            ABGR2101010 => DRM_FORMAT_ABGR2101010,
-            RGBA1010102 => DRM_FORMAT_RGBA1010102,
-            BGRA1010102 => DRM_FORMAT_BGRA1010102,
-
-            YUYV => DRM_FORMAT_YUYV,
-            YVYU => DRM_FORMAT_YVYU,
-            UYVY => DRM_FORMAT_UYVY,
-            VYUY => DRM_FORMAT_VYUY,
-
-            AYUV => DRM_FORMAT_AYUV,
+        self.0
+    }
+
+    /// Returns whether the big-endian flag is set.
+    pub fn is_big_endian(&self) -> bool {
+        self.0 & DRM_FORMAT_BIG_ENDIAN != 0
+    }
+
+    /// Returns the format of the code, if it is known and little-endian.
+    pub fn format(&self) -> Option<PixelFormat> {
+        PixelFormat::from_raw(self.0)
+    }
+
+    /// Returns the four characters of the code, if they are printable.
+    fn chars(&self) -> Option<[u8; 4]> {
+        let code = self.0 & !DRM_FORMAT_BIG_ENDIAN;
+        let chars = [code as u8, (code >> 8) as u8, (code >> 16) as u8, (code >> 24) as u8];
+
+        if chars.iter().all(|&c| c == b' ' || c.is_ascii_graphic()) && chars[0] != b' ' {
+            Some(chars)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<u32> for Fourcc {
+    fn from(raw: u32) -> Self {
+        Fourcc(raw)
+    }
+}
+
+impl From<Fourcc> for u32 {
+    fn from(fourcc: Fourcc) -> Self {
+        fourcc.0
+    }
+}
+
+impl From<PixelFormat> for Fourcc {
+    fn from(format: PixelFormat) -> Self {
+        Fourcc(format.as_raw())
+    }
+}
+
+impl fmt::Debug for Fourcc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Fourcc({})", self)
+    }
+}
+
+impl fmt::Display for Fourcc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = PixelFormat::from_raw(self.0 & !DRM_FORMAT_BIG_ENDIAN);
+
+        match (self.chars(), format) {
+            (_, Some(format)) if f.alternate() => {
+                write!(f, "DRM_FORMAT_{}", format.name())?;
+                if self.is_big_endian() {
+                    f.write_str(" | DRM_FORMAT_BIG_ENDIAN")?;
+                }
+                Ok(())
+            }
+            (Some(chars), _) => {
+                let len = chars.iter().rposition(|&c| c != b' ').map_or(0, |i| i + 1);
+                let chars = chars[..len].iter().map(|&c| c as char).collect::<String>();
+                f.write_str(&chars)?;
+                if self.is_big_endian() {
+                    f.write_str(" big-endian")?;
+                }
+                Ok(())
+            }
+            (None, _) => write!(f, "0x{:08x}", self.0),
         }
     }
+}
+
+impl FromStr for Fourcc {
+    type Err = ParseFourccError;
+
+    fn from_str(s: &str) -> Result<Fourcc, ParseFourccError> {
+        let s = s.trim();
+        let (s, big_endian) = match s
+            .strip_suffix("big-endian")
+            .or_else(|| s.strip_suffix("| DRM_FORMAT_BIG_ENDIAN"))
+        {
+            Some(s) => (s.trim_end(), DRM_FORMAT_BIG_ENDIAN),
+            None => (s, 0),
+        };
+
+        if let Some(hex) = s.strip_prefix("0x") {
+            return u32::from_str_radix(hex, 16)
+                .map(|code| Fourcc(code | big_endian))
+                .map_err(|_| ParseFourccError);
+        }
+
+        let name = s.strip_prefix("DRM_FORMAT_").unwrap_or(s);
+        if let Some(format) = PixelFormat::from_name(name) {
+            return Ok(Fourcc(format.as_raw() | big_endian));
+        }
 
-    pub fn from_raw(raw: u32) -> Option<PixelFormat> {
-        use self::PixelFormat::*;
-        Some(match raw {
-           DRM_FORMAT_C8 => C8,
-           DRM_FORMAT_R8 => R8,
-           DRM_FORMAT_GR88 => GR88,
-
-           DRM_FORMAT_RGB332 => RGB332,
-           DRM_FORMAT_BGR233 => BGR233,
-
-           DRM_FORMAT_XRGB4444 => XRGB4444,
-           DRM_FORMAT_XBGR4444 => XBGR4444,
-           DRM_FORMAT_RGBX4444 => RGBX4444,
-           DRM_FORMAT_BGRX4444 => BGRX4444,
-
-           DRM_FORMAT_ARGB4444 => ARGB4444,
-           DRM_FORMAT_ABGR4444 => ABGR4444,
-           DRM_FORMAT_RGBA4444 => RGBA4444,
-           DRM_FORMAT_BGRA4444 => BGRA4444,
-
-           DRM_FORMAT_XRGB1555 => XRGB1555,
-           DRM_FORMAT_XBGR1555 => XBGR1555,
-           DRM_FORMAT_RGBX5551 => RGBX5551,
-           DRM_FORMAT_BGRX5551 => BGRX5551,
-
-           DRM_FORMAT_ARGB1555 => ARGB1555,
-           DRM_FORMAT_ABGR1555 => ABGR1555,
-           DRM_FORMAT_RGBA5551 => RGBA4444,
-           DRM_FORMAT_BGRA5551 => RGBA5551,
-
-           DRM_FORMAT_RGB565 => RGB565,
-           DRM_FORMAT_BGR565 => BGR565,
-
-           DRM_FORMAT_XRGB8888 => XRGB8888,
-           DRM_FORMAT_XBGR8888 => XBGR8888,
-           DRM_FORMAT_RGBX8888 => RGBX8888,
-           DRM_FORMAT_BGRX8888 => BGRX8888,
-
-           DRM_FORMAT_ARGB8888 => ARGB8888,
-           DRM_FORMAT_ABGR8888 => ABGR8888,
-           DRM_FORMAT_RGBA8888 => RGBA8888,
-           DRM_FORMAT_BGRA8888 => BGRA8888,
-
-           DRM_FORMAT_XRGB2101010 => XRGB2101010,
-           DRM_FORMAT_XBGR2101010 => XBGR2101010,
-           DRM_FORMAT_RGBX1010102 => RGBX1010102,
-           DRM_FORMAT_BGRX1010102 => BGRX1010102,
-
-           DRM_FORMAT_ARGB2101010 => ARGB2101010,
-           DRM_FORMAT_ABGR2101010 => ABGR2101010,
-           DRM_FORMAT_RGBA1010102 => RGBA1010102,
-           DRM_FORMAT_BGRA1010102 => BGRA1010102,
-
-           DRM_FORMAT_YUYV => YUYV,
-           DRM_FORMAT_YVYU => YVYU,
-           DRM_FORMAT_UYVY => UYVY,
-           DRM_FORMAT_VYUY => VYUY,
-
-           DRM_FORMAT_AYUV => AYUV,
-
-           _ => { return None; }
-        })
-    }
-
-    /// The depth in bits per pixel.
-    pub fn depth(&self) -> u32 {
-        match *self {
-            // TODO
-            PixelFormat::C8 => unimplemented!(),
-            PixelFormat::R8 => unimplemented!(),
-            PixelFormat::GR88 => unimplemented!(),
-
-            PixelFormat::RGB332 => 8,
-            PixelFormat::BGR233 => 8,
-
-            PixelFormat::XRGB4444 => 12,
-            PixelFormat::XBGR4444 => 12,
-            PixelFormat::RGBX4444 => 12,
-            PixelFormat::BGRX4444 => 12,
-
-            PixelFormat::ARGB4444 => 16,
-            PixelFormat::ABGR4444 => 16,
-            PixelFormat::RGBA4444 => 16,
-            PixelFormat::BGRA4444 => 16,
-
-            PixelFormat::XRGB1555 => 15,
-            PixelFormat::XBGR1555 => 15,
-            PixelFormat::RGBX5551 => 15,
-            PixelFormat::BGRX5551 => 15,
-
-            PixelFormat::ARGB1555 => 16,
-            PixelFormat::ABGR1555 => 16,
-            PixelFormat::RGBA5551 => 16,
-            PixelFormat::BGRA5551 => 16,
-
-            PixelFormat::RGB565 => 16,
-            PixelFormat::BGR565 => 16,
-
-            PixelFormat::XRGB8888 => 24,
-            PixelFormat::XBGR8888 => 24,
-            PixelFormat::RGBX8888 => 24,
-            PixelFormat::BGRX8888 => 24,
-
-            PixelFormat::ARGB8888 => 32,
-            PixelFormat::ABGR8888 => 32,
-            PixelFormat::RGBA8888 => 32,
-            PixelFormat::BGRA8888 => 32,
-
-            PixelFormat::XRGB2101010 => 30,
-            PixelFormat::XBGR2101010 => 30,
-            PixelFormat::RGBX1010102 => 30,
-            PixelFormat::BGRX1010102 => 30,
-
-            PixelFormat::ARGB2101010 => 32,
-            PixelFormat::ABGR2101010 => 32,
-            PixelFormat::RGBA1010102 => 32,
-            PixelFormat::BGRA1010102 => 32,
-
-            // TODO
-            PixelFormat::YUYV => unimplemented!(),
-            PixelFormat::YVYU => unimplemented!(),
-            PixelFormat::UYVY => unimplemented!(),
-            PixelFormat::VYUY => unimplemented!(),
-
-            PixelFormat::AYUV => unimplemented!(),
+        let bytes = s.as_bytes();
+        if bytes.is_empty() || bytes.len() > 4 || !bytes.iter().all(|c| c.is_ascii_graphic()) {
+            return Err(ParseFourccError);
         }
+
+        let code = (0..4).fold(0, |acc, i| acc | u32::from(*bytes.get(i).unwrap_or(&b' ')) << (i * 8));
+        Ok(Fourcc(code | big_endian))
     }
+}
 
-    /// Bytes per pixel of the used format
-    pub fn bpp(&self) -> u32 {
-        match *self {
-            PixelFormat::C8 => 8,
-            PixelFormat::R8 => 8,
-            PixelFormat::GR88 => 16,
-
-            PixelFormat::RGB332 => 8,
-            PixelFormat::BGR233 => 8,
-
-            PixelFormat::XRGB4444 => 16,
-            PixelFormat::XBGR4444 => 16,
-            PixelFormat::RGBX4444 => 16,
-            PixelFormat::BGRX4444 => 16,
-
-            PixelFormat::ARGB4444 => 16,
-            PixelFormat::ABGR4444 => 16,
-            PixelFormat::RGBA4444 => 16,
-            PixelFormat::BGRA4444 => 16,
-
-            PixelFormat::XRGB1555 => 16,
-            PixelFormat::XBGR1555 => 16,
-            PixelFormat::RGBX5551 => 16,
-            PixelFormat::BGRX5551 => 16,
-
-            PixelFormat::ARGB1555 => 16,
-            PixelFormat::ABGR1555 => 16,
-            PixelFormat::RGBA5551 => 16,
-            PixelFormat::BGRA5551 => 16,
-
-            PixelFormat::RGB565 => 16,
-            PixelFormat::BGR565 => 16,
-
-            PixelFormat::XRGB8888 => 24,
-            PixelFormat::XBGR8888 => 24,
-            PixelFormat::RGBX8888 => 24,
-            PixelFormat::BGRX8888 => 24,
-
-            PixelFormat::ARGB8888 => 32,
-            PixelFormat::ABGR8888 => 32,
-            PixelFormat::RGBA8888 => 32,
-            PixelFormat::BGRA8888 => 32,
-
-            PixelFormat::XRGB2101010 => 32,
-            PixelFormat::XBGR2101010 => 32,
-            PixelFormat::RGBX1010102 => 32,
-            PixelFormat::BGRX1010102 => 32,
-
-            PixelFormat::ARGB2101010 => 32,
-            PixelFormat::ABGR2101010 => 32,
-            PixelFormat::RGBA1010102 => 32,
-            PixelFormat::BGRA1010102 => 32,
-
-            PixelFormat::YUYV => 16,
-            PixelFormat::YVYU => 16,
-            PixelFormat::UYVY => 16,
-            PixelFormat::VYUY => 16,
-
-            PixelFormat::AYUV => 32,
+/// The error returned when parsing a `Fourcc` or `PixelFormat` fails.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct ParseFourccError;
+
+impl fmt::Display for ParseFourccError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid fourcc code")
+    }
+}
+
+impl Error for ParseFourccError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_depth_round_trips() {
+        let legacy = [
+            (PixelFormat::C8, 8, 8),
+            (PixelFormat::XRGB1555, 16, 15),
+            (PixelFormat::RGB565, 16, 16),
+            (PixelFormat::RGB888, 24, 24),
+            (PixelFormat::XRGB8888, 32, 24),
+            (PixelFormat::XRGB2101010, 32, 30),
+            (PixelFormat::ARGB8888, 32, 32),
+        ];
+
+        for &(format, bpp, depth) in legacy.iter() {
+            assert_eq!(format.bpp(), bpp, "{}", format.name());
+            assert_eq!(format.depth(), Some(depth), "{}", format.name());
+        }
+    }
+
+    #[test]
+    fn ambiguous_formats_have_no_depth() {
+        let ambiguous = [
+            PixelFormat::C4,
+            PixelFormat::R8,
+            PixelFormat::RGB332,
+            PixelFormat::XBGR1555,
+            PixelFormat::ARGB1555,
+            PixelFormat::RGBA5551,
+            PixelFormat::BGR565,
+            PixelFormat::BGR888,
+            PixelFormat::XBGR8888,
+            PixelFormat::RGBX8888,
+            PixelFormat::ABGR8888,
+            PixelFormat::ARGB2101010,
+            PixelFormat::XBGR2101010,
+            PixelFormat::NV12,
+            PixelFormat::YUYV,
+        ];
+
+        for format in ambiguous.iter() {
+            assert_eq!(format.depth(), None, "{}", format.name());
+        }
+    }
+
+    #[test]
+    fn p010_layout() {
+        let info = PixelFormat::P010.info();
+
+        assert_eq!(info.planes(), 2);
+        assert!(info.is_yuv());
+        assert_eq!(info.subsampling(), (2, 2));
+        assert_eq!(info.block_bytes(0), 2);
+        assert_eq!(info.block_bytes(1), 4);
+        assert_eq!(info.bpp(0), 16);
+        assert_eq!(info.bpp(1), 32);
+        assert_eq!(info.plane_size(1, (1919, 1079)), (960, 540));
+        assert_eq!(info.min_pitch(0, 1920), 3840);
+        assert_eq!(info.min_pitch(1, 1920), 3840);
+    }
+
+    #[test]
+    fn yuv420_layout() {
+        let info = PixelFormat::YUV420.info();
+
+        assert_eq!(info.planes(), 3);
+        assert_eq!(info.subsampling(), (2, 2));
+        for plane in 0..3 {
+            assert_eq!(info.block_bytes(plane), 1);
+            assert_eq!(info.block_size(plane), (1, 1));
+        }
+        assert_eq!(info.block_bytes(3), 0);
+        assert_eq!(info.plane_size(0, (1920, 1080)), (1920, 1080));
+        assert_eq!(info.plane_size(2, (1920, 1080)), (960, 540));
+        assert_eq!(info.min_pitch(1, 1921), 961);
+    }
+
+    #[test]
+    fn nv15_blocks() {
+        let info = PixelFormat::NV15.info();
+
+        assert_eq!(info.planes(), 2);
+        assert_eq!(info.block_size(0), (4, 1));
+        assert_eq!(info.block_size(1), (2, 1));
+        assert_eq!(info.block_bytes(0), 5);
+        assert_eq!(info.block_bytes(1), 5);
+        assert_eq!(info.bpp(0), 10);
+        assert_eq!(info.bpp(1), 20);
+        // Four 10-bit luma samples in 5 bytes, two CbCr pairs for 4 pixels.
+        assert_eq!(info.min_pitch(0, 1920), 2400);
+        assert_eq!(info.min_pitch(1, 1920), 2400);
+        assert_eq!(info.min_pitch(0, 1922), 2405);
+    }
+
+    #[test]
+    fn p030_blocks() {
+        let info = PixelFormat::P030.info();
+
+        assert_eq!(info.planes(), 2);
+        assert_eq!(info.block_size(0), (3, 1));
+        assert_eq!(info.block_size(1), (3, 1));
+        assert_eq!(info.block_bytes(0), 4);
+        assert_eq!(info.block_bytes(1), 8);
+        assert_eq!(info.bpp(0), 11);
+        assert_eq!(info.bpp(1), 22);
+        // Three samples per 32-bit word, the last word of a row partially used.
+        assert_eq!(info.min_pitch(0, 1920), 2560);
+        assert_eq!(info.min_pitch(1, 1920), 2560);
+        assert_eq!(info.min_pitch(0, 4), 8);
+    }
+
+    #[test]
+    fn try_from_unknown_codes() {
+        assert_eq!(PixelFormat::try_from(0), Err(0));
+        assert_eq!(PixelFormat::try_from(0x2020_2020), Err(0x2020_2020));
+        // XRGB8888 with the big-endian flag is a different format.
+        assert_eq!(PixelFormat::try_from(0xb432_5258), Err(0xb432_5258));
+        assert_eq!(PixelFormat::try_from(0x3231_564e), Ok(PixelFormat::NV12));
+    }
+
+    #[test]
+    fn parse_hex() {
+        assert_eq!("0x34325258".parse(), Ok(Fourcc::from(0x3432_5258)));
+        assert_eq!("0x34325258".parse(), Ok(PixelFormat::XRGB8888));
+        assert_eq!("0x1".parse(), Ok(Fourcc::from(1)));
+        assert_eq!("0x34325258 big-endian".parse(), Ok(Fourcc::from(0xb432_5258)));
+        assert_eq!("0x".parse::<Fourcc>(), Err(ParseFourccError));
+        assert_eq!("0x123456789".parse::<Fourcc>(), Err(ParseFourccError));
+        assert_eq!("0xXR24".parse::<Fourcc>(), Err(ParseFourccError));
+    }
+
+    #[test]
+    fn big_endian_round_trips() {
+        let fourcc = Fourcc::from(PixelFormat::RGB565.as_raw() | DRM_FORMAT_BIG_ENDIAN);
+
+        assert_eq!(fourcc.to_string(), "RG16 big-endian");
+        assert_eq!(format!("{:#}", fourcc), "DRM_FORMAT_RGB565 | DRM_FORMAT_BIG_ENDIAN");
+        assert_eq!(fourcc.to_string().parse(), Ok(fourcc));
+        assert_eq!(format!("{:#}", fourcc).parse(), Ok(fourcc));
+        assert_eq!("RGB565 | DRM_FORMAT_BIG_ENDIAN".parse(), Ok(fourcc));
+
+        // Big-endian codes are not a `PixelFormat`.
+        assert_eq!("RG16 big-endian".parse::<PixelFormat>(), Err(ParseFourccError));
+    }
+
+    #[test]
+    fn short_codes_are_padded() {
+        let c8 = Fourcc::from(0x2020_3843);
+        assert_eq!("C8".parse(), Ok(c8));
+        assert_eq!(c8.to_string(), "C8");
+        assert_eq!(c8.format(), Some(PixelFormat::C8));
+
+        let r16 = Fourcc::from(0x2036_3152);
+        assert_eq!("R16".parse(), Ok(r16));
+        assert_eq!(r16.to_string(), "R16");
+        assert_eq!(format!("{:#}", r16), "DRM_FORMAT_R16");
+
+        // Unknown short codes are padded the same way.
+        assert_eq!("Z".parse(), Ok(Fourcc::from(0x2020_205a)));
+        assert_eq!(Fourcc::from(0x2020_205a).to_string(), "Z");
+    }
+
+    #[test]
+    fn names_and_codes() {
+        let xrgb = Ok(PixelFormat::XRGB8888);
+        assert_eq!("XR24".parse(), xrgb);
+        assert_eq!("XRGB8888".parse(), xrgb);
+        assert_eq!("DRM_FORMAT_XRGB8888".parse(), xrgb);
+        assert_eq!(" XR24 ".parse(), xrgb);
+
+        // Codes are case sensitive, and unknown ones are still a `Fourcc`.
+        assert_eq!("xr24".parse(), Ok(Fourcc::from(0x3432_7278)));
+        assert_eq!("xr24".parse::<PixelFormat>(), Err(ParseFourccError));
+        assert_eq!("DRM_FORMAT_XR24".parse::<Fourcc>(), Err(ParseFourccError));
+
+        // Names are only written with the `#` flag.
+        assert_eq!(PixelFormat::YUV410.to_string(), "YUV9");
+        assert_eq!(format!("{:#}", PixelFormat::YUV410), "DRM_FORMAT_YUV410");
+        assert_eq!(format!("{:#}", Fourcc::from(0x3432_7278)), "xr24");
+    }
+
+    #[test]
+    fn non_printable_codes() {
+        assert_eq!(Fourcc::from(0).to_string(), "0x00000000");
+        assert_eq!(Fourcc::from(0x0a34_3252).to_string(), "0x0a343252");
+        // A leading space would be lost when parsing.
+        assert_eq!(Fourcc::from(0x3432_5220).to_string(), "0x34325220");
+        // The big-endian flag is part of the hexadecimal code.
+        assert_eq!(Fourcc::from(0x8000_0001).to_string(), "0x80000001");
+
+        for &raw in &[0, 0x0a34_3252, 0x3432_5220, 0x8000_0001] {
+            let fourcc = Fourcc::from(raw);
+            assert_eq!(fourcc.to_string().parse(), Ok(fourcc));
+            assert_eq!(format!("{:#}", fourcc).parse(), Ok(fourcc));
+        }
+    }
+
+    #[test]
+    fn rejected_strings() {
+        for s in &["", "   ", "big-endian", "XRGB88888", "ABCDE", "A B", "X R24", "R\t16", "XR24 little-endian"] {
+            assert_eq!(s.parse::<Fourcc>(), Err(ParseFourccError), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        for &format in PixelFormat::ALL {
+            let code = format.to_string();
+            let name = format!("{:#}", format);
+            assert_eq!(name, format!("DRM_FORMAT_{}", format.name()));
+
+            assert_eq!(code.parse(), Ok(format), "{}", name);
+            assert_eq!(name.parse(), Ok(format), "{}", name);
+            assert_eq!(format.name().parse(), Ok(format), "{}", name);
+
+            let big_endian = Fourcc::from(format.as_raw() | DRM_FORMAT_BIG_ENDIAN);
+            assert_eq!(big_endian.to_string().parse(), Ok(big_endian), "{}", name);
+            assert_eq!(format!("{:#}", big_endian).parse(), Ok(big_endian), "{}", name);
         }
     }
 }
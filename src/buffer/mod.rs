@@ -22,9 +22,13 @@
 //!
 //! 2. Converting the GEM handle into a PRIME file descriptor, and passing it
 //! like a regular one. This allows better control and security, and is the
-//! recommended method of sharing buffers.
+//! recommended method of sharing buffers. These file descriptors refer to
+//! dma-bufs, which the `dmabuf` module provides access to.
 
+pub mod convert;
+pub mod dmabuf;
 pub mod format;
+pub mod modifier;
 
 /// A handle to a GEM buffer.
 ///
@@ -35,6 +39,7 @@ pub mod format;
 /// prevent buffers from leaking by properly closing them after they are done.
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Handle(::control::RawResourceHandle);
 
 impl Into<::control::RawResourceHandle> for Handle {
@@ -72,6 +77,7 @@ impl std::fmt::Debug for Handle {
 /// prevent buffers from leaking by properly closing them after they are done.
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Name(u32);
 
 impl Into<u32> for Name {
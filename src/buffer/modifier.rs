@@ -0,0 +1,1262 @@
+//! Format modifiers, describing the tiling and compression of buffers.
+//!
+//! A modifier tells how the pixels of a buffer are arranged in memory beyond
+//! its format: whether they are stored linearly, in tiles, or compressed. The
+//! upper 8 bits identify the vendor that defined the layout, and the lower 56
+//! bits are assigned by that vendor.
+//!
+//! Layouts with parameters, such as Arm's AFBC or AMD's tiling, can be built
+//! and decoded with the types of this module. A `Modifier` is printed the way
+//! `drm_fourcc.h` spells it, which helps to make sense of the modifiers a
+//! plane supports.
+//!
+//! ```
+//! use drm::buffer::modifier::{Afbc, AfbcBlockSize, Modifier, Vendor};
+//!
+//! let modifier = Modifier::from(0x0100000000000001);
+//! assert_eq!(modifier, Modifier::I915_X_TILED);
+//! assert_eq!(modifier.vendor(), Vendor::Intel);
+//! assert_eq!(modifier.to_string(), "I915_FORMAT_MOD_X_TILED");
+//!
+//! let afbc = Afbc {
+//!     ytr: true,
+//!     sparse: true,
+//!     ..Afbc::new(AfbcBlockSize::Size16x16)
+//! };
+//! let modifier = Modifier::arm_afbc(afbc);
+//! assert_eq!(Afbc::from_modifier(modifier), Some(afbc));
+//! assert_eq!(
+//!     modifier.to_string(),
+//!     "DRM_FORMAT_MOD_ARM_AFBC(AFBC_FORMAT_MOD_BLOCK_SIZE_16x16 | AFBC_FORMAT_MOD_YTR | AFBC_FORMAT_MOD_SPARSE)"
+//! );
+//! ```
+
+use drm_ffi::fourcc::*;
+
+use std::fmt;
+
+/// The vendor that defined the layout of a modifier.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Vendor {
+    None,
+    Intel,
+    Amd,
+    Nvidia,
+    Samsung,
+    Qcom,
+    Vivante,
+    Broadcom,
+    Arm,
+    Allwinner,
+    Amlogic,
+    /// A vendor id that is not known to this crate
+    Unknown(u8),
+}
+
+impl Vendor {
+    /// Returns the vendor of a raw vendor id.
+    pub fn from_raw(raw: u8) -> Vendor {
+        match u64::from(raw) {
+            DRM_FORMAT_MOD_VENDOR_NONE => Vendor::None,
+            DRM_FORMAT_MOD_VENDOR_INTEL => Vendor::Intel,
+            DRM_FORMAT_MOD_VENDOR_AMD => Vendor::Amd,
+            DRM_FORMAT_MOD_VENDOR_NVIDIA => Vendor::Nvidia,
+            DRM_FORMAT_MOD_VENDOR_SAMSUNG => Vendor::Samsung,
+            DRM_FORMAT_MOD_VENDOR_QCOM => Vendor::Qcom,
+            DRM_FORMAT_MOD_VENDOR_VIVANTE => Vendor::Vivante,
+            DRM_FORMAT_MOD_VENDOR_BROADCOM => Vendor::Broadcom,
+            DRM_FORMAT_MOD_VENDOR_ARM => Vendor::Arm,
+            DRM_FORMAT_MOD_VENDOR_ALLWINNER => Vendor::Allwinner,
+            DRM_FORMAT_MOD_VENDOR_AMLOGIC => Vendor::Amlogic,
+            _ => Vendor::Unknown(raw),
+        }
+    }
+
+    /// Returns the raw vendor id.
+    pub fn as_raw(&self) -> u8 {
+        let raw = match *self {
+            Vendor::None => DRM_FORMAT_MOD_VENDOR_NONE,
+            Vendor::Intel => DRM_FORMAT_MOD_VENDOR_INTEL,
+            Vendor::Amd => DRM_FORMAT_MOD_VENDOR_AMD,
+            Vendor::Nvidia => DRM_FORMAT_MOD_VENDOR_NVIDIA,
+            Vendor::Samsung => DRM_FORMAT_MOD_VENDOR_SAMSUNG,
+            Vendor::Qcom => DRM_FORMAT_MOD_VENDOR_QCOM,
+            Vendor::Vivante => DRM_FORMAT_MOD_VENDOR_VIVANTE,
+            Vendor::Broadcom => DRM_FORMAT_MOD_VENDOR_BROADCOM,
+            Vendor::Arm => DRM_FORMAT_MOD_VENDOR_ARM,
+            Vendor::Allwinner => DRM_FORMAT_MOD_VENDOR_ALLWINNER,
+            Vendor::Amlogic => DRM_FORMAT_MOD_VENDOR_AMLOGIC,
+            Vendor::Unknown(raw) => return raw,
+        };
+
+        raw as u8
+    }
+
+    /// Returns the name of the vendor as used by `fourcc_mod_code`.
+    fn name(&self) -> Option<&'static str> {
+        Some(match *self {
+            Vendor::None => "NONE",
+            Vendor::Intel => "INTEL",
+            Vendor::Amd => "AMD",
+            Vendor::Nvidia => "NVIDIA",
+            Vendor::Samsung => "SAMSUNG",
+            Vendor::Qcom => "QCOM",
+            Vendor::Vivante => "VIVANTE",
+            Vendor::Broadcom => "BROADCOM",
+            Vendor::Arm => "ARM",
+            Vendor::Allwinner => "ALLWINNER",
+            Vendor::Amlogic => "AMLOGIC",
+            Vendor::Unknown(_) => return None,
+        })
+    }
+}
+
+/// The bits of a modifier assigned by the vendor.
+const VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+
+/// A format modifier.
+#[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Modifier(u64);
+
+/// Modifiers without parameters, with their names in `drm_fourcc.h`.
+const NAMES: &[(u64, &str)] = &[
+    (DRM_FORMAT_MOD_LINEAR, "DRM_FORMAT_MOD_LINEAR"),
+    (DRM_FORMAT_MOD_INVALID, "DRM_FORMAT_MOD_INVALID"),
+    (I915_FORMAT_MOD_X_TILED, "I915_FORMAT_MOD_X_TILED"),
+    (I915_FORMAT_MOD_Y_TILED, "I915_FORMAT_MOD_Y_TILED"),
+    (I915_FORMAT_MOD_Yf_TILED, "I915_FORMAT_MOD_Yf_TILED"),
+    (I915_FORMAT_MOD_Y_TILED_CCS, "I915_FORMAT_MOD_Y_TILED_CCS"),
+    (I915_FORMAT_MOD_Yf_TILED_CCS, "I915_FORMAT_MOD_Yf_TILED_CCS"),
+    (I915_FORMAT_MOD_Y_TILED_GEN12_RC_CCS, "I915_FORMAT_MOD_Y_TILED_GEN12_RC_CCS"),
+    (I915_FORMAT_MOD_Y_TILED_GEN12_MC_CCS, "I915_FORMAT_MOD_Y_TILED_GEN12_MC_CCS"),
+    (I915_FORMAT_MOD_Y_TILED_GEN12_RC_CCS_CC, "I915_FORMAT_MOD_Y_TILED_GEN12_RC_CCS_CC"),
+    (I915_FORMAT_MOD_4_TILED, "I915_FORMAT_MOD_4_TILED"),
+    (I915_FORMAT_MOD_4_TILED_DG2_RC_CCS, "I915_FORMAT_MOD_4_TILED_DG2_RC_CCS"),
+    (I915_FORMAT_MOD_4_TILED_DG2_MC_CCS, "I915_FORMAT_MOD_4_TILED_DG2_MC_CCS"),
+    (I915_FORMAT_MOD_4_TILED_DG2_RC_CCS_CC, "I915_FORMAT_MOD_4_TILED_DG2_RC_CCS_CC"),
+    (I915_FORMAT_MOD_4_TILED_MTL_RC_CCS, "I915_FORMAT_MOD_4_TILED_MTL_RC_CCS"),
+    (I915_FORMAT_MOD_4_TILED_MTL_MC_CCS, "I915_FORMAT_MOD_4_TILED_MTL_MC_CCS"),
+    (I915_FORMAT_MOD_4_TILED_MTL_RC_CCS_CC, "I915_FORMAT_MOD_4_TILED_MTL_RC_CCS_CC"),
+    (I915_FORMAT_MOD_4_TILED_LNL_CCS, "I915_FORMAT_MOD_4_TILED_LNL_CCS"),
+    (I915_FORMAT_MOD_4_TILED_BMG_CCS, "I915_FORMAT_MOD_4_TILED_BMG_CCS"),
+    (DRM_FORMAT_MOD_SAMSUNG_64_32_TILE, "DRM_FORMAT_MOD_SAMSUNG_64_32_TILE"),
+    (DRM_FORMAT_MOD_SAMSUNG_16_16_TILE, "DRM_FORMAT_MOD_SAMSUNG_16_16_TILE"),
+    (DRM_FORMAT_MOD_QCOM_COMPRESSED, "DRM_FORMAT_MOD_QCOM_COMPRESSED"),
+    (DRM_FORMAT_MOD_QCOM_TILED2, "DRM_FORMAT_MOD_QCOM_TILED2"),
+    (DRM_FORMAT_MOD_QCOM_TILED3, "DRM_FORMAT_MOD_QCOM_TILED3"),
+    (DRM_FORMAT_MOD_VIVANTE_TILED, "DRM_FORMAT_MOD_VIVANTE_TILED"),
+    (DRM_FORMAT_MOD_VIVANTE_SUPER_TILED, "DRM_FORMAT_MOD_VIVANTE_SUPER_TILED"),
+    (DRM_FORMAT_MOD_VIVANTE_SPLIT_TILED, "DRM_FORMAT_MOD_VIVANTE_SPLIT_TILED"),
+    (DRM_FORMAT_MOD_VIVANTE_SPLIT_SUPER_TILED, "DRM_FORMAT_MOD_VIVANTE_SPLIT_SUPER_TILED"),
+    (DRM_FORMAT_MOD_NVIDIA_TEGRA_TILED, "DRM_FORMAT_MOD_NVIDIA_TEGRA_TILED"),
+    (DRM_FORMAT_MOD_BROADCOM_VC4_T_TILED, "DRM_FORMAT_MOD_BROADCOM_VC4_T_TILED"),
+    (DRM_FORMAT_MOD_BROADCOM_SAND32, "DRM_FORMAT_MOD_BROADCOM_SAND32"),
+    (DRM_FORMAT_MOD_BROADCOM_SAND64, "DRM_FORMAT_MOD_BROADCOM_SAND64"),
+    (DRM_FORMAT_MOD_BROADCOM_SAND128, "DRM_FORMAT_MOD_BROADCOM_SAND128"),
+    (DRM_FORMAT_MOD_BROADCOM_SAND256, "DRM_FORMAT_MOD_BROADCOM_SAND256"),
+    (DRM_FORMAT_MOD_BROADCOM_UIF, "DRM_FORMAT_MOD_BROADCOM_UIF"),
+    (DRM_FORMAT_MOD_ARM_16X16_BLOCK_U_INTERLEAVED, "DRM_FORMAT_MOD_ARM_16X16_BLOCK_U_INTERLEAVED"),
+    (DRM_FORMAT_MOD_ALLWINNER_TILED, "DRM_FORMAT_MOD_ALLWINNER_TILED"),
+];
+
+#[allow(missing_docs)]
+impl Modifier {
+    /// The buffer is stored linearly, row after row.
+    pub const LINEAR: Modifier = Modifier(DRM_FORMAT_MOD_LINEAR);
+    /// No modifier was specified, the layout is up to the driver.
+    pub const INVALID: Modifier = Modifier(DRM_FORMAT_MOD_INVALID);
+
+    pub const I915_X_TILED: Modifier = Modifier(I915_FORMAT_MOD_X_TILED);
+    pub const I915_Y_TILED: Modifier = Modifier(I915_FORMAT_MOD_Y_TILED);
+    pub const I915_YF_TILED: Modifier = Modifier(I915_FORMAT_MOD_Yf_TILED);
+    pub const I915_Y_TILED_CCS: Modifier = Modifier(I915_FORMAT_MOD_Y_TILED_CCS);
+    pub const I915_YF_TILED_CCS: Modifier = Modifier(I915_FORMAT_MOD_Yf_TILED_CCS);
+    pub const I915_Y_TILED_GEN12_RC_CCS: Modifier = Modifier(I915_FORMAT_MOD_Y_TILED_GEN12_RC_CCS);
+    pub const I915_Y_TILED_GEN12_MC_CCS: Modifier = Modifier(I915_FORMAT_MOD_Y_TILED_GEN12_MC_CCS);
+    pub const I915_Y_TILED_GEN12_RC_CCS_CC: Modifier = Modifier(I915_FORMAT_MOD_Y_TILED_GEN12_RC_CCS_CC);
+    pub const I915_4_TILED: Modifier = Modifier(I915_FORMAT_MOD_4_TILED);
+    pub const I915_4_TILED_DG2_RC_CCS: Modifier = Modifier(I915_FORMAT_MOD_4_TILED_DG2_RC_CCS);
+    pub const I915_4_TILED_DG2_MC_CCS: Modifier = Modifier(I915_FORMAT_MOD_4_TILED_DG2_MC_CCS);
+    pub const I915_4_TILED_DG2_RC_CCS_CC: Modifier = Modifier(I915_FORMAT_MOD_4_TILED_DG2_RC_CCS_CC);
+    pub const I915_4_TILED_MTL_RC_CCS: Modifier = Modifier(I915_FORMAT_MOD_4_TILED_MTL_RC_CCS);
+    pub const I915_4_TILED_MTL_MC_CCS: Modifier = Modifier(I915_FORMAT_MOD_4_TILED_MTL_MC_CCS);
+    pub const I915_4_TILED_MTL_RC_CCS_CC: Modifier = Modifier(I915_FORMAT_MOD_4_TILED_MTL_RC_CCS_CC);
+    pub const I915_4_TILED_LNL_CCS: Modifier = Modifier(I915_FORMAT_MOD_4_TILED_LNL_CCS);
+    pub const I915_4_TILED_BMG_CCS: Modifier = Modifier(I915_FORMAT_MOD_4_TILED_BMG_CCS);
+
+    pub const SAMSUNG_64_32_TILE: Modifier = Modifier(DRM_FORMAT_MOD_SAMSUNG_64_32_TILE);
+    pub const SAMSUNG_16_16_TILE: Modifier = Modifier(DRM_FORMAT_MOD_SAMSUNG_16_16_TILE);
+
+    pub const QCOM_COMPRESSED: Modifier = Modifier(DRM_FORMAT_MOD_QCOM_COMPRESSED);
+    pub const QCOM_TILED2: Modifier = Modifier(DRM_FORMAT_MOD_QCOM_TILED2);
+    pub const QCOM_TILED3: Modifier = Modifier(DRM_FORMAT_MOD_QCOM_TILED3);
+
+    pub const VIVANTE_TILED: Modifier = Modifier(DRM_FORMAT_MOD_VIVANTE_TILED);
+    pub const VIVANTE_SUPER_TILED: Modifier = Modifier(DRM_FORMAT_MOD_VIVANTE_SUPER_TILED);
+    pub const VIVANTE_SPLIT_TILED: Modifier = Modifier(DRM_FORMAT_MOD_VIVANTE_SPLIT_TILED);
+    pub const VIVANTE_SPLIT_SUPER_TILED: Modifier = Modifier(DRM_FORMAT_MOD_VIVANTE_SPLIT_SUPER_TILED);
+
+    pub const NVIDIA_TEGRA_TILED: Modifier = Modifier(DRM_FORMAT_MOD_NVIDIA_TEGRA_TILED);
+
+    pub const BROADCOM_VC4_T_TILED: Modifier = Modifier(DRM_FORMAT_MOD_BROADCOM_VC4_T_TILED);
+    pub const BROADCOM_SAND32: Modifier = Modifier(DRM_FORMAT_MOD_BROADCOM_SAND32);
+    pub const BROADCOM_SAND64: Modifier = Modifier(DRM_FORMAT_MOD_BROADCOM_SAND64);
+    pub const BROADCOM_SAND128: Modifier = Modifier(DRM_FORMAT_MOD_BROADCOM_SAND128);
+    pub const BROADCOM_SAND256: Modifier = Modifier(DRM_FORMAT_MOD_BROADCOM_SAND256);
+    pub const BROADCOM_UIF: Modifier = Modifier(DRM_FORMAT_MOD_BROADCOM_UIF);
+
+    pub const ARM_16X16_BLOCK_U_INTERLEAVED: Modifier = Modifier(DRM_FORMAT_MOD_ARM_16X16_BLOCK_U_INTERLEAVED);
+
+    pub const ALLWINNER_TILED: Modifier = Modifier(DRM_FORMAT_MOD_ALLWINNER_TILED);
+}
+
+impl Modifier {
+    /// Builds a modifier from a vendor and the value it assigned, like
+    /// `fourcc_mod_code`. Only the lower 56 bits of the value are used.
+    pub fn new(vendor: Vendor, value: u64) -> Modifier {
+        Modifier(u64::from(vendor.as_raw()) << 56 | value & VALUE_MASK)
+    }
+
+    /// Convert into the raw modifier
+    pub fn as_raw(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the vendor that defined the layout.
+    pub fn vendor(&self) -> Vendor {
+        Vendor::from_raw((self.0 >> 56) as u8)
+    }
+
+    /// Returns the lower 56 bits, assigned by the vendor.
+    pub fn value(&self) -> u64 {
+        self.0 & VALUE_MASK
+    }
+
+    /// Returns the name of a modifier without parameters, as defined in
+    /// `drm_fourcc.h`.
+    pub fn name(&self) -> Option<&'static str> {
+        NAMES.iter().find(|&&(raw, _)| raw == self.0).map(|&(_, name)| name)
+    }
+
+    /// Builds an Arm Framebuffer Compression modifier, like
+    /// `DRM_FORMAT_MOD_ARM_AFBC`.
+    pub fn arm_afbc(afbc: Afbc) -> Modifier {
+        let mode = afbc.block_size.as_raw()
+            | flag(afbc.ytr, AFBC_FORMAT_MOD_YTR)
+            | flag(afbc.split, AFBC_FORMAT_MOD_SPLIT)
+            | flag(afbc.sparse, AFBC_FORMAT_MOD_SPARSE)
+            | flag(afbc.cbr, AFBC_FORMAT_MOD_CBR)
+            | flag(afbc.tiled, AFBC_FORMAT_MOD_TILED)
+            | flag(afbc.sc, AFBC_FORMAT_MOD_SC)
+            | flag(afbc.db, AFBC_FORMAT_MOD_DB)
+            | flag(afbc.bch, AFBC_FORMAT_MOD_BCH)
+            | flag(afbc.usm, AFBC_FORMAT_MOD_USM);
+
+        arm_code(DRM_FORMAT_MOD_ARM_TYPE_AFBC, mode)
+    }
+
+    /// Builds an Arm Fixed-Rate Compression modifier, like
+    /// `DRM_FORMAT_MOD_ARM_AFRC`.
+    pub fn arm_afrc(afrc: Afrc) -> Modifier {
+        let mode = afrc.p0.as_raw()
+            | afrc.p12.map_or(0, |size| size.as_raw() << 4)
+            | flag(afrc.scan, AFRC_FORMAT_MOD_LAYOUT_SCAN);
+
+        arm_code(DRM_FORMAT_MOD_ARM_TYPE_AFRC, mode)
+    }
+
+    /// Builds an AMD modifier out of its fields.
+    pub fn amd(amd: AmdModifier) -> Modifier {
+        let fields = amd
+            .fields()
+            .iter()
+            .fold(0, |acc, &(_, shift, mask, value)| acc | (u64::from(value) & mask) << shift);
+
+        Modifier(AMD_FMT_MOD | fields)
+    }
+
+    /// Builds an NVIDIA block linear modifier, like
+    /// `DRM_FORMAT_MOD_NVIDIA_BLOCK_LINEAR_2D`.
+    pub fn nvidia_block_linear(layout: NvidiaBlockLinear) -> Modifier {
+        let value = DRM_FORMAT_MOD_NVIDIA_BLOCK_LINEAR
+            | u64::from(layout.height_log2) & 0xf
+            | (u64::from(layout.page_kind) & 0xff) << 12
+            | (u64::from(layout.generation) & 0x3) << 20
+            | (u64::from(layout.sector_layout) & 0x1) << 22
+            | (u64::from(layout.compression) & 0x7) << 23;
+
+        Modifier::new(Vendor::Nvidia, value)
+    }
+
+    /// Builds a Broadcom SAND modifier, like
+    /// `DRM_FORMAT_MOD_BROADCOM_SAND128_COL_HEIGHT`.
+    pub fn broadcom_sand(sand: BroadcomSand) -> Modifier {
+        let value = sand.width.as_raw() | (sand.column_height & 0xffff_ffff_ffff) << 8;
+        Modifier::new(Vendor::Broadcom, value)
+    }
+
+    /// Builds a Vivante modifier out of a tiling layout and its extensions.
+    pub fn vivante(vivante: Vivante) -> Modifier {
+        let value = vivante.tiling.as_raw()
+            | vivante.tile_status.map_or(0, |ts| ts.as_raw())
+            | flag(vivante.dec400, VIVANTE_MOD_COMP_DEC400);
+
+        Modifier::new(Vendor::Vivante, value)
+    }
+}
+
+fn flag(set: bool, bit: u64) -> u64 {
+    if set {
+        bit
+    } else {
+        0
+    }
+}
+
+fn arm_code(kind: u64, value: u64) -> Modifier {
+    Modifier::new(Vendor::Arm, kind << 52 | value & 0x000f_ffff_ffff_ffff)
+}
+
+/// Returns the type and mode of an Arm modifier.
+fn arm_mode(modifier: Modifier) -> Option<(u64, u64)> {
+    if modifier.vendor() != Vendor::Arm {
+        return None;
+    }
+
+    let value = modifier.value();
+    Some((value >> 52 & DRM_FORMAT_MOD_ARM_TYPE_MASK, value & 0x000f_ffff_ffff_ffff))
+}
+
+impl From<u64> for Modifier {
+    fn from(raw: u64) -> Self {
+        Modifier(raw)
+    }
+}
+
+impl From<Modifier> for u64 {
+    fn from(modifier: Modifier) -> Self {
+        modifier.0
+    }
+}
+
+impl fmt::Debug for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Modifier(0x{:016x}: {})", self.0, self)
+    }
+}
+
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = self.name() {
+            return f.write_str(name);
+        }
+
+        if let Some(afbc) = Afbc::from_modifier(*self) {
+            return write!(f, "DRM_FORMAT_MOD_ARM_AFBC({})", afbc);
+        }
+
+        if let Some(afrc) = Afrc::from_modifier(*self) {
+            return write!(f, "DRM_FORMAT_MOD_ARM_AFRC({})", afrc);
+        }
+
+        if let Some(amd) = AmdModifier::from_modifier(*self) {
+            return write!(f, "AMD_FMT_MOD{}", amd);
+        }
+
+        if let Some(layout) = NvidiaBlockLinear::from_modifier(*self) {
+            return write!(
+                f,
+                "DRM_FORMAT_MOD_NVIDIA_BLOCK_LINEAR_2D({}, {}, {}, 0x{:x}, {})",
+                layout.compression, layout.sector_layout, layout.generation, layout.page_kind, layout.height_log2
+            );
+        }
+
+        if let Some(sand) = BroadcomSand::from_modifier(*self) {
+            return write!(f, "DRM_FORMAT_MOD_BROADCOM_{}_COL_HEIGHT({})", sand.width.name(), sand.column_height);
+        }
+
+        if let Some(vivante) = Vivante::from_modifier(*self) {
+            return write!(f, "{}", vivante);
+        }
+
+        match self.vendor().name() {
+            Some(vendor) => write!(f, "fourcc_mod_code({}, 0x{:x})", vendor, self.value()),
+            None => write!(f, "0x{:016x}", self.0),
+        }
+    }
+}
+
+/// The size of the superblocks of an AFBC buffer.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum AfbcBlockSize {
+    /// 16x16 pixels
+    Size16x16,
+    /// 32x8 pixels
+    Size32x8,
+    /// 64x4 pixels
+    Size64x4,
+    /// 32x8 pixels for the luma plane and 64x4 pixels for the chroma planes
+    Size32x8And64x4,
+}
+
+impl AfbcBlockSize {
+    fn from_raw(raw: u64) -> Option<AfbcBlockSize> {
+        let sizes = [
+            AfbcBlockSize::Size16x16,
+            AfbcBlockSize::Size32x8,
+            AfbcBlockSize::Size64x4,
+            AfbcBlockSize::Size32x8And64x4,
+        ];
+
+        sizes.iter().copied().find(|size| size.as_raw() == raw)
+    }
+
+    fn as_raw(&self) -> u64 {
+        match *self {
+            AfbcBlockSize::Size16x16 => AFBC_FORMAT_MOD_BLOCK_SIZE_16x16,
+            AfbcBlockSize::Size32x8 => AFBC_FORMAT_MOD_BLOCK_SIZE_32x8,
+            AfbcBlockSize::Size64x4 => AFBC_FORMAT_MOD_BLOCK_SIZE_64x4,
+            AfbcBlockSize::Size32x8And64x4 => AFBC_FORMAT_MOD_BLOCK_SIZE_32x8_64x4,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match *self {
+            AfbcBlockSize::Size16x16 => "AFBC_FORMAT_MOD_BLOCK_SIZE_16x16",
+            AfbcBlockSize::Size32x8 => "AFBC_FORMAT_MOD_BLOCK_SIZE_32x8",
+            AfbcBlockSize::Size64x4 => "AFBC_FORMAT_MOD_BLOCK_SIZE_64x4",
+            AfbcBlockSize::Size32x8And64x4 => "AFBC_FORMAT_MOD_BLOCK_SIZE_32x8_64x4",
+        }
+    }
+}
+
+/// The mode of an Arm Framebuffer Compression (AFBC) modifier.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Afbc {
+    /// The size of a superblock
+    pub block_size: AfbcBlockSize,
+    /// Lossless color transformation
+    pub ytr: bool,
+    /// Payloads are split in two halves
+    pub split: bool,
+    /// Payloads are stored in fixed-size slots
+    pub sparse: bool,
+    /// Copy-block restrict
+    pub cbr: bool,
+    /// Headers and payloads are stored in tiles of superblocks
+    pub tiled: bool,
+    /// Solid color blocks
+    pub sc: bool,
+    /// Double-buffered headers
+    pub db: bool,
+    /// Buffer content hints
+    pub bch: bool,
+    /// Uncompressed storage mode
+    pub usm: bool,
+}
+
+impl Afbc {
+    /// Returns a mode with the given block size and no flags.
+    pub fn new(block_size: AfbcBlockSize) -> Afbc {
+        Afbc {
+            block_size: block_size,
+            ytr: false,
+            split: false,
+            sparse: false,
+            cbr: false,
+            tiled: false,
+            sc: false,
+            db: false,
+            bch: false,
+            usm: false,
+        }
+    }
+
+    /// Decodes an AFBC modifier.
+    pub fn from_modifier(modifier: Modifier) -> Option<Afbc> {
+        let mode = match arm_mode(modifier) {
+            Some((DRM_FORMAT_MOD_ARM_TYPE_AFBC, mode)) if mode >> 13 == 0 => mode,
+            _ => return None,
+        };
+
+        Some(Afbc {
+            block_size: AfbcBlockSize::from_raw(mode & AFBC_FORMAT_MOD_BLOCK_SIZE_MASK)?,
+            ytr: mode & AFBC_FORMAT_MOD_YTR != 0,
+            split: mode & AFBC_FORMAT_MOD_SPLIT != 0,
+            sparse: mode & AFBC_FORMAT_MOD_SPARSE != 0,
+            cbr: mode & AFBC_FORMAT_MOD_CBR != 0,
+            tiled: mode & AFBC_FORMAT_MOD_TILED != 0,
+            sc: mode & AFBC_FORMAT_MOD_SC != 0,
+            db: mode & AFBC_FORMAT_MOD_DB != 0,
+            bch: mode & AFBC_FORMAT_MOD_BCH != 0,
+            usm: mode & AFBC_FORMAT_MOD_USM != 0,
+        })
+    }
+}
+
+impl fmt::Display for Afbc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.block_size.name())?;
+
+        let flags = [
+            (self.ytr, "YTR"),
+            (self.split, "SPLIT"),
+            (self.sparse, "SPARSE"),
+            (self.cbr, "CBR"),
+            (self.tiled, "TILED"),
+            (self.sc, "SC"),
+            (self.db, "DB"),
+            (self.bch, "BCH"),
+            (self.usm, "USM"),
+        ];
+
+        for &(_, name) in flags.iter().filter(|&&(set, _)| set) {
+            write!(f, " | AFBC_FORMAT_MOD_{}", name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The size of a coding unit of an AFRC buffer.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum AfrcCodingUnit {
+    /// 16 bytes
+    Size16,
+    /// 24 bytes
+    Size24,
+    /// 32 bytes
+    Size32,
+}
+
+impl AfrcCodingUnit {
+    fn from_raw(raw: u64) -> Option<AfrcCodingUnit> {
+        match raw {
+            AFRC_FORMAT_MOD_CU_SIZE_16 => Some(AfrcCodingUnit::Size16),
+            AFRC_FORMAT_MOD_CU_SIZE_24 => Some(AfrcCodingUnit::Size24),
+            AFRC_FORMAT_MOD_CU_SIZE_32 => Some(AfrcCodingUnit::Size32),
+            _ => None,
+        }
+    }
+
+    fn as_raw(&self) -> u64 {
+        match *self {
+            AfrcCodingUnit::Size16 => AFRC_FORMAT_MOD_CU_SIZE_16,
+            AfrcCodingUnit::Size24 => AFRC_FORMAT_MOD_CU_SIZE_24,
+            AfrcCodingUnit::Size32 => AFRC_FORMAT_MOD_CU_SIZE_32,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match *self {
+            AfrcCodingUnit::Size16 => "AFRC_FORMAT_MOD_CU_SIZE_16",
+            AfrcCodingUnit::Size24 => "AFRC_FORMAT_MOD_CU_SIZE_24",
+            AfrcCodingUnit::Size32 => "AFRC_FORMAT_MOD_CU_SIZE_32",
+        }
+    }
+}
+
+/// The mode of an Arm Fixed-Rate Compression (AFRC) modifier.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Afrc {
+    /// The coding unit size of the first plane
+    pub p0: AfrcCodingUnit,
+    /// The coding unit size of the second and third plane, if any
+    pub p12: Option<AfrcCodingUnit>,
+    /// Coding units are stored in scan order rather than rotation order
+    pub scan: bool,
+}
+
+impl Afrc {
+    /// Decodes an AFRC modifier.
+    pub fn from_modifier(modifier: Modifier) -> Option<Afrc> {
+        let mode = match arm_mode(modifier) {
+            Some((DRM_FORMAT_MOD_ARM_TYPE_AFRC, mode)) if mode >> 9 == 0 => mode,
+            _ => return None,
+        };
+
+        let p12 = match mode >> 4 & AFRC_FORMAT_MOD_CU_SIZE_MASK {
+            0 => None,
+            raw => Some(AfrcCodingUnit::from_raw(raw)?),
+        };
+
+        Some(Afrc {
+            p0: AfrcCodingUnit::from_raw(mode & AFRC_FORMAT_MOD_CU_SIZE_MASK)?,
+            p12: p12,
+            scan: mode & AFRC_FORMAT_MOD_LAYOUT_SCAN != 0,
+        })
+    }
+}
+
+impl fmt::Display for Afrc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AFRC_FORMAT_MOD_CU_SIZE_P0({})", self.p0.name())?;
+        if let Some(p12) = self.p12 {
+            write!(f, " | AFRC_FORMAT_MOD_CU_SIZE_P12({})", p12.name())?;
+        }
+        if self.scan {
+            f.write_str(" | AFRC_FORMAT_MOD_LAYOUT_SCAN")?;
+        }
+        Ok(())
+    }
+}
+
+/// The fields of an AMD modifier, for GFX9 and later.
+///
+/// `tile_version` and `tile` take the `AMD_FMT_MOD_TILE_VER_*` and
+/// `AMD_FMT_MOD_TILE_*` values of `drm_fourcc.h`. The other fields only
+/// matter for tiles ending in `_X` or `_T`.
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct AmdModifier {
+    pub tile_version: u8,
+    pub tile: u8,
+    pub dcc: bool,
+    pub dcc_retile: bool,
+    pub dcc_pipe_align: bool,
+    pub dcc_independent_64b: bool,
+    pub dcc_independent_128b: bool,
+    pub dcc_max_compressed_block: u8,
+    pub dcc_constant_encode: bool,
+    pub pipe_xor_bits: u8,
+    pub bank_xor_bits: u8,
+    pub packers: u8,
+    pub rb: u8,
+    pub pipe: u8,
+}
+
+/// The bits of an AMD modifier holding its fields.
+const AMD_FIELDS_MASK: u64 = (1 << 36) - 1;
+
+impl AmdModifier {
+    /// Returns the name, shift, mask and value of each field.
+    fn fields(&self) -> [(&'static str, u64, u64, u8); 14] {
+        [
+            ("TILE_VERSION", AMD_FMT_MOD_TILE_VERSION_SHIFT, AMD_FMT_MOD_TILE_VERSION_MASK, self.tile_version),
+            ("TILE", AMD_FMT_MOD_TILE_SHIFT, AMD_FMT_MOD_TILE_MASK, self.tile),
+            ("DCC", AMD_FMT_MOD_DCC_SHIFT, AMD_FMT_MOD_DCC_MASK, self.dcc as u8),
+            ("DCC_RETILE", AMD_FMT_MOD_DCC_RETILE_SHIFT, AMD_FMT_MOD_DCC_RETILE_MASK, self.dcc_retile as u8),
+            ("DCC_PIPE_ALIGN", AMD_FMT_MOD_DCC_PIPE_ALIGN_SHIFT, AMD_FMT_MOD_DCC_PIPE_ALIGN_MASK, self.dcc_pipe_align as u8),
+            (
+                "DCC_INDEPENDENT_64B",
+                AMD_FMT_MOD_DCC_INDEPENDENT_64B_SHIFT,
+                AMD_FMT_MOD_DCC_INDEPENDENT_64B_MASK,
+                self.dcc_independent_64b as u8,
+            ),
+            (
+                "DCC_INDEPENDENT_128B",
+                AMD_FMT_MOD_DCC_INDEPENDENT_128B_SHIFT,
+                AMD_FMT_MOD_DCC_INDEPENDENT_128B_MASK,
+                self.dcc_independent_128b as u8,
+            ),
+            (
+                "DCC_MAX_COMPRESSED_BLOCK",
+                AMD_FMT_MOD_DCC_MAX_COMPRESSED_BLOCK_SHIFT,
+                AMD_FMT_MOD_DCC_MAX_COMPRESSED_BLOCK_MASK,
+                self.dcc_max_compressed_block,
+            ),
+            (
+                "DCC_CONSTANT_ENCODE",
+                AMD_FMT_MOD_DCC_CONSTANT_ENCODE_SHIFT,
+                AMD_FMT_MOD_DCC_CONSTANT_ENCODE_MASK,
+                self.dcc_constant_encode as u8,
+            ),
+            ("PIPE_XOR_BITS", AMD_FMT_MOD_PIPE_XOR_BITS_SHIFT, AMD_FMT_MOD_PIPE_XOR_BITS_MASK, self.pipe_xor_bits),
+            ("BANK_XOR_BITS", AMD_FMT_MOD_BANK_XOR_BITS_SHIFT, AMD_FMT_MOD_BANK_XOR_BITS_MASK, self.bank_xor_bits),
+            ("PACKERS", AMD_FMT_MOD_PACKERS_SHIFT, AMD_FMT_MOD_PACKERS_MASK, self.packers),
+            ("RB", AMD_FMT_MOD_RB_SHIFT, AMD_FMT_MOD_RB_MASK, self.rb),
+            ("PIPE", AMD_FMT_MOD_PIPE_SHIFT, AMD_FMT_MOD_PIPE_MASK, self.pipe),
+        ]
+    }
+
+    /// Decodes an AMD modifier.
+    pub fn from_modifier(modifier: Modifier) -> Option<AmdModifier> {
+        let value = modifier.value();
+        if modifier.vendor() != Vendor::Amd || value & !AMD_FIELDS_MASK != 0 {
+            return None;
+        }
+
+        let field = |shift: u64, mask: u64| (value >> shift & mask) as u8;
+
+        Some(AmdModifier {
+            tile_version: field(AMD_FMT_MOD_TILE_VERSION_SHIFT, AMD_FMT_MOD_TILE_VERSION_MASK),
+            tile: field(AMD_FMT_MOD_TILE_SHIFT, AMD_FMT_MOD_TILE_MASK),
+            dcc: field(AMD_FMT_MOD_DCC_SHIFT, AMD_FMT_MOD_DCC_MASK) != 0,
+            dcc_retile: field(AMD_FMT_MOD_DCC_RETILE_SHIFT, AMD_FMT_MOD_DCC_RETILE_MASK) != 0,
+            dcc_pipe_align: field(AMD_FMT_MOD_DCC_PIPE_ALIGN_SHIFT, AMD_FMT_MOD_DCC_PIPE_ALIGN_MASK) != 0,
+            dcc_independent_64b: field(AMD_FMT_MOD_DCC_INDEPENDENT_64B_SHIFT, AMD_FMT_MOD_DCC_INDEPENDENT_64B_MASK) != 0,
+            dcc_independent_128b: field(AMD_FMT_MOD_DCC_INDEPENDENT_128B_SHIFT, AMD_FMT_MOD_DCC_INDEPENDENT_128B_MASK)
+                != 0,
+            dcc_max_compressed_block: field(
+                AMD_FMT_MOD_DCC_MAX_COMPRESSED_BLOCK_SHIFT,
+                AMD_FMT_MOD_DCC_MAX_COMPRESSED_BLOCK_MASK,
+            ),
+            dcc_constant_encode: field(AMD_FMT_MOD_DCC_CONSTANT_ENCODE_SHIFT, AMD_FMT_MOD_DCC_CONSTANT_ENCODE_MASK) != 0,
+            pipe_xor_bits: field(AMD_FMT_MOD_PIPE_XOR_BITS_SHIFT, AMD_FMT_MOD_PIPE_XOR_BITS_MASK),
+            bank_xor_bits: field(AMD_FMT_MOD_BANK_XOR_BITS_SHIFT, AMD_FMT_MOD_BANK_XOR_BITS_MASK),
+            packers: field(AMD_FMT_MOD_PACKERS_SHIFT, AMD_FMT_MOD_PACKERS_MASK),
+            rb: field(AMD_FMT_MOD_RB_SHIFT, AMD_FMT_MOD_RB_MASK),
+            pipe: field(AMD_FMT_MOD_PIPE_SHIFT, AMD_FMT_MOD_PIPE_MASK),
+        })
+    }
+
+    fn tile_version_name(&self) -> Option<&'static str> {
+        Some(match u64::from(self.tile_version) {
+            AMD_FMT_MOD_TILE_VER_GFX9 => "AMD_FMT_MOD_TILE_VER_GFX9",
+            AMD_FMT_MOD_TILE_VER_GFX10 => "AMD_FMT_MOD_TILE_VER_GFX10",
+            AMD_FMT_MOD_TILE_VER_GFX10_RBPLUS => "AMD_FMT_MOD_TILE_VER_GFX10_RBPLUS",
+            AMD_FMT_MOD_TILE_VER_GFX11 => "AMD_FMT_MOD_TILE_VER_GFX11",
+            AMD_FMT_MOD_TILE_VER_GFX12 => "AMD_FMT_MOD_TILE_VER_GFX12",
+            _ => return None,
+        })
+    }
+
+    fn tile_name(&self) -> Option<&'static str> {
+        // GFX12 numbers its tiles anew
+        if u64::from(self.tile_version) == AMD_FMT_MOD_TILE_VER_GFX12 {
+            return Some(match u64::from(self.tile) {
+                AMD_FMT_MOD_TILE_GFX12_256B_2D => "AMD_FMT_MOD_TILE_GFX12_256B_2D",
+                AMD_FMT_MOD_TILE_GFX12_4K_2D => "AMD_FMT_MOD_TILE_GFX12_4K_2D",
+                AMD_FMT_MOD_TILE_GFX12_64K_2D => "AMD_FMT_MOD_TILE_GFX12_64K_2D",
+                AMD_FMT_MOD_TILE_GFX12_256K_2D => "AMD_FMT_MOD_TILE_GFX12_256K_2D",
+                _ => return None,
+            });
+        }
+
+        Some(match u64::from(self.tile) {
+            AMD_FMT_MOD_TILE_GFX9_64K_S => "AMD_FMT_MOD_TILE_GFX9_64K_S",
+            AMD_FMT_MOD_TILE_GFX9_64K_D => "AMD_FMT_MOD_TILE_GFX9_64K_D",
+            AMD_FMT_MOD_TILE_GFX9_64K_S_X => "AMD_FMT_MOD_TILE_GFX9_64K_S_X",
+            AMD_FMT_MOD_TILE_GFX9_64K_D_X => "AMD_FMT_MOD_TILE_GFX9_64K_D_X",
+            AMD_FMT_MOD_TILE_GFX9_64K_R_X => "AMD_FMT_MOD_TILE_GFX9_64K_R_X",
+            AMD_FMT_MOD_TILE_GFX11_256K_R_X => "AMD_FMT_MOD_TILE_GFX11_256K_R_X",
+            _ => return None,
+        })
+    }
+
+    fn dcc_block_name(value: u8) -> Option<&'static str> {
+        Some(match u64::from(value) {
+            AMD_FMT_MOD_DCC_BLOCK_64B => "AMD_FMT_MOD_DCC_BLOCK_64B",
+            AMD_FMT_MOD_DCC_BLOCK_128B => "AMD_FMT_MOD_DCC_BLOCK_128B",
+            AMD_FMT_MOD_DCC_BLOCK_256B => "AMD_FMT_MOD_DCC_BLOCK_256B",
+            _ => return None,
+        })
+    }
+}
+
+/// Lists the fields that are set as `| AMD_FMT_MOD_SET(FIELD, value)`.
+impl fmt::Display for AmdModifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &(field, _, _, value) in self.fields().iter().filter(|field| field.3 != 0) {
+            let name = match field {
+                "TILE_VERSION" => self.tile_version_name(),
+                "TILE" => self.tile_name(),
+                "DCC_MAX_COMPRESSED_BLOCK" => AmdModifier::dcc_block_name(value),
+                _ => None,
+            };
+
+            match name {
+                Some(name) => write!(f, " | AMD_FMT_MOD_SET({}, {})", field, name)?,
+                None => write!(f, " | AMD_FMT_MOD_SET({}, {})", field, value)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The parameters of an NVIDIA block linear modifier.
+///
+/// See `DRM_FORMAT_MOD_NVIDIA_BLOCK_LINEAR_2D` in `drm_fourcc.h`.
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct NvidiaBlockLinear {
+    /// Lossless framebuffer compression type (`c`)
+    pub compression: u8,
+    /// Sector layout, `1` for desktop GPUs and later Tegra (`s`)
+    pub sector_layout: u8,
+    /// GOB height and page kind generation (`g`)
+    pub generation: u8,
+    /// Page kind (`k`)
+    pub page_kind: u8,
+    /// The log2 of the height of a block in GOBs (`h`)
+    pub height_log2: u8,
+}
+
+impl NvidiaBlockLinear {
+    /// Decodes an NVIDIA block linear modifier.
+    pub fn from_modifier(modifier: Modifier) -> Option<NvidiaBlockLinear> {
+        let value = modifier.value();
+        let reserved = 0x7f << 5 | !0 << 26;
+        if modifier.vendor() != Vendor::Nvidia
+            || value & DRM_FORMAT_MOD_NVIDIA_BLOCK_LINEAR == 0
+            || value & reserved & VALUE_MASK != 0
+        {
+            return None;
+        }
+
+        Some(NvidiaBlockLinear {
+            compression: (value >> 23 & 0x7) as u8,
+            sector_layout: (value >> 22 & 0x1) as u8,
+            generation: (value >> 20 & 0x3) as u8,
+            page_kind: (value >> 12 & 0xff) as u8,
+            height_log2: (value & 0xf) as u8,
+        })
+    }
+}
+
+/// The column width of a Broadcom SAND buffer.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum SandWidth {
+    Sand32,
+    Sand64,
+    Sand128,
+    Sand256,
+}
+
+impl SandWidth {
+    fn as_raw(&self) -> u64 {
+        let modifier = match *self {
+            SandWidth::Sand32 => Modifier::BROADCOM_SAND32,
+            SandWidth::Sand64 => Modifier::BROADCOM_SAND64,
+            SandWidth::Sand128 => Modifier::BROADCOM_SAND128,
+            SandWidth::Sand256 => Modifier::BROADCOM_SAND256,
+        };
+
+        modifier.value()
+    }
+
+    fn name(&self) -> &'static str {
+        match *self {
+            SandWidth::Sand32 => "SAND32",
+            SandWidth::Sand64 => "SAND64",
+            SandWidth::Sand128 => "SAND128",
+            SandWidth::Sand256 => "SAND256",
+        }
+    }
+}
+
+/// A Broadcom SAND layout, storing the image in columns of a fixed width.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct BroadcomSand {
+    /// The width of a column in bytes
+    pub width: SandWidth,
+    /// The height of a column in rows, `0` if it is the height of the
+    /// buffer
+    pub column_height: u64,
+}
+
+impl BroadcomSand {
+    /// Decodes a Broadcom SAND modifier.
+    pub fn from_modifier(modifier: Modifier) -> Option<BroadcomSand> {
+        if modifier.vendor() != Vendor::Broadcom {
+            return None;
+        }
+
+        let value = modifier.value();
+        let width = match Modifier::new(Vendor::Broadcom, value & 0xff) {
+            Modifier::BROADCOM_SAND32 => SandWidth::Sand32,
+            Modifier::BROADCOM_SAND64 => SandWidth::Sand64,
+            Modifier::BROADCOM_SAND128 => SandWidth::Sand128,
+            Modifier::BROADCOM_SAND256 => SandWidth::Sand256,
+            _ => return None,
+        };
+
+        Some(BroadcomSand {
+            width: width,
+            column_height: value >> 8,
+        })
+    }
+}
+
+/// The tiling layout of a Vivante modifier.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum VivanteTiling {
+    Tiled,
+    SuperTiled,
+    SplitTiled,
+    SplitSuperTiled,
+}
+
+impl VivanteTiling {
+    fn as_raw(&self) -> u64 {
+        let modifier = match *self {
+            VivanteTiling::Tiled => Modifier::VIVANTE_TILED,
+            VivanteTiling::SuperTiled => Modifier::VIVANTE_SUPER_TILED,
+            VivanteTiling::SplitTiled => Modifier::VIVANTE_SPLIT_TILED,
+            VivanteTiling::SplitSuperTiled => Modifier::VIVANTE_SPLIT_SUPER_TILED,
+        };
+
+        modifier.value()
+    }
+}
+
+/// The layout of the tile-status buffer of a Vivante modifier.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum VivanteTileStatus {
+    Ts64x4,
+    Ts64x2,
+    Ts128x4,
+    Ts256x4,
+}
+
+impl VivanteTileStatus {
+    fn as_raw(&self) -> u64 {
+        match *self {
+            VivanteTileStatus::Ts64x4 => VIVANTE_MOD_TS_64_4,
+            VivanteTileStatus::Ts64x2 => VIVANTE_MOD_TS_64_2,
+            VivanteTileStatus::Ts128x4 => VIVANTE_MOD_TS_128_4,
+            VivanteTileStatus::Ts256x4 => VIVANTE_MOD_TS_256_4,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match *self {
+            VivanteTileStatus::Ts64x4 => "VIVANTE_MOD_TS_64_4",
+            VivanteTileStatus::Ts64x2 => "VIVANTE_MOD_TS_64_2",
+            VivanteTileStatus::Ts128x4 => "VIVANTE_MOD_TS_128_4",
+            VivanteTileStatus::Ts256x4 => "VIVANTE_MOD_TS_256_4",
+        }
+    }
+}
+
+/// A Vivante modifier: a tiling layout, optionally with a tile-status buffer
+/// and compression.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Vivante {
+    /// The tiling layout
+    pub tiling: VivanteTiling,
+    /// The layout of the tile-status buffer, if any
+    pub tile_status: Option<VivanteTileStatus>,
+    /// DEC400 compression
+    pub dec400: bool,
+}
+
+impl Vivante {
+    /// Decodes a Vivante modifier.
+    pub fn from_modifier(modifier: Modifier) -> Option<Vivante> {
+        if modifier.vendor() != Vendor::Vivante {
+            return None;
+        }
+
+        let value = modifier.value();
+        let tiling = match Modifier::new(Vendor::Vivante, value & !VIVANTE_MOD_EXT_MASK) {
+            Modifier::VIVANTE_TILED => VivanteTiling::Tiled,
+            Modifier::VIVANTE_SUPER_TILED => VivanteTiling::SuperTiled,
+            Modifier::VIVANTE_SPLIT_TILED => VivanteTiling::SplitTiled,
+            Modifier::VIVANTE_SPLIT_SUPER_TILED => VivanteTiling::SplitSuperTiled,
+            _ => return None,
+        };
+
+        let tile_status = match value & VIVANTE_MOD_TS_MASK {
+            0 => None,
+            VIVANTE_MOD_TS_64_4 => Some(VivanteTileStatus::Ts64x4),
+            VIVANTE_MOD_TS_64_2 => Some(VivanteTileStatus::Ts64x2),
+            VIVANTE_MOD_TS_128_4 => Some(VivanteTileStatus::Ts128x4),
+            VIVANTE_MOD_TS_256_4 => Some(VivanteTileStatus::Ts256x4),
+            _ => return None,
+        };
+
+        let dec400 = match value & VIVANTE_MOD_COMP_MASK {
+            0 => false,
+            VIVANTE_MOD_COMP_DEC400 => true,
+            _ => return None,
+        };
+
+        Some(Vivante {
+            tiling: tiling,
+            tile_status: tile_status,
+            dec400: dec400,
+        })
+    }
+}
+
+impl fmt::Display for Vivante {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tiling = Modifier::new(Vendor::Vivante, self.tiling.as_raw());
+        write!(f, "{}", tiling)?;
+
+        if let Some(tile_status) = self.tile_status {
+            write!(f, " | {}", tile_status.name())?;
+        }
+        if self.dec400 {
+            f.write_str(" | VIVANTE_MOD_COMP_DEC400")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that a modifier decodes into `fields` and prints as `name`.
+    fn check<T: fmt::Debug + PartialEq>(raw: u64, decode: fn(Modifier) -> Option<T>, fields: T, name: &str) {
+        let modifier = Modifier::from(raw);
+        assert_eq!(decode(modifier), Some(fields));
+        assert_eq!(modifier.to_string(), name);
+    }
+
+    #[test]
+    fn amd_gfx9() {
+        let amd = AmdModifier {
+            tile_version: 1,
+            tile: 9,
+            ..AmdModifier::default()
+        };
+
+        assert_eq!(Modifier::amd(amd).as_raw(), 0x0200_0000_0000_0901);
+        check(
+            0x0200_0000_0000_0901,
+            AmdModifier::from_modifier,
+            amd,
+            "AMD_FMT_MOD | AMD_FMT_MOD_SET(TILE_VERSION, AMD_FMT_MOD_TILE_VER_GFX9) | \
+             AMD_FMT_MOD_SET(TILE, AMD_FMT_MOD_TILE_GFX9_64K_S)",
+        );
+    }
+
+    #[test]
+    fn amd_gfx10_dcc() {
+        let amd = AmdModifier {
+            tile_version: 3,
+            tile: 27,
+            dcc: true,
+            dcc_independent_64b: true,
+            pipe_xor_bits: 4,
+            packers: 3,
+            ..AmdModifier::default()
+        };
+
+        assert_eq!(Modifier::amd(amd).as_raw(), 0x0200_0000_1881_3b03);
+        check(
+            0x0200_0000_1881_3b03,
+            AmdModifier::from_modifier,
+            amd,
+            "AMD_FMT_MOD | AMD_FMT_MOD_SET(TILE_VERSION, AMD_FMT_MOD_TILE_VER_GFX10_RBPLUS) | \
+             AMD_FMT_MOD_SET(TILE, AMD_FMT_MOD_TILE_GFX9_64K_R_X) | AMD_FMT_MOD_SET(DCC, 1) | \
+             AMD_FMT_MOD_SET(DCC_INDEPENDENT_64B, 1) | AMD_FMT_MOD_SET(PIPE_XOR_BITS, 4) | \
+             AMD_FMT_MOD_SET(PACKERS, 3)",
+        );
+
+        // GFX10 has no tile 3, so its number is printed.
+        assert_eq!(
+            Modifier::from(0x0200_0000_0000_0302).to_string(),
+            "AMD_FMT_MOD | AMD_FMT_MOD_SET(TILE_VERSION, AMD_FMT_MOD_TILE_VER_GFX10) | AMD_FMT_MOD_SET(TILE, 3)"
+        );
+    }
+
+    #[test]
+    fn amd_gfx11_every_field() {
+        let amd = AmdModifier {
+            tile_version: 4,
+            tile: 31,
+            dcc: true,
+            dcc_retile: true,
+            dcc_pipe_align: true,
+            dcc_independent_64b: true,
+            dcc_independent_128b: true,
+            dcc_max_compressed_block: 1,
+            dcc_constant_encode: true,
+            pipe_xor_bits: 3,
+            bank_xor_bits: 2,
+            packers: 0,
+            rb: 1,
+            pipe: 2,
+        };
+
+        assert_eq!(Modifier::amd(amd).as_raw(), 0x0200_0004_4277_ff04);
+        check(
+            0x0200_0004_4277_ff04,
+            AmdModifier::from_modifier,
+            amd,
+            "AMD_FMT_MOD | AMD_FMT_MOD_SET(TILE_VERSION, AMD_FMT_MOD_TILE_VER_GFX11) | \
+             AMD_FMT_MOD_SET(TILE, AMD_FMT_MOD_TILE_GFX11_256K_R_X) | AMD_FMT_MOD_SET(DCC, 1) | \
+             AMD_FMT_MOD_SET(DCC_RETILE, 1) | AMD_FMT_MOD_SET(DCC_PIPE_ALIGN, 1) | \
+             AMD_FMT_MOD_SET(DCC_INDEPENDENT_64B, 1) | AMD_FMT_MOD_SET(DCC_INDEPENDENT_128B, 1) | \
+             AMD_FMT_MOD_SET(DCC_MAX_COMPRESSED_BLOCK, AMD_FMT_MOD_DCC_BLOCK_128B) | \
+             AMD_FMT_MOD_SET(DCC_CONSTANT_ENCODE, 1) | AMD_FMT_MOD_SET(PIPE_XOR_BITS, 3) | \
+             AMD_FMT_MOD_SET(BANK_XOR_BITS, 2) | AMD_FMT_MOD_SET(RB, 1) | AMD_FMT_MOD_SET(PIPE, 2)",
+        );
+    }
+
+    #[test]
+    fn amd_gfx12() {
+        let amd = AmdModifier {
+            tile_version: 5,
+            tile: 3,
+            dcc: true,
+            dcc_max_compressed_block: 2,
+            ..AmdModifier::default()
+        };
+
+        assert_eq!(Modifier::amd(amd).as_raw(), 0x0200_0000_0008_2305);
+        check(
+            0x0200_0000_0008_2305,
+            AmdModifier::from_modifier,
+            amd,
+            "AMD_FMT_MOD | AMD_FMT_MOD_SET(TILE_VERSION, AMD_FMT_MOD_TILE_VER_GFX12) | \
+             AMD_FMT_MOD_SET(TILE, AMD_FMT_MOD_TILE_GFX12_64K_2D) | AMD_FMT_MOD_SET(DCC, 1) | \
+             AMD_FMT_MOD_SET(DCC_MAX_COMPRESSED_BLOCK, AMD_FMT_MOD_DCC_BLOCK_256B)",
+        );
+    }
+
+    #[test]
+    fn amd_rejects_unknown_bits() {
+        let modifier = Modifier::new(Vendor::Amd, 1 << 40 | 0x901);
+
+        assert_eq!(AmdModifier::from_modifier(modifier), None);
+        assert_eq!(modifier.to_string(), "fourcc_mod_code(AMD, 0x10000000901)");
+    }
+
+    #[test]
+    fn nvidia_block_linear() {
+        // DRM_FORMAT_MOD_NVIDIA_BLOCK_LINEAR_2D(0, 1, 2, 0xfe, 4)
+        let layout = NvidiaBlockLinear {
+            compression: 0,
+            sector_layout: 1,
+            generation: 2,
+            page_kind: 0xfe,
+            height_log2: 4,
+        };
+
+        assert_eq!(Modifier::nvidia_block_linear(layout).as_raw(), 0x0300_0000_006f_e014);
+        check(
+            0x0300_0000_006f_e014,
+            NvidiaBlockLinear::from_modifier,
+            layout,
+            "DRM_FORMAT_MOD_NVIDIA_BLOCK_LINEAR_2D(0, 1, 2, 0xfe, 4)",
+        );
+
+        // DRM_FORMAT_MOD_NVIDIA_BLOCK_LINEAR_2D(7, 0, 0, 0, 0)
+        let compressed = NvidiaBlockLinear {
+            compression: 7,
+            ..NvidiaBlockLinear::default()
+        };
+        assert_eq!(Modifier::nvidia_block_linear(compressed).as_raw(), 0x0300_0000_0380_0010);
+    }
+
+    #[test]
+    fn nvidia_reserved_bits() {
+        for &bit in &[5, 11, 26, 55] {
+            let modifier = Modifier::from(0x0300_0000_006f_e014 | 1 << bit);
+            assert_eq!(NvidiaBlockLinear::from_modifier(modifier), None, "bit {}", bit);
+            assert!(modifier.to_string().starts_with("fourcc_mod_code(NVIDIA, "));
+        }
+
+        // The Tegra tiling lacks the block linear bit.
+        assert_eq!(NvidiaBlockLinear::from_modifier(Modifier::NVIDIA_TEGRA_TILED), None);
+        assert_eq!(Modifier::NVIDIA_TEGRA_TILED.to_string(), "DRM_FORMAT_MOD_NVIDIA_TEGRA_TILED");
+    }
+
+    #[test]
+    fn broadcom_sand_column_height() {
+        let sand = BroadcomSand {
+            width: SandWidth::Sand128,
+            column_height: 96,
+        };
+
+        // DRM_FORMAT_MOD_BROADCOM_SAND128_COL_HEIGHT(96)
+        assert_eq!(Modifier::broadcom_sand(sand).as_raw(), 0x0700_0000_0000_6004);
+        check(
+            0x0700_0000_0000_6004,
+            BroadcomSand::from_modifier,
+            sand,
+            "DRM_FORMAT_MOD_BROADCOM_SAND128_COL_HEIGHT(96)",
+        );
+
+        // Without a column height it is the plain SAND modifier.
+        let plain = BroadcomSand {
+            width: SandWidth::Sand32,
+            column_height: 0,
+        };
+        assert_eq!(Modifier::broadcom_sand(plain), Modifier::BROADCOM_SAND32);
+        check(0x0700_0000_0000_0002, BroadcomSand::from_modifier, plain, "DRM_FORMAT_MOD_BROADCOM_SAND32");
+
+        assert_eq!(BroadcomSand::from_modifier(Modifier::BROADCOM_UIF), None);
+    }
+
+    #[test]
+    fn vivante_ts_and_dec400() {
+        let vivante = Vivante {
+            tiling: VivanteTiling::SuperTiled,
+            tile_status: Some(VivanteTileStatus::Ts128x4),
+            dec400: true,
+        };
+
+        // DRM_FORMAT_MOD_VIVANTE_SUPER_TILED | VIVANTE_MOD_TS_128_4 | VIVANTE_MOD_COMP_DEC400
+        assert_eq!(Modifier::vivante(vivante).as_raw(), 0x0613_0000_0000_0002);
+        check(
+            0x0613_0000_0000_0002,
+            Vivante::from_modifier,
+            vivante,
+            "DRM_FORMAT_MOD_VIVANTE_SUPER_TILED | VIVANTE_MOD_TS_128_4 | VIVANTE_MOD_COMP_DEC400",
+        );
+
+        let ts_only = Vivante {
+            tiling: VivanteTiling::SplitTiled,
+            tile_status: Some(VivanteTileStatus::Ts64x2),
+            dec400: false,
+        };
+        check(
+            0x0602_0000_0000_0003,
+            Vivante::from_modifier,
+            ts_only,
+            "DRM_FORMAT_MOD_VIVANTE_SPLIT_TILED | VIVANTE_MOD_TS_64_2",
+        );
+
+        // Unknown tile-status and compression values.
+        assert_eq!(Vivante::from_modifier(Modifier::from(0x0605_0000_0000_0001)), None);
+        assert_eq!(Vivante::from_modifier(Modifier::from(0x0620_0000_0000_0001)), None);
+    }
+
+    #[test]
+    fn afrc() {
+        let afrc = Afrc {
+            p0: AfrcCodingUnit::Size24,
+            p12: None,
+            scan: false,
+        };
+
+        // DRM_FORMAT_MOD_ARM_AFRC(AFRC_FORMAT_MOD_CU_SIZE_P0(AFRC_FORMAT_MOD_CU_SIZE_24))
+        assert_eq!(Modifier::arm_afrc(afrc).as_raw(), 0x0820_0000_0000_0002);
+        check(
+            0x0820_0000_0000_0002,
+            Afrc::from_modifier,
+            afrc,
+            "DRM_FORMAT_MOD_ARM_AFRC(AFRC_FORMAT_MOD_CU_SIZE_P0(AFRC_FORMAT_MOD_CU_SIZE_24))",
+        );
+
+        let p12 = Afrc {
+            p0: AfrcCodingUnit::Size24,
+            p12: Some(AfrcCodingUnit::Size16),
+            scan: true,
+        };
+        assert_eq!(Modifier::arm_afrc(p12).as_raw(), 0x0820_0000_0000_0112);
+        check(
+            0x0820_0000_0000_0112,
+            Afrc::from_modifier,
+            p12,
+            "DRM_FORMAT_MOD_ARM_AFRC(AFRC_FORMAT_MOD_CU_SIZE_P0(AFRC_FORMAT_MOD_CU_SIZE_24) | \
+             AFRC_FORMAT_MOD_CU_SIZE_P12(AFRC_FORMAT_MOD_CU_SIZE_16) | AFRC_FORMAT_MOD_LAYOUT_SCAN)",
+        );
+
+        // P0 is mandatory.
+        assert_eq!(Afrc::from_modifier(Modifier::from(0x0820_0000_0000_0010)), None);
+    }
+
+    #[test]
+    fn unknown_layouts() {
+        assert_eq!(Modifier::new(Vendor::Samsung, 0x42).to_string(), "fourcc_mod_code(SAMSUNG, 0x42)");
+        assert_eq!(Modifier::from(0x0830_0000_0000_0002).to_string(), "fourcc_mod_code(ARM, 0x30000000000002)");
+
+        let unknown = Modifier::from(0x7f00_0000_0000_0001);
+        assert_eq!(unknown.vendor(), Vendor::Unknown(0x7f));
+        assert_eq!(unknown.vendor().as_raw(), 0x7f);
+        assert_eq!(Modifier::new(unknown.vendor(), unknown.value()), unknown);
+        assert_eq!(unknown.to_string(), "0x7f00000000000001");
+    }
+}
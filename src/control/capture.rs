@@ -0,0 +1,161 @@
+//! # Capture
+//!
+//! Reading back the contents of the screen.
+//!
+//! The framebuffer scanned out by a CRTC can be read when its buffer is linear
+//! and the driver allows it to be mapped. Reading another process' framebuffer
+//! requires being the DRM Master or having CAP_SYS_ADMIN privileges.
+
+use control::{crtc, Device};
+use drm_ffi as ffi;
+use drm_ffi::fourcc::*;
+use drm_ffi::result::SystemError;
+
+/// Format modifier of buffers with a linear layout.
+const DRM_FORMAT_MOD_LINEAR: u64 = 0;
+
+/// An image in 8-bit RGBA, without padding between rows.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Image {
+    pub(crate) size: (u32, u32),
+    pub(crate) data: Vec<u8>,
+}
+
+impl Image {
+    /// Returns the size of this image.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the pixels of this image.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consumes the image, returning its pixels.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// Captures the framebuffer currently scanned out on a CRTC.
+///
+/// Returns `None` if the CRTC is not scanning out a framebuffer.
+///
+/// Fails with `SystemError::PermissionDenied` if the kernel did not hand out
+/// the buffer of the framebuffer, and with `SystemError::InvalidArgument` if
+/// its format or layout is not supported.
+pub fn capture<D: Device>(dev: &D, crtc: crtc::Handle) -> Result<Option<Image>, SystemError> {
+    let fb = match dev.get_crtc(crtc)?.framebuffer() {
+        Some(fb) => fb,
+        None => return Ok(None),
+    };
+
+    let info = dev.get_planar_framebuffer(fb)?;
+    let image = read_framebuffer(dev, &info);
+    info.close()?;
+
+    image.map(Some)
+}
+
+fn read_framebuffer<D: Device>(
+    dev: &D,
+    info: &::control::framebuffer::PlanarInfo<&D>,
+) -> Result<Image, SystemError> {
+    use nix::sys::mman;
+
+    let handle = info.buffers()[0].ok_or(SystemError::PermissionDenied)?;
+
+    match info.modifier() {
+        None | Some(DRM_FORMAT_MOD_LINEAR) => (),
+        Some(_) => return Err(SystemError::InvalidArgument),
+    }
+
+    let (_, height) = info.size();
+    let pitch = info.pitches()[0] as usize;
+    let offset = info.offsets()[0] as usize;
+    let length = offset + pitch * height as usize;
+
+    let map = ffi::mode::dumbbuffer::map(dev.as_raw_fd(), handle.into(), 0, 0)?;
+
+    let addr = unsafe {
+        mman::mmap(
+            ::std::ptr::null_mut(),
+            length,
+            mman::ProtFlags::PROT_READ,
+            mman::MapFlags::MAP_SHARED,
+            dev.as_raw_fd(),
+            map.offset as i64,
+        )?
+    };
+
+    let data = unsafe { ::std::slice::from_raw_parts(addr as *const u8, length) };
+    let image = to_rgba(info.raw_format(), &data[offset..], info.size(), pitch as u32);
+
+    unsafe {
+        mman::munmap(addr, length)?;
+    }
+
+    image.ok_or(SystemError::InvalidArgument)
+}
+
+/// Converts pixels of a linear buffer into an 8-bit RGBA `Image`.
+///
+/// Supports the 32-bit RGB formats and RGB565. Returns `None` for other
+/// formats, or if `data` is too short.
+///
+/// # Example
+///
+/// ```
+/// use drm::buffer::format::PixelFormat;
+/// use drm::control::capture;
+///
+/// // Two XRGB8888 pixels, stored little endian, with four bytes of padding.
+/// let data = [0x30, 0x20, 0x10, 0x00, 0xff, 0x00, 0x00, 0x00, 0, 0, 0, 0];
+///
+/// let image = capture::to_rgba(PixelFormat::XRGB8888.as_raw(), &data, (2, 1), 12).unwrap();
+/// assert_eq!(image.data(), &[0x10, 0x20, 0x30, 0xff, 0x00, 0x00, 0xff, 0xff]);
+/// ```
+pub fn to_rgba(format: u32, data: &[u8], size: (u32, u32), pitch: u32) -> Option<Image> {
+    let (w, h) = (size.0 as usize, size.1 as usize);
+    let pitch = pitch as usize;
+
+    let bpp = match format {
+        DRM_FORMAT_XRGB8888 | DRM_FORMAT_ARGB8888 | DRM_FORMAT_XBGR8888 | DRM_FORMAT_ABGR8888 => 4,
+        DRM_FORMAT_RGB565 => 2,
+        _ => return None,
+    };
+
+    if h > 0 && data.len() < pitch * (h - 1) + w * bpp {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(w * h * 4);
+
+    for y in 0..h {
+        let row = &data[y * pitch..y * pitch + w * bpp];
+
+        for px in row.chunks(bpp) {
+            let rgba = match format {
+                DRM_FORMAT_XRGB8888 => [px[2], px[1], px[0], 0xff],
+                DRM_FORMAT_ARGB8888 => [px[2], px[1], px[0], px[3]],
+                DRM_FORMAT_XBGR8888 => [px[0], px[1], px[2], 0xff],
+                DRM_FORMAT_ABGR8888 => [px[0], px[1], px[2], px[3]],
+                _ => {
+                    let v = px[0] as u16 | (px[1] as u16) << 8;
+                    let r = (v >> 11) as u8;
+                    let g = (v >> 5 & 0x3f) as u8;
+                    let b = (v & 0x1f) as u8;
+                    [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2, 0xff]
+                }
+            };
+
+            out.extend_from_slice(&rgba);
+        }
+    }
+
+    Some(Image {
+        size: size,
+        data: out,
+    })
+}
@@ -0,0 +1,542 @@
+//! # Color
+//!
+//! Color management of a CRTC's output pipeline.
+//!
+//! Drivers that support atomic color management expose up to three stages on
+//! each CRTC, applied in the following order:
+//!
+//! * `DEGAMMA_LUT` - A lookup table that linearizes the incoming pixel data.
+//!
+//! * `CTM` - A 3x3 color transformation matrix applied to the linear data.
+//!
+//! * `GAMMA_LUT` - A lookup table that encodes the result for the display.
+//!
+//! The number of entries each lookup table must have is given by the immutable
+//! `DEGAMMA_LUT_SIZE` and `GAMMA_LUT_SIZE` properties of the CRTC. Each stage is
+//! configured by attaching a property blob to the CRTC in an atomic request,
+//! which the helpers in this module take care of.
+
+use control::atomic::AtomicModeReq;
+use control::{crtc, property, Device};
+use drm_ffi as ffi;
+use drm_ffi::result::SystemError;
+
+/// A color lookup table, as used by the `GAMMA_LUT` and `DEGAMMA_LUT`
+/// properties.
+///
+/// Every entry maps an evenly spaced input value to a 16-bit output value for
+/// each of the red, green and blue channels.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Lut {
+    entries: Vec<ffi::drm_color_lut>,
+}
+
+impl Lut {
+    /// Creates a lookup table of `size` entries that does not alter any color.
+    pub fn identity(size: usize) -> Lut {
+        Lut::from_fn(size, |x| x)
+    }
+
+    /// Creates a lookup table of `size` entries by sampling a transfer
+    /// function, applied to all three channels.
+    ///
+    /// The function receives input values in the range `[0.0, 1.0]`, and its
+    /// results are clamped to the same range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use drm::control::color::Lut;
+    ///
+    /// let lut = Lut::from_fn(256, |x| x.powf(1.0 / 2.2));
+    /// assert_eq!(lut.len(), 256);
+    /// assert_eq!(lut.entry(0), Some((0, 0, 0)));
+    /// assert_eq!(lut.entry(255), Some((0xffff, 0xffff, 0xffff)));
+    /// ```
+    pub fn from_fn<F>(size: usize, f: F) -> Lut
+    where
+        F: Fn(f64) -> f64,
+    {
+        Lut::from_fn_rgb(size, &f, &f, &f)
+    }
+
+    /// Creates a lookup table of `size` entries by sampling a separate transfer
+    /// function for each channel.
+    pub fn from_fn_rgb<R, G, B>(size: usize, red: R, green: G, blue: B) -> Lut
+    where
+        R: Fn(f64) -> f64,
+        G: Fn(f64) -> f64,
+        B: Fn(f64) -> f64,
+    {
+        let entries = (0..size)
+            .map(|i| {
+                let x = position(i, size);
+                ffi::drm_color_lut {
+                    red: quantize(red(x)),
+                    green: quantize(green(x)),
+                    blue: quantize(blue(x)),
+                    reserved: 0,
+                }
+            })
+            .collect();
+
+        Lut { entries: entries }
+    }
+
+    /// Creates a lookup table of `size` entries from a curve given as a set of
+    /// `(input, output)` points in the range `[0.0, 1.0]`.
+    ///
+    /// Values between two points are linearly interpolated, while values
+    /// outside of the points are extended from the closest one. An empty set of
+    /// points results in an identity table.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use drm::control::color::Lut;
+    ///
+    /// // Invert all colors
+    /// let lut = Lut::from_points(3, &[(0.0, 1.0), (1.0, 0.0)]);
+    /// assert_eq!(lut.entry(0), Some((0xffff, 0xffff, 0xffff)));
+    /// assert_eq!(lut.entry(1), Some((0x8000, 0x8000, 0x8000)));
+    /// assert_eq!(lut.entry(2), Some((0, 0, 0)));
+    /// ```
+    pub fn from_points(size: usize, points: &[(f64, f64)]) -> Lut {
+        if points.is_empty() {
+            return Lut::identity(size);
+        }
+
+        let mut points = points.to_vec();
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(::std::cmp::Ordering::Equal));
+
+        Lut::from_fn(size, |x| {
+            let first = points[0];
+            let last = points[points.len() - 1];
+
+            if x <= first.0 {
+                return first.1;
+            }
+            if x >= last.0 {
+                return last.1;
+            }
+
+            let upper = points.iter().position(|p| p.0 >= x).unwrap_or(points.len() - 1);
+            let (x0, y0) = points[upper - 1];
+            let (x1, y1) = points[upper];
+
+            if x1 - x0 <= 0.0 {
+                y1
+            } else {
+                y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+            }
+        })
+    }
+
+    /// Creates a lookup table from raw entries.
+    pub fn from_raw(entries: Vec<ffi::drm_color_lut>) -> Lut {
+        Lut { entries: entries }
+    }
+
+    /// Returns the number of entries in this table.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether this table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the red, green and blue values of an entry.
+    pub fn entry(&self, index: usize) -> Option<(u16, u16, u16)> {
+        self.entries.get(index).map(|e| (e.red, e.green, e.blue))
+    }
+
+    /// Returns the raw entries of this table, as they are laid out in the
+    /// property blob.
+    pub fn as_raw(&self) -> &[ffi::drm_color_lut] {
+        &self.entries
+    }
+
+    /// Creates a new table with `size` entries that follows the same curve,
+    /// linearly interpolating between the existing entries.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use drm::control::color::Lut;
+    ///
+    /// let lut = Lut::identity(256).resample(1024);
+    /// assert_eq!(lut, Lut::identity(1024));
+    /// ```
+    pub fn resample(&self, size: usize) -> Lut {
+        if self.entries.is_empty() {
+            return Lut::identity(size);
+        }
+
+        let entries = &self.entries;
+
+        Lut::from_fn_rgb(
+            size,
+            |x| sample(entries, x, |e| e.red),
+            |x| sample(entries, x, |e| e.green),
+            |x| sample(entries, x, |e| e.blue),
+        )
+    }
+}
+
+/// A 3x3 color transformation matrix, as used by the `CTM` property.
+///
+/// The matrix is stored in row-major order, and is applied to a column vector
+/// of linear red, green and blue values.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ctm {
+    matrix: [[f64; 3]; 3],
+}
+
+impl Ctm {
+    /// Creates a matrix that does not alter any color.
+    pub fn identity() -> Ctm {
+        Ctm::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    /// Creates a matrix from its rows.
+    pub fn new(matrix: [[f64; 3]; 3]) -> Ctm {
+        Ctm { matrix: matrix }
+    }
+
+    /// Returns the rows of this matrix.
+    pub fn matrix(&self) -> [[f64; 3]; 3] {
+        self.matrix
+    }
+
+    /// Converts this matrix into the layout of the property blob.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use drm::control::color::Ctm;
+    ///
+    /// let raw = Ctm::identity().to_raw();
+    /// assert_eq!(raw.matrix[0], 1 << 32);
+    /// assert_eq!(raw.matrix[1], 0);
+    /// assert_eq!(Ctm::from_raw(&raw), Ctm::identity());
+    /// ```
+    pub fn to_raw(&self) -> ffi::drm_color_ctm {
+        let mut raw = ffi::drm_color_ctm::default();
+
+        for (i, value) in self.matrix.iter().flat_map(|row| row.iter()).enumerate() {
+            raw.matrix[i] = to_s31_32(*value);
+        }
+
+        raw
+    }
+
+    /// Creates a matrix from the layout of the property blob.
+    pub fn from_raw(raw: &ffi::drm_color_ctm) -> Ctm {
+        let mut matrix = [[0.0; 3]; 3];
+
+        for (i, &value) in raw.matrix.iter().enumerate() {
+            matrix[i / 3][i % 3] = from_s31_32(value);
+        }
+
+        Ctm::new(matrix)
+    }
+}
+
+impl Default for Ctm {
+    fn default() -> Ctm {
+        Ctm::identity()
+    }
+}
+
+/// Converts a number into the S31.32 sign-magnitude fixed point format used by
+/// the `CTM` property.
+///
+/// Unlike two's complement, the most significant bit only holds the sign, while
+/// the remaining bits hold the absolute value. Values that are too large to be
+/// represented are saturated.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::color::to_s31_32;
+///
+/// assert_eq!(to_s31_32(1.0), 0x0000_0001_0000_0000);
+/// assert_eq!(to_s31_32(0.5), 0x0000_0000_8000_0000);
+/// assert_eq!(to_s31_32(-0.5), 0x8000_0000_8000_0000);
+/// assert_eq!(to_s31_32(-2.25), 0x8000_0002_4000_0000);
+/// ```
+pub fn to_s31_32(value: f64) -> u64 {
+    const SIGN: u64 = 1 << 63;
+
+    if value.is_nan() {
+        return 0;
+    }
+
+    let magnitude = (value.abs() * 4294967296.0).round();
+    let magnitude = if magnitude >= (SIGN as f64) {
+        !SIGN
+    } else {
+        magnitude as u64
+    };
+
+    if value < 0.0 && magnitude != 0 {
+        SIGN | magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Converts a number from the S31.32 sign-magnitude fixed point format used by
+/// the `CTM` property.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::color::from_s31_32;
+///
+/// assert_eq!(from_s31_32(0x0000_0001_0000_0000), 1.0);
+/// assert_eq!(from_s31_32(0x8000_0000_4000_0000), -0.25);
+/// ```
+pub fn from_s31_32(raw: u64) -> f64 {
+    const SIGN: u64 = 1 << 63;
+
+    let magnitude = (raw & !SIGN) as f64 / 4294967296.0;
+
+    if raw & SIGN != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Returns the number of entries the `DEGAMMA_LUT` of a CRTC must have, or
+/// `None` if the CRTC does not support it.
+pub fn degamma_lut_size<D: Device>(dev: &D, crtc: crtc::Handle) -> Result<Option<usize>, SystemError> {
+    lut_size(dev, crtc, "DEGAMMA_LUT_SIZE")
+}
+
+/// Returns the number of entries the `GAMMA_LUT` of a CRTC must have, or
+/// `None` if the CRTC does not support it.
+pub fn gamma_lut_size<D: Device>(dev: &D, crtc: crtc::Handle) -> Result<Option<usize>, SystemError> {
+    lut_size(dev, crtc, "GAMMA_LUT_SIZE")
+}
+
+/// Sets the `DEGAMMA_LUT` of a CRTC in an atomic request.
+///
+/// The table is resampled to the size the CRTC expects. Passing `None` resets
+/// the stage to a linear mapping.
+///
+/// Returns the blob that was created for the table. It must be kept alive until
+/// the request is committed, and should be destroyed afterwards.
+///
+/// Fails with `SystemError::InvalidArgument` if the CRTC has no such property.
+pub fn add_degamma_lut<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    crtc: crtc::Handle,
+    lut: Option<&Lut>,
+) -> Result<Option<property::Value>, SystemError> {
+    add_lut(dev, req, crtc, "DEGAMMA_LUT", "DEGAMMA_LUT_SIZE", lut)
+}
+
+/// Sets the `GAMMA_LUT` of a CRTC in an atomic request.
+///
+/// The table is resampled to the size the CRTC expects. Passing `None` resets
+/// the stage to a linear mapping.
+///
+/// Returns the blob that was created for the table. It must be kept alive until
+/// the request is committed, and should be destroyed afterwards.
+///
+/// Fails with `SystemError::InvalidArgument` if the CRTC has no such property.
+pub fn add_gamma_lut<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    crtc: crtc::Handle,
+    lut: Option<&Lut>,
+) -> Result<Option<property::Value>, SystemError> {
+    add_lut(dev, req, crtc, "GAMMA_LUT", "GAMMA_LUT_SIZE", lut)
+}
+
+/// Sets the `CTM` of a CRTC in an atomic request.
+///
+/// Passing `None` resets the stage to the identity matrix.
+///
+/// Returns the blob that was created for the matrix. It must be kept alive
+/// until the request is committed, and should be destroyed afterwards.
+///
+/// Fails with `SystemError::InvalidArgument` if the CRTC has no such property.
+pub fn add_ctm<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    crtc: crtc::Handle,
+    ctm: Option<&Ctm>,
+) -> Result<Option<property::Value>, SystemError> {
+    let (prop, _) = dev.find_property(crtc, "CTM")?.ok_or(SystemError::InvalidArgument)?;
+
+    let blob = match ctm {
+        Some(ctm) => Some(dev.create_property_blob(&ctm.to_raw())?),
+        None => None,
+    };
+
+    req.add_property(crtc, prop, blob.unwrap_or(property::Value::Blob(0)));
+
+    Ok(blob)
+}
+
+fn add_lut<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    crtc: crtc::Handle,
+    name: &str,
+    size_name: &str,
+    lut: Option<&Lut>,
+) -> Result<Option<property::Value>, SystemError> {
+    let (prop, _) = dev.find_property(crtc, name)?.ok_or(SystemError::InvalidArgument)?;
+
+    let blob = match lut {
+        Some(lut) => {
+            let blob = match lut_size(dev, crtc, size_name)? {
+                Some(size) if size != lut.len() => {
+                    dev.create_property_blob(lut.resample(size).as_raw())?
+                }
+                _ => dev.create_property_blob(lut.as_raw())?,
+            };
+            Some(blob)
+        }
+        None => None,
+    };
+
+    req.add_property(crtc, prop, blob.unwrap_or(property::Value::Blob(0)));
+
+    Ok(blob)
+}
+
+fn lut_size<D: Device>(dev: &D, crtc: crtc::Handle, name: &str) -> Result<Option<usize>, SystemError> {
+    Ok(dev.find_property(crtc, name)?.map(|(_, size)| size as usize))
+}
+
+/// Position of the `index`th entry of a table with `size` entries.
+fn position(index: usize, size: usize) -> f64 {
+    if size <= 1 {
+        0.0
+    } else {
+        index as f64 / (size - 1) as f64
+    }
+}
+
+/// Linearly interpolates a single channel of a non-empty table.
+fn sample(entries: &[ffi::drm_color_lut], x: f64, channel: fn(&ffi::drm_color_lut) -> u16) -> f64 {
+    let last = entries.len() - 1;
+    let pos = x * last as f64;
+    let lo = (pos.floor() as usize).min(last);
+    let hi = (lo + 1).min(last);
+    let frac = pos - lo as f64;
+
+    let a = channel(&entries[lo]) as f64;
+    let b = channel(&entries[hi]) as f64;
+    (a + (b - a) * frac) / 65535.0
+}
+
+/// Converts a value in the range `[0.0, 1.0]` to a 16-bit table value.
+fn quantize(value: f64) -> u16 {
+    if value.is_nan() || value <= 0.0 {
+        0
+    } else if value >= 1.0 {
+        0xffff
+    } else {
+        (value * 65535.0).round() as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn s31_32_negative() {
+        assert_eq!(to_s31_32(-1.0), 0x8000_0001_0000_0000);
+        assert_eq!(to_s31_32(-0.75), 0x8000_0000_c000_0000);
+        assert_eq!(from_s31_32(0x8000_0001_0000_0000), -1.0);
+    }
+
+    #[test]
+    fn s31_32_zero() {
+        assert_eq!(to_s31_32(0.0), 0);
+        assert_eq!(from_s31_32(0), 0.0);
+    }
+
+    #[test]
+    fn s31_32_negative_zero_has_no_sign() {
+        assert_eq!(to_s31_32(-0.0), 0);
+        // Too small to be represented, rounds to zero.
+        assert_eq!(to_s31_32(-1e-12), 0);
+        // A set sign bit without magnitude still reads as zero.
+        assert_eq!(from_s31_32(1 << 63), 0.0);
+    }
+
+    #[test]
+    fn s31_32_out_of_range_saturates() {
+        assert_eq!(to_s31_32(1e12), 0x7fff_ffff_ffff_ffff);
+        assert_eq!(to_s31_32(-1e12), 0xffff_ffff_ffff_ffff);
+        assert_eq!(to_s31_32(f64::INFINITY), 0x7fff_ffff_ffff_ffff);
+        assert_eq!(to_s31_32(f64::NEG_INFINITY), 0xffff_ffff_ffff_ffff);
+    }
+
+    #[test]
+    fn s31_32_nan_is_zero() {
+        assert_eq!(to_s31_32(f64::NAN), 0);
+        assert_eq!(to_s31_32(-f64::NAN), 0);
+    }
+
+    #[test]
+    fn ctm_round_trip() {
+        let ctm = Ctm::new([[0.5, -0.25, 0.0], [-0.0, 1.0, 2.0], [-3.5, 0.125, 1.0]]);
+        let raw = ctm.to_raw();
+
+        assert_eq!(raw.matrix[1], 0x8000_0000_4000_0000);
+        assert_eq!(raw.matrix[3], 0);
+        assert_eq!(Ctm::from_raw(&raw), ctm);
+    }
+
+    #[test]
+    fn quantize_nan_and_range() {
+        let lut = Lut::from_fn(3, |x| if x == 0.5 { f64::NAN } else { x * 2.0 - 0.5 });
+
+        assert_eq!(lut.entry(0), Some((0, 0, 0)));
+        assert_eq!(lut.entry(1), Some((0, 0, 0)));
+        assert_eq!(lut.entry(2), Some((0xffff, 0xffff, 0xffff)));
+    }
+
+    #[test]
+    fn resample_upscales_linearly() {
+        let lut = Lut::from_fn(2, |x| 1.0 - x).resample(5);
+        let red: Vec<u16> = (0..5).map(|i| lut.entry(i).unwrap().0).collect();
+
+        assert_eq!(red, vec![0xffff, 0xbfff, 0x8000, 0x4000, 0]);
+    }
+
+    #[test]
+    fn resample_downscales_keeping_endpoints() {
+        let lut = Lut::from_points(5, &[(0.0, 0.0), (0.5, 1.0), (1.0, 0.0)]).resample(3);
+
+        assert_eq!(lut.entry(0), Some((0, 0, 0)));
+        assert_eq!(lut.entry(1), Some((0xffff, 0xffff, 0xffff)));
+        assert_eq!(lut.entry(2), Some((0, 0, 0)));
+    }
+
+    #[test]
+    fn resample_keeps_channels_apart() {
+        let lut = Lut::from_fn_rgb(2, |x| x, |_| 1.0, |_| 0.0).resample(3);
+
+        assert_eq!(lut.entry(1), Some((0x8000, 0xffff, 0)));
+    }
+
+    #[test]
+    fn resample_edge_sizes() {
+        assert_eq!(Lut::from_raw(Vec::new()).resample(4), Lut::identity(4));
+        assert_eq!(Lut::identity(16).resample(0).len(), 0);
+        assert_eq!(Lut::from_fn(4, |x| 1.0 - x).resample(1).entry(0), Some((0xffff, 0xffff, 0xffff)));
+    }
+}
@@ -9,9 +9,13 @@
 use control;
 use drm_ffi as ffi;
 
+use std::hash::{Hash, Hasher};
+use std::sync::OnceLock;
+
 /// A handle to a connector
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Handle(control::RawResourceHandle);
 
 impl Into<control::RawResourceHandle> for Handle {
@@ -45,19 +49,54 @@ impl std::fmt::Debug for Handle {
 }
 
 /// Information about a connector
+///
+/// The connection state, size and modes reflect the last time the kernel
+/// probed the connector. They are only guaranteed to be fresh when the
+/// information was requested with `force_probe` set.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Info {
     pub(crate) handle: Handle,
     pub(crate) interface: Interface,
     pub(crate) interface_id: u32,
     pub(crate) connection: State,
     pub(crate) size: Option<(u32, u32)>,
+    pub(crate) subpixel: SubPixel,
     pub(crate) modes: Vec<control::Mode>,
-    pub(crate) encoders: [Option<control::encoder::Handle>; 3],
+    pub(crate) encoders: Vec<control::encoder::Handle>,
     pub(crate) curr_enc: Option<control::encoder::Handle>,
+    pub(crate) props: Vec<(control::property::Handle, control::property::RawValue)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) blob_names: BlobNames,
 }
 
 impl Info {
+    /// Creates information about a connector without querying a device.
+    ///
+    /// This is mostly useful to build `Topology` snapshots in tests. The
+    /// connector has no physical size, encoders or properties.
+    pub fn new(
+        handle: Handle,
+        interface: Interface,
+        interface_id: u32,
+        state: State,
+        modes: Vec<control::Mode>,
+    ) -> Info {
+        Info {
+            handle: handle,
+            interface: interface,
+            interface_id: interface_id,
+            connection: state,
+            size: None,
+            subpixel: SubPixel::Unknown,
+            modes: modes,
+            encoders: Vec::new(),
+            curr_enc: None,
+            props: Vec::new(),
+            blob_names: BlobNames::default(),
+        }
+    }
+
     /// Returns the handle to this connector.
     pub fn handle(&self) -> Handle {
         self.handle
@@ -76,6 +115,11 @@ impl Info {
         self.interface_id
     }
 
+    /// Returns the `Name` of this connector, such as `HDMI-A-1`.
+    pub fn name(&self) -> Name {
+        Name::new(self.interface, self.interface_id)
+    }
+
     /// Returns the `State` of this connector.
     pub fn state(&self) -> State {
         self.connection
@@ -86,8 +130,13 @@ impl Info {
         self.size
     }
 
+    /// Returns the layout of the subpixels of the display.
+    pub fn subpixel(&self) -> SubPixel {
+        self.subpixel
+    }
+
     /// Returns a list of encoders that can be possibly used by this connector.
-    pub fn encoders(&self) -> &[Option<control::encoder::Handle>] {
+    pub fn encoders(&self) -> &[control::encoder::Handle] {
         &self.encoders
     }
 
@@ -99,11 +148,268 @@ impl Info {
     pub fn current_encoder(&self) -> Option<control::encoder::Handle> {
         self.curr_enc
     }
+
+    /// Returns the properties of this connector along with their values, as
+    /// they were when this information was retrieved.
+    pub fn properties(&self) -> &[(control::property::Handle, control::property::RawValue)] {
+        &self.props
+    }
+}
+
+/// The names of the properties of a connector that hold a blob, along with
+/// the blob ids, looked up once per `Info` by `control::connector_blob`.
+///
+/// Reading a property's name takes an ioctl, so the lookup is shared by all
+/// blobs read from the same `Info`. Being a cache, it is ignored when
+/// comparing or hashing the `Info`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BlobNames(OnceLock<Vec<(Vec<u8>, control::property::RawValue)>>);
+
+impl BlobNames {
+    /// Returns the names and blob ids, looking them up with `lookup` on first
+    /// use.
+    pub(crate) fn get_or_lookup<F, E>(&self, lookup: F) -> Result<&[(Vec<u8>, control::property::RawValue)], E>
+    where
+        F: FnOnce() -> Result<Vec<(Vec<u8>, control::property::RawValue)>, E>,
+    {
+        if let Some(names) = self.0.get() {
+            return Ok(names);
+        }
+
+        let _ = self.0.set(lookup()?);
+        Ok(self.0.get().unwrap())
+    }
+}
+
+impl PartialEq for BlobNames {
+    fn eq(&self, _other: &BlobNames) -> bool {
+        true
+    }
+}
+
+impl Eq for BlobNames {}
+
+impl Hash for BlobNames {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+/// The location of a connector in a DisplayPort MST topology, as reported by
+/// its `PATH` property. See `Device::connector_path`.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::connector::Path;
+///
+/// let path = Path::from_blob(b"mst:45-1-8\0").unwrap();
+/// assert_eq!(path.parent(), 45);
+/// assert_eq!(path.ports(), &[1, 8]);
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Path {
+    pub(crate) parent: u32,
+    pub(crate) ports: Vec<u8>,
+}
+
+impl Path {
+    /// Parses the contents of a `PATH` property blob.
+    pub fn from_blob(data: &[u8]) -> Option<Path> {
+        let path = blob_str(data)?;
+
+        if !path.starts_with("mst:") {
+            return None;
+        }
+
+        let mut ids = path[4..].split('-');
+        let parent = ids.next()?.parse().ok()?;
+        let ports = ids.map(|p| p.parse().ok()).collect::<Option<Vec<u8>>>()?;
+
+        Some(Path {
+            parent: parent,
+            ports: ports,
+        })
+    }
+
+    /// Returns the ID of the connector the MST branch is attached to.
+    pub fn parent(&self) -> u32 {
+        self.parent
+    }
+
+    /// Returns the port numbers leading from the parent connector to this
+    /// connector.
+    pub fn ports(&self) -> &[u8] {
+        &self.ports
+    }
+}
+
+/// The position of a connector in a tiled display, as reported by its `TILE`
+/// property. See `Device::connector_tile`.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::connector::Tile;
+///
+/// let tile = Tile::from_blob(b"1:1:2:1:1:0:2560:2880\0").unwrap();
+/// assert_eq!(tile.group_id(), 1);
+/// assert!(tile.single_monitor());
+/// assert_eq!(tile.num_tiles(), (2, 1));
+/// assert_eq!(tile.location(), (1, 0));
+/// assert_eq!(tile.size(), (2560, 2880));
+/// ```
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tile {
+    pub(crate) group_id: u32,
+    pub(crate) single_monitor: bool,
+    pub(crate) num_tiles: (u32, u32),
+    pub(crate) location: (u32, u32),
+    pub(crate) size: (u32, u32),
+}
+
+impl Tile {
+    /// Parses the contents of a `TILE` property blob.
+    pub fn from_blob(data: &[u8]) -> Option<Tile> {
+        let fields = blob_str(data)?
+            .split(':')
+            .map(|f| f.parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+
+        if fields.len() != 8 {
+            return None;
+        }
+
+        Some(Tile {
+            group_id: fields[0],
+            single_monitor: fields[1] != 0,
+            num_tiles: (fields[2], fields[3]),
+            location: (fields[4], fields[5]),
+            size: (fields[6], fields[7]),
+        })
+    }
+
+    /// Returns the ID shared by all tiles of the same display.
+    pub fn group_id(&self) -> u32 {
+        self.group_id
+    }
+
+    /// Returns whether all tiles are contained in a single enclosure.
+    pub fn single_monitor(&self) -> bool {
+        self.single_monitor
+    }
+
+    /// Returns the number of horizontal and vertical tiles of the display.
+    pub fn num_tiles(&self) -> (u32, u32) {
+        self.num_tiles
+    }
+
+    /// Returns the horizontal and vertical index of this tile.
+    pub fn location(&self) -> (u32, u32) {
+        self.location
+    }
+
+    /// Returns the size of this tile in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+}
+
+/// A tiled display, driven through several connectors.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct TiledDisplay {
+    pub(crate) group_id: u32,
+    pub(crate) num_tiles: (u32, u32),
+    pub(crate) tiles: Vec<(Handle, Tile)>,
+}
+
+impl TiledDisplay {
+    /// Returns the ID of the tile group.
+    pub fn group_id(&self) -> u32 {
+        self.group_id
+    }
+
+    /// Returns the connectors driving this display along with their tiles,
+    /// ordered by row and then by column.
+    pub fn tiles(&self) -> &[(Handle, Tile)] {
+        &self.tiles
+    }
+
+    /// Returns whether a connector was found for every tile of the display.
+    pub fn is_complete(&self) -> bool {
+        self.tiles.len() as u32 == self.num_tiles.0 * self.num_tiles.1
+    }
+
+    /// Returns the combined resolution of all tiles.
+    pub fn size(&self) -> (u32, u32) {
+        let width = self.tiles.iter()
+            .filter(|&&(_, t)| t.location.1 == 0)
+            .map(|&(_, t)| t.size.0)
+            .sum();
+        let height = self.tiles.iter()
+            .filter(|&&(_, t)| t.location.0 == 0)
+            .map(|&(_, t)| t.size.1)
+            .sum();
+
+        (width, height)
+    }
+}
+
+/// Groups the connectors of tiled displays into one `TiledDisplay` per
+/// display.
+///
+/// The tiles of a set of connectors can be read with
+/// `Device::connector_tile`.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::connector::{self, Tile};
+/// # let handle = |id| drm::control::from_u32::<connector::Handle>(id).unwrap();
+///
+/// let left = Tile::from_blob(b"3:1:2:1:0:0:2560:2880").unwrap();
+/// let right = Tile::from_blob(b"3:1:2:1:1:0:2560:2880").unwrap();
+///
+/// let displays = connector::group_tiles(vec![(handle(41), right), (handle(40), left)]);
+/// assert_eq!(displays.len(), 1);
+/// assert!(displays[0].is_complete());
+/// assert_eq!(displays[0].size(), (5120, 2880));
+/// assert_eq!(displays[0].tiles()[0].0, handle(40));
+/// ```
+pub fn group_tiles<I>(tiles: I) -> Vec<TiledDisplay>
+where
+    I: IntoIterator<Item = (Handle, Tile)>,
+{
+    let mut displays: Vec<TiledDisplay> = Vec::new();
+
+    for (handle, tile) in tiles {
+        match displays.iter().position(|d| d.group_id == tile.group_id) {
+            Some(i) => displays[i].tiles.push((handle, tile)),
+            None => displays.push(TiledDisplay {
+                group_id: tile.group_id,
+                num_tiles: tile.num_tiles,
+                tiles: vec![(handle, tile)],
+            }),
+        }
+    }
+
+    for display in &mut displays {
+        display.tiles.sort_by_key(|&(_, t)| (t.location.1, t.location.0));
+    }
+
+    displays
+}
+
+/// Returns the string stored in a property blob, without its trailing nul.
+fn blob_str(data: &[u8]) -> Option<&str> {
+    let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    ::std::str::from_utf8(&data[..len]).ok()
 }
 
 /// A physical interface type.
 #[allow(missing_docs)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Interface {
     Unknown,
     VGA,
@@ -123,6 +429,9 @@ pub enum Interface {
     Virtual,
     DSI,
     DPI,
+    Writeback,
+    SPI,
+    USB,
 }
 
 impl From<u32> for Interface {
@@ -146,6 +455,9 @@ impl From<u32> for Interface {
             ffi::DRM_MODE_CONNECTOR_VIRTUAL => Interface::Virtual,
             ffi::DRM_MODE_CONNECTOR_DSI => Interface::DSI,
             ffi::DRM_MODE_CONNECTOR_DPI => Interface::DPI,
+            ffi::DRM_MODE_CONNECTOR_WRITEBACK => Interface::Writeback,
+            ffi::DRM_MODE_CONNECTOR_SPI => Interface::SPI,
+            ffi::DRM_MODE_CONNECTOR_USB => Interface::USB,
             _ => Interface::Unknown,
         }
     }
@@ -172,6 +484,178 @@ impl Into<u32> for Interface {
             Interface::Virtual => ffi::DRM_MODE_CONNECTOR_VIRTUAL,
             Interface::DSI => ffi::DRM_MODE_CONNECTOR_DSI,
             Interface::DPI => ffi::DRM_MODE_CONNECTOR_DPI,
+            Interface::Writeback => ffi::DRM_MODE_CONNECTOR_WRITEBACK,
+            Interface::SPI => ffi::DRM_MODE_CONNECTOR_SPI,
+            Interface::USB => ffi::DRM_MODE_CONNECTOR_USB,
+        }
+    }
+}
+
+impl Interface {
+    /// Returns the name the kernel uses for this type of interface.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Interface::Unknown => "Unknown",
+            Interface::VGA => "VGA",
+            Interface::DVII => "DVI-I",
+            Interface::DVID => "DVI-D",
+            Interface::DVIA => "DVI-A",
+            Interface::Composite => "Composite",
+            Interface::SVideo => "SVIDEO",
+            Interface::LVDS => "LVDS",
+            Interface::Component => "Component",
+            Interface::NinePinDIN => "DIN",
+            Interface::DisplayPort => "DP",
+            Interface::HDMIA => "HDMI-A",
+            Interface::HDMIB => "HDMI-B",
+            Interface::TV => "TV",
+            Interface::EmbeddedDisplayPort => "eDP",
+            Interface::Virtual => "Virtual",
+            Interface::DSI => "DSI",
+            Interface::DPI => "DPI",
+            Interface::Writeback => "Writeback",
+            Interface::SPI => "SPI",
+            Interface::USB => "USB",
+        }
+    }
+}
+
+const INTERFACES: [Interface; 21] = [
+    Interface::Unknown,
+    Interface::VGA,
+    Interface::DVII,
+    Interface::DVID,
+    Interface::DVIA,
+    Interface::Composite,
+    Interface::SVideo,
+    Interface::LVDS,
+    Interface::Component,
+    Interface::NinePinDIN,
+    Interface::DisplayPort,
+    Interface::HDMIA,
+    Interface::HDMIB,
+    Interface::TV,
+    Interface::EmbeddedDisplayPort,
+    Interface::Virtual,
+    Interface::DSI,
+    Interface::DPI,
+    Interface::Writeback,
+    Interface::SPI,
+    Interface::USB,
+];
+
+/// The name of a connector, as used by the kernel, sysfs and most display
+/// servers (e.g. `HDMI-A-1` or `eDP-1`).
+///
+/// # Example
+///
+/// ```
+/// use drm::control::connector::{Interface, Name};
+///
+/// let name: Name = "HDMI-A-2".parse().unwrap();
+/// assert_eq!(name.interface(), Interface::HDMIA);
+/// assert_eq!(name.interface_id(), 2);
+/// assert_eq!(Name::new(Interface::DisplayPort, 3).to_string(), "DP-3");
+/// assert!("HDMI-2".parse::<Name>().is_err());
+/// ```
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Name {
+    pub(crate) interface: Interface,
+    pub(crate) interface_id: u32,
+}
+
+impl Name {
+    /// Creates a name from an interface type and its interface ID.
+    pub fn new(interface: Interface, interface_id: u32) -> Name {
+        Name {
+            interface: interface,
+            interface_id: interface_id,
+        }
+    }
+
+    /// Returns the type of `Interface` of this name.
+    pub fn interface(&self) -> Interface {
+        self.interface
+    }
+
+    /// Returns the interface ID of this name.
+    pub fn interface_id(&self) -> u32 {
+        self.interface_id
+    }
+}
+
+impl std::fmt::Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}-{}", self.interface.as_str(), self.interface_id)
+    }
+}
+
+impl std::str::FromStr for Name {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Name, ParseNameError> {
+        let split = s.rfind('-').ok_or(ParseNameError)?;
+        let (prefix, id) = (&s[..split], &s[split + 1..]);
+
+        let interface = INTERFACES
+            .iter()
+            .find(|i| i.as_str() == prefix)
+            .ok_or(ParseNameError)?;
+        let interface_id = id.parse().map_err(|_| ParseNameError)?;
+
+        Ok(Name::new(*interface, interface_id))
+    }
+}
+
+/// The error returned when parsing a connector `Name` fails.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct ParseNameError;
+
+impl std::fmt::Display for ParseNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "invalid connector name")
+    }
+}
+
+impl std::error::Error for ParseNameError {}
+
+/// The arrangement of the subpixels of a display.
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SubPixel {
+    Unknown,
+    HorizontalRgb,
+    HorizontalBgr,
+    VerticalRgb,
+    VerticalBgr,
+    None,
+}
+
+impl From<u32> for SubPixel {
+    fn from(n: u32) -> Self {
+        // These values are not defined in drm_mode.h either. They were copied
+        // from the kernel's drm_connector.h, as libdrm offsets them by one.
+        match n {
+            1 => SubPixel::HorizontalRgb,
+            2 => SubPixel::HorizontalBgr,
+            3 => SubPixel::VerticalRgb,
+            4 => SubPixel::VerticalBgr,
+            5 => SubPixel::None,
+            _ => SubPixel::Unknown,
+        }
+    }
+}
+
+impl Into<u32> for SubPixel {
+    fn into(self) -> u32 {
+        match self {
+            SubPixel::Unknown => 0,
+            SubPixel::HorizontalRgb => 1,
+            SubPixel::HorizontalBgr => 2,
+            SubPixel::VerticalRgb => 3,
+            SubPixel::VerticalBgr => 4,
+            SubPixel::None => 5,
         }
     }
 }
@@ -179,6 +663,7 @@ impl Into<u32> for Interface {
 /// The state of a connector.
 #[allow(missing_docs)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum State {
     Connected,
     Disconnected,
@@ -208,3 +693,111 @@ impl Into<u32> for State {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use control::{from_u32, property, Device};
+    use drm_ffi::result::SystemError;
+    use std::cell::Cell;
+    use std::os::unix::io::{AsRawFd, RawFd};
+
+    /// A device with the `EDID` (property 2), `PATH` (3) and `TILE` (4)
+    /// blobs, whose blob ids match their property ids, and the `DPMS` enum
+    /// (1). Counts the property lookups.
+    #[derive(Debug, Default)]
+    struct Mock {
+        lookups: Cell<usize>,
+    }
+
+    impl AsRawFd for Mock {
+        fn as_raw_fd(&self) -> RawFd {
+            -1
+        }
+    }
+
+    impl ::Device for Mock {}
+
+    impl control::Device for Mock {
+        fn get_property(&self, handle: property::Handle) -> Result<property::Info, SystemError> {
+            self.lookups.set(self.lookups.get() + 1);
+
+            let id: u32 = handle.into();
+            let (name, val_type) = match id {
+                1 => ("DPMS", property::ValueType::Unknown),
+                2 => ("EDID", property::ValueType::Blob),
+                3 => ("PATH", property::ValueType::Blob),
+                4 => ("TILE", property::ValueType::Blob),
+                _ => return Err(SystemError::InvalidArgument),
+            };
+
+            Ok(property::Info {
+                handle: handle,
+                val_type: val_type,
+                mutable: false,
+                atomic: false,
+                info: ffi::drm_mode_get_property {
+                    name: ::util::to_c_name(name).unwrap(),
+                    ..Default::default()
+                },
+            })
+        }
+
+        fn get_property_blob(&self, blob: u64) -> Result<Vec<u8>, SystemError> {
+            match blob {
+                3 => Ok(b"mst:45-1\0".to_vec()),
+                4 => Ok(b"1:1:2:1:0:0:1920:2160\0".to_vec()),
+                _ => Err(SystemError::InvalidArgument),
+            }
+        }
+    }
+
+    fn info(props: &[(u32, u64)]) -> Info {
+        let mut info = Info::new(from_u32(40).unwrap(), Interface::DisplayPort, 1, State::Connected, vec![]);
+        info.props = props.iter().map(|&(id, value)| (from_u32(id).unwrap(), value)).collect();
+        info
+    }
+
+    #[test]
+    fn property_names_are_read_once() {
+        let dev = Mock::default();
+        let info = info(&[(1, 3), (2, 0), (3, 3), (4, 4)]);
+
+        let path = dev.connector_path(&info).unwrap().unwrap();
+        assert_eq!((path.parent(), path.ports()), (45, &[1][..]));
+
+        // The unset `EDID` blob is skipped, the others are read once.
+        assert_eq!(dev.lookups.get(), 3);
+
+        let tile = dev.connector_tile(&info).unwrap().unwrap();
+        assert_eq!(tile.size(), (1920, 2160));
+        assert_eq!(dev.connector_path(&info).unwrap(), Some(path));
+        assert_eq!(dev.lookups.get(), 3);
+
+        // A copy of the information shares the names looked up so far.
+        let copy = info.clone();
+        assert_eq!(copy, info);
+        assert_eq!(dev.connector_tile(&copy).unwrap(), Some(tile));
+        assert_eq!(dev.lookups.get(), 3);
+    }
+
+    #[test]
+    fn missing_blobs() {
+        let dev = Mock::default();
+        let info = info(&[(1, 1), (2, 0)]);
+
+        assert_eq!(dev.connector_path(&info).unwrap(), None);
+        assert_eq!(dev.connector_tile(&info).unwrap(), None);
+        assert_eq!(dev.lookups.get(), 1);
+    }
+
+    #[test]
+    fn failed_lookups_are_retried() {
+        let dev = Mock::default();
+        let info = info(&[(9, 1)]);
+
+        assert!(dev.connector_path(&info).is_err());
+        assert!(dev.connector_path(&info).is_err());
+        assert_eq!(dev.lookups.get(), 2);
+    }
+}
@@ -17,6 +17,7 @@ use drm_ffi as ffi;
 
 /// A handle to a specific CRTC
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Handle(control::RawResourceHandle);
 
 impl Into<control::RawResourceHandle> for Handle {
@@ -51,6 +52,7 @@ impl std::fmt::Debug for Handle {
 
 /// Information about a specific CRTC
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Info {
     pub(crate) handle: Handle,
     pub(crate) position: (u32, u32),
@@ -60,6 +62,25 @@ pub struct Info {
 }
 
 impl Info {
+    /// Creates information about a CRTC without querying a device.
+    ///
+    /// This is mostly useful to build `Topology` snapshots in tests.
+    pub fn new(
+        handle: Handle,
+        position: (u32, u32),
+        mode: Option<control::Mode>,
+        fb: Option<control::framebuffer::Handle>,
+        gamma_length: u32,
+    ) -> Info {
+        Info {
+            handle: handle,
+            position: position,
+            mode: mode,
+            fb: fb,
+            gamma_length: gamma_length,
+        }
+    }
+
     /// Returns the handle to this CRTC.
     pub fn handle(&self) -> Handle {
         self.handle
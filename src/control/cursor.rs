@@ -0,0 +1,590 @@
+//! # Cursor
+//!
+//! Hardware cursor management.
+//!
+//! The `CursorManager` owns a pair of cursor buffers sized to the driver's
+//! cursor capabilities, and shows them on a CRTC. When the process has enabled
+//! both universal planes and atomic modesetting, the cursor plane of the CRTC
+//! is driven through atomic commits. Otherwise the legacy cursor ioctls are
+//! used.
+//!
+//! Atomic cursor updates do not wait for the display. An update made while
+//! the previous one is still pending is dropped, and the latest state of the
+//! cursor is sent with the next update or by `CursorManager::flush`.
+
+use buffer::format::PixelFormat;
+use control::atomic::AtomicModeReq;
+use control::dumbbuffer::DumbBuffer;
+use control::{crtc, framebuffer, plane, property, AtomicCommitFlags, Device, PlaneType};
+use drm_ffi::result::SystemError;
+use DriverCapability;
+
+use nix::errno::Errno;
+
+/// Cursor size used by the kernel when the driver does not report one.
+const DEFAULT_CURSOR_SIZE: u32 = 64;
+
+/// Properties of a cursor plane used to show the cursor.
+#[derive(Debug, Copy, Clone)]
+struct CursorPlane {
+    handle: plane::Handle,
+    fb_id: property::Handle,
+    crtc_id: property::Handle,
+    crtc_x: property::Handle,
+    crtc_y: property::Handle,
+    crtc_w: property::Handle,
+    crtc_h: property::Handle,
+    src_x: property::Handle,
+    src_y: property::Handle,
+    src_w: property::Handle,
+    src_h: property::Handle,
+    hotspot_x: Option<property::Handle>,
+    hotspot_y: Option<property::Handle>,
+}
+
+/// A double-buffered hardware cursor on a single CRTC.
+///
+/// Positions passed to the manager are those of the pointer. The image is
+/// placed so that its hotspot lies on the pointer.
+#[derive(Debug)]
+pub struct CursorManager {
+    crtc: crtc::Handle,
+    size: (u32, u32),
+    buffers: [DumbBuffer; 2],
+    framebuffers: [framebuffer::Handle; 2],
+    front: usize,
+    plane: Option<CursorPlane>,
+    hotspot: (i32, i32),
+    position: (i32, i32),
+    visible: bool,
+    // Whether the last update was dropped because the display was busy.
+    dirty: bool,
+}
+
+impl CursorManager {
+    /// Allocates the cursor buffers for a CRTC and picks the way the cursor
+    /// is shown.
+    ///
+    /// The cursor starts out hidden.
+    pub fn new<D: Device>(dev: &D, crtc: crtc::Handle) -> Result<CursorManager, SystemError> {
+        let cap = |cap| match dev.get_driver_capability(cap) {
+            Ok(0) | Err(_) => DEFAULT_CURSOR_SIZE,
+            Ok(size) => size as u32,
+        };
+        let size = (cap(DriverCapability::CursorWidth), cap(DriverCapability::CursorHeight));
+
+        let front = dev.create_dumb_buffer(size, PixelFormat::ARGB8888)?;
+        let back = match dev.create_dumb_buffer(size, PixelFormat::ARGB8888) {
+            Ok(back) => back,
+            Err(e) => {
+                let _ = dev.destroy_dumb_buffer(front);
+                return Err(e);
+            }
+        };
+
+        let framebuffers = match add_framebuffers(dev, &front, &back) {
+            Ok(framebuffers) => framebuffers,
+            Err(e) => {
+                let _ = dev.destroy_dumb_buffer(front);
+                let _ = dev.destroy_dumb_buffer(back);
+                return Err(e);
+            }
+        };
+
+        let mut manager = CursorManager {
+            crtc: crtc,
+            size: size,
+            buffers: [front, back],
+            framebuffers: framebuffers,
+            front: 0,
+            plane: None,
+            hotspot: (0, 0),
+            position: (0, 0),
+            visible: false,
+            dirty: false,
+        };
+
+        manager.plane = match find_cursor_plane(dev, crtc) {
+            Ok(plane) => plane,
+            Err(e) => {
+                manager.destroy(dev)?;
+                return Err(e);
+            }
+        };
+
+        Ok(manager)
+    }
+
+    /// Returns the CRTC this cursor is shown on.
+    pub fn crtc(&self) -> crtc::Handle {
+        self.crtc
+    }
+
+    /// Returns the size of the cursor buffers.
+    ///
+    /// Images passed to `set_image` may not be larger than this.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the cursor plane used to show the cursor, or `None` if the
+    /// legacy cursor ioctls are used.
+    pub fn plane(&self) -> Option<plane::Handle> {
+        self.plane.map(|p| p.handle)
+    }
+
+    /// Returns the current pointer position.
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    /// Returns whether the cursor is currently shown.
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Uploads a new cursor image and shows it.
+    ///
+    /// `pixels` holds `size.0 * size.1` ARGB8888 values in row-major order.
+    /// Unless `premultiplied` is set, the color channels are multiplied by
+    /// their alpha before they are uploaded, as the kernel expects.
+    ///
+    /// The image is written into the buffer that is currently not shown, which
+    /// then becomes the front buffer.
+    ///
+    /// Fails with `SystemError::InvalidArgument` if the image is larger than
+    /// the cursor buffers or `pixels` is too short.
+    pub fn set_image<D: Device>(
+        &mut self,
+        dev: &D,
+        pixels: &[u32],
+        size: (u32, u32),
+        hotspot: (i32, i32),
+        premultiplied: bool,
+    ) -> Result<(), SystemError> {
+        let (w, h) = (size.0 as usize, size.1 as usize);
+
+        if size.0 > self.size.0 || size.1 > self.size.1 || pixels.len() < w * h {
+            return Err(SystemError::InvalidArgument);
+        }
+
+        let back = 1 - self.front;
+        {
+            let buffer = &mut self.buffers[back];
+            let pitch = buffer.pitch as usize;
+            let mut mapping = dev.map_dumb_buffer(buffer)?;
+            let map = mapping.as_mut();
+
+            for b in map.iter_mut() {
+                *b = 0;
+            }
+
+            for (y, row) in pixels[..w * h].chunks(w).enumerate() {
+                let line = &mut map[y * pitch..y * pitch + w * 4];
+
+                for (dst, &src) in line.chunks_mut(4).zip(row) {
+                    let pixel = if premultiplied { src } else { premultiply(src) };
+                    dst.copy_from_slice(&pixel.to_le_bytes());
+                }
+            }
+        }
+
+        self.front = back;
+        self.hotspot = hotspot;
+        self.show(dev)
+    }
+
+    /// Moves the cursor so its hotspot lies on the given position.
+    pub fn move_to<D: Device>(&mut self, dev: &D, position: (i32, i32)) -> Result<(), SystemError> {
+        self.position = position;
+
+        if self.dirty {
+            return self.flush(dev);
+        }
+
+        if !self.visible {
+            return Ok(());
+        }
+
+        let (x, y) = self.origin();
+
+        match self.plane {
+            Some(plane) => {
+                let mut req = AtomicModeReq::new();
+                req.add_raw_property(plane.handle.into(), plane.crtc_x, x as u64);
+                req.add_raw_property(plane.handle.into(), plane.crtc_y, y as u64);
+                self.commit(dev, req)
+            }
+            None => dev.move_cursor(self.crtc, (x, y)),
+        }
+    }
+
+    /// Shows the current cursor image.
+    pub fn show<D: Device>(&mut self, dev: &D) -> Result<(), SystemError> {
+        let (x, y) = self.origin();
+
+        match self.plane {
+            Some(plane) => {
+                let (w, h) = self.size;
+                let handle = plane.handle.into();
+                let fb: u32 = self.framebuffers[self.front].into();
+                let crtc: u32 = self.crtc.into();
+
+                let mut req = AtomicModeReq::new();
+                req.add_raw_property(handle, plane.fb_id, fb as u64);
+                req.add_raw_property(handle, plane.crtc_id, crtc as u64);
+                req.add_raw_property(handle, plane.crtc_x, x as u64);
+                req.add_raw_property(handle, plane.crtc_y, y as u64);
+                req.add_raw_property(handle, plane.crtc_w, w as u64);
+                req.add_raw_property(handle, plane.crtc_h, h as u64);
+                req.add_raw_property(handle, plane.src_x, 0);
+                req.add_raw_property(handle, plane.src_y, 0);
+                req.add_raw_property(handle, plane.src_w, (w as u64) << 16);
+                req.add_raw_property(handle, plane.src_h, (h as u64) << 16);
+
+                if let (Some(hx), Some(hy)) = (plane.hotspot_x, plane.hotspot_y) {
+                    req.add_raw_property(handle, hx, self.hotspot.0 as u64);
+                    req.add_raw_property(handle, hy, self.hotspot.1 as u64);
+                }
+
+                self.commit(dev, req)?;
+            }
+            None => {
+                dev.set_cursor2(self.crtc, Some(&self.buffers[self.front]), self.hotspot)?;
+                dev.move_cursor(self.crtc, (x, y))?;
+            }
+        }
+
+        self.visible = true;
+
+        Ok(())
+    }
+
+    /// Hides the cursor.
+    pub fn hide<D: Device>(&mut self, dev: &D) -> Result<(), SystemError> {
+        match self.plane {
+            Some(plane) => {
+                let mut req = AtomicModeReq::new();
+                req.add_raw_property(plane.handle.into(), plane.fb_id, 0);
+                req.add_raw_property(plane.handle.into(), plane.crtc_id, 0);
+                self.commit(dev, req)?;
+            }
+            None => dev.set_cursor(self.crtc, None::<&DumbBuffer>)?,
+        }
+
+        self.visible = false;
+
+        Ok(())
+    }
+
+    /// Sends the state of the cursor if its last update was dropped because
+    /// the display was busy, e.g. after the next vblank or page flip event.
+    pub fn flush<D: Device>(&mut self, dev: &D) -> Result<(), SystemError> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if self.visible {
+            self.show(dev)
+        } else {
+            self.hide(dev)
+        }
+    }
+
+    /// Hides the cursor and frees its buffers.
+    pub fn destroy<D: Device>(mut self, dev: &D) -> Result<(), SystemError> {
+        if self.visible {
+            self.hide(dev)?;
+        }
+
+        for &fb in &self.framebuffers {
+            dev.destroy_framebuffer(fb)?;
+        }
+
+        for buffer in self.buffers {
+            dev.destroy_dumb_buffer(buffer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the position of the top left corner of the image.
+    fn origin(&self) -> (i32, i32) {
+        (self.position.0 - self.hotspot.0, self.position.1 - self.hotspot.1)
+    }
+
+    /// Commits an update of the cursor plane without waiting for the display.
+    ///
+    /// The update is dropped if the previous one is still pending.
+    fn commit<D: Device>(&mut self, dev: &D, req: AtomicModeReq) -> Result<(), SystemError> {
+        match dev.atomic_commit(&[AtomicCommitFlags::Nonblock], req) {
+            Ok(()) => self.dirty = false,
+            Err(SystemError::Unknown { errno: Errno::EBUSY }) => self.dirty = true,
+            Err(e) => return Err(e),
+        }
+
+        Ok(())
+    }
+}
+
+/// Multiplies the color channels of an ARGB8888 pixel by its alpha.
+fn premultiply(pixel: u32) -> u32 {
+    let a = pixel >> 24;
+    let channel = |shift: u32| ((((pixel >> shift) & 0xff) * a + 127) / 255) << shift;
+
+    (a << 24) | channel(16) | channel(8) | channel(0)
+}
+
+fn add_framebuffers<D: Device>(
+    dev: &D,
+    front: &DumbBuffer,
+    back: &DumbBuffer,
+) -> Result<[framebuffer::Handle; 2], SystemError> {
+    let front = dev.add_framebuffer(front)?;
+    match dev.add_framebuffer(back) {
+        Ok(back) => Ok([front, back]),
+        Err(e) => {
+            let _ = dev.destroy_framebuffer(front);
+            Err(e)
+        }
+    }
+}
+
+/// Finds an atomic cursor plane that can be used with a CRTC.
+///
+/// Cursor planes are only listed with universal planes enabled, and their
+/// atomic properties only with atomic modesetting enabled.
+fn find_cursor_plane<D: Device>(dev: &D, crtc: crtc::Handle) -> Result<Option<CursorPlane>, SystemError> {
+    let resources = dev.resource_handles()?;
+    let planes = dev.plane_handles()?;
+
+    for &handle in planes.planes() {
+        let info = dev.get_plane(handle)?;
+        if !resources.filter_crtcs(info.possible_crtcs()).contains(&crtc) {
+            continue;
+        }
+
+        let props = dev.get_properties(handle)?;
+        let (ids, vals) = props.as_props_and_values();
+
+        let mut names = Vec::with_capacity(ids.len());
+        let mut is_cursor = false;
+        for (&id, &val) in ids.iter().zip(vals.iter()) {
+            let name = dev.get_property(id)?.name().to_bytes().to_vec();
+            if name == b"type" {
+                is_cursor = val == PlaneType::Cursor as u64;
+            }
+            names.push((name, id));
+        }
+
+        if !is_cursor {
+            continue;
+        }
+
+        let find = |name: &str| {
+            names
+                .iter()
+                .find(|(n, _)| n.as_slice() == name.as_bytes())
+                .map(|&(_, id)| id)
+        };
+
+        let plane = (|| {
+            Some(CursorPlane {
+                handle: handle,
+                fb_id: find("FB_ID")?,
+                crtc_id: find("CRTC_ID")?,
+                crtc_x: find("CRTC_X")?,
+                crtc_y: find("CRTC_Y")?,
+                crtc_w: find("CRTC_W")?,
+                crtc_h: find("CRTC_H")?,
+                src_x: find("SRC_X")?,
+                src_y: find("SRC_Y")?,
+                src_w: find("SRC_W")?,
+                src_h: find("SRC_H")?,
+                hotspot_x: find("HOTSPOT_X"),
+                hotspot_y: find("HOTSPOT_Y"),
+            })
+        })();
+
+        if plane.is_some() {
+            return Ok(plane);
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use buffer;
+    use control::{from_u32, RawResourceHandle};
+    use std::cell::RefCell;
+    use std::os::unix::io::{AsRawFd, RawFd};
+
+    /// The flags and properties of an atomic commit.
+    type Commit = (Vec<AtomicCommitFlags>, Vec<(u32, u64)>);
+
+    /// A device whose atomic commits fail with the queued errors, and are
+    /// recorded otherwise.
+    #[derive(Debug, Default)]
+    struct Mock {
+        errors: RefCell<Vec<SystemError>>,
+        commits: RefCell<Vec<Commit>>,
+    }
+
+    impl AsRawFd for Mock {
+        fn as_raw_fd(&self) -> RawFd {
+            -1
+        }
+    }
+
+    impl ::Device for Mock {}
+
+    impl Device for Mock {
+        fn atomic_commit(&self, flags: &[AtomicCommitFlags], req: AtomicModeReq) -> Result<(), SystemError> {
+            if !self.errors.borrow().is_empty() {
+                return Err(self.errors.borrow_mut().remove(0));
+            }
+
+            let props = req.props.iter().map(|&p| p.into()).zip(req.values).collect();
+            self.commits.borrow_mut().push((flags.to_vec(), props));
+            Ok(())
+        }
+    }
+
+    fn busy() -> SystemError {
+        SystemError::Unknown { errno: Errno::EBUSY }
+    }
+
+    fn buffer(id: u32) -> DumbBuffer {
+        DumbBuffer {
+            size: (64, 64),
+            length: 64 * 256,
+            format: PixelFormat::ARGB8888,
+            pitch: 256,
+            handle: buffer::Handle::from(RawResourceHandle::new(id).unwrap()),
+        }
+    }
+
+    /// A visible 64x64 cursor at (100, 50) on a cursor plane whose
+    /// properties are numbered from 10 in the order `FB_ID`, `CRTC_ID`,
+    /// `CRTC_X`, `CRTC_Y`, `CRTC_W`, `CRTC_H`, `SRC_X`, `SRC_Y`, `SRC_W`
+    /// and `SRC_H`.
+    fn manager() -> CursorManager {
+        let prop = |id| from_u32(id).unwrap();
+
+        CursorManager {
+            crtc: from_u32(36).unwrap(),
+            size: (64, 64),
+            buffers: [buffer(1), buffer(2)],
+            framebuffers: [from_u32(90).unwrap(), from_u32(91).unwrap()],
+            front: 0,
+            plane: Some(CursorPlane {
+                handle: from_u32(31).unwrap(),
+                fb_id: prop(10),
+                crtc_id: prop(11),
+                crtc_x: prop(12),
+                crtc_y: prop(13),
+                crtc_w: prop(14),
+                crtc_h: prop(15),
+                src_x: prop(16),
+                src_y: prop(17),
+                src_w: prop(18),
+                src_h: prop(19),
+                hotspot_x: None,
+                hotspot_y: None,
+            }),
+            hotspot: (0, 0),
+            position: (100, 50),
+            visible: true,
+            dirty: false,
+        }
+    }
+
+    fn shown_at(x: u64, y: u64) -> Vec<(u32, u64)> {
+        vec![
+            (10, 90),
+            (11, 36),
+            (12, x),
+            (13, y),
+            (14, 64),
+            (15, 64),
+            (16, 0),
+            (17, 0),
+            (18, 64 << 16),
+            (19, 64 << 16),
+        ]
+    }
+
+    #[test]
+    fn updates_do_not_block() {
+        let dev = Mock::default();
+        let mut cursor = manager();
+
+        cursor.move_to(&dev, (120, 60)).unwrap();
+        cursor.hide(&dev).unwrap();
+        cursor.show(&dev).unwrap();
+
+        assert_eq!(*dev.commits.borrow(), vec![
+            (vec![AtomicCommitFlags::Nonblock], vec![(12, 120), (13, 60)]),
+            (vec![AtomicCommitFlags::Nonblock], vec![(10, 0), (11, 0)]),
+            (vec![AtomicCommitFlags::Nonblock], shown_at(120, 60)),
+        ]);
+    }
+
+    #[test]
+    fn busy_moves_are_coalesced() {
+        let dev = Mock::default();
+        let mut cursor = manager();
+
+        dev.errors.borrow_mut().push(busy());
+        cursor.move_to(&dev, (120, 60)).unwrap();
+        assert!(dev.commits.borrow().is_empty());
+
+        // The next move sends the whole state, in case an image was dropped.
+        cursor.move_to(&dev, (130, 70)).unwrap();
+        cursor.flush(&dev).unwrap();
+        assert_eq!(*dev.commits.borrow(), vec![(vec![AtomicCommitFlags::Nonblock], shown_at(130, 70))]);
+    }
+
+    #[test]
+    fn flush_sends_dropped_hide() {
+        let dev = Mock::default();
+        let mut cursor = manager();
+
+        dev.errors.borrow_mut().push(busy());
+        cursor.hide(&dev).unwrap();
+        assert!(!cursor.visible());
+
+        // Moving a hidden cursor only sends the dropped update.
+        cursor.move_to(&dev, (0, 0)).unwrap();
+        cursor.move_to(&dev, (10, 10)).unwrap();
+        cursor.flush(&dev).unwrap();
+        assert_eq!(*dev.commits.borrow(), vec![(vec![AtomicCommitFlags::Nonblock], vec![(10, 0), (11, 0)])]);
+    }
+
+    #[test]
+    fn other_errors_are_returned() {
+        let dev = Mock::default();
+        let mut cursor = manager();
+
+        dev.errors.borrow_mut().push(SystemError::InvalidArgument);
+        match cursor.hide(&dev) {
+            Err(SystemError::InvalidArgument) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+        assert!(cursor.visible());
+        assert!(!cursor.dirty);
+    }
+
+    #[test]
+    fn premultiply_alpha() {
+        assert_eq!(premultiply(0xff12_3456), 0xff12_3456);
+        assert_eq!(premultiply(0x00ff_ffff), 0);
+        assert_eq!(premultiply(0x80ff_8000), 0x8080_4000);
+        // Channels are rounded to the nearest value.
+        assert_eq!(premultiply(0x01ff_807f), 0x0101_0100);
+        assert_eq!(premultiply(0x4040_4040), 0x4010_1010);
+    }
+}
@@ -0,0 +1,680 @@
+//! # Damage
+//!
+//! Tracking of the parts of a framebuffer that changed between frames.
+//!
+//! Drivers for displays behind a slow link (USB, SPI, virtual machines) only
+//! need to transfer the damaged parts of a framebuffer. Damage is passed to
+//! the kernel either through `Device::dirty_framebuffer`, or through the
+//! `FB_DAMAGE_CLIPS` property of a plane in an atomic commit.
+
+use control::atomic::AtomicModeReq;
+use control::{plane, property, Device};
+use drm_ffi as ffi;
+use drm_ffi::result::SystemError;
+
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+
+/// An axis-aligned rectangle, spanning from `(x1, y1)` inclusive to
+/// `(x2, y2)` exclusive.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Rect {
+    /// Left edge
+    pub x1: i32,
+    /// Top edge
+    pub y1: i32,
+    /// Right edge
+    pub x2: i32,
+    /// Bottom edge
+    pub y2: i32,
+}
+
+impl Rect {
+    /// Creates a rectangle from its top left corner and its size.
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect {
+            x1: x,
+            y1: y,
+            x2: x + width as i32,
+            y2: y + height as i32,
+        }
+    }
+
+    /// Returns the size of this rectangle.
+    pub fn size(&self) -> (u32, u32) {
+        ((self.x2 - self.x1).max(0) as u32, (self.y2 - self.y1).max(0) as u32)
+    }
+
+    /// Returns the number of pixels covered by this rectangle.
+    pub fn area(&self) -> u64 {
+        let (w, h) = self.size();
+        w as u64 * h as u64
+    }
+
+    /// Returns whether this rectangle covers no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.x1 >= self.x2 || self.y1 >= self.y2
+    }
+
+    /// Returns the overlap of two rectangles, if there is any.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect {
+            x1: max(self.x1, other.x1),
+            y1: max(self.y1, other.y1),
+            x2: min(self.x2, other.x2),
+            y2: min(self.y2, other.y2),
+        };
+
+        if rect.is_empty() {
+            None
+        } else {
+            Some(rect)
+        }
+    }
+
+    /// Returns the smallest rectangle containing both rectangles.
+    pub fn bounding(&self, other: &Rect) -> Rect {
+        Rect {
+            x1: min(self.x1, other.x1),
+            y1: min(self.y1, other.y1),
+            x2: max(self.x2, other.x2),
+            y2: max(self.y2, other.y2),
+        }
+    }
+
+    /// Splits the parts of this rectangle not covered by `other` into at most
+    /// four rectangles.
+    fn subtract(&self, other: &Rect, out: &mut Vec<Rect>) {
+        let overlap = match self.intersection(other) {
+            Some(overlap) => overlap,
+            None => return out.push(*self),
+        };
+
+        let pieces = [
+            Rect { x1: self.x1, y1: self.y1, x2: self.x2, y2: overlap.y1 },
+            Rect { x1: self.x1, y1: overlap.y2, x2: self.x2, y2: self.y2 },
+            Rect { x1: self.x1, y1: overlap.y1, x2: overlap.x1, y2: overlap.y2 },
+            Rect { x1: overlap.x2, y1: overlap.y1, x2: self.x2, y2: overlap.y2 },
+        ];
+
+        out.extend(pieces.iter().filter(|r| !r.is_empty()));
+    }
+}
+
+impl From<Rect> for ffi::drm_mode_rect {
+    fn from(rect: Rect) -> ffi::drm_mode_rect {
+        ffi::drm_mode_rect {
+            x1: rect.x1,
+            y1: rect.y1,
+            x2: rect.x2,
+            y2: rect.y2,
+        }
+    }
+}
+
+impl From<Rect> for ffi::drm_clip_rect {
+    fn from(rect: Rect) -> ffi::drm_clip_rect {
+        let clamp = |v: i32| min(max(v, 0), u16::MAX as i32) as u16;
+
+        ffi::drm_clip_rect {
+            x1: clamp(rect.x1),
+            y1: clamp(rect.y1),
+            x2: clamp(rect.x2),
+            y2: clamp(rect.y2),
+        }
+    }
+}
+
+/// A set of pixels, stored as non-overlapping rectangles.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::damage::{Rect, Region};
+///
+/// let mut region = Region::from(Rect::new(0, 0, 100, 100));
+/// region.union(&Region::from(Rect::new(50, 50, 100, 100)));
+/// assert_eq!(region.area(), 17500);
+///
+/// region.subtract(&Region::from(Rect::new(25, 25, 50, 50)));
+/// assert_eq!(region.area(), 15000);
+///
+/// region.intersect(&Region::from(Rect::new(0, 0, 100, 100)));
+/// assert_eq!(region.area(), 7500);
+/// assert_eq!(region.extents(), Some(Rect::new(0, 0, 100, 100)));
+///
+/// region.simplify(1);
+/// assert_eq!(region.rects(), &[Rect::new(0, 0, 100, 100)]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Region {
+    rects: Vec<Rect>,
+}
+
+impl From<Rect> for Region {
+    fn from(rect: Rect) -> Region {
+        let mut region = Region::new();
+        region.add(rect);
+        region
+    }
+}
+
+impl Region {
+    /// Creates an empty region.
+    pub fn new() -> Region {
+        Region { rects: Vec::new() }
+    }
+
+    /// Returns the rectangles making up this region.
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// Returns whether this region covers no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Returns the number of pixels covered by this region.
+    pub fn area(&self) -> u64 {
+        self.rects.iter().map(Rect::area).sum()
+    }
+
+    /// Returns the smallest rectangle containing the whole region.
+    pub fn extents(&self) -> Option<Rect> {
+        let mut rects = self.rects.iter();
+        let first = *rects.next()?;
+        Some(rects.fold(first, |acc, r| acc.bounding(r)))
+    }
+
+    /// Adds a rectangle to this region.
+    pub fn add(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return;
+        }
+
+        let mut pieces = vec![rect];
+        for existing in &self.rects {
+            let mut remaining = Vec::new();
+            for piece in &pieces {
+                piece.subtract(existing, &mut remaining);
+            }
+            pieces = remaining;
+        }
+
+        self.rects.extend(pieces);
+    }
+
+    /// Adds all pixels of another region to this region.
+    pub fn union(&mut self, other: &Region) {
+        for &rect in &other.rects {
+            self.add(rect);
+        }
+    }
+
+    /// Removes all pixels that are not part of another region.
+    pub fn intersect(&mut self, other: &Region) {
+        let mut rects = Vec::new();
+        for a in &self.rects {
+            rects.extend(other.rects.iter().filter_map(|b| a.intersection(b)));
+        }
+        self.rects = rects;
+    }
+
+    /// Removes all pixels of another region from this region.
+    pub fn subtract(&mut self, other: &Region) {
+        for b in &other.rects {
+            let mut rects = Vec::new();
+            for a in &self.rects {
+                a.subtract(b, &mut rects);
+            }
+            self.rects = rects;
+        }
+    }
+
+    /// Moves the region by the given offset.
+    pub fn translate(&mut self, dx: i32, dy: i32) {
+        for rect in &mut self.rects {
+            rect.x1 += dx;
+            rect.y1 += dy;
+            rect.x2 += dx;
+            rect.y2 += dy;
+        }
+    }
+
+    /// Scales the region by the given factors.
+    ///
+    /// Edges are rounded outwards, so the scaled region covers every pixel
+    /// that is touched by the original one.
+    pub fn scale(&mut self, sx: f64, sy: f64) {
+        let rects = ::std::mem::take(&mut self.rects);
+
+        for rect in rects {
+            let (x1, x2) = scale_span(rect.x1, rect.x2, sx);
+            let (y1, y2) = scale_span(rect.y1, rect.y2, sy);
+            self.add(Rect { x1: x1, y1: y1, x2: x2, y2: y2 });
+        }
+    }
+
+    /// Reduces the region to at most `max_rects` rectangles by merging
+    /// rectangles into their bounding boxes.
+    ///
+    /// The resulting region covers at least all pixels of the original one.
+    /// Pairs that add the fewest pixels are merged first.
+    pub fn simplify(&mut self, max_rects: usize) {
+        let max_rects = max(max_rects, 1);
+
+        while self.rects.len() > max_rects {
+            let mut best = (0, 1, u64::MAX);
+            for i in 0..self.rects.len() {
+                for j in i + 1..self.rects.len() {
+                    let (a, b) = (self.rects[i], self.rects[j]);
+                    let cost = a.bounding(&b).area() - a.area() - b.area();
+                    if cost < best.2 {
+                        best = (i, j, cost);
+                    }
+                }
+            }
+
+            let count = self.rects.len();
+            let merged = self.rects[best.0].bounding(&self.rects[best.1]);
+            self.rects.swap_remove(best.1);
+            self.rects.swap_remove(best.0);
+
+            let mut region = Region::from(merged);
+            region.union(self);
+            *self = region;
+
+            // Merging may split neighbouring rectangles, so ensure progress.
+            if self.rects.len() >= count {
+                *self = self.extents().map(Region::from).unwrap_or_default();
+            }
+        }
+    }
+
+    /// Converts the region into clip rectangles for
+    /// `Device::dirty_framebuffer`.
+    ///
+    /// Coordinates are clamped to the range of the kernel's 16-bit fields.
+    pub fn to_clip_rects(&self) -> Vec<ffi::drm_clip_rect> {
+        self.rects.iter().map(|&r| r.into()).collect()
+    }
+
+    /// Converts the region into the contents of an `FB_DAMAGE_CLIPS` blob.
+    pub fn to_damage_clips(&self) -> Vec<ffi::drm_mode_rect> {
+        self.rects.iter().map(|&r| r.into()).collect()
+    }
+}
+
+/// Rounds a scaled span outwards.
+fn scale_span(start: i32, end: i32, factor: f64) -> (i32, i32) {
+    let (a, b) = (start as f64 * factor, end as f64 * factor);
+    (a.min(b).floor() as i32, a.max(b).ceil() as i32)
+}
+
+/// Accumulates damage over the last frames, to repaint buffers that were
+/// last used several frames ago.
+///
+/// The age of a buffer is the number of frames since its contents were last
+/// presented: a buffer with age 1 holds the previous frame, and age 0 means
+/// its contents are undefined.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::damage::{DamageTracker, Rect, Region};
+///
+/// let mut tracker = DamageTracker::new(Rect::new(0, 0, 640, 480), 3);
+/// tracker.push(Region::from(Rect::new(0, 0, 10, 10)));
+/// tracker.push(Region::from(Rect::new(20, 0, 10, 10)));
+///
+/// let current = Region::from(Rect::new(40, 0, 10, 10));
+/// assert_eq!(tracker.accumulate(1, &current).area(), 100);
+/// assert_eq!(tracker.accumulate(3, &current).area(), 300);
+/// assert_eq!(tracker.accumulate(0, &current).area(), 640 * 480);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DamageTracker {
+    bounds: Rect,
+    capacity: usize,
+    frames: VecDeque<Region>,
+}
+
+impl DamageTracker {
+    /// Creates a tracker for a buffer of the given bounds, that remembers the
+    /// damage of up to `capacity` frames.
+    ///
+    /// The capacity should be at least the number of buffers in the swapchain.
+    pub fn new(bounds: Rect, capacity: usize) -> DamageTracker {
+        DamageTracker {
+            bounds: bounds,
+            capacity: capacity,
+            frames: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the bounds of the tracked buffers.
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Records the damage of a presented frame.
+    pub fn push(&mut self, damage: Region) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+
+        if self.capacity > 0 {
+            self.frames.push_back(damage);
+        }
+    }
+
+    /// Returns the region that has to be repainted in a buffer of the given
+    /// age, to bring it up to date with the `current` damage.
+    ///
+    /// If the damage of that many frames is not known, the whole buffer has
+    /// to be repainted.
+    pub fn accumulate(&self, age: usize, current: &Region) -> Region {
+        let full = Region::from(self.bounds);
+
+        if age == 0 || age - 1 > self.frames.len() {
+            return full;
+        }
+
+        let mut region = current.clone();
+        for frame in self.frames.iter().rev().take(age - 1) {
+            region.union(frame);
+        }
+        region.intersect(&full);
+
+        region
+    }
+
+    /// Forgets the damage of all frames, e.g. after the buffers were
+    /// reallocated.
+    pub fn reset(&mut self) {
+        self.frames.clear();
+    }
+}
+
+/// Sets the `FB_DAMAGE_CLIPS` of a plane in an atomic request.
+///
+/// Returns the blob that was created for the damage. It must be kept alive
+/// until the request is committed, and should be destroyed afterwards. An
+/// empty region leaves the property unset, which the kernel treats as damage
+/// of the whole framebuffer.
+///
+/// Fails with `SystemError::InvalidArgument` if the plane has no such property.
+pub fn add_damage_clips<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    plane: plane::Handle,
+    region: &Region,
+) -> Result<Option<property::Value>, SystemError> {
+    let (prop, _) = dev
+        .find_property(plane, "FB_DAMAGE_CLIPS")?
+        .ok_or(SystemError::InvalidArgument)?;
+
+    if region.is_empty() {
+        return Ok(None);
+    }
+
+    let blob = dev.create_property_blob(&region.to_damage_clips()[..])?;
+    req.add_property(plane, prop, blob);
+
+    Ok(Some(blob))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Returns the pixels of a region, checking that its rectangles do not
+    /// overlap.
+    fn pixels(region: &Region) -> HashSet<(i32, i32)> {
+        let mut pixels = HashSet::new();
+        for rect in region.rects() {
+            assert!(!rect.is_empty(), "empty rectangle {:?} in {:?}", rect, region);
+            for y in rect.y1..rect.y2 {
+                for x in rect.x1..rect.x2 {
+                    pixels.insert((x, y));
+                }
+            }
+        }
+        assert_eq!(pixels.len() as u64, region.area(), "overlapping rectangles in {:?}", region);
+        pixels
+    }
+
+    /// A deterministic set of small, overlapping rectangles.
+    fn scattered(count: usize) -> Vec<Rect> {
+        let mut state = 0x2545_f491u32;
+        let mut next = move |range: u32| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (state >> 16) % range
+        };
+
+        (0..count)
+            .map(|_| Rect::new(next(40) as i32 - 10, next(40) as i32 - 10, next(8) + 1, next(8) + 1))
+            .collect()
+    }
+
+    #[test]
+    fn empty_rects() {
+        assert!(Rect::new(5, 5, 0, 10).is_empty());
+        assert!(Rect { x1: 10, y1: 0, x2: 5, y2: 10 }.is_empty());
+        assert_eq!(Rect { x1: 10, y1: 0, x2: 5, y2: 10 }.size(), (0, 10));
+        assert_eq!(Rect { x1: 10, y1: 0, x2: 5, y2: 10 }.area(), 0);
+
+        // Touching rectangles do not overlap.
+        assert_eq!(Rect::new(0, 0, 10, 10).intersection(&Rect::new(10, 0, 10, 10)), None);
+    }
+
+    #[test]
+    fn empty_regions() {
+        let mut region = Region::from(Rect::new(5, 5, 0, 10));
+        assert!(region.is_empty());
+        assert_eq!(region.area(), 0);
+        assert_eq!(region.extents(), None);
+
+        region.add(Rect { x1: 3, y1: 3, x2: 1, y2: 1 });
+        region.simplify(0);
+        region.scale(2.0, 2.0);
+        assert!(region.is_empty());
+        assert!(region.to_clip_rects().is_empty());
+        assert!(region.to_damage_clips().is_empty());
+
+        let mut full = Region::from(Rect::new(0, 0, 10, 10));
+        full.union(&Region::new());
+        assert_eq!(full.area(), 100);
+
+        full.subtract(&Region::new());
+        assert_eq!(full.area(), 100);
+
+        let mut cleared = full.clone();
+        cleared.intersect(&Region::new());
+        assert!(cleared.is_empty());
+
+        full.subtract(&Region::from(Rect::new(-5, -5, 20, 20)));
+        assert!(full.is_empty());
+    }
+
+    #[test]
+    fn negative_coordinates() {
+        let mut region = Region::from(Rect::new(-10, -10, 20, 20));
+        assert_eq!(region.area(), 400);
+
+        region.subtract(&Region::from(Rect::new(-5, -5, 10, 10)));
+        assert_eq!(region.area(), 300);
+        assert_eq!(pixels(&region).len(), 300);
+        assert_eq!(region.extents(), Some(Rect::new(-10, -10, 20, 20)));
+
+        region.intersect(&Region::from(Rect::new(0, 0, 100, 100)));
+        assert_eq!(region.area(), 75);
+        assert_eq!(region.extents(), Some(Rect::new(0, 0, 10, 10)));
+
+        region.translate(-20, -30);
+        assert_eq!(region.extents(), Some(Rect::new(-20, -30, 10, 10)));
+    }
+
+    #[test]
+    fn clip_rects_are_clamped() {
+        let region = Region::from(Rect { x1: -10, y1: -5, x2: 70_000, y2: 20 });
+
+        let clip = region.to_clip_rects()[0];
+        assert_eq!((clip.x1, clip.y1, clip.x2, clip.y2), (0, 0, u16::MAX, 20));
+
+        let damage = region.to_damage_clips()[0];
+        assert_eq!((damage.x1, damage.y1, damage.x2, damage.y2), (-10, -5, 70_000, 20));
+    }
+
+    #[test]
+    fn set_operations() {
+        let rects = scattered(30);
+
+        let mut expected = HashSet::new();
+        let mut region = Region::new();
+        for rect in &rects {
+            expected.extend(pixels(&Region { rects: vec![*rect] }));
+            region.add(*rect);
+        }
+        assert_eq!(pixels(&region), expected);
+
+        let cut = Region::from(Rect::new(0, 0, 15, 15));
+        let inside: HashSet<_> = expected
+            .iter()
+            .filter(|&&(x, y)| (0..15).contains(&x) && (0..15).contains(&y))
+            .cloned()
+            .collect();
+
+        let mut intersected = region.clone();
+        intersected.intersect(&cut);
+        assert_eq!(pixels(&intersected), inside);
+
+        let mut subtracted = region.clone();
+        subtracted.subtract(&cut);
+        assert_eq!(pixels(&subtracted), &expected - &inside);
+    }
+
+    #[test]
+    fn scale_rounds_outwards() {
+        let mut region = Region::from(Rect::new(1, 1, 1, 1));
+        region.scale(0.5, 0.5);
+        assert_eq!(region.rects(), &[Rect::new(0, 0, 1, 1)]);
+
+        let mut region = Region::from(Rect::new(1, 3, 1, 1));
+        region.scale(1.5, 1.5);
+        assert_eq!(region.rects(), &[Rect { x1: 1, y1: 4, x2: 3, y2: 6 }]);
+
+        let mut region = Region::from(Rect::new(-3, -3, 1, 2));
+        region.scale(0.5, 0.5);
+        assert_eq!(region.rects(), &[Rect { x1: -2, y1: -2, x2: -1, y2: 0 }]);
+
+        // Mirroring keeps the edges in order.
+        let mut region = Region::from(Rect::new(2, 0, 3, 1));
+        region.scale(-1.0, 1.0);
+        assert_eq!(region.rects(), &[Rect { x1: -5, y1: 0, x2: -2, y2: 1 }]);
+
+        // Rectangles that round onto the same pixels are not duplicated.
+        let mut region = Region::from(Rect::new(0, 0, 1, 1));
+        region.add(Rect::new(1, 0, 1, 1));
+        region.scale(0.1, 0.1);
+        assert_eq!(region.rects(), &[Rect::new(0, 0, 1, 1)]);
+    }
+
+    #[test]
+    fn scale_covers_original() {
+        let mut region = Region::new();
+        for rect in scattered(20) {
+            region.add(rect);
+        }
+
+        for &(sx, sy) in &[(0.3, 0.7), (1.25, 2.5), (0.5, 0.5)] {
+            let mut scaled = region.clone();
+            scaled.scale(sx, sy);
+
+            let covered = pixels(&scaled);
+            for (x, y) in pixels(&region) {
+                let (px, py) = ((x as f64 * sx).floor() as i32, (y as f64 * sy).floor() as i32);
+                assert!(covered.contains(&(px, py)), "({}, {}) not covered at {}x{}", x, y, sx, sy);
+            }
+        }
+    }
+
+    #[test]
+    fn simplify_covers_input() {
+        let mut region = Region::new();
+        for rect in scattered(40) {
+            region.add(rect);
+        }
+        let original = pixels(&region);
+        assert!(region.rects().len() > 8);
+
+        for max_rects in 0..10 {
+            let mut simplified = region.clone();
+            simplified.simplify(max_rects);
+
+            assert!(simplified.rects().len() <= max(max_rects, 1));
+            assert!(pixels(&simplified).is_superset(&original));
+            assert_eq!(simplified.extents(), region.extents());
+        }
+    }
+
+    #[test]
+    fn simplify_keeps_small_regions() {
+        let mut region = Region::from(Rect::new(0, 0, 10, 10));
+        region.add(Rect::new(20, 0, 10, 10));
+
+        let mut simplified = region.clone();
+        simplified.simplify(2);
+        assert_eq!(simplified, region);
+
+        simplified.simplify(1);
+        assert_eq!(simplified.rects(), &[Rect::new(0, 0, 30, 10)]);
+    }
+
+    #[test]
+    fn tracker_ages() {
+        let bounds = Rect::new(0, 0, 100, 100);
+        let mut tracker = DamageTracker::new(bounds, 2);
+        for x in 0..3 {
+            tracker.push(Region::from(Rect::new(x * 10, 0, 10, 10)));
+        }
+        let current = Region::from(Rect::new(50, 0, 10, 10));
+
+        // The first frame was dropped from the history of two frames.
+        assert_eq!(tracker.accumulate(1, &current), current);
+        assert_eq!(tracker.accumulate(2, &current).area(), 200);
+        assert_eq!(tracker.accumulate(3, &current).extents(), Some(Rect { x1: 10, y1: 0, x2: 60, y2: 10 }));
+        assert_eq!(tracker.accumulate(3, &current).area(), 300);
+        assert_eq!(tracker.accumulate(4, &current), Region::from(bounds));
+        assert_eq!(tracker.accumulate(usize::MAX, &current), Region::from(bounds));
+        assert_eq!(tracker.accumulate(0, &current), Region::from(bounds));
+
+        tracker.reset();
+        assert_eq!(tracker.accumulate(1, &current), current);
+        assert_eq!(tracker.accumulate(2, &current), Region::from(bounds));
+    }
+
+    #[test]
+    fn tracker_clips_to_bounds() {
+        let bounds = Rect::new(0, 0, 100, 100);
+        let mut tracker = DamageTracker::new(bounds, 1);
+        tracker.push(Region::from(Rect::new(-50, -50, 100, 100)));
+
+        let current = Region::from(Rect::new(90, 90, 20, 20));
+        assert_eq!(tracker.accumulate(1, &current).area(), 100);
+        assert_eq!(tracker.accumulate(2, &current).area(), 2600);
+    }
+
+    #[test]
+    fn tracker_without_history() {
+        let bounds = Rect::new(0, 0, 100, 100);
+        let mut tracker = DamageTracker::new(bounds, 0);
+        tracker.push(Region::from(Rect::new(0, 0, 10, 10)));
+
+        let current = Region::from(Rect::new(50, 0, 10, 10));
+        assert_eq!(tracker.accumulate(1, &current), current);
+        assert_eq!(tracker.accumulate(2, &current), Region::from(bounds));
+    }
+}
@@ -3,11 +3,33 @@
 //!
 //! Memory-supported, slow, but easy & cross-platform buffer implementation
 //!
+//! The pixels of a dumb buffer are accessed by mapping it into memory. Rows
+//! of a buffer are `pitch` bytes apart, which may be more than the width of
+//! the buffer. The `PixelView` and `PixelViewMut` traits iterate over the
+//! visible part of each row, either as bytes or typed as whole pixels.
+//!
+//! Drivers that set `DriverCapability::DumbPreferShadow` map dumb buffers as
+//! uncached memory, which is slow to read back. Such buffers are best drawn
+//! into a `ShadowBuffer`, which is copied into the mapping on `flush`.
+
+use buffer::{self, format::PixelFormat};
+use control::damage::{Rect, Region};
+use control::Device;
+use drm_ffi::result::SystemError;
+use DriverCapability;
 
-use buffer;
+use nix::errno::Errno;
+use nix::libc;
 
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+use std::marker::PhantomData;
+use std::os::unix::io::RawFd;
+use std::{cmp, mem, ptr, slice};
+
+#[derive(Debug, Hash, PartialEq, Eq)]
 /// Slow, but generic `Buffer` implementation
+///
+/// The buffer can not be copied, as it is consumed by
+/// `Device::destroy_dumb_buffer`.
 pub struct DumbBuffer {
     pub(crate) size: (u32, u32),
     pub(crate) length: usize,
@@ -16,39 +38,668 @@ pub struct DumbBuffer {
     pub(crate) handle: buffer::Handle,
 }
 
+impl buffer::Buffer for DumbBuffer {
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+    fn format(&self) -> buffer::format::PixelFormat {
+        self.format
+    }
+    fn pitch(&self) -> u32 {
+        self.pitch
+    }
+    fn handle(&self) -> buffer::Handle {
+        self.handle
+    }
+}
+
+/// Returns whether the driver prefers dumb buffers to be drawn through a
+/// `ShadowBuffer`.
+pub fn prefers_shadow<D: Device>(dev: &D) -> bool {
+    match dev.get_driver_capability(DriverCapability::DumbPreferShadow) {
+        Ok(value) => value != 0,
+        Err(_) => false,
+    }
+}
+
+/// The geometry of the pixels in a block of memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Layout {
+    size: (u32, u32),
+    pitch: u32,
+    format: PixelFormat,
+}
+
+impl Layout {
+    fn of(buffer: &DumbBuffer) -> Layout {
+        Layout {
+            size: buffer.size,
+            pitch: buffer.pitch,
+            format: buffer.format,
+        }
+    }
+
+    fn of_view<V: PixelView + ?Sized>(view: &V) -> Layout {
+        Layout {
+            size: view.size(),
+            pitch: view.pitch(),
+            format: view.format(),
+        }
+    }
+
+    /// Returns the number of bytes of a pixel.
+    fn pixel_bytes(&self) -> usize {
+        (self.format.bpp() as usize).div_ceil(8)
+    }
+
+    /// Returns the number of bytes of the visible part of a row.
+    fn row_bytes(&self) -> usize {
+        self.size.0 as usize * self.pixel_bytes()
+    }
+}
+
+/// A type that pixels can be read and written as.
+///
+/// # Safety
+///
+/// Rows of bytes are reinterpreted as slices of the type, so implementors
+/// must guarantee that:
+///
+/// - the type contains no padding bytes,
+/// - every bit pattern is a valid value of the type,
+/// - its alignment is at most 8, the alignment `ShadowBuffer` provides.
+pub unsafe trait Pixel: Copy {}
+
+unsafe impl Pixel for u8 {}
+unsafe impl Pixel for u16 {}
+unsafe impl Pixel for u32 {}
+unsafe impl Pixel for u64 {}
+unsafe impl Pixel for [u8; 2] {}
+unsafe impl Pixel for [u8; 3] {}
+unsafe impl Pixel for [u8; 4] {}
+
+/// Returns the number of `T` in the visible part of a row, or `None` if rows
+/// cannot be viewed as `T`.
+fn typed_width<T: Pixel>(layout: &Layout, data: &[u8]) -> Option<usize> {
+    let size = mem::size_of::<T>();
+
+    let fits = layout.pixel_bytes().is_multiple_of(size)
+        && (layout.pitch as usize).is_multiple_of(size)
+        && (data.as_ptr() as usize).is_multiple_of(mem::align_of::<T>());
+
+    if fits {
+        Some(layout.row_bytes() / size)
+    } else {
+        None
+    }
+}
+
+/// An iterator over the rows of a buffer.
+#[derive(Debug)]
+pub struct Rows<'a, T: Pixel = u8> {
+    chunks: slice::Chunks<'a, u8>,
+    width: usize,
+    _pixel: PhantomData<T>,
+}
+
+impl<'a, T: Pixel + 'a> Iterator for Rows<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<&'a [T]> {
+        let row = self.chunks.next()?;
+        let bytes = &row[..self.width * mem::size_of::<T>()];
+        Some(unsafe { slice::from_raw_parts(bytes.as_ptr() as *const T, self.width) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+/// An iterator over the mutable rows of a buffer.
+#[derive(Debug)]
+pub struct RowsMut<'a, T: Pixel = u8> {
+    chunks: slice::ChunksMut<'a, u8>,
+    width: usize,
+    _pixel: PhantomData<T>,
+}
+
+impl<'a, T: Pixel + 'a> Iterator for RowsMut<'a, T> {
+    type Item = &'a mut [T];
+
+    fn next(&mut self) -> Option<&'a mut [T]> {
+        let row = self.chunks.next()?;
+        let bytes = &mut row[..self.width * mem::size_of::<T>()];
+        Some(unsafe { slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut T, self.width) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+/// Read access to the pixels of a buffer.
+pub trait PixelView {
+    /// Returns the raw bytes, including the padding at the end of each row.
+    fn data(&self) -> &[u8];
+
+    /// Returns the width and height in pixels.
+    fn size(&self) -> (u32, u32);
+
+    /// Returns the distance between the start of two rows in bytes.
+    fn pitch(&self) -> u32;
+
+    /// Returns the format of the pixels.
+    fn format(&self) -> PixelFormat;
+
+    /// Returns an iterator over the visible bytes of each row.
+    fn rows(&self) -> Rows<'_, u8> {
+        self.rows_as().expect("rows can always be viewed as bytes")
+    }
+
+    /// Returns an iterator over each row as a slice of `T`, e.g. `u32` for
+    /// 32 bits per pixel formats.
+    ///
+    /// Returns `None` if the pixels cannot be split into `T`, such as `u32`
+    /// for a 16 bits per pixel format.
+    fn rows_as<T: Pixel>(&self) -> Option<Rows<'_, T>> {
+        let layout = Layout::of_view(self);
+        let data = self.data();
+        let width = typed_width::<T>(&layout, data)?;
+        let rows = cmp::min(layout.size.1 as usize * layout.pitch as usize, data.len());
+
+        Some(Rows {
+            chunks: data[..rows].chunks(cmp::max(layout.pitch as usize, 1)),
+            width: width,
+            _pixel: PhantomData,
+        })
+    }
+}
+
+/// Write access to the pixels of a buffer.
+pub trait PixelViewMut: PixelView {
+    /// Returns the raw bytes, including the padding at the end of each row.
+    fn data_mut(&mut self) -> &mut [u8];
+
+    /// Returns an iterator over the visible bytes of each row.
+    fn rows_mut(&mut self) -> RowsMut<'_, u8> {
+        self.rows_as_mut().expect("rows can always be viewed as bytes")
+    }
+
+    /// Returns an iterator over each row as a mutable slice of `T`.
+    ///
+    /// Returns `None` if the pixels cannot be split into `T`.
+    fn rows_as_mut<T: Pixel>(&mut self) -> Option<RowsMut<'_, T>> {
+        let layout = Layout::of_view(self);
+        let data = self.data_mut();
+        let width = typed_width::<T>(&layout, data)?;
+        let rows = cmp::min(layout.size.1 as usize * layout.pitch as usize, data.len());
+
+        Some(RowsMut {
+            chunks: data[..rows].chunks_mut(cmp::max(layout.pitch as usize, 1)),
+            width: width,
+            _pixel: PhantomData,
+        })
+    }
+}
+
+/// A memory mapping of a dumb buffer or dma-buf.
+#[derive(Debug)]
+pub(crate) struct Mmap {
+    ptr: *mut u8,
+    len: usize,
+}
+
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    pub(crate) fn new(fd: RawFd, offset: u64, len: usize, writable: bool) -> Result<Mmap, SystemError> {
+        let prot = if writable {
+            libc::PROT_READ | libc::PROT_WRITE
+        } else {
+            libc::PROT_READ
+        };
+
+        let ptr = unsafe { libc::mmap(ptr::null_mut(), len, prot, libc::MAP_SHARED, fd, offset as libc::off_t) };
+        if ptr == libc::MAP_FAILED {
+            return Err(Errno::last().into());
+        }
+
+        Ok(Mmap {
+            ptr: ptr as *mut u8,
+            len: len,
+        })
+    }
+
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+
+    pub(crate) fn unmap(&mut self) -> Result<(), SystemError> {
+        if self.len == 0 {
+            return Ok(());
+        }
+
+        let len = mem::replace(&mut self.len, 0);
+        if unsafe { libc::munmap(self.ptr as *mut libc::c_void, len) } < 0 {
+            return Err(Errno::last().into());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        let _ = self.unmap();
+    }
+}
+
+/// Maps a dumb buffer into memory.
+pub(crate) fn map<D: Device + ?Sized>(dev: &D, buffer: &DumbBuffer, writable: bool) -> Result<(Mmap, Layout), SystemError> {
+    let info = ::drm_ffi::mode::dumbbuffer::map(dev.as_raw_fd(), buffer.handle.into(), 0, 0)?;
+    let map = Mmap::new(dev.as_raw_fd(), info.offset, buffer.length, writable)?;
+
+    Ok((map, Layout::of(buffer)))
+}
+
 /// Mapping of a dumbbuffer
+///
+/// The buffer is unmapped when the mapping is dropped. Errors of unmapping
+/// are only reported by `unmap`.
+#[derive(Debug)]
 pub struct DumbMapping<'a> {
-    pub(crate) _phantom: core::marker::PhantomData<&'a ()>,
-    pub(crate) map: &'a mut [u8],
+    pub(crate) _phantom: PhantomData<&'a mut DumbBuffer>,
+    pub(crate) map: Mmap,
+    pub(crate) layout: Layout,
+}
+
+impl<'a> DumbMapping<'a> {
+    /// Unmaps the buffer.
+    pub fn unmap(mut self) -> Result<(), SystemError> {
+        self.map.unmap()
+    }
+}
+
+impl<'a> AsRef<[u8]> for DumbMapping<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.map.as_slice()
+    }
 }
 
 impl<'a> AsMut<[u8]> for DumbMapping<'a> {
     fn as_mut(&mut self) -> &mut [u8] {
-        self.map
+        self.map.as_mut_slice()
     }
 }
 
-impl<'a> Drop for DumbMapping<'a> {
-    fn drop(&mut self) {
-        use nix::sys::mman;
+impl<'a> PixelView for DumbMapping<'a> {
+    fn data(&self) -> &[u8] {
+        self.map.as_slice()
+    }
 
-        unsafe {
-            mman::munmap(self.map.as_mut_ptr() as *mut _, self.map.len()).expect("Unmap failed");
-        }
+    fn size(&self) -> (u32, u32) {
+        self.layout.size
+    }
+
+    fn pitch(&self) -> u32 {
+        self.layout.pitch
+    }
+
+    fn format(&self) -> PixelFormat {
+        self.layout.format
     }
 }
 
-impl buffer::Buffer for DumbBuffer {
+impl<'a> PixelViewMut for DumbMapping<'a> {
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.map.as_mut_slice()
+    }
+}
+
+/// Read-only mapping of a dumbbuffer
+///
+/// Any number of read-only mappings of a buffer may exist at the same time.
+#[derive(Debug)]
+pub struct DumbMappingRef<'a> {
+    pub(crate) _phantom: PhantomData<&'a DumbBuffer>,
+    pub(crate) map: Mmap,
+    pub(crate) layout: Layout,
+}
+
+impl<'a> DumbMappingRef<'a> {
+    /// Unmaps the buffer.
+    pub fn unmap(mut self) -> Result<(), SystemError> {
+        self.map.unmap()
+    }
+}
+
+impl<'a> AsRef<[u8]> for DumbMappingRef<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.map.as_slice()
+    }
+}
+
+impl<'a> PixelView for DumbMappingRef<'a> {
+    fn data(&self) -> &[u8] {
+        self.map.as_slice()
+    }
+
     fn size(&self) -> (u32, u32) {
-        self.size
+        self.layout.size
     }
-    fn format(&self) -> buffer::format::PixelFormat {
-        self.format
+
+    fn pitch(&self) -> u32 {
+        self.layout.pitch
+    }
+
+    fn format(&self) -> PixelFormat {
+        self.layout.format
+    }
+}
+
+/// Mapping of a dumbbuffer that does not borrow the buffer
+///
+/// The mapping can be sent to and shared between threads. The kernel keeps
+/// the memory of the buffer alive until it is unmapped, even if the buffer
+/// is destroyed in the meantime.
+#[derive(Debug)]
+pub struct OwnedDumbMapping {
+    pub(crate) map: Mmap,
+    pub(crate) layout: Layout,
+}
+
+impl OwnedDumbMapping {
+    /// Unmaps the buffer.
+    pub fn unmap(mut self) -> Result<(), SystemError> {
+        self.map.unmap()
+    }
+}
+
+impl AsRef<[u8]> for OwnedDumbMapping {
+    fn as_ref(&self) -> &[u8] {
+        self.map.as_slice()
+    }
+}
+
+impl AsMut<[u8]> for OwnedDumbMapping {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.map.as_mut_slice()
+    }
+}
+
+impl PixelView for OwnedDumbMapping {
+    fn data(&self) -> &[u8] {
+        self.map.as_slice()
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.layout.size
     }
+
     fn pitch(&self) -> u32 {
-        self.pitch
+        self.layout.pitch
     }
-    fn handle(&self) -> buffer::Handle {
-        self.handle
+
+    fn format(&self) -> PixelFormat {
+        self.layout.format
+    }
+}
+
+impl PixelViewMut for OwnedDumbMapping {
+    fn data_mut(&mut self) -> &mut [u8] {
+        self.map.as_mut_slice()
+    }
+}
+
+/// A copy of a buffer in regular memory, to be flushed into a mapping.
+///
+/// # Example
+///
+/// ```
+/// use drm::buffer::format::PixelFormat;
+/// use drm::control::damage::Rect;
+/// use drm::control::dumbbuffer::{PixelView, PixelViewMut, ShadowBuffer};
+///
+/// let mut shadow = ShadowBuffer::new((4, 2), 16, PixelFormat::ARGB8888);
+/// let mut target = ShadowBuffer::new((4, 2), 20, PixelFormat::ARGB8888);
+///
+/// for row in shadow.rows_as_mut::<u32>().unwrap() {
+///     assert_eq!(row.len(), 4);
+///     row[1] = 0xff00_ff00;
+/// }
+///
+/// // Only the damaged pixel of the first row is copied.
+/// shadow.flush_region(&mut target, &Rect::new(1, 0, 1, 1).into());
+///
+/// let rows: Vec<&[u32]> = target.rows_as().unwrap().collect();
+/// assert_eq!(rows, vec![&[0, 0xff00_ff00, 0, 0][..], &[0; 4][..]]);
+///
+/// // 32 bits per pixel cannot be split into three bytes.
+/// assert!(target.rows_as::<[u8; 3]>().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShadowBuffer {
+    // Stored as `u64` so rows can be viewed as any `Pixel`.
+    data: Vec<u64>,
+    layout: Layout,
+}
+
+impl ShadowBuffer {
+    /// Creates a cleared shadow buffer with the given geometry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pitch` is smaller than a row of `size.0` pixels.
+    pub fn new(size: (u32, u32), pitch: u32, format: PixelFormat) -> ShadowBuffer {
+        let layout = Layout {
+            size: size,
+            pitch: pitch,
+            format: format,
+        };
+        assert!(
+            pitch as usize >= layout.row_bytes(),
+            "pitch of {} bytes is too small for a row of {} bytes",
+            pitch,
+            layout.row_bytes()
+        );
+
+        let len = pitch as usize * size.1 as usize;
+
+        ShadowBuffer {
+            data: vec![0; len.div_ceil(8)],
+            layout: layout,
+        }
+    }
+
+    /// Creates a cleared shadow buffer with the geometry of a dumb buffer.
+    pub fn for_buffer(buffer: &DumbBuffer) -> ShadowBuffer {
+        ShadowBuffer::new(buffer.size, buffer.pitch, buffer.format)
+    }
+
+    /// Copies every pixel into a buffer of the same size and format.
+    pub fn flush<M: PixelViewMut + ?Sized>(&self, target: &mut M) {
+        let (w, h) = self.layout.size;
+        self.copy_rect(target, Rect::new(0, 0, w, h));
+    }
+
+    /// Copies the pixels of a region into a buffer of the same size and
+    /// format.
+    pub fn flush_region<M: PixelViewMut + ?Sized>(&self, target: &mut M, region: &Region) {
+        for &rect in region.rects() {
+            self.copy_rect(target, rect);
+        }
+    }
+
+    fn copy_rect<M: PixelViewMut + ?Sized>(&self, target: &mut M, rect: Rect) {
+        let (w, h) = self.layout.size;
+        let (tw, th) = target.size();
+        let bounds = Rect::new(0, 0, cmp::min(w, tw), cmp::min(h, th));
+
+        let rect = match rect.intersection(&bounds) {
+            Some(rect) => rect,
+            None => return,
+        };
+
+        let bpp = self.layout.pixel_bytes();
+        let (pitch, target_pitch) = (self.layout.pitch as usize, target.pitch() as usize);
+        let (x1, x2) = (rect.x1 as usize * bpp, rect.x2 as usize * bpp);
+        let dst = target.data_mut();
+
+        for y in rect.y1 as usize..rect.y2 as usize {
+            let (src, dst_start) = (y * pitch, y * target_pitch);
+            if dst_start + x2 > dst.len() {
+                break;
+            }
+            dst[dst_start + x1..dst_start + x2].copy_from_slice(&self.data()[src + x1..src + x2]);
+        }
+    }
+}
+
+impl PixelView for ShadowBuffer {
+    fn data(&self) -> &[u8] {
+        let len = self.layout.pitch as usize * self.layout.size.1 as usize;
+        unsafe { slice::from_raw_parts(self.data.as_ptr() as *const u8, len) }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        self.layout.size
+    }
+
+    fn pitch(&self) -> u32 {
+        self.layout.pitch
+    }
+
+    fn format(&self) -> PixelFormat {
+        self.layout.format
+    }
+}
+
+impl PixelViewMut for ShadowBuffer {
+    fn data_mut(&mut self) -> &mut [u8] {
+        let len = self.layout.pitch as usize * self.layout.size.1 as usize;
+        unsafe { slice::from_raw_parts_mut(self.data.as_mut_ptr() as *mut u8, len) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A shadow buffer whose bytes count up from 0.
+    fn numbered(size: (u32, u32), pitch: u32, format: PixelFormat) -> ShadowBuffer {
+        let mut shadow = ShadowBuffer::new(size, pitch, format);
+        for (i, byte) in shadow.data_mut().iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        shadow
+    }
+
+    #[test]
+    fn rows_skip_padding() {
+        let shadow = numbered((3, 2), 8, PixelFormat::R8);
+
+        let mut rows = shadow.rows();
+        assert_eq!(rows.size_hint(), (2, Some(2)));
+        assert_eq!(rows.next(), Some(&[0, 1, 2][..]));
+        assert_eq!(rows.next(), Some(&[8, 9, 10][..]));
+        assert_eq!(rows.next(), None);
+    }
+
+    #[test]
+    fn rows_as_pixels() {
+        let shadow = numbered((3, 2), 8, PixelFormat::RGB565);
+
+        let rows: Vec<&[[u8; 2]]> = shadow.rows_as().unwrap().collect();
+        assert_eq!(rows, vec![&[[0, 1], [2, 3], [4, 5]][..], &[[8, 9], [10, 11], [12, 13]][..]]);
+
+        let rows: Vec<&[u16]> = shadow.rows_as().unwrap().collect();
+        assert_eq!(rows[1], &[u16::from_ne_bytes([8, 9]), u16::from_ne_bytes([10, 11]), u16::from_ne_bytes([12, 13])]);
+
+        assert!(shadow.rows_as::<u32>().is_none());
+    }
+
+    #[test]
+    fn rows_mut_write_visible_pixels() {
+        let mut shadow = ShadowBuffer::new((2, 2), 12, PixelFormat::XRGB8888);
+
+        for (y, row) in shadow.rows_as_mut::<u32>().unwrap().enumerate() {
+            row[y] = 0x00ff_ffff;
+        }
+
+        let rows: Vec<&[u32]> = shadow.rows_as().unwrap().collect();
+        assert_eq!(rows, vec![&[0x00ff_ffff, 0][..], &[0, 0x00ff_ffff][..]]);
+        // The padding is left alone.
+        assert!(shadow.data()[8..12].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn typed_width_needs_whole_pixels() {
+        let layout = |width, pitch, format| Layout {
+            size: (width, 1),
+            pitch: pitch,
+            format: format,
+        };
+        let data = [0u64; 4];
+        let bytes = unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, 32) };
+
+        let xrgb = layout(5, 20, PixelFormat::XRGB8888);
+        assert_eq!(typed_width::<u8>(&xrgb, bytes), Some(20));
+        assert_eq!(typed_width::<u32>(&xrgb, bytes), Some(5));
+        assert_eq!(typed_width::<[u8; 4]>(&xrgb, bytes), Some(5));
+        assert_eq!(typed_width::<u64>(&xrgb, bytes), None);
+        assert_eq!(typed_width::<[u8; 3]>(&xrgb, bytes), None);
+
+        // Rows that are not a whole number of pixels apart.
+        let padded = layout(2, 10, PixelFormat::XRGB8888);
+        assert_eq!(typed_width::<u32>(&padded, bytes), None);
+        assert_eq!(typed_width::<u16>(&padded, bytes), Some(4));
+
+        let rgb = layout(4, 12, PixelFormat::RGB888);
+        assert_eq!(typed_width::<[u8; 3]>(&rgb, bytes), Some(4));
+        assert_eq!(typed_width::<u32>(&rgb, bytes), None);
+
+        // Misaligned data.
+        assert_eq!(typed_width::<u32>(&xrgb, &bytes[1..]), None);
+        assert_eq!(typed_width::<[u8; 4]>(&xrgb, &bytes[1..]), Some(5));
+    }
+
+    #[test]
+    fn flush_region_copies_damage() {
+        let shadow = numbered((4, 3), 4, PixelFormat::R8);
+        let mut target = ShadowBuffer::new((4, 3), 6, PixelFormat::R8);
+
+        let mut region = Region::new();
+        region.add(Rect::new(0, 0, 1, 1));
+        region.add(Rect::new(2, 1, 2, 2));
+        shadow.flush_region(&mut target, &region);
+
+        let rows: Vec<&[u8]> = target.rows().collect();
+        assert_eq!(rows, vec![&[0, 0, 0, 0][..], &[0, 0, 6, 7][..], &[0, 0, 10, 11][..]]);
+    }
+
+    #[test]
+    fn flush_region_clips_to_both_buffers() {
+        let shadow = numbered((4, 3), 4, PixelFormat::R8);
+        let mut target = ShadowBuffer::new((3, 2), 3, PixelFormat::R8);
+
+        shadow.flush_region(&mut target, &Rect::new(-2, 1, 10, 10).into());
+        assert_eq!(target.data(), &[0, 0, 0, 4, 5, 6]);
+
+        shadow.flush_region(&mut target, &Rect::new(3, 0, 1, 3).into());
+        assert_eq!(target.data(), &[0, 0, 0, 4, 5, 6]);
+
+        shadow.flush(&mut target);
+        assert_eq!(target.data(), &[0, 1, 2, 4, 5, 6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "pitch")]
+    fn pitch_shorter_than_row() {
+        ShadowBuffer::new((4, 2), 15, PixelFormat::XRGB8888);
     }
 }
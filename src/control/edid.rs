@@ -0,0 +1,344 @@
+//! # EDID
+//!
+//! Parsing of the Extended Display Identification Data that a display reports
+//! through the `EDID` blob property of its connector.
+//!
+//! Only the parts of the EDID that are relevant to modesetting are decoded.
+
+/// Size of a single EDID block.
+const BLOCK_SIZE: usize = 128;
+
+/// Fixed pattern at the start of every base EDID block.
+const HEADER: [u8; 8] = [0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+
+/// Display descriptors that follow the header and established timings.
+const DESCRIPTORS: usize = 54;
+
+/// Size of a display descriptor.
+const DESCRIPTOR_SIZE: usize = 18;
+
+/// Tag of the display descriptor holding the product name.
+const PRODUCT_NAME_TAG: u8 = 0xfc;
+
+/// Tag of a CTA-861 extension block.
+const CTA_EXTENSION_TAG: u8 = 0x02;
+
+/// Data block tag that introduces an extended tag.
+const CTA_EXTENDED_TAG: u8 = 7;
+
+/// Extended tag of the HDR Static Metadata Data Block.
+const CTA_HDR_STATIC_METADATA: u8 = 0x06;
+
+/// The identity of a display, as reported by the base block of its EDID.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Identity {
+    pub(crate) manufacturer: String,
+    pub(crate) product: u16,
+    pub(crate) serial: u32,
+    pub(crate) year: u16,
+    pub(crate) name: Option<String>,
+}
+
+impl Identity {
+    /// Returns the three letter PNP ID of the manufacturer.
+    pub fn manufacturer(&self) -> &str {
+        &self.manufacturer
+    }
+
+    /// Returns the manufacturer's product code.
+    pub fn product(&self) -> u16 {
+        self.product
+    }
+
+    /// Returns the serial number, which is zero if not reported.
+    pub fn serial(&self) -> u32 {
+        self.serial
+    }
+
+    /// Returns the year of manufacture, or the model year.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Returns the product name from the display descriptors, if reported.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// Decodes the identity of a display from the base block of an EDID.
+///
+/// Returns `None` if the EDID is malformed.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::edid;
+///
+/// let mut data = vec![0u8; 128];
+/// data[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+/// data[8..18].copy_from_slice(&[0x10, 0xac, 0x7a, 0x41, 0x4c, 0x30, 0x32, 0x30, 0x00, 0x1d]);
+/// data[72..90].copy_from_slice(b"\0\0\0\xfc\0DELL U2719D\n ");
+///
+/// let identity = edid::identity(&data).unwrap();
+/// assert_eq!(identity.manufacturer(), "DEL");
+/// assert_eq!(identity.product(), 0x417a);
+/// assert_eq!(identity.serial(), 0x3032_304c);
+/// assert_eq!(identity.year(), 2019);
+/// assert_eq!(identity.name(), Some("DELL U2719D"));
+/// ```
+pub fn identity(edid: &[u8]) -> Option<Identity> {
+    let base = base_block(edid)?;
+
+    let vendor = (u16::from(base[8]) << 8) | u16::from(base[9]);
+    let manufacturer = [10, 5, 0]
+        .iter()
+        .map(|&shift| (b'A' - 1 + ((vendor >> shift) & 0x1f) as u8) as char)
+        .collect();
+
+    let name = base[DESCRIPTORS..DESCRIPTORS + 4 * DESCRIPTOR_SIZE]
+        .chunks(DESCRIPTOR_SIZE)
+        .find(|d| d[..4] == [0x00, 0x00, 0x00, PRODUCT_NAME_TAG])
+        .map(|d| {
+            // The text is terminated by a newline and padded with spaces.
+            let text = &d[5..];
+            let len = text.iter().position(|&b| b == b'\n').unwrap_or(text.len());
+            String::from_utf8_lossy(&text[..len]).trim_end().to_string()
+        });
+
+    Some(Identity {
+        manufacturer: manufacturer,
+        product: u16::from_le_bytes([base[10], base[11]]),
+        serial: u32::from_le_bytes([base[12], base[13], base[14], base[15]]),
+        year: 1990 + u16::from(base[17]),
+        name: name,
+    })
+}
+
+/// The HDR capabilities of a display, as reported by the HDR Static Metadata
+/// Data Block of its CTA-861 extension.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HdrStaticMetadata {
+    pub(crate) eotfs: u8,
+    pub(crate) descriptors: u8,
+    pub(crate) max_luminance: Option<f64>,
+    pub(crate) max_frame_average_luminance: Option<f64>,
+    pub(crate) min_luminance: Option<f64>,
+}
+
+impl HdrStaticMetadata {
+    /// Returns whether the display supports the traditional gamma curve with
+    /// SDR luminance.
+    pub fn supports_traditional_sdr(&self) -> bool {
+        self.eotfs & (1 << 0) != 0
+    }
+
+    /// Returns whether the display supports the traditional gamma curve with
+    /// HDR luminance.
+    pub fn supports_traditional_hdr(&self) -> bool {
+        self.eotfs & (1 << 1) != 0
+    }
+
+    /// Returns whether the display supports the SMPTE ST 2084 (PQ) curve.
+    pub fn supports_pq(&self) -> bool {
+        self.eotfs & (1 << 2) != 0
+    }
+
+    /// Returns whether the display supports the Hybrid Log-Gamma curve.
+    pub fn supports_hlg(&self) -> bool {
+        self.eotfs & (1 << 3) != 0
+    }
+
+    /// Returns whether the display accepts Static Metadata Type 1.
+    pub fn supports_static_metadata_type1(&self) -> bool {
+        self.descriptors & (1 << 0) != 0
+    }
+
+    /// Returns the desired maximum content luminance in cd/m², if reported.
+    pub fn max_luminance(&self) -> Option<f64> {
+        self.max_luminance
+    }
+
+    /// Returns the desired maximum frame-average luminance in cd/m², if
+    /// reported.
+    pub fn max_frame_average_luminance(&self) -> Option<f64> {
+        self.max_frame_average_luminance
+    }
+
+    /// Returns the desired minimum content luminance in cd/m², if reported.
+    pub fn min_luminance(&self) -> Option<f64> {
+        self.min_luminance
+    }
+}
+
+/// Finds and decodes the HDR Static Metadata Data Block of an EDID.
+///
+/// Returns `None` if the EDID is malformed or the display does not report any
+/// HDR capabilities.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::edid;
+///
+/// let mut data = vec![0u8; 256];
+/// data[..8].copy_from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+/// data[126] = 1;
+///
+/// // CTA-861 extension holding a single HDR Static Metadata Data Block.
+/// data[128..132].copy_from_slice(&[0x02, 0x03, 10, 0x00]);
+/// data[132..138].copy_from_slice(&[0xe5, 0x06, 0x05, 0x01, 0x80, 0x00]);
+///
+/// let hdr = edid::hdr_static_metadata(&data).unwrap();
+/// assert!(hdr.supports_traditional_sdr());
+/// assert!(hdr.supports_pq());
+/// assert!(!hdr.supports_hlg());
+/// assert!(hdr.supports_static_metadata_type1());
+/// assert_eq!(hdr.max_luminance(), Some(800.0));
+/// assert_eq!(hdr.max_frame_average_luminance(), None);
+/// ```
+pub fn hdr_static_metadata(edid: &[u8]) -> Option<HdrStaticMetadata> {
+    cta_data_blocks(edid)
+        .into_iter()
+        .filter(|&(tag, payload)| tag == CTA_EXTENDED_TAG && payload.first() == Some(&CTA_HDR_STATIC_METADATA))
+        .map(|(_, payload)| &payload[1..])
+        .filter(|payload| payload.len() >= 2)
+        .map(|payload| {
+            let byte = |n: usize| payload.get(n).cloned().filter(|&v| v != 0);
+
+            HdrStaticMetadata {
+                eotfs: payload[0],
+                descriptors: payload[1],
+                max_luminance: byte(2).map(decode_max_luminance),
+                max_frame_average_luminance: byte(3).map(decode_max_luminance),
+                min_luminance: match (byte(2), payload.get(4)) {
+                    (Some(max), Some(&min)) => Some(decode_min_luminance(max, min)),
+                    _ => None,
+                },
+            }
+        })
+        .next()
+}
+
+/// Collects the `(tag, payload)` pairs of every data block found in the
+/// CTA-861 extensions of an EDID.
+pub(crate) fn cta_data_blocks(edid: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut blocks = Vec::new();
+
+    let extensions = match base_block(edid) {
+        Some(base) => base[126] as usize,
+        None => return blocks,
+    };
+
+    for ext in edid[BLOCK_SIZE..].chunks(BLOCK_SIZE).take(extensions) {
+        if ext.len() < BLOCK_SIZE || ext[0] != CTA_EXTENSION_TAG {
+            continue;
+        }
+
+        // Data blocks sit between the header and the detailed timings.
+        let end = (ext[2] as usize).min(BLOCK_SIZE - 1);
+        let mut pos = 4;
+
+        while pos < end {
+            let tag = ext[pos] >> 5;
+            let len = (ext[pos] & 0x1f) as usize;
+
+            if pos + 1 + len > end {
+                break;
+            }
+
+            blocks.push((tag, &ext[pos + 1..pos + 1 + len]));
+            pos += 1 + len;
+        }
+    }
+
+    blocks
+}
+
+/// Returns the base block of an EDID, if it starts with the EDID header.
+fn base_block(edid: &[u8]) -> Option<&[u8]> {
+    if edid.len() < BLOCK_SIZE || edid[..8] != HEADER {
+        return None;
+    }
+
+    Some(&edid[..BLOCK_SIZE])
+}
+
+/// Decodes a maximum luminance code value into cd/m².
+fn decode_max_luminance(value: u8) -> f64 {
+    50.0 * 2f64.powf(value as f64 / 32.0)
+}
+
+/// Decodes a minimum luminance code value into cd/m².
+fn decode_min_luminance(max: u8, min: u8) -> f64 {
+    let ratio = min as f64 / 255.0;
+    decode_max_luminance(max) * ratio * ratio / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Vec<u8> {
+        let mut data = vec![0u8; BLOCK_SIZE];
+        data[..8].copy_from_slice(&HEADER);
+        data[8..10].copy_from_slice(&[0x4c, 0x2d]);
+        data
+    }
+
+    #[test]
+    fn identity_needs_a_base_block() {
+        assert_eq!(identity(&[]), None);
+        assert_eq!(identity(&base()[..BLOCK_SIZE - 1]), None);
+
+        let mut data = base();
+        data[0] = 0xff;
+        assert_eq!(identity(&data), None);
+    }
+
+    #[test]
+    fn identity_without_name() {
+        let identity = identity(&base()).unwrap();
+        assert_eq!(identity.manufacturer(), "SAM");
+        assert_eq!(identity.name(), None);
+    }
+
+    #[test]
+    fn name_in_last_descriptor() {
+        let mut data = base();
+        // A range limits descriptor, followed by the name.
+        data[90..95].copy_from_slice(&[0x00, 0x00, 0x00, 0xfd, 0x00]);
+        data[108..126].copy_from_slice(b"\0\0\0\xfc\0Odyssey G7\n  ");
+
+        assert_eq!(identity(&data).unwrap().name(), Some("Odyssey G7"));
+    }
+
+    #[test]
+    fn data_blocks_of_cta_extensions() {
+        let mut data = base();
+        data[126] = 2;
+        data.resize(3 * BLOCK_SIZE, 0);
+
+        // Two data blocks, followed by a block overrunning the data area.
+        data[128..132].copy_from_slice(&[CTA_EXTENSION_TAG, 0x03, 13, 0x00]);
+        data[132..141].copy_from_slice(&[0x43, 0x90, 0x04, 0x03, 0x23, 0x09, 0x07, 0x07, 0x45]);
+
+        // Not a CTA extension.
+        data[256..260].copy_from_slice(&[0x70, 0x03, 13, 0x00]);
+        data[260..264].copy_from_slice(&[0x43, 0x90, 0x04, 0x03]);
+
+        assert_eq!(cta_data_blocks(&data), vec![
+            (2, &[0x90, 0x04, 0x03][..]),
+            (1, &[0x09, 0x07, 0x07][..]),
+        ]);
+    }
+
+    #[test]
+    fn data_blocks_of_missing_extensions() {
+        let mut data = base();
+        data[126] = 1;
+
+        assert!(cta_data_blocks(&data).is_empty());
+    }
+}
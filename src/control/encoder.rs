@@ -8,6 +8,7 @@ use drm_ffi as ffi;
 
 /// A handle to an encoder
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Handle(control::RawResourceHandle);
 
 impl Into<control::RawResourceHandle> for Handle {
@@ -42,6 +43,7 @@ impl std::fmt::Debug for Handle {
 
 /// Information about an encoder
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Info {
     pub(crate) handle: Handle,
     pub(crate) enc_type: Kind,
@@ -51,6 +53,27 @@ pub struct Info {
 }
 
 impl Info {
+    /// Creates information about an encoder without querying a device.
+    ///
+    /// This is mostly useful to build `Topology` snapshots in tests.
+    /// `possible_crtcs` and `possible_clones` are bitmasks of indices into
+    /// the CRTCs and encoders of the device.
+    pub fn new(
+        handle: Handle,
+        kind: Kind,
+        crtc: Option<control::crtc::Handle>,
+        possible_crtcs: u32,
+        possible_clones: u32,
+    ) -> Info {
+        Info {
+            handle: handle,
+            enc_type: kind,
+            crtc: crtc,
+            pos_crtcs: possible_crtcs,
+            pos_clones: possible_clones,
+        }
+    }
+
     /// Returns the handle to this encoder.
     pub fn handle(&self) -> Handle {
         self.handle
@@ -80,6 +103,7 @@ impl Info {
 /// The type of encoder.
 #[allow(missing_docs)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Kind {
     None,
     DAC,
@@ -0,0 +1,163 @@
+//! # Event
+//!
+//! Events read from the file descriptor of a device.
+//!
+//! The kernel only queues vblank and page flip events when they are
+//! requested, e.g. with `PageFlipFlags::PageFlipEvent` or
+//! `AtomicCommitFlags::PageFlipEvent`. The file descriptor becomes readable
+//! once an event is pending.
+
+use control::{self, crtc};
+use drm_ffi as ffi;
+
+use std::mem;
+use std::time::Duration;
+
+/// A vblank event, requested through the wait vblank ioctl.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct VblankEvent {
+    pub(crate) frame: u32,
+    pub(crate) time: Duration,
+    pub(crate) crtc: Option<crtc::Handle>,
+    pub(crate) user_data: u64,
+}
+
+impl VblankEvent {
+    /// Returns the vblank counter at the time of the event.
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Returns the time of the vblank, measured by the clock the driver uses
+    /// for timestamps. See `DriverCapability::MonotonicTimestamp`.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns the CRTC the event was sent for.
+    ///
+    /// This is `None` unless the driver supports
+    /// `DriverCapability::CRTCInVBlankEvent`.
+    pub fn crtc(&self) -> Option<crtc::Handle> {
+        self.crtc
+    }
+
+    /// Returns the value passed along with the request.
+    pub fn user_data(&self) -> u64 {
+        self.user_data
+    }
+}
+
+/// A page flip event, sent once a flip or atomic commit has completed.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct PageFlipEvent {
+    pub(crate) frame: u32,
+    pub(crate) time: Duration,
+    pub(crate) crtc: Option<crtc::Handle>,
+}
+
+impl PageFlipEvent {
+    /// Returns the vblank counter at the time of the flip.
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Returns the time of the flip, measured by the clock the driver uses
+    /// for timestamps. See `DriverCapability::MonotonicTimestamp`.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns the CRTC that flipped.
+    ///
+    /// This is `None` unless the driver supports
+    /// `DriverCapability::CRTCInVBlankEvent`.
+    pub fn crtc(&self) -> Option<crtc::Handle> {
+        self.crtc
+    }
+}
+
+/// An event read from a device.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Event {
+    /// A vblank happened
+    Vblank(VblankEvent),
+    /// A page flip completed
+    PageFlip(PageFlipEvent),
+    /// An event this crate does not know, including its header
+    Unknown(Vec<u8>),
+}
+
+/// Parses the events read from a device.
+///
+/// Trailing bytes that do not form a complete event are ignored.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::event::{self, Event};
+/// use std::time::Duration;
+///
+/// // A `drm_event_vblank` of type `DRM_EVENT_FLIP_COMPLETE` for CRTC 41.
+/// let mut data = Vec::new();
+/// for field in &[2u32, 32, 41, 0, 7, 500, 1200, 41] {
+///     data.extend_from_slice(&field.to_ne_bytes());
+/// }
+///
+/// let events = event::parse_events(&data);
+/// assert_eq!(events.len(), 1);
+///
+/// match events[0] {
+///     Event::PageFlip(flip) => {
+///         assert_eq!(flip.frame(), 1200);
+///         assert_eq!(flip.time(), Duration::new(7, 500_000));
+///         assert_eq!(flip.crtc().map(Into::<u32>::into), Some(41));
+///     }
+///     ref event => panic!("unexpected event {:?}", event),
+/// }
+/// ```
+pub fn parse_events(mut data: &[u8]) -> Vec<Event> {
+    let mut events = Vec::new();
+
+    while data.len() >= mem::size_of::<ffi::drm_event>() {
+        let header: ffi::drm_event = unsafe { ::std::ptr::read_unaligned(data.as_ptr() as *const _) };
+        let length = header.length as usize;
+
+        if length < mem::size_of::<ffi::drm_event>() || length > data.len() {
+            break;
+        }
+
+        let (raw, rest) = data.split_at(length);
+        data = rest;
+
+        let event = match header.type_ {
+            ffi::DRM_EVENT_VBLANK | ffi::DRM_EVENT_FLIP_COMPLETE
+                if length >= mem::size_of::<ffi::drm_event_vblank>() =>
+            {
+                let vblank: ffi::drm_event_vblank = unsafe { ::std::ptr::read_unaligned(raw.as_ptr() as *const _) };
+                let time = Duration::new(vblank.tv_sec.into(), vblank.tv_usec * 1000);
+                let crtc = control::from_u32(vblank.crtc_id);
+
+                if header.type_ == ffi::DRM_EVENT_VBLANK {
+                    Event::Vblank(VblankEvent {
+                        frame: vblank.sequence,
+                        time: time,
+                        crtc: crtc,
+                        user_data: vblank.user_data,
+                    })
+                } else {
+                    Event::PageFlip(PageFlipEvent {
+                        frame: vblank.sequence,
+                        time: time,
+                        crtc: crtc,
+                    })
+                }
+            }
+            _ => Event::Unknown(raw.to_vec()),
+        };
+
+        events.push(event);
+    }
+
+    events
+}
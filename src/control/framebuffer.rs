@@ -2,12 +2,17 @@
 //!
 //! Process specific GPU buffers that can be attached to a plane.
 
-use control;
+use buffer;
+use control::{self, Device};
 use drm_ffi as ffi;
+use drm_ffi::result::SystemError;
+
+use std::ops::Deref;
 
 /// A handle to an framebuffer
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Handle(control::RawResourceHandle);
 
 impl Into<control::RawResourceHandle> for Handle {
@@ -42,6 +47,7 @@ impl std::fmt::Debug for Handle {
 
 /// Information about a framebuffer
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Info {
     pub(crate) handle: Handle,
     pub(crate) size: (u32, u32),
@@ -76,4 +82,196 @@ impl Info {
     pub fn depth(&self) -> u32 {
         self.depth
     }
+
+    /// Returns the GEM handle of the buffer backing this framebuffer.
+    ///
+    /// The kernel only hands out handles to the DRM Master or to processes
+    /// with CAP_SYS_ADMIN privileges. The handle is a new reference to the
+    /// buffer, and must be closed with `Device::close_buffer` once it is no
+    /// longer needed.
+    pub fn buffer(&self) -> Option<buffer::Handle> {
+        control::RawResourceHandle::new(self.buffer).map(buffer::Handle::from)
+    }
+}
+
+/// Information about a framebuffer, including its format, modifier and the
+/// layout of each of its planes.
+///
+/// The GEM handles of the planes are new references to the buffers, owned by
+/// this value. They are closed on drop, or by `PlanarInfo::close` to handle
+/// errors of closing them.
+#[derive(Debug)]
+pub struct PlanarInfo<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    pub(crate) dev: D,
+    pub(crate) handle: Handle,
+    pub(crate) size: (u32, u32),
+    pub(crate) pixel_format: u32,
+    pub(crate) flags: u32,
+    pub(crate) buffers: [Option<buffer::Handle>; 4],
+    pub(crate) pitches: [u32; 4],
+    pub(crate) offsets: [u32; 4],
+    pub(crate) modifier: Option<u64>,
+}
+
+impl<D> PlanarInfo<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    /// Returns the handle to this framebuffer.
+    pub fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    /// Returns the size of this framebuffer.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the fourcc code of the pixel format of this framebuffer.
+    pub fn raw_format(&self) -> u32 {
+        self.pixel_format
+    }
+
+    /// Returns the pixel format of this framebuffer, if it is known.
+    pub fn format(&self) -> Option<buffer::format::PixelFormat> {
+        buffer::format::PixelFormat::from_raw(self.pixel_format)
+    }
+
+    /// Returns the flags this framebuffer was created with.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Returns the GEM handles of the buffers backing each plane.
+    ///
+    /// The handles are `None` if the process lacks the privileges to receive
+    /// them. Several planes may share the same handle.
+    pub fn buffers(&self) -> &[Option<buffer::Handle>; 4] {
+        &self.buffers
+    }
+
+    /// Returns the pitch of each plane.
+    pub fn pitches(&self) -> [u32; 4] {
+        self.pitches
+    }
+
+    /// Returns the offset of each plane into its buffer.
+    pub fn offsets(&self) -> [u32; 4] {
+        self.offsets
+    }
+
+    /// Returns the format modifier of this framebuffer, if it was created with
+    /// one.
+    pub fn modifier(&self) -> Option<u64> {
+        self.modifier
+    }
+
+    /// Closes the GEM handles of this framebuffer.
+    ///
+    /// This does not destroy the framebuffer itself.
+    pub fn close(mut self) -> Result<(), SystemError> {
+        self.close_buffers()
+    }
+
+    fn close_buffers(&mut self) -> Result<(), SystemError> {
+        let buffers = std::mem::take(&mut self.buffers);
+        let mut result = Ok(());
+
+        for (i, handle) in buffers.iter().enumerate() {
+            if let Some(handle) = *handle {
+                // The same buffer may back several planes, but owns one reference.
+                if buffers[..i].contains(&Some(handle)) {
+                    continue;
+                }
+
+                if let Err(e) = self.dev.close_buffer(handle) {
+                    result = Err(e);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+impl<D> Drop for PlanarInfo<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    fn drop(&mut self) {
+        let _ = self.close_buffers();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use control::RawResourceHandle;
+    use std::cell::RefCell;
+    use std::os::unix::io::{AsRawFd, RawFd};
+
+    /// A device that records the GEM handles it closes.
+    #[derive(Debug, Default)]
+    struct Mock {
+        closed: RefCell<Vec<buffer::Handle>>,
+    }
+
+    impl AsRawFd for Mock {
+        fn as_raw_fd(&self) -> RawFd {
+            -1
+        }
+    }
+
+    impl ::Device for Mock {}
+
+    impl Device for Mock {
+        fn close_buffer(&self, handle: buffer::Handle) -> Result<(), SystemError> {
+            self.closed.borrow_mut().push(handle);
+            Err(SystemError::InvalidArgument)
+        }
+    }
+
+    fn gem(id: u32) -> buffer::Handle {
+        buffer::Handle::from(RawResourceHandle::new(id).unwrap())
+    }
+
+    /// An NV12 framebuffer with both planes in buffer 7.
+    fn nv12(dev: &Mock) -> PlanarInfo<&Mock> {
+        PlanarInfo {
+            dev: dev,
+            handle: control::from_u32(90).unwrap(),
+            size: (1920, 1080),
+            pixel_format: buffer::format::PixelFormat::NV12.as_raw(),
+            flags: 0,
+            buffers: [Some(gem(7)), Some(gem(7)), None, None],
+            pitches: [1920, 1920, 0, 0],
+            offsets: [0, 1920 * 1080, 0, 0],
+            modifier: None,
+        }
+    }
+
+    #[test]
+    fn handles_are_closed_on_drop() {
+        let dev = Mock::default();
+        drop(nv12(&dev));
+
+        assert_eq!(*dev.closed.borrow(), vec![gem(7)]);
+    }
+
+    #[test]
+    fn close_reports_errors_once() {
+        let dev = Mock::default();
+
+        match nv12(&dev).close() {
+            Err(SystemError::InvalidArgument) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+        assert_eq!(*dev.closed.borrow(), vec![gem(7)]);
+    }
 }
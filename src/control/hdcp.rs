@@ -0,0 +1,274 @@
+//! # HDCP
+//!
+//! High-bandwidth Digital Content Protection on connectors.
+//!
+//! Userspace requests protection by setting the `Content Protection` property
+//! of a connector to `Desired`. Once the driver has authenticated the link it
+//! changes the property to `Enabled` and sends a hotplug event. Should the
+//! link lose its authentication later on, the driver moves the property back
+//! to `Desired` and retries.
+
+use control;
+use control::atomic::AtomicModeReq;
+use control::{connector, property, Device};
+use drm_ffi as ffi;
+use drm_ffi::result::SystemError;
+
+/// The state of the `Content Protection` property.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ContentProtection {
+    /// Content protection is not requested
+    Undesired,
+    /// Content protection is requested, but not yet established
+    Desired,
+    /// Content protection is established
+    Enabled,
+}
+
+impl ContentProtection {
+    /// Returns the name the kernel uses for this value of the property.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ContentProtection::Undesired => "Undesired",
+            ContentProtection::Desired => "Desired",
+            ContentProtection::Enabled => "Enabled",
+        }
+    }
+}
+
+impl From<property::RawValue> for ContentProtection {
+    fn from(n: property::RawValue) -> Self {
+        match n as u32 {
+            ffi::DRM_MODE_CONTENT_PROTECTION_DESIRED => ContentProtection::Desired,
+            ffi::DRM_MODE_CONTENT_PROTECTION_ENABLED => ContentProtection::Enabled,
+            _ => ContentProtection::Undesired,
+        }
+    }
+}
+
+/// The type of the content, as set through the `HDCP Content Type` property.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ContentType {
+    /// Content that may be transmitted with any HDCP version
+    Type0,
+    /// Content that requires HDCP 2.2 or newer
+    Type1,
+}
+
+impl ContentType {
+    /// Returns the name the kernel uses for this value of the property.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ContentType::Type0 => "HDCP Type0",
+            ContentType::Type1 => "HDCP Type1",
+        }
+    }
+}
+
+impl From<property::RawValue> for ContentType {
+    fn from(n: property::RawValue) -> Self {
+        match n as u32 {
+            ffi::DRM_MODE_HDCP_CONTENT_TYPE1 => ContentType::Type1,
+            _ => ContentType::Type0,
+        }
+    }
+}
+
+/// Returns the `Content Protection` state of a connector.
+///
+/// Returns `None` if the driver does not support HDCP on this connector.
+pub fn content_protection<D: Device>(
+    dev: &D,
+    connector: connector::Handle,
+) -> Result<Option<ContentProtection>, SystemError> {
+    Ok(dev
+        .find_property(connector, "Content Protection")?
+        .map(|(_, value)| ContentProtection::from(value)))
+}
+
+/// Returns the `HDCP Content Type` of a connector.
+///
+/// Returns `None` if the driver does not support HDCP 2.2 on this connector.
+pub fn content_type<D: Device>(
+    dev: &D,
+    connector: connector::Handle,
+) -> Result<Option<ContentType>, SystemError> {
+    Ok(dev
+        .find_property(connector, "HDCP Content Type")?
+        .map(|(_, value)| ContentType::from(value)))
+}
+
+/// Sets the `Content Protection` of a connector in an atomic request.
+///
+/// Userspace may only request `Undesired` or `Desired`; `Enabled` is rejected
+/// with `SystemError::InvalidArgument`.
+pub fn add_content_protection<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    connector: connector::Handle,
+    protection: ContentProtection,
+) -> Result<(), SystemError> {
+    if protection == ContentProtection::Enabled {
+        return Err(SystemError::InvalidArgument);
+    }
+
+    add_enum(dev, req, connector, "Content Protection", protection.name())
+}
+
+/// Sets the `HDCP Content Type` of a connector in an atomic request.
+pub fn add_content_type<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    connector: connector::Handle,
+    content_type: ContentType,
+) -> Result<(), SystemError> {
+    add_enum(dev, req, connector, "HDCP Content Type", content_type.name())
+}
+
+/// A change of the `Content Protection` state of a connector.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Event {
+    /// Protection went from `Desired` to `Enabled`
+    Enabled(connector::Handle),
+    /// Protection went from `Enabled` back to `Desired`
+    Lost(connector::Handle),
+}
+
+impl Event {
+    /// Returns the event for a change of the state of a connector, if any.
+    fn from_change(
+        connector: connector::Handle,
+        previous: ContentProtection,
+        current: ContentProtection,
+    ) -> Option<Event> {
+        match (previous, current) {
+            (ContentProtection::Desired, ContentProtection::Enabled) => Some(Event::Enabled(connector)),
+            (ContentProtection::Enabled, ContentProtection::Desired) => Some(Event::Lost(connector)),
+            _ => None,
+        }
+    }
+}
+
+/// Tracks the `Content Protection` state of a set of connectors.
+///
+/// The kernel announces changes of the property through hotplug events, so
+/// `Watcher::update` is best called whenever a `hotplug::Event::Property` is
+/// received.
+#[derive(Debug, Clone, Default)]
+pub struct Watcher {
+    states: Vec<(connector::Handle, ContentProtection)>,
+}
+
+impl Watcher {
+    /// Creates a watcher that tracks no connectors.
+    pub fn new() -> Watcher {
+        Watcher { states: Vec::new() }
+    }
+
+    /// Starts tracking a connector, recording its current state.
+    ///
+    /// Fails with `SystemError::InvalidArgument` if the connector does not
+    /// support HDCP.
+    pub fn watch<D: Device>(&mut self, dev: &D, connector: connector::Handle) -> Result<(), SystemError> {
+        let state = content_protection(dev, connector)?.ok_or(SystemError::InvalidArgument)?;
+
+        self.unwatch(connector);
+        self.states.push((connector, state));
+
+        Ok(())
+    }
+
+    /// Stops tracking a connector.
+    pub fn unwatch(&mut self, connector: connector::Handle) {
+        self.states.retain(|&(c, _)| c != connector);
+    }
+
+    /// Returns the last known state of a connector.
+    pub fn state(&self, connector: connector::Handle) -> Option<ContentProtection> {
+        self.states.iter().find(|&&(c, _)| c == connector).map(|&(_, s)| s)
+    }
+
+    /// Reads the current state of every tracked connector and returns the
+    /// changes since the last update.
+    pub fn update<D: Device>(&mut self, dev: &D) -> Result<Vec<Event>, SystemError> {
+        let mut events = Vec::new();
+
+        for &mut (connector, ref mut state) in &mut self.states {
+            let current = match content_protection(dev, connector)? {
+                Some(current) => current,
+                None => continue,
+            };
+
+            events.extend(Event::from_change(connector, *state, current));
+            *state = current;
+        }
+
+        Ok(events)
+    }
+}
+
+fn add_enum<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    connector: connector::Handle,
+    name: &str,
+    value: &str,
+) -> Result<(), SystemError> {
+    let (prop, _) = dev
+        .find_property(connector, name)?
+        .ok_or(SystemError::InvalidArgument)?;
+
+    let value = control::enum_value(dev, prop, value)?;
+    req.add_raw_property(connector.into(), prop, value);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use control::from_u32;
+
+    #[test]
+    fn content_protection_values() {
+        assert_eq!(ContentProtection::from(0), ContentProtection::Undesired);
+        assert_eq!(ContentProtection::from(1), ContentProtection::Desired);
+        assert_eq!(ContentProtection::from(2), ContentProtection::Enabled);
+        assert_eq!(ContentProtection::from(9), ContentProtection::Undesired);
+        assert_eq!(ContentProtection::from(1 << 32 | 2), ContentProtection::Enabled);
+
+        assert_eq!(ContentProtection::Desired.name(), "Desired");
+    }
+
+    #[test]
+    fn content_type_values() {
+        assert_eq!(ContentType::from(0), ContentType::Type0);
+        assert_eq!(ContentType::from(1), ContentType::Type1);
+        assert_eq!(ContentType::from(5), ContentType::Type0);
+
+        assert_eq!(ContentType::Type0.name(), "HDCP Type0");
+        assert_eq!(ContentType::Type1.name(), "HDCP Type1");
+    }
+
+    #[test]
+    fn transitions() {
+        use self::ContentProtection::*;
+
+        let connector = from_u32(57).unwrap();
+        let change = |previous, current| Event::from_change(connector, previous, current);
+
+        assert_eq!(change(Desired, Enabled), Some(Event::Enabled(connector)));
+        assert_eq!(change(Enabled, Desired), Some(Event::Lost(connector)));
+
+        // Turning protection off, or a request that is still pending, is not
+        // an event.
+        assert_eq!(change(Desired, Undesired), None);
+        assert_eq!(change(Enabled, Undesired), None);
+        assert_eq!(change(Undesired, Desired), None);
+        assert_eq!(change(Desired, Desired), None);
+        assert_eq!(change(Enabled, Enabled), None);
+
+        // Enabling needs the driver to authenticate a request first.
+        assert_eq!(change(Undesired, Enabled), None);
+    }
+}
@@ -0,0 +1,450 @@
+//! # HDR
+//!
+//! High dynamic range output and wide color gamut signalling on connectors.
+//!
+//! A display is switched into an HDR mode by setting the `HDR_OUTPUT_METADATA`
+//! blob property of its connector. The blob describes the transfer function
+//! (EOTF) of the content along with the color volume of the display it was
+//! mastered on. Most drivers also expect the `Colorspace` and `max bpc`
+//! properties to be set to values fitting the content.
+
+use control;
+use control::atomic::AtomicModeReq;
+use control::edid::{self, HdrStaticMetadata};
+use control::{connector, property, Device};
+use drm_ffi as ffi;
+use drm_ffi::result::SystemError;
+
+use std::error::Error;
+use std::{fmt, mem};
+
+/// An electro-optical transfer function.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Eotf {
+    /// Traditional gamma curve with SDR luminance range
+    TraditionalSdr,
+    /// Traditional gamma curve with HDR luminance range
+    TraditionalHdr,
+    /// SMPTE ST 2084, also known as Perceptual Quantizer (PQ)
+    Pq,
+    /// Hybrid Log-Gamma, as defined by ITU-R BT.2100
+    Hlg,
+}
+
+impl From<Eotf> for u8 {
+    fn from(eotf: Eotf) -> u8 {
+        match eotf {
+            Eotf::TraditionalSdr => ffi::HDMI_EOTF_TRADITIONAL_GAMMA_SDR,
+            Eotf::TraditionalHdr => ffi::HDMI_EOTF_TRADITIONAL_GAMMA_HDR,
+            Eotf::Pq => ffi::HDMI_EOTF_SMPTE_ST2084,
+            Eotf::Hlg => ffi::HDMI_EOTF_BT_2100_HLG,
+        }
+    }
+}
+
+/// A CIE 1931 xy chromaticity coordinate.
+pub type Chromaticity = (f64, f64);
+
+/// Red, green and blue primaries of the ITU-R BT.2020 color space.
+pub const BT2020_PRIMARIES: [Chromaticity; 3] = [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046)];
+
+/// Red, green and blue primaries of the DCI-P3 color space.
+pub const DCI_P3_PRIMARIES: [Chromaticity; 3] = [(0.680, 0.320), (0.265, 0.690), (0.150, 0.060)];
+
+/// Red, green and blue primaries of the ITU-R BT.709 (sRGB) color space.
+pub const BT709_PRIMARIES: [Chromaticity; 3] = [(0.640, 0.330), (0.300, 0.600), (0.150, 0.060)];
+
+/// The CIE standard illuminant D65.
+pub const D65_WHITE_POINT: Chromaticity = (0.3127, 0.3290);
+
+/// Errors that can occur when validating `HdrMetadata`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HdrError {
+    /// A primary or white point is outside of the CIE 1931 xy range.
+    InvalidChromaticity,
+    /// The mastering luminance range is out of bounds or empty.
+    InvalidMasteringLuminance,
+    /// The maximum frame-average light level exceeds the maximum content
+    /// light level.
+    InvalidContentLightLevel,
+    /// The display does not support the requested EOTF.
+    UnsupportedEotf,
+    /// The display does not accept Static Metadata Type 1.
+    UnsupportedMetadata,
+}
+
+impl fmt::Display for HdrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            HdrError::InvalidChromaticity => "chromaticity coordinate out of range",
+            HdrError::InvalidMasteringLuminance => "invalid mastering luminance range",
+            HdrError::InvalidContentLightLevel => "maximum frame-average light level exceeds maximum content light level",
+            HdrError::UnsupportedEotf => "EOTF not supported by the display",
+            HdrError::UnsupportedMetadata => "static metadata type not supported by the display",
+        })
+    }
+}
+
+impl Error for HdrError {}
+
+/// The contents of an `HDR_OUTPUT_METADATA` blob, using Static Metadata
+/// Type 1 as defined by CTA-861-G.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::hdr::{self, Eotf, HdrMetadata};
+///
+/// let metadata = HdrMetadata::new(Eotf::Pq)
+///     .primaries(hdr::BT2020_PRIMARIES)
+///     .white_point(hdr::D65_WHITE_POINT)
+///     .mastering_luminance(0.005, 1000.0)
+///     .content_light_level(1000, 400);
+///
+/// let raw = metadata.to_raw().unwrap();
+/// let frame = raw.hdmi_metadata_type1;
+/// assert_eq!(frame.eotf, 2);
+/// assert_eq!(frame.display_primaries[0].x, 35400);
+/// assert_eq!(frame.white_point.y, 16450);
+/// assert_eq!(frame.max_display_mastering_luminance, 1000);
+/// assert_eq!(frame.min_display_mastering_luminance, 50);
+/// assert_eq!(frame.max_fall, 400);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HdrMetadata {
+    eotf: Eotf,
+    primaries: [Chromaticity; 3],
+    white_point: Chromaticity,
+    min_luminance: f64,
+    max_luminance: f64,
+    max_cll: u16,
+    max_fall: u16,
+}
+
+impl HdrMetadata {
+    /// Creates metadata for content using the given EOTF.
+    ///
+    /// The mastering display defaults to unknown, which signals the display to
+    /// use its own defaults.
+    pub fn new(eotf: Eotf) -> HdrMetadata {
+        HdrMetadata {
+            eotf: eotf,
+            primaries: [(0.0, 0.0); 3],
+            white_point: (0.0, 0.0),
+            min_luminance: 0.0,
+            max_luminance: 0.0,
+            max_cll: 0,
+            max_fall: 0,
+        }
+    }
+
+    /// Sets the red, green and blue primaries of the mastering display.
+    pub fn primaries(mut self, primaries: [Chromaticity; 3]) -> HdrMetadata {
+        self.primaries = primaries;
+        self
+    }
+
+    /// Sets the white point of the mastering display.
+    pub fn white_point(mut self, white_point: Chromaticity) -> HdrMetadata {
+        self.white_point = white_point;
+        self
+    }
+
+    /// Sets the minimum and maximum luminance of the mastering display in cd/m².
+    pub fn mastering_luminance(mut self, min: f64, max: f64) -> HdrMetadata {
+        self.min_luminance = min;
+        self.max_luminance = max;
+        self
+    }
+
+    /// Sets the maximum content light level (MaxCLL) and maximum frame-average
+    /// light level (MaxFALL) of the content in cd/m².
+    pub fn content_light_level(mut self, max_cll: u16, max_fall: u16) -> HdrMetadata {
+        self.max_cll = max_cll;
+        self.max_fall = max_fall;
+        self
+    }
+
+    /// Returns the EOTF of the content.
+    pub fn eotf(&self) -> Eotf {
+        self.eotf
+    }
+
+    /// Checks that every value can be encoded and is consistent.
+    pub fn validate(&self) -> Result<(), HdrError> {
+        let valid_coord = |&(x, y): &Chromaticity| (0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y);
+
+        if !self.primaries.iter().all(&valid_coord) || !valid_coord(&self.white_point) {
+            return Err(HdrError::InvalidChromaticity);
+        }
+
+        let min_valid = (0.0..=6.5535).contains(&self.min_luminance);
+        let max_valid = (0.0..=65535.0).contains(&self.max_luminance);
+        let ordered = self.max_luminance == 0.0 || self.min_luminance < self.max_luminance;
+        if !min_valid || !max_valid || !ordered {
+            return Err(HdrError::InvalidMasteringLuminance);
+        }
+
+        if self.max_cll != 0 && self.max_fall > self.max_cll {
+            return Err(HdrError::InvalidContentLightLevel);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that a display with the given capabilities can show this
+    /// content.
+    pub fn check_sink(&self, sink: &HdrStaticMetadata) -> Result<(), HdrError> {
+        let supported = match self.eotf {
+            Eotf::TraditionalSdr => sink.supports_traditional_sdr(),
+            Eotf::TraditionalHdr => sink.supports_traditional_hdr(),
+            Eotf::Pq => sink.supports_pq(),
+            Eotf::Hlg => sink.supports_hlg(),
+        };
+
+        if !supported {
+            return Err(HdrError::UnsupportedEotf);
+        }
+
+        if !sink.supports_static_metadata_type1() {
+            return Err(HdrError::UnsupportedMetadata);
+        }
+
+        Ok(())
+    }
+
+    /// Validates and converts this metadata into the layout of the property
+    /// blob.
+    pub fn to_raw(&self) -> Result<ffi::hdr_output_metadata, HdrError> {
+        self.validate()?;
+
+        let coord = |(x, y): Chromaticity| ffi::hdr_metadata_chromaticity {
+            x: (x * 50000.0).round() as u16,
+            y: (y * 50000.0).round() as u16,
+        };
+
+        let frame = ffi::hdr_metadata_infoframe {
+            eotf: self.eotf.into(),
+            metadata_type: ffi::HDMI_STATIC_METADATA_TYPE1 as u8,
+            display_primaries: [
+                coord(self.primaries[0]),
+                coord(self.primaries[1]),
+                coord(self.primaries[2]),
+            ],
+            white_point: coord(self.white_point),
+            max_display_mastering_luminance: self.max_luminance.round() as u16,
+            min_display_mastering_luminance: (self.min_luminance * 10000.0).round() as u16,
+            max_cll: self.max_cll,
+            max_fall: self.max_fall,
+        };
+
+        Ok(ffi::hdr_output_metadata {
+            metadata_type: ffi::HDMI_STATIC_METADATA_TYPE1,
+            hdmi_metadata_type1: frame,
+        })
+    }
+}
+
+/// Colorimetry signalled to the display through the `Colorspace` property.
+#[allow(missing_docs)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Colorspace {
+    Default,
+    Smpte170mYcc,
+    Bt709Ycc,
+    Xvycc601,
+    Xvycc709,
+    Sycc601,
+    OpYcc601,
+    OpRgb,
+    Bt2020Cycc,
+    Bt2020Rgb,
+    Bt2020Ycc,
+    DciP3RgbD65,
+    DciP3RgbTheater,
+    RgbWideFixed,
+    RgbWideFloat,
+    Bt601Ycc,
+}
+
+impl Colorspace {
+    /// Returns the name the kernel uses for this value of the property.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Colorspace::Default => "Default",
+            Colorspace::Smpte170mYcc => "SMPTE_170M_YCC",
+            Colorspace::Bt709Ycc => "BT709_YCC",
+            Colorspace::Xvycc601 => "XVYCC_601",
+            Colorspace::Xvycc709 => "XVYCC_709",
+            Colorspace::Sycc601 => "SYCC_601",
+            Colorspace::OpYcc601 => "opYCC_601",
+            Colorspace::OpRgb => "opRGB",
+            Colorspace::Bt2020Cycc => "BT2020_CYCC",
+            Colorspace::Bt2020Rgb => "BT2020_RGB",
+            Colorspace::Bt2020Ycc => "BT2020_YCC",
+            Colorspace::DciP3RgbD65 => "DCI-P3_RGB_D65",
+            Colorspace::DciP3RgbTheater => "DCI-P3_RGB_Theater",
+            Colorspace::RgbWideFixed => "RGB_WIDE_FIXED",
+            Colorspace::RgbWideFloat => "RGB_WIDE_FLOAT",
+            Colorspace::Bt601Ycc => "BT601_YCC",
+        }
+    }
+}
+
+/// Reads the HDR capabilities of the display attached to a connector from its
+/// EDID.
+///
+/// Returns `None` if the connector has no EDID, or the display does not report
+/// any HDR capabilities.
+pub fn sink_capabilities<D: Device>(
+    dev: &D,
+    connector: connector::Handle,
+) -> Result<Option<HdrStaticMetadata>, SystemError> {
+    match dev.find_property(connector, "EDID")? {
+        Some((_, 0)) | None => Ok(None),
+        Some((_, blob)) => Ok(edid::hdr_static_metadata(&dev.get_property_blob(blob)?)),
+    }
+}
+
+/// Sets the `HDR_OUTPUT_METADATA` of a connector in an atomic request.
+///
+/// The metadata is validated, and checked against the capabilities of the
+/// display when its EDID reports them. Passing `None` switches the display
+/// back to SDR.
+///
+/// Returns the blob that was created for the metadata. It must be kept alive
+/// until the request is committed, and should be destroyed afterwards.
+///
+/// Fails with `SystemError::InvalidArgument` if the connector has no such
+/// property or the metadata is rejected. Use `HdrMetadata::validate` and
+/// `HdrMetadata::check_sink` to find out why.
+pub fn add_hdr_output_metadata<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    connector: connector::Handle,
+    metadata: Option<&HdrMetadata>,
+) -> Result<Option<property::Value>, SystemError> {
+    let (prop, _) = dev
+        .find_property(connector, "HDR_OUTPUT_METADATA")?
+        .ok_or(SystemError::InvalidArgument)?;
+
+    let blob = match metadata {
+        Some(metadata) => {
+            let raw = metadata.to_raw().map_err(|_| SystemError::InvalidArgument)?;
+
+            if let Some(sink) = sink_capabilities(dev, connector)? {
+                metadata.check_sink(&sink).map_err(|_| SystemError::InvalidArgument)?;
+            }
+
+            Some(dev.create_property_blob(&blob_bytes(&raw)[..])?)
+        }
+        None => None,
+    };
+
+    req.add_property(connector, prop, blob.unwrap_or(property::Value::Blob(0)));
+
+    Ok(blob)
+}
+
+/// Returns the contents of the `HDR_OUTPUT_METADATA` blob.
+///
+/// The C struct ends in two bytes of padding, so it is written field by field
+/// into a zeroed buffer of its size.
+fn blob_bytes(raw: &ffi::hdr_output_metadata) -> Vec<u8> {
+    let frame = &raw.hdmi_metadata_type1;
+    let mut bytes = Vec::with_capacity(mem::size_of::<ffi::hdr_output_metadata>());
+
+    bytes.extend_from_slice(&raw.metadata_type.to_ne_bytes());
+    bytes.push(frame.eotf);
+    bytes.push(frame.metadata_type);
+
+    for point in frame.display_primaries.iter().chain(Some(&frame.white_point)) {
+        bytes.extend_from_slice(&point.x.to_ne_bytes());
+        bytes.extend_from_slice(&point.y.to_ne_bytes());
+    }
+
+    let luminance = [
+        frame.max_display_mastering_luminance,
+        frame.min_display_mastering_luminance,
+        frame.max_cll,
+        frame.max_fall,
+    ];
+    for value in luminance.iter() {
+        bytes.extend_from_slice(&value.to_ne_bytes());
+    }
+
+    bytes.resize(mem::size_of::<ffi::hdr_output_metadata>(), 0);
+    bytes
+}
+
+/// Sets the `Colorspace` of a connector in an atomic request.
+///
+/// Fails with `SystemError::InvalidArgument` if the connector has no such
+/// property or the driver does not support the colorspace.
+pub fn add_colorspace<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    connector: connector::Handle,
+    colorspace: Colorspace,
+) -> Result<(), SystemError> {
+    let (prop, _) = dev
+        .find_property(connector, "Colorspace")?
+        .ok_or(SystemError::InvalidArgument)?;
+
+    let value = control::enum_value(dev, prop, colorspace.name())?;
+
+    req.add_raw_property(connector.into(), prop, value);
+
+    Ok(())
+}
+
+/// Sets the `max bpc` of a connector in an atomic request.
+///
+/// Fails with `SystemError::InvalidArgument` if the connector has no such
+/// property or the value is out of the range the driver accepts.
+pub fn add_max_bpc<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    connector: connector::Handle,
+    bpc: u64,
+) -> Result<(), SystemError> {
+    let (prop, _) = dev
+        .find_property(connector, "max bpc")?
+        .ok_or(SystemError::InvalidArgument)?;
+
+    match dev.get_property(prop)?.value_type() {
+        property::ValueType::UnsignedRange(min, max) if (min..=max).contains(&bpc) => (),
+        _ => return Err(SystemError::InvalidArgument),
+    }
+
+    req.add_property(connector, prop, property::Value::UnsignedRange(bpc));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_bytes_match_c_layout() {
+        let raw = ffi::hdr_output_metadata {
+            metadata_type: 0x0403_0201,
+            hdmi_metadata_type1: ffi::hdr_metadata_infoframe {
+                eotf: 2,
+                white_point: ffi::hdr_metadata_chromaticity { x: 0, y: 0x0605 },
+                max_fall: 0x0807,
+                ..Default::default()
+            },
+        };
+
+        let bytes = blob_bytes(&raw);
+
+        assert_eq!(bytes.len(), mem::size_of::<ffi::hdr_output_metadata>());
+        assert_eq!(&bytes[..4], &0x0403_0201u32.to_ne_bytes());
+        assert_eq!(bytes[4], 2);
+        assert_eq!(&bytes[20..22], &0x0605u16.to_ne_bytes());
+        assert_eq!(&bytes[28..30], &0x0807u16.to_ne_bytes());
+        assert_eq!(&bytes[30..], &[0, 0]);
+    }
+}
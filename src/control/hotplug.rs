@@ -0,0 +1,446 @@
+//! # Hotplug
+//!
+//! Monitoring of display changes through kernel uevents.
+//!
+//! The kernel announces changes of a card's outputs, such as a monitor being
+//! plugged in, by broadcasting a uevent for the card's device node. These are
+//! received through a `NETLINK_KOBJECT_UEVENT` socket, without going through
+//! udev.
+//!
+//! Events only tell that something changed. The new state has to be read
+//! from the device, e.g. with `Device::get_connector`.
+
+use control::{self, connector, property, Device};
+use drm_ffi::result::SystemError;
+
+use nix::errno::Errno;
+use nix::libc;
+
+use std::mem;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// Netlink multicast group of the uevents sent by the kernel.
+const KERNEL_UEVENT_GROUP: u32 = 1;
+
+/// Size of the buffer uevents are received into.
+const UEVENT_BUFFER_SIZE: usize = 8192;
+
+/// A change of a DRM device, announced through a uevent.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Event {
+    /// Any output of the device may have changed
+    Hotplug,
+    /// The connection state of a connector changed
+    Connector(connector::Handle),
+    /// The value of a connector's property changed
+    Property(connector::Handle, property::Handle),
+    /// A lease of the device was created or revoked
+    Lease,
+}
+
+/// A uevent of a DRM device.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Uevent {
+    pub(crate) device: (u32, u32),
+    pub(crate) event: Event,
+}
+
+impl Uevent {
+    /// Returns the major and minor number of the device node that sent the
+    /// event.
+    pub fn device(&self) -> (u32, u32) {
+        self.device
+    }
+
+    /// Returns the change that was announced.
+    pub fn event(&self) -> Event {
+        self.event
+    }
+}
+
+/// Parses a uevent message, as received from the kernel.
+///
+/// Returns `None` for messages that are not a `change` of a DRM device, or
+/// that do not announce a hotplug or lease. Messages relayed by udev, which
+/// start with a binary `libudev` header, are not parsed either.
+///
+/// # Example
+///
+/// ```
+/// use drm::control::hotplug::{self, Event};
+///
+/// let msg = b"change@/devices/pci0000:00/0000:00:02.0/drm/card0\0\
+///             ACTION=change\0\
+///             DEVPATH=/devices/pci0000:00/0000:00:02.0/drm/card0\0\
+///             SUBSYSTEM=drm\0\
+///             HOTPLUG=1\0\
+///             CONNECTOR=95\0\
+///             PROPERTY=104\0\
+///             DEVNAME=dri/card0\0\
+///             DEVTYPE=drm_minor\0\
+///             SEQNUM=4012\0\
+///             MAJOR=226\0\
+///             MINOR=0\0";
+///
+/// let uevent = hotplug::parse_uevent(msg).unwrap();
+/// assert_eq!(uevent.device(), (226, 0));
+///
+/// match uevent.event() {
+///     Event::Property(connector, property) => {
+///         assert_eq!(Into::<u32>::into(connector), 95);
+///         assert_eq!(Into::<u32>::into(property), 104);
+///     }
+///     event => panic!("unexpected event {:?}", event),
+/// }
+///
+/// let hotplug = b"change@/devices/platform/vc4/drm/card1\0ACTION=change\0\
+///                 SUBSYSTEM=drm\0HOTPLUG=1\0MAJOR=226\0MINOR=1\0";
+/// assert_eq!(hotplug::parse_uevent(hotplug).unwrap().event(), Event::Hotplug);
+///
+/// let input = b"add@/devices/virtual/input/input9\0ACTION=add\0SUBSYSTEM=input\0";
+/// assert_eq!(hotplug::parse_uevent(input), None);
+/// ```
+pub fn parse_uevent(msg: &[u8]) -> Option<Uevent> {
+    let mut action = None;
+    let mut subsystem = None;
+    let mut major = None;
+    let mut minor = None;
+    let mut hotplug = false;
+    let mut lease = false;
+    let mut connector = None;
+    let mut property = None;
+
+    // Kernel messages start with an `action@devpath` header.
+    let mut fields = msg.split(|&b| b == 0);
+    match fields.next() {
+        Some(header) if header.contains(&b'@') => (),
+        _ => return None,
+    }
+
+    for field in fields {
+        let field = match ::std::str::from_utf8(field) {
+            Ok(field) => field,
+            Err(_) => continue,
+        };
+
+        let mut kv = field.splitn(2, '=');
+        let (key, value) = match (kv.next(), kv.next()) {
+            (Some(key), Some(value)) => (key, value),
+            _ => continue,
+        };
+
+        match key {
+            "ACTION" => action = Some(value),
+            "SUBSYSTEM" => subsystem = Some(value),
+            "MAJOR" => major = value.parse().ok(),
+            "MINOR" => minor = value.parse().ok(),
+            "HOTPLUG" => hotplug = value == "1",
+            "LEASE" => lease = value == "1",
+            "CONNECTOR" => connector = value.parse().ok().and_then(control::from_u32),
+            "PROPERTY" => property = value.parse().ok().and_then(control::from_u32),
+            _ => (),
+        }
+    }
+
+    if action != Some("change") || subsystem != Some("drm") {
+        return None;
+    }
+
+    let event = match (hotplug, lease, connector, property) {
+        (true, _, Some(connector), Some(property)) => Event::Property(connector, property),
+        (true, _, Some(connector), None) => Event::Connector(connector),
+        (true, _, None, _) => Event::Hotplug,
+        (false, true, _, _) => Event::Lease,
+        _ => return None,
+    };
+
+    Some(Uevent {
+        device: (major?, minor?),
+        event: event,
+    })
+}
+
+/// Receives the uevents of a single DRM device.
+///
+/// The monitor is non-blocking. Its file descriptor becomes readable when
+/// uevents are pending, so it can be added to an event loop.
+#[derive(Debug)]
+pub struct HotplugMonitor {
+    fd: RawFd,
+    device: (u32, u32),
+}
+
+impl HotplugMonitor {
+    /// Opens a uevent socket, filtering the events of the given device.
+    pub fn new<D: Device>(dev: &D) -> Result<HotplugMonitor, SystemError> {
+        let device = unsafe {
+            let mut stat: libc::stat = mem::zeroed();
+            if libc::fstat(dev.as_raw_fd(), &mut stat) < 0 {
+                return Err(Errno::last().into());
+            }
+            device_numbers(stat.st_rdev as u64)
+        };
+
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+                libc::NETLINK_KOBJECT_UEVENT,
+            )
+        };
+
+        if fd < 0 {
+            return Err(Errno::last().into());
+        }
+
+        let monitor = HotplugMonitor {
+            fd: fd,
+            device: device,
+        };
+
+        unsafe {
+            let mut addr: libc::sockaddr_nl = mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            addr.nl_groups = KERNEL_UEVENT_GROUP;
+
+            let res = libc::bind(
+                fd,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            );
+
+            if res < 0 {
+                return Err(Errno::last().into());
+            }
+        }
+
+        Ok(monitor)
+    }
+
+    /// Returns the major and minor number of the monitored device.
+    pub fn device(&self) -> (u32, u32) {
+        self.device
+    }
+
+    /// Returns the next pending event of the monitored device.
+    ///
+    /// Returns `None` once no more uevents are pending. Uevents of other
+    /// devices are skipped.
+    pub fn next_event(&self) -> Result<Option<Event>, SystemError> {
+        let mut buffer = [0u8; UEVENT_BUFFER_SIZE];
+
+        loop {
+            let mut addr: libc::sockaddr_nl = unsafe { mem::zeroed() };
+            let mut addr_len = mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+
+            let len = unsafe {
+                libc::recvfrom(
+                    self.fd,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                    0,
+                    &mut addr as *mut libc::sockaddr_nl as *mut libc::sockaddr,
+                    &mut addr_len,
+                )
+            };
+
+            if len < 0 {
+                return match Errno::last() {
+                    Errno::EAGAIN => Ok(None),
+                    Errno::EINTR => continue,
+                    errno => Err(errno.into()),
+                };
+            }
+
+            // Only trust messages sent by the kernel itself.
+            if addr.nl_pid != 0 {
+                continue;
+            }
+
+            match parse_uevent(&buffer[..len as usize]) {
+                Some(ref uevent) if uevent.device == self.device => return Ok(Some(uevent.event)),
+                _ => continue,
+            }
+        }
+    }
+}
+
+impl AsRawFd for HotplugMonitor {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl Drop for HotplugMonitor {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Splits a device number into its major and minor number.
+fn device_numbers(dev: u64) -> (u32, u32) {
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    (major as u32, minor as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uevent(header: &str, fields: &[&str]) -> Vec<u8> {
+        let mut msg = header.as_bytes().to_vec();
+        msg.push(0);
+        for field in fields {
+            msg.extend_from_slice(field.as_bytes());
+            msg.push(0);
+        }
+        msg
+    }
+
+    fn card(minor: u32, fields: &[&str]) -> Vec<u8> {
+        let devpath = format!("/devices/pci0000:00/0000:00:02.0/drm/card{}", minor);
+        let mut all = vec![
+            "ACTION=change".to_string(),
+            format!("DEVPATH={}", devpath),
+            "SUBSYSTEM=drm".to_string(),
+        ];
+        all.extend(fields.iter().map(|f| f.to_string()));
+        all.push("MAJOR=226".to_string());
+        all.push(format!("MINOR={}", minor));
+
+        let all: Vec<&str> = all.iter().map(|f| f.as_str()).collect();
+        uevent(&format!("change@{}", devpath), &all)
+    }
+
+    fn event(msg: &[u8]) -> Option<Event> {
+        parse_uevent(msg).map(|uevent| uevent.event())
+    }
+
+    fn handle<T: control::ResourceHandle>(id: u32) -> T {
+        control::from_u32(id).unwrap()
+    }
+
+    #[test]
+    fn hotplug_connector_and_property() {
+        assert_eq!(event(&card(0, &["HOTPLUG=1"])), Some(Event::Hotplug));
+        assert_eq!(event(&card(0, &["HOTPLUG=1", "CONNECTOR=95"])), Some(Event::Connector(handle(95))));
+        assert_eq!(
+            event(&card(0, &["HOTPLUG=1", "CONNECTOR=95", "PROPERTY=104"])),
+            Some(Event::Property(handle(95), handle(104)))
+        );
+    }
+
+    #[test]
+    fn non_drm_subsystem() {
+        let msg = uevent(
+            "change@/devices/platform/i8042/serio0/input/input3",
+            &["ACTION=change", "SUBSYSTEM=input", "HOTPLUG=1", "MAJOR=13", "MINOR=67"],
+        );
+        assert_eq!(parse_uevent(&msg), None);
+    }
+
+    #[test]
+    fn other_actions() {
+        let msg = uevent(
+            "add@/devices/pci0000:00/0000:00:02.0/drm/card0",
+            &["ACTION=add", "SUBSYSTEM=drm", "HOTPLUG=1", "MAJOR=226", "MINOR=0"],
+        );
+        assert_eq!(parse_uevent(&msg), None);
+    }
+
+    #[test]
+    fn other_card() {
+        let uevent = parse_uevent(&card(1, &["HOTPLUG=1"])).unwrap();
+        assert_eq!(uevent.device(), (226, 1));
+        assert_eq!(uevent.event(), Event::Hotplug);
+
+        let render = parse_uevent(&card(128, &["HOTPLUG=1"])).unwrap();
+        assert_eq!(render.device(), (226, 128));
+    }
+
+    #[test]
+    fn connector_devpath_has_no_device_numbers() {
+        let msg = uevent(
+            "change@/devices/pci0000:00/0000:00:02.0/drm/card0/card0-DP-1",
+            &["ACTION=change", "SUBSYSTEM=drm", "HOTPLUG=1"],
+        );
+        assert_eq!(parse_uevent(&msg), None);
+    }
+
+    #[test]
+    fn libudev_framed() {
+        // The libudev header: magic, header size, property offset and
+        // length, and filter hashes, followed by the properties.
+        let mut msg = b"libudev\0".to_vec();
+        msg.extend_from_slice(&0xfeed_cafe_u32.to_be_bytes());
+        msg.extend_from_slice(&40u32.to_ne_bytes());
+        msg.extend_from_slice(&40u32.to_ne_bytes());
+        msg.extend_from_slice(&0u32.to_ne_bytes());
+        msg.extend_from_slice(&[0; 16]);
+        msg.extend_from_slice(&card(0, &["HOTPLUG=1"])[..]);
+
+        assert_eq!(parse_uevent(&msg), None);
+    }
+
+    #[test]
+    fn missing_and_garbage_ids() {
+        // Without a usable connector, the whole device has to be probed.
+        assert_eq!(event(&card(0, &["HOTPLUG=1", "CONNECTOR="])), Some(Event::Hotplug));
+        assert_eq!(event(&card(0, &["HOTPLUG=1", "CONNECTOR=0"])), Some(Event::Hotplug));
+        assert_eq!(event(&card(0, &["HOTPLUG=1", "CONNECTOR=DP-1"])), Some(Event::Hotplug));
+        assert_eq!(event(&card(0, &["HOTPLUG=1", "CONNECTOR=-5", "PROPERTY=104"])), Some(Event::Hotplug));
+        assert_eq!(event(&card(0, &["HOTPLUG=1", "CONNECTOR=99999999999"])), Some(Event::Hotplug));
+        assert_eq!(event(&card(0, &["HOTPLUG=1", "PROPERTY=104"])), Some(Event::Hotplug));
+
+        assert_eq!(
+            event(&card(0, &["HOTPLUG=1", "CONNECTOR=95", "PROPERTY=0x68"])),
+            Some(Event::Connector(handle(95)))
+        );
+        assert_eq!(
+            event(&card(0, &["HOTPLUG=1", "CONNECTOR=95", "PROPERTY"])),
+            Some(Event::Connector(handle(95)))
+        );
+    }
+
+    #[test]
+    fn missing_device_numbers() {
+        let msg = uevent(
+            "change@/devices/pci0000:00/0000:00:02.0/drm/card0",
+            &["ACTION=change", "SUBSYSTEM=drm", "HOTPLUG=1", "MAJOR=226"],
+        );
+        assert_eq!(parse_uevent(&msg), None);
+    }
+
+    #[test]
+    fn lease() {
+        assert_eq!(event(&card(0, &["LEASE=1"])), Some(Event::Lease));
+        assert_eq!(event(&card(0, &["LEASE=0"])), None);
+        assert_eq!(event(&card(0, &["HOTPLUG=1", "LEASE=1"])), Some(Event::Hotplug));
+    }
+
+    #[test]
+    fn no_event() {
+        assert_eq!(event(&card(0, &[])), None);
+        assert_eq!(event(&card(0, &["HOTPLUG=0", "CONNECTOR=95"])), None);
+    }
+
+    #[test]
+    fn truncated_and_invalid_utf8() {
+        assert_eq!(parse_uevent(b""), None);
+        assert_eq!(parse_uevent(b"change@/devices/pci0000:00/0000:00:02.0/drm/card0"), None);
+
+        let mut msg = card(0, &["HOTPLUG=1"]);
+        msg.extend_from_slice(b"CONNECTOR=\xff\xfe\0");
+        assert_eq!(event(&msg), Some(Event::Hotplug));
+    }
+
+    #[test]
+    fn device_number_split() {
+        assert_eq!(device_numbers(0xe200), (226, 0));
+        assert_eq!(device_numbers(0xe280), (226, 128));
+        assert_eq!(device_numbers(0x1000_123f_ff45), (0x1fff, 0x12345));
+    }
+}
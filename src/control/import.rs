@@ -0,0 +1,443 @@
+//! # Import
+//!
+//! Buffers of other devices, shown without copying them.
+//!
+//! Video decoders, cameras and other GPUs hand out their buffers as one
+//! dma-buf per plane, or as a single dma-buf holding all planes at different
+//! offsets. An `ImportedBuffer` turns these into GEM handles of the device,
+//! which a framebuffer can then be added for.
+//!
+//! The layout of the planes is described by a format and a modifier, and
+//! must be one the display engine can scan out. Once imported, the dma-bufs
+//! may be closed, the GEM handles keep the memory alive.
+//!
+//! A device has a single GEM handle per buffer. Importing a dma-buf that was
+//! exported by the same device, e.g. from a `DumbBuffer`, returns the handle
+//! the buffer already has. Closing it would pull the buffer out from under
+//! its exporter, so such dma-bufs must be imported with
+//! `ImportedBuffer::new_unowned`.
+//!
+//! # Example
+//!
+//! ```
+//! use drm::buffer::format::PixelFormat;
+//! use drm::buffer::modifier::Modifier;
+//! use drm::control::import::{DmaBufPlane, ImportedBuffer};
+//! use drm::control::owned::OwnedFramebuffer;
+//! use drm::control::Device;
+//! use drm::SystemError;
+//! use std::os::unix::io::RawFd;
+//!
+//! // An NV12 frame of a video decoder, with both planes in one dma-buf.
+//! fn show_frame<D: Device>(dev: &D, fd: RawFd) -> Result<OwnedFramebuffer<&D>, SystemError> {
+//!     let planes = [
+//!         DmaBufPlane { fd: fd, offset: 0, pitch: 1920 },
+//!         DmaBufPlane { fd: fd, offset: 1920 * 1088, pitch: 1920 },
+//!     ];
+//!
+//!     let buffer = ImportedBuffer::new(dev, (1920, 1080), PixelFormat::NV12, Modifier::LINEAR, &planes)?;
+//!     buffer.add_framebuffer()
+//! }
+//! ```
+
+use buffer::format::PixelFormat;
+use buffer::modifier::Modifier;
+use buffer::{self, PlanarBuffer};
+use control::owned::{OwnedFramebuffer, OwnedGemHandle};
+use control::Device;
+use drm_ffi as ffi;
+use drm_ffi::result::SystemError;
+
+use std::ops::Deref;
+use std::os::unix::io::RawFd;
+
+/// A plane of a buffer, stored in a dma-buf.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct DmaBufPlane {
+    /// The dma-buf the plane is stored in
+    pub fd: RawFd,
+    /// The offset of the plane in the dma-buf, in bytes
+    pub offset: u32,
+    /// The distance between the rows of the plane, in bytes
+    pub pitch: u32,
+}
+
+/// A buffer of one to four planes, imported from dma-bufs.
+///
+/// The GEM handles of the planes are closed on drop, unless the buffer was
+/// imported with `new_unowned`.
+#[derive(Debug)]
+pub struct ImportedBuffer<D>
+where
+    D: Deref + Clone,
+    D::Target: Device,
+{
+    dev: D,
+    size: (u32, u32),
+    format: PixelFormat,
+    modifier: Modifier,
+    planes: usize,
+    handles: [Option<buffer::Handle>; 4],
+    pitches: [u32; 4],
+    offsets: [u32; 4],
+    owns_handles: bool,
+    // Each GEM handle once, as planes may share a dma-buf.
+    owned: Vec<OwnedGemHandle<D>>,
+}
+
+impl<D> ImportedBuffer<D>
+where
+    D: Deref + Clone,
+    D::Target: Device,
+{
+    /// Imports the planes of a buffer, taking ownership of their GEM handles.
+    ///
+    /// `Modifier::INVALID` leaves the layout up to the driver, as it is for
+    /// buffers shared without a modifier. Fails with
+    /// `SystemError::InvalidArgument` if there are fewer planes than the
+    /// format has, or more than four.
+    ///
+    /// The dma-bufs must not have been exported by this device, or imported
+    /// into it elsewhere, as their handles would then be closed twice. Use
+    /// `new_unowned` for those.
+    pub fn new(
+        dev: D,
+        size: (u32, u32),
+        format: PixelFormat,
+        modifier: Modifier,
+        planes: &[DmaBufPlane],
+    ) -> Result<ImportedBuffer<D>, SystemError> {
+        ImportedBuffer::import_planes(dev, size, format, modifier, planes, true)
+    }
+
+    /// Imports the planes of a buffer, leaving their GEM handles open on drop.
+    ///
+    /// This is the way to import dma-bufs exported by the device itself, whose
+    /// handles belong to their exporter. Handles of other dma-bufs are leaked
+    /// unless closed with `Device::close_buffer`.
+    pub fn new_unowned(
+        dev: D,
+        size: (u32, u32),
+        format: PixelFormat,
+        modifier: Modifier,
+        planes: &[DmaBufPlane],
+    ) -> Result<ImportedBuffer<D>, SystemError> {
+        ImportedBuffer::import_planes(dev, size, format, modifier, planes, false)
+    }
+
+    fn import_planes(
+        dev: D,
+        size: (u32, u32),
+        format: PixelFormat,
+        modifier: Modifier,
+        planes: &[DmaBufPlane],
+        owns_handles: bool,
+    ) -> Result<ImportedBuffer<D>, SystemError> {
+        // Modifiers such as compression may add auxiliary planes.
+        if planes.len() < format.info().planes() || planes.len() > 4 {
+            return Err(SystemError::InvalidArgument);
+        }
+
+        let mut buffer = ImportedBuffer {
+            dev: dev,
+            size: size,
+            format: format,
+            modifier: modifier,
+            planes: planes.len(),
+            handles: [None; 4],
+            pitches: [0; 4],
+            offsets: [0; 4],
+            owns_handles: owns_handles,
+            owned: Vec::new(),
+        };
+
+        for (i, plane) in planes.iter().enumerate() {
+            let shared = planes[..i].iter().position(|other| other.fd == plane.fd);
+
+            let handle = match shared {
+                Some(j) => buffer.handles[j].unwrap(),
+                None => buffer.import(plane.fd)?,
+            };
+
+            buffer.handles[i] = Some(handle);
+            buffer.pitches[i] = plane.pitch;
+            buffer.offsets[i] = plane.offset;
+        }
+
+        Ok(buffer)
+    }
+
+    /// Imports a dma-buf, owning its handle unless it is owned already.
+    ///
+    /// Different file descriptors of the same dma-buf import as the same
+    /// handle, which must only be closed once.
+    fn import(&mut self, fd: RawFd) -> Result<buffer::Handle, SystemError> {
+        let handle = self.dev.prime_fd_to_buffer(fd)?;
+
+        if self.owns_handles && self.owned.iter().all(|owned| owned.handle() != handle) {
+            self.owned.push(OwnedGemHandle::from_raw(self.dev.clone(), handle));
+        }
+
+        Ok(handle)
+    }
+
+    /// Returns whether the GEM handles of the buffer are closed on drop.
+    pub fn owns_handles(&self) -> bool {
+        self.owns_handles
+    }
+
+    /// Returns the modifier of the buffer.
+    pub fn modifier(&self) -> Modifier {
+        self.modifier
+    }
+
+    /// Returns the number of planes of the buffer.
+    pub fn planes(&self) -> usize {
+        self.planes
+    }
+
+    /// Returns the modifier of each plane, as passed to
+    /// `Device::add_planar_framebuffer`.
+    pub fn modifiers(&self) -> [u64; 4] {
+        let mut modifiers = [0; 4];
+        if self.modifier != Modifier::INVALID {
+            for modifier in modifiers.iter_mut().take(self.planes) {
+                *modifier = self.modifier.into();
+            }
+        }
+        modifiers
+    }
+
+    /// Adds a framebuffer for the buffer.
+    ///
+    /// The modifier is passed along unless it is `Modifier::INVALID`, which
+    /// drivers without modifier support require.
+    pub fn add_framebuffer(&self) -> Result<OwnedFramebuffer<D>, SystemError> {
+        let flags = if self.modifier != Modifier::INVALID {
+            ffi::DRM_MODE_FB_MODIFIERS
+        } else {
+            0
+        };
+
+        OwnedFramebuffer::add_planar(self.dev.clone(), self, &self.modifiers(), flags)
+    }
+
+    /// Returns the device the buffer was imported into.
+    pub fn device(&self) -> &D {
+        &self.dev
+    }
+}
+
+impl<D> PlanarBuffer for ImportedBuffer<D>
+where
+    D: Deref + Clone,
+    D::Target: Device,
+{
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    fn pitches(&self) -> [u32; 4] {
+        self.pitches
+    }
+
+    fn handles(&self) -> [Option<buffer::Handle>; 4] {
+        self.handles
+    }
+
+    fn offsets(&self) -> [u32; 4] {
+        self.offsets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use control::{framebuffer, RawResourceHandle};
+
+    use std::cell::RefCell;
+    use std::os::unix::io::AsRawFd;
+
+    fn handle(id: u32) -> buffer::Handle {
+        buffer::Handle::from(RawResourceHandle::new(id).unwrap())
+    }
+
+    /// The handles, modifiers and flags a framebuffer was added with.
+    type AddedFramebuffer = ([Option<buffer::Handle>; 4], [u64; 4], u32);
+
+    /// A device that imports dma-bufs as fixed GEM handles, and records what
+    /// is done with them.
+    #[derive(Debug, Default)]
+    struct Mock {
+        // The GEM handle each dma-buf imports as.
+        dma_bufs: Vec<(RawFd, u32)>,
+        imported: RefCell<Vec<RawFd>>,
+        closed: RefCell<Vec<buffer::Handle>>,
+        framebuffers: RefCell<Vec<AddedFramebuffer>>,
+    }
+
+    impl Mock {
+        fn new(dma_bufs: &[(RawFd, u32)]) -> Mock {
+            Mock {
+                dma_bufs: dma_bufs.to_vec(),
+                ..Mock::default()
+            }
+        }
+    }
+
+    impl AsRawFd for Mock {
+        fn as_raw_fd(&self) -> RawFd {
+            -1
+        }
+    }
+
+    impl ::Device for Mock {}
+
+    impl Device for Mock {
+        fn prime_fd_to_buffer(&self, fd: RawFd) -> Result<buffer::Handle, SystemError> {
+            self.imported.borrow_mut().push(fd);
+            self.dma_bufs
+                .iter()
+                .find(|&&(buf, _)| buf == fd)
+                .map(|&(_, id)| handle(id))
+                .ok_or(SystemError::InvalidFileDescriptor)
+        }
+
+        fn close_buffer(&self, handle: buffer::Handle) -> Result<(), SystemError> {
+            self.closed.borrow_mut().push(handle);
+            Ok(())
+        }
+
+        fn add_planar_framebuffer<B>(
+            &self,
+            planar_buffer: &B,
+            modifiers: &[u64; 4],
+            flags: u32,
+        ) -> Result<framebuffer::Handle, SystemError>
+        where
+            B: buffer::PlanarBuffer + ?Sized,
+        {
+            self.framebuffers.borrow_mut().push((planar_buffer.handles(), *modifiers, flags));
+            Ok(::control::from_u32(90).unwrap())
+        }
+
+        fn destroy_framebuffer(&self, _handle: framebuffer::Handle) -> Result<(), SystemError> {
+            Ok(())
+        }
+    }
+
+    fn plane(fd: RawFd, offset: u32) -> DmaBufPlane {
+        DmaBufPlane {
+            fd: fd,
+            offset: offset,
+            pitch: 1920,
+        }
+    }
+
+    #[test]
+    fn planes_of_one_fd_are_imported_once() {
+        let dev = Mock::new(&[(10, 1)]);
+        let planes = [plane(10, 0), plane(10, 1920 * 1088)];
+
+        {
+            let buffer = ImportedBuffer::new(&dev, (1920, 1080), PixelFormat::NV12, Modifier::LINEAR, &planes).unwrap();
+            assert_eq!(buffer.handles(), [Some(handle(1)), Some(handle(1)), None, None]);
+            assert_eq!(buffer.offsets(), [0, 1920 * 1088, 0, 0]);
+            assert_eq!(buffer.pitches(), [1920, 1920, 0, 0]);
+            assert_eq!(*dev.imported.borrow(), vec![10]);
+        }
+
+        assert_eq!(*dev.closed.borrow(), vec![handle(1)]);
+    }
+
+    #[test]
+    fn fds_of_one_dma_buf_close_their_handle_once() {
+        // Two file descriptors of the same dma-buf import as the same handle.
+        let dev = Mock::new(&[(10, 1), (11, 1), (12, 2)]);
+        let planes = [plane(10, 0), plane(11, 1920 * 1080), plane(12, 0)];
+
+        {
+            let buffer = ImportedBuffer::new(&dev, (1920, 1080), PixelFormat::YUV420, Modifier::LINEAR, &planes).unwrap();
+            assert_eq!(buffer.handles(), [Some(handle(1)), Some(handle(1)), Some(handle(2)), None]);
+            assert_eq!(*dev.imported.borrow(), vec![10, 11, 12]);
+        }
+
+        assert_eq!(*dev.closed.borrow(), vec![handle(1), handle(2)]);
+    }
+
+    #[test]
+    fn unowned_handles_stay_open() {
+        let dev = Mock::new(&[(10, 1)]);
+
+        {
+            let buffer = ImportedBuffer::new_unowned(&dev, (64, 64), PixelFormat::XRGB8888, Modifier::LINEAR, &[plane(10, 0)]).unwrap();
+            assert!(!buffer.owns_handles());
+            assert_eq!(buffer.handles(), [Some(handle(1)), None, None, None]);
+        }
+
+        assert!(dev.closed.borrow().is_empty());
+    }
+
+    #[test]
+    fn failed_import_closes_earlier_handles() {
+        let dev = Mock::new(&[(10, 1)]);
+        let planes = [plane(10, 0), plane(11, 0)];
+
+        assert!(ImportedBuffer::new(&dev, (1920, 1080), PixelFormat::NV12, Modifier::LINEAR, &planes).is_err());
+        assert_eq!(*dev.closed.borrow(), vec![handle(1)]);
+    }
+
+    #[test]
+    fn plane_count_is_checked() {
+        let dev = Mock::new(&[(10, 1)]);
+
+        assert!(ImportedBuffer::new(&dev, (1920, 1080), PixelFormat::NV12, Modifier::LINEAR, &[plane(10, 0)]).is_err());
+        assert!(ImportedBuffer::new(&dev, (1920, 1080), PixelFormat::XRGB8888, Modifier::LINEAR, &[plane(10, 0); 5]).is_err());
+        assert!(dev.imported.borrow().is_empty());
+    }
+
+    #[test]
+    fn modifier_is_passed_per_plane() {
+        let dev = Mock::new(&[(10, 1)]);
+        let modifier = Modifier::I915_Y_TILED_CCS;
+        let planes = [plane(10, 0), plane(10, 1920 * 1088 * 4)];
+
+        let buffer = ImportedBuffer::new(&dev, (1920, 1080), PixelFormat::XRGB8888, modifier, &planes).unwrap();
+        let raw: u64 = modifier.into();
+        assert_eq!(buffer.modifiers(), [raw, raw, 0, 0]);
+
+        buffer.add_framebuffer().unwrap();
+        assert_eq!(dev.framebuffers.borrow()[0].1, [raw, raw, 0, 0]);
+        assert_eq!(dev.framebuffers.borrow()[0].2, ffi::DRM_MODE_FB_MODIFIERS);
+    }
+
+    #[test]
+    fn linear_modifier_is_explicit() {
+        let dev = Mock::new(&[(10, 1)]);
+
+        let buffer = ImportedBuffer::new(&dev, (64, 64), PixelFormat::XRGB8888, Modifier::LINEAR, &[plane(10, 0)]).unwrap();
+        assert_eq!(buffer.modifiers(), [0; 4]);
+
+        buffer.add_framebuffer().unwrap();
+        assert_eq!(dev.framebuffers.borrow()[0].2, ffi::DRM_MODE_FB_MODIFIERS);
+    }
+
+    #[test]
+    fn invalid_modifier_is_left_out() {
+        let dev = Mock::new(&[(10, 1)]);
+        let planes = [plane(10, 0), plane(10, 1920 * 1088)];
+
+        let buffer = ImportedBuffer::new(&dev, (1920, 1080), PixelFormat::NV12, Modifier::INVALID, &planes).unwrap();
+        assert_eq!(buffer.modifiers(), [0; 4]);
+
+        buffer.add_framebuffer().unwrap();
+        let framebuffers = dev.framebuffers.borrow();
+        assert_eq!(framebuffers[0].0, [Some(handle(1)), Some(handle(1)), None, None]);
+        assert_eq!(framebuffers[0].1, [0; 4]);
+        assert_eq!(framebuffers[0].2, 0);
+    }
+}
@@ -0,0 +1,133 @@
+//! # Link
+//!
+//! Monitoring and recovery of the link between a connector and its display.
+//!
+//! When link training fails after a modeset has already succeeded (typically
+//! on DisplayPort), the kernel sets the `link-status` property of the connector
+//! to `Bad` and sends a hotplug event. The display stays dark until userspace
+//! commits the mode again, usually after pruning modes that no longer fit the
+//! reduced link bandwidth.
+
+use control;
+use control::atomic::AtomicModeReq;
+use control::{connector, property, AtomicCommitFlags, Device};
+use drm_ffi as ffi;
+use drm_ffi::result::SystemError;
+
+/// The state of the link of a connector.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// The link is working
+    Good,
+    /// Link training failed and the mode has to be committed again
+    Bad,
+}
+
+impl LinkStatus {
+    /// Returns the name the kernel uses for this value of the property.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            LinkStatus::Good => "Good",
+            LinkStatus::Bad => "Bad",
+        }
+    }
+}
+
+impl From<property::RawValue> for LinkStatus {
+    fn from(n: property::RawValue) -> Self {
+        match n as u32 {
+            ffi::DRM_MODE_LINK_STATUS_BAD => LinkStatus::Bad,
+            _ => LinkStatus::Good,
+        }
+    }
+}
+
+/// Returns the `link-status` of a connector.
+///
+/// Returns `None` if the driver does not expose the property.
+pub fn link_status<D: Device>(
+    dev: &D,
+    connector: connector::Handle,
+) -> Result<Option<LinkStatus>, SystemError> {
+    Ok(dev
+        .find_property(connector, "link-status")?
+        .map(|(_, value)| LinkStatus::from(value)))
+}
+
+/// Sets the `link-status` of a connector in an atomic request.
+///
+/// The kernel only accepts `Good` from userspace. Requesting it on a connector
+/// with a bad link forces a full modeset on the connector's CRTC.
+pub fn add_link_status<D: Device>(
+    dev: &D,
+    req: &mut AtomicModeReq,
+    connector: connector::Handle,
+    status: LinkStatus,
+) -> Result<(), SystemError> {
+    let (prop, _) = dev
+        .find_property(connector, "link-status")?
+        .ok_or(SystemError::InvalidArgument)?;
+
+    let value = control::enum_value(dev, prop, status.name())?;
+    req.add_raw_property(connector.into(), prop, value);
+
+    Ok(())
+}
+
+/// Recovers a connector whose link went bad by committing its current mode
+/// again.
+///
+/// Returns `true` if a recovery modeset was committed, or `false` if the link
+/// is good or the connector is not driving a CRTC.
+pub fn recover_link<D: Device>(dev: &D, connector: connector::Handle) -> Result<bool, SystemError> {
+    let status = link_status(dev, connector)?;
+    let (crtc_prop, crtc) = match dev.find_property(connector, "CRTC_ID")? {
+        Some(prop) => prop,
+        None => return Ok(false),
+    };
+
+    if !needs_recovery(status, crtc) {
+        return Ok(false);
+    }
+
+    let mut req = AtomicModeReq::new();
+    add_link_status(dev, &mut req, connector, LinkStatus::Good)?;
+    req.add_raw_property(connector.into(), crtc_prop, crtc);
+
+    dev.atomic_commit(&[AtomicCommitFlags::AllowModeset], req)?;
+
+    Ok(true)
+}
+
+/// Returns whether a connector with the given `link-status`, driving the CRTC
+/// with the given id, needs a recovery modeset.
+fn needs_recovery(status: Option<LinkStatus>, crtc: property::RawValue) -> bool {
+    status == Some(LinkStatus::Bad) && crtc != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_status_values() {
+        assert_eq!(LinkStatus::from(0), LinkStatus::Good);
+        assert_eq!(LinkStatus::from(1), LinkStatus::Bad);
+
+        // Values added by newer kernels are not treated as failures.
+        assert_eq!(LinkStatus::from(7), LinkStatus::Good);
+        assert_eq!(LinkStatus::from(1 << 32), LinkStatus::Good);
+
+        assert_eq!(LinkStatus::Good.name(), "Good");
+        assert_eq!(LinkStatus::Bad.name(), "Bad");
+    }
+
+    #[test]
+    fn recovery() {
+        assert!(needs_recovery(Some(LinkStatus::Bad), 41));
+
+        assert!(!needs_recovery(Some(LinkStatus::Good), 41));
+        assert!(!needs_recovery(None, 41));
+        assert!(!needs_recovery(Some(LinkStatus::Bad), 0));
+    }
+}
@@ -32,19 +32,34 @@ use drm_ffi as ffi;
 use drm_ffi::result::SystemError;
 
 pub mod atomic;
+pub mod capture;
+pub mod color;
 pub mod connector;
 pub mod crtc;
+pub mod cursor;
+pub mod damage;
 pub mod dumbbuffer;
+pub mod edid;
 pub mod encoder;
+pub mod event;
 pub mod framebuffer;
+pub mod hdcp;
+pub mod hdr;
+pub mod hotplug;
+pub mod import;
+pub mod link;
+pub mod owned;
 pub mod plane;
 
 pub mod property;
+pub mod swapchain;
+pub mod topology;
 
 use self::dumbbuffer::*;
 use buffer;
+use buffer::dmabuf::DmaBuf;
 use std::mem;
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{FromRawFd, RawFd};
 
 use core::num::NonZeroU32;
 pub type RawResourceHandle = NonZeroU32;
@@ -57,6 +72,56 @@ pub fn from_u32<T: ResourceHandle>(raw: u32) -> Option<T> {
     RawResourceHandle::new(raw).map(|n| T::from(n))
 }
 
+/// Reads the blob property of a connector with the given name, from the
+/// property values of `info`.
+///
+/// The names of the properties are only read on the first call for an
+/// `info`, later calls only read the blob.
+pub(crate) fn connector_blob<D: Device + ?Sized>(
+    dev: &D,
+    info: &connector::Info,
+    name: &str,
+) -> Result<Option<Vec<u8>>, SystemError> {
+    let names = info.blob_names.get_or_lookup(|| -> Result<_, SystemError> {
+        let mut names = Vec::new();
+        for &(id, value) in info.properties() {
+            if value == 0 {
+                continue;
+            }
+
+            let prop = dev.get_property(id)?;
+            if let property::ValueType::Blob = prop.value_type() {
+                names.push((prop.name().to_bytes().to_vec(), value));
+            }
+        }
+
+        Ok(names)
+    })?;
+
+    match names.iter().find(|(n, _)| n.as_slice() == name.as_bytes()) {
+        Some(&(_, blob)) => dev.get_property_blob(blob).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Looks up the raw value of an enum property's entry by its name.
+///
+/// Fails with `SystemError::InvalidArgument` if the property is not an enum or
+/// has no entry with that name.
+pub(crate) fn enum_value<D: Device>(
+    dev: &D,
+    prop: property::Handle,
+    name: &str,
+) -> Result<property::RawValue, SystemError> {
+    match dev.get_property(prop)?.value_type() {
+        property::ValueType::Enum(values) => values
+            .get_value_from_name(name)
+            .map(|v| v.value())
+            .ok_or(SystemError::InvalidArgument),
+        _ => Err(SystemError::InvalidArgument),
+    }
+}
+
 /// This trait should be implemented by any object that acts as a DRM device and
 /// provides modesetting functionality.
 ///
@@ -130,21 +195,33 @@ pub trait Device: super::Device {
     }
 
     /// Returns information about a specific connector
-    fn get_connector(&self, handle: connector::Handle) -> Result<connector::Info, SystemError> {
-        // Maximum number of encoders is 3 due to kernel restrictions
-        let mut encoders = [0u32; 3];
-        let mut enc_slice = &mut encoders[..];
+    ///
+    /// With `force_probe` set, the kernel probes the connector first: it
+    /// detects the display and reads its EDID, which can take hundreds of
+    /// milliseconds. Otherwise the state cached by the kernel at the last
+    /// probe is returned, without touching the hardware.
+    fn get_connector(&self, handle: connector::Handle, force_probe: bool) -> Result<connector::Info, SystemError> {
+        let mut props = Vec::new();
+        let mut prop_values = Vec::new();
         let mut modes = Vec::new();
+        let mut encoders = Vec::new();
 
         let ffi_info = ffi::mode::get_connector(
             self.as_raw_fd(),
             handle.into(),
-            None,
-            None,
+            Some(&mut props),
+            Some(&mut prop_values),
             Some(&mut modes),
-            Some(&mut enc_slice),
+            Some(&mut encoders),
+            force_probe,
             )?;
 
+        let props: Vec<(property::Handle, property::RawValue)> = props
+            .into_iter()
+            .zip(prop_values)
+            .filter_map(|(id, value)| from_u32(id).map(|id| (id, value)))
+            .collect();
+
         let connector = connector::Info {
             handle: handle,
             interface: connector::Interface::from(ffi_info.connector_type),
@@ -154,14 +231,58 @@ pub trait Device: super::Device {
                 (0, 0) => None,
                 (x, y) => Some((x, y)),
             },
+            subpixel: connector::SubPixel::from(ffi_info.subpixel),
             modes: unsafe { mem::transmute(modes) },
-            encoders: unsafe { mem::transmute(encoders) },
+            encoders: encoders.into_iter().filter_map(from_u32).collect(),
             curr_enc: unsafe { mem::transmute(ffi_info.encoder_id) },
+            props: props,
+            blob_names: connector::BlobNames::default(),
         };
 
         Ok(connector)
     }
 
+    /// Returns the `Path` of a connector, if it has one.
+    ///
+    /// Only connectors created for DisplayPort MST ports have a path. The
+    /// `PATH` blob is read as it was when `info` was retrieved.
+    ///
+    /// The path is not part of `connector::Info`, as finding the blob takes
+    /// an ioctl per property of the connector, which `get_connector` would
+    /// then pay on every call. The property names are read once per `info`
+    /// and reused by `connector_tile`.
+    fn connector_path(&self, info: &connector::Info) -> Result<Option<connector::Path>, SystemError> {
+        Ok(connector_blob(self, info, "PATH")?.and_then(|data| connector::Path::from_blob(&data)))
+    }
+
+    /// Returns the `Tile` a connector drives, if the display attached to it
+    /// is part of a tiled display.
+    ///
+    /// The `TILE` blob is read as it was when `info` was retrieved. Like
+    /// `connector_path`, it is read on demand.
+    fn connector_tile(&self, info: &connector::Info) -> Result<Option<connector::Tile>, SystemError> {
+        Ok(connector_blob(self, info, "TILE")?.and_then(|data| connector::Tile::from_blob(&data)))
+    }
+
+    /// Finds a connector by its name, such as `HDMI-A-1` or `eDP-1`.
+    ///
+    /// Fails with `SystemError::InvalidArgument` if the name cannot be parsed.
+    fn find_connector_by_name(&self, name: &str) -> Result<Option<connector::Handle>, SystemError> {
+        let name: connector::Name = name.parse().map_err(|_| SystemError::InvalidArgument)?;
+
+        for &handle in self.resource_handles()?.connectors() {
+            let info = ffi::mode::get_connector(self.as_raw_fd(), handle.into(), None, None, None, None, false)?;
+
+            if connector::Interface::from(info.connector_type) == name.interface()
+                && info.connector_type_id == name.interface_id()
+            {
+                return Ok(Some(handle));
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Returns information about a specific encoder
     fn get_encoder(&self, handle: encoder::Handle) -> Result<encoder::Info, SystemError> {
         let info = ffi::mode::get_encoder(
@@ -244,7 +365,48 @@ pub trait Device: super::Device {
         Ok(fb)
     }
 
+    /// Returns information about a specific framebuffer, including its
+    /// format, modifier and the layout of each of its planes.
+    ///
+    /// The returned GEM handles are closed when the `PlanarInfo` is dropped.
+    fn get_planar_framebuffer(
+        &self,
+        handle: framebuffer::Handle,
+        ) -> Result<framebuffer::PlanarInfo<&Self>, SystemError> {
+        let info = ffi::mode::get_framebuffer2(
+            self.as_raw_fd(),
+            handle.into(),
+            )?;
+
+        let fb = framebuffer::PlanarInfo {
+            dev: self,
+            handle: handle,
+            size: (info.width, info.height),
+            pixel_format: info.pixel_format,
+            flags: info.flags,
+            buffers: [
+                RawResourceHandle::new(info.handles[0]).map(buffer::Handle::from),
+                RawResourceHandle::new(info.handles[1]).map(buffer::Handle::from),
+                RawResourceHandle::new(info.handles[2]).map(buffer::Handle::from),
+                RawResourceHandle::new(info.handles[3]).map(buffer::Handle::from),
+            ],
+            pitches: info.pitches,
+            offsets: info.offsets,
+            modifier: if info.flags & ffi::DRM_MODE_FB_MODIFIERS != 0 {
+                Some(info.modifier[0])
+            } else {
+                None
+            },
+        };
+
+        Ok(fb)
+    }
+
     /// Add a new framebuffer
+    ///
+    /// Fails with `SystemError::InvalidArgument` for formats that have no
+    /// legacy depth, see `PixelFormat::depth`. Use `add_planar_framebuffer`
+    /// for those.
     fn add_framebuffer<B>(
         &self,
         buffer: &B,
@@ -253,12 +415,14 @@ pub trait Device: super::Device {
         B: buffer::Buffer + ?Sized,
     {
         let (w, h) = buffer.size();
+        let format = buffer.format();
+        let depth = format.depth().ok_or(SystemError::InvalidArgument)?;
         let info = ffi::mode::add_fb(
             self.as_raw_fd(),
             w, h,
             buffer.pitch(),
-            buffer.format().bpp(),
-            buffer.format().depth(),
+            format.bpp(),
+            depth,
             buffer.handle().into(),
         )?;
 
@@ -453,12 +617,19 @@ pub trait Device: super::Device {
         Ok(())
     }
 
-    fn create_property_blob(&self, mode: Mode) -> Result<property::Value<'static>, SystemError> {
-        let mut raw_mode: ffi::drm_mode_modeinfo = mode.into();
+    /// Creates a property blob containing the raw bytes of `data`.
+    ///
+    /// The blob is owned by this file descriptor and must be destroyed with
+    /// `destroy_property_blob` once it is no longer referenced.
+    fn create_property_blob<T>(&self, data: &T) -> Result<property::Value, SystemError>
+    where
+        T: property::BlobData + ?Sized,
+    {
+        // `BlobData` guarantees that every byte of the value is initialized.
         let data = unsafe {
-            std::slice::from_raw_parts_mut(
-                mem::transmute(&mut raw_mode as *mut ffi::drm_mode_modeinfo),
-                mem::size_of::<ffi::drm_mode_modeinfo>()
+            std::slice::from_raw_parts(
+                data as *const T as *const u8,
+                mem::size_of_val(data)
             )
         };
         let blob = ffi::mode::create_property_blob(
@@ -475,8 +646,25 @@ pub trait Device: super::Device {
         Ok(())
     }
 
+    /// Returns the contents of a property blob.
+    fn get_property_blob(&self, blob: u64) -> Result<Vec<u8>, SystemError> {
+        let info = ffi::mode::get_property_blob(self.as_raw_fd(), blob as u32, None)?;
+
+        let mut data = vec![0u8; info.length as usize];
+        let len = {
+            let mut data_slice = &mut data[..];
+            ffi::mode::get_property_blob(self.as_raw_fd(), blob as u32, Some(&mut data_slice))?;
+            data_slice.len()
+        };
+        data.truncate(len);
+
+        Ok(data)
+    }
+
     /// Returns the set of `Mode`s that a particular connector supports.
-    fn get_modes(&self, handle: connector::Handle) -> Result<Vec<Mode>, SystemError> {
+    ///
+    /// See `get_connector` for the meaning of `force_probe`.
+    fn get_modes(&self, handle: connector::Handle, force_probe: bool) -> Result<Vec<Mode>, SystemError> {
         let mut modes = Vec::new();
 
         let _ffi_info = ffi::mode::get_connector(
@@ -486,6 +674,7 @@ pub trait Device: super::Device {
             None,
             Some(&mut modes),
             None,
+            force_probe,
             )?;
 
         Ok(unsafe { mem::transmute(modes) })
@@ -517,7 +706,29 @@ pub trait Device: super::Device {
 
         Ok(prop_val_set)
     }
-    
+
+    /// Looks up a property of a resource by its name.
+    ///
+    /// Returns the handle of the property and its current raw value, or `None`
+    /// if the resource does not expose a property with that name.
+    fn find_property<T: ResourceHandle>(
+        &self,
+        handle: T,
+        name: &str,
+    ) -> Result<Option<(property::Handle, property::RawValue)>, SystemError> {
+        let props = self.get_properties(handle)?;
+        let (ids, vals) = props.as_props_and_values();
+
+        for (&id, &val) in ids.iter().zip(vals.iter()) {
+            let info = self.get_property(id)?;
+            if info.name().to_bytes() == name.as_bytes() {
+                return Ok(Some((id, val)));
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Receive the currently set gamma ramp of a crtc
     fn get_gamma(&self, crtc: crtc::Handle, red: &mut [u16], green: &mut [u16], blue: &mut [u16]) -> Result<(), SystemError> {
         let crtc_info = self.get_crtc(crtc)?;
@@ -594,25 +805,37 @@ pub trait Device: super::Device {
     }
     /// Map the buffer for access
     fn map_dumb_buffer<'a>(&self, buffer: &'a mut DumbBuffer) -> Result<DumbMapping<'a>, SystemError> {
-        let info = drm_ffi::mode::dumbbuffer::map(self.as_raw_fd(), buffer.handle.into(), 0, 0)?;
-
-        let map = {
-            use ::nix::sys::mman;
-            let addr = core::ptr::null_mut();
-            let prot = mman::ProtFlags::PROT_READ | mman::ProtFlags::PROT_WRITE;
-            let flags = mman::MapFlags::MAP_SHARED;
-            let length = buffer.length;
-            let fd = self.as_raw_fd();
-            let offset = info.offset as i64;
-            unsafe { mman::mmap(addr, length, prot, flags, fd, offset)? }
-        };
+        let (map, layout) = dumbbuffer::map(self, buffer, true)?;
 
-        let mapping = DumbMapping {
+        Ok(DumbMapping {
             _phantom: ::std::marker::PhantomData,
-            map: unsafe { ::std::slice::from_raw_parts_mut(map as *mut _, buffer.length) },
-        };
+            map: map,
+            layout: layout,
+        })
+    }
 
-        Ok(mapping)
+    /// Map the buffer for reading
+    fn map_dumb_buffer_ref<'a>(&self, buffer: &'a DumbBuffer) -> Result<DumbMappingRef<'a>, SystemError> {
+        let (map, layout) = dumbbuffer::map(self, buffer, false)?;
+
+        Ok(DumbMappingRef {
+            _phantom: ::std::marker::PhantomData,
+            map: map,
+            layout: layout,
+        })
+    }
+
+    /// Map the buffer for access, without borrowing it
+    ///
+    /// The mapping may be sent to other threads. Nothing prevents other
+    /// mappings of the same buffer from writing to it concurrently.
+    fn map_dumb_buffer_owned(&self, buffer: &DumbBuffer) -> Result<OwnedDumbMapping, SystemError> {
+        let (map, layout) = dumbbuffer::map(self, buffer, true)?;
+
+        Ok(OwnedDumbMapping {
+            map: map,
+            layout: layout,
+        })
     }
 
     /// Free the memory resources of a dumb buffer
@@ -679,17 +902,59 @@ pub trait Device: super::Device {
         )
     }
 
+    /// Schedules a page flip of a CRTC to a new framebuffer.
+    ///
+    /// The flip happens on the next vblank, unless
+    /// `PageFlipFlags::AsyncPageFlip` is given. Only one flip per CRTC may be
+    /// pending at any time.
+    fn page_flip(
+        &self,
+        handle: crtc::Handle,
+        framebuffer: framebuffer::Handle,
+        flags: &[PageFlipFlags],
+    ) -> Result<(), SystemError> {
+        ffi::mode::page_flip(
+            self.as_raw_fd(),
+            handle.into(),
+            framebuffer.into(),
+            flags.iter().fold(0, |acc, x| acc | *x as u32),
+            0,
+        )
+    }
+
+    /// Reads the pending events of this device.
+    ///
+    /// This blocks until at least one event is available, unless the file
+    /// descriptor is non-blocking.
+    fn receive_events(&self) -> Result<Vec<event::Event>, SystemError> {
+        // The kernel never splits an event, so a single read of a buffer
+        // this large always returns complete events.
+        let mut buffer = [0u8; 1024];
+        let len = ::nix::unistd::read(self.as_raw_fd(), &mut buffer)?;
+
+        Ok(event::parse_events(&buffer[..len]))
+    }
+
     /// Convert a prime file descriptor to a GEM buffer handle
     fn prime_fd_to_buffer(&self, fd: RawFd) -> Result<buffer::Handle, SystemError> {
         let info = ffi::gem::fd_to_handle(self.as_raw_fd(), fd)?;
         Ok(unsafe { mem::transmute(info.handle) })
     }
 
-    /// Convert a prime file descriptor to a GEM buffer handle
+    /// Convert a GEM buffer handle to a prime file descriptor
     fn buffer_to_prime_fd(&self, handle: buffer::Handle, flags: u32) -> Result<RawFd, SystemError> {
         let info = ffi::gem::handle_to_fd(self.as_raw_fd(), handle.into(), flags)?;
         Ok(info.fd)
     }
+
+    /// Exports a GEM buffer as a dma-buf.
+    ///
+    /// Without `PrimeFlags::ReadWrite`, the dma-buf can only be mapped for
+    /// reading.
+    fn buffer_to_dma_buf(&self, handle: buffer::Handle, flags: &[PrimeFlags]) -> Result<DmaBuf, SystemError> {
+        let fd = self.buffer_to_prime_fd(handle, flags.iter().fold(0, |acc, x| acc | *x as u32))?;
+        Ok(unsafe { DmaBuf::from_raw_fd(fd) })
+    }
 }
 
 /// The set of [ResourceHandles](ResourceHandle.t.html) that a
@@ -783,6 +1048,7 @@ impl std::fmt::Debug for PlaneResourceHandles {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// A filter that can be used with a ResourceHandles to determine the set of
 /// Crtcs that can attach to a specific encoder.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CrtcListFilter(u32);
 
 /// Resolution and timing information for a display mode.
@@ -864,6 +1130,87 @@ impl std::fmt::Debug for Mode {
     }
 }
 
+/// Modes are serialized with the name and timing fields of
+/// `drm_mode_modeinfo`, so a deserialized mode is identical to the one the
+/// kernel handed out.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Mode")]
+struct ModeInfo {
+    name: String,
+    clock: u32,
+    hdisplay: u16,
+    hsync_start: u16,
+    hsync_end: u16,
+    htotal: u16,
+    hskew: u16,
+    vdisplay: u16,
+    vsync_start: u16,
+    vsync_end: u16,
+    vtotal: u16,
+    vscan: u16,
+    vrefresh: u32,
+    flags: u32,
+    #[serde(rename = "type")]
+    type_: u32,
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Mode {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mode = &self.mode;
+
+        ModeInfo {
+            name: ::util::c_name(&mode.name).into_owned(),
+            clock: mode.clock,
+            hdisplay: mode.hdisplay,
+            hsync_start: mode.hsync_start,
+            hsync_end: mode.hsync_end,
+            htotal: mode.htotal,
+            hskew: mode.hskew,
+            vdisplay: mode.vdisplay,
+            vsync_start: mode.vsync_start,
+            vsync_end: mode.vsync_end,
+            vtotal: mode.vtotal,
+            vscan: mode.vscan,
+            vrefresh: mode.vrefresh,
+            flags: mode.flags,
+            type_: mode.type_,
+        }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Mode {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, Unexpected};
+
+        let mode = ModeInfo::deserialize(deserializer)?;
+        let name = ::util::to_c_name(&mode.name)
+            .ok_or_else(|| D::Error::invalid_value(Unexpected::Str(&mode.name), &"a mode name"))?;
+
+        Ok(Mode {
+            mode: ffi::drm_mode_modeinfo {
+                name: name,
+                clock: mode.clock,
+                hdisplay: mode.hdisplay,
+                hsync_start: mode.hsync_start,
+                hsync_end: mode.hsync_end,
+                htotal: mode.htotal,
+                hskew: mode.hskew,
+                vdisplay: mode.vdisplay,
+                vsync_start: mode.vsync_start,
+                vsync_end: mode.vsync_end,
+                vtotal: mode.vtotal,
+                vscan: mode.vscan,
+                vrefresh: mode.vrefresh,
+                flags: mode.flags,
+                type_: mode.type_,
+            },
+        })
+    }
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PlaneType {
@@ -891,6 +1238,26 @@ impl PropertyValueSet {
 
 type ClipRect = ffi::drm_sys::drm_clip_rect;
 
+/// Flags for `Device::page_flip`.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PageFlipFlags {
+    /// Send a `PageFlipEvent` once the flip completed
+    PageFlipEvent = ffi::drm_sys::DRM_MODE_PAGE_FLIP_EVENT,
+    /// Flip immediately instead of waiting for the next vblank
+    AsyncPageFlip = ffi::drm_sys::DRM_MODE_PAGE_FLIP_ASYNC,
+}
+
+/// Flags for `Device::buffer_to_dma_buf`.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PrimeFlags {
+    /// Close the file descriptor on `exec`
+    CloseOnExec = ffi::drm_sys::DRM_CLOEXEC,
+    /// Allow mapping the dma-buf for writing
+    ReadWrite = ffi::drm_sys::DRM_RDWR,
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum AtomicCommitFlags {
@@ -898,4 +1265,47 @@ pub enum AtomicCommitFlags {
     Nonblock =  ffi::drm_sys::DRM_MODE_ATOMIC_NONBLOCK,
     AllowModeset = ffi::drm_sys::DRM_MODE_ATOMIC_ALLOW_MODESET,
     PageFlipEvent = ffi::drm_sys::DRM_MODE_PAGE_FLIP_EVENT,
-}
\ No newline at end of file
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn mode_round_trips_through_serde() {
+        let raw = ffi::drm_mode_modeinfo {
+            name: ::util::to_c_name("1920x1080i").unwrap(),
+            clock: 74250,
+            hdisplay: 1920,
+            hsync_start: 2008,
+            hsync_end: 2052,
+            htotal: 2200,
+            hskew: 1,
+            vdisplay: 1080,
+            vsync_start: 1084,
+            vsync_end: 1094,
+            vtotal: 1125,
+            vscan: 2,
+            vrefresh: 60,
+            flags: ffi::DRM_MODE_FLAG_INTERLACE | ffi::DRM_MODE_FLAG_NHSYNC,
+            type_: ffi::DRM_MODE_TYPE_PREFERRED | ffi::DRM_MODE_TYPE_DRIVER,
+        };
+
+        let json = serde_json::to_string(&Mode::from(raw)).unwrap();
+        let mode: Mode = serde_json::from_str(&json).unwrap();
+        let round_trip: ffi::drm_mode_modeinfo = mode.into();
+
+        assert_eq!(round_trip, raw);
+        assert_eq!(mode.name().to_str(), Ok("1920x1080i"));
+    }
+
+    #[test]
+    fn mode_with_long_name_is_rejected() {
+        let json = serde_json::to_string(&Mode::from(ffi::drm_mode_modeinfo::default()))
+            .unwrap()
+            .replace("\"name\":\"\"", &format!("\"name\":\"{}\"", "x".repeat(32)));
+
+        assert!(serde_json::from_str::<Mode>(&json).is_err());
+    }
+}
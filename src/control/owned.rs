@@ -0,0 +1,344 @@
+//! # Owned
+//!
+//! Kernel objects that are freed when they go out of scope.
+//!
+//! Framebuffers, dumb buffers, GEM handles and property blobs are owned by
+//! the file descriptor they were created on, and only freed when it is
+//! closed. The types of this module destroy their object on drop instead.
+//!
+//! Each type holds on to its device through any pointer to it, such as a
+//! `&Card`, `Rc<Card>` or `Arc<Card>`. Errors of destroying an object on
+//! drop are ignored, use the explicit `destroy` or `close` methods to handle
+//! them. `into_raw` releases ownership of the object and `from_raw` takes it
+//! over, to mix these types with the unowned handles of `Device`.
+
+use buffer::{self, format::PixelFormat, Buffer};
+use control::dumbbuffer::{DumbBuffer, DumbMapping, DumbMappingRef};
+use control::{framebuffer, property, Device};
+use drm_ffi::result::SystemError;
+
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::os::unix::io::RawFd;
+use std::ptr;
+
+/// Drops the device of an owned object without destroying the object.
+macro_rules! release {
+    ($owned:expr, $field:ident) => {{
+        let mut owned = ManuallyDrop::new($owned);
+        unsafe {
+            ptr::drop_in_place(&mut owned.dev);
+            ptr::read(&owned.$field)
+        }
+    }};
+}
+
+/// A framebuffer that is destroyed on drop.
+#[derive(Debug)]
+pub struct OwnedFramebuffer<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    dev: D,
+    handle: framebuffer::Handle,
+}
+
+impl<D> OwnedFramebuffer<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    /// Adds a framebuffer for a buffer. See `Device::add_framebuffer`.
+    pub fn add<B: Buffer + ?Sized>(dev: D, buffer: &B) -> Result<OwnedFramebuffer<D>, SystemError> {
+        let handle = dev.add_framebuffer(buffer)?;
+        Ok(OwnedFramebuffer::from_raw(dev, handle))
+    }
+
+    /// Adds a framebuffer for a planar buffer. See
+    /// `Device::add_planar_framebuffer`.
+    pub fn add_planar<B: buffer::PlanarBuffer + ?Sized>(
+        dev: D,
+        buffer: &B,
+        modifiers: &[u64; 4],
+        flags: u32,
+    ) -> Result<OwnedFramebuffer<D>, SystemError> {
+        let handle = dev.add_planar_framebuffer(buffer, modifiers, flags)?;
+        Ok(OwnedFramebuffer::from_raw(dev, handle))
+    }
+
+    /// Takes ownership of a framebuffer created on the device.
+    pub fn from_raw(dev: D, handle: framebuffer::Handle) -> OwnedFramebuffer<D> {
+        OwnedFramebuffer {
+            dev: dev,
+            handle: handle,
+        }
+    }
+
+    /// Returns the handle of the framebuffer.
+    pub fn handle(&self) -> framebuffer::Handle {
+        self.handle
+    }
+
+    /// Returns the device the framebuffer was created on.
+    pub fn device(&self) -> &D {
+        &self.dev
+    }
+
+    /// Releases ownership of the framebuffer, returning its handle.
+    pub fn into_raw(self) -> framebuffer::Handle {
+        release!(self, handle)
+    }
+
+    /// Destroys the framebuffer.
+    pub fn destroy(self) -> Result<(), SystemError> {
+        let result = self.dev.destroy_framebuffer(self.handle);
+        self.into_raw();
+        result
+    }
+}
+
+impl<D> Drop for OwnedFramebuffer<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    fn drop(&mut self) {
+        let _ = self.dev.destroy_framebuffer(self.handle);
+    }
+}
+
+/// A dumb buffer that is destroyed on drop.
+#[derive(Debug)]
+pub struct OwnedDumbBuffer<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    dev: D,
+    buffer: DumbBuffer,
+}
+
+impl<D> OwnedDumbBuffer<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    /// Creates a dumb buffer. See `Device::create_dumb_buffer`.
+    pub fn create(dev: D, size: (u32, u32), format: PixelFormat) -> Result<OwnedDumbBuffer<D>, SystemError> {
+        let buffer = dev.create_dumb_buffer(size, format)?;
+        Ok(OwnedDumbBuffer::from_raw(dev, buffer))
+    }
+
+    /// Takes ownership of a dumb buffer created on the device.
+    pub fn from_raw(dev: D, buffer: DumbBuffer) -> OwnedDumbBuffer<D> {
+        OwnedDumbBuffer {
+            dev: dev,
+            buffer: buffer,
+        }
+    }
+
+    /// Returns the unowned dumb buffer.
+    pub fn buffer(&self) -> &DumbBuffer {
+        &self.buffer
+    }
+
+    /// Returns the device the buffer was created on.
+    pub fn device(&self) -> &D {
+        &self.dev
+    }
+
+    /// Maps the buffer for access.
+    pub fn map(&mut self) -> Result<DumbMapping<'_>, SystemError> {
+        self.dev.map_dumb_buffer(&mut self.buffer)
+    }
+
+    /// Maps the buffer for reading.
+    pub fn map_ref(&self) -> Result<DumbMappingRef<'_>, SystemError> {
+        self.dev.map_dumb_buffer_ref(&self.buffer)
+    }
+
+    /// Releases ownership of the buffer.
+    pub fn into_raw(self) -> DumbBuffer {
+        release!(self, buffer)
+    }
+
+    /// Destroys the buffer.
+    pub fn destroy(self) -> Result<(), SystemError> {
+        let mut owned = ManuallyDrop::new(self);
+        let buffer = unsafe { ptr::read(&owned.buffer) };
+        let result = owned.dev.destroy_dumb_buffer(buffer);
+        unsafe { ptr::drop_in_place(&mut owned.dev) };
+        result
+    }
+}
+
+impl<D> Buffer for OwnedDumbBuffer<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    fn size(&self) -> (u32, u32) {
+        self.buffer.size()
+    }
+    fn format(&self) -> PixelFormat {
+        self.buffer.format()
+    }
+    fn pitch(&self) -> u32 {
+        self.buffer.pitch()
+    }
+    fn handle(&self) -> buffer::Handle {
+        self.buffer.handle()
+    }
+}
+
+impl<D> Drop for OwnedDumbBuffer<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    fn drop(&mut self) {
+        // The buffer is not used again, and has no drop glue of its own.
+        let buffer = unsafe { ptr::read(&self.buffer) };
+        let _ = self.dev.destroy_dumb_buffer(buffer);
+    }
+}
+
+/// A GEM handle that is closed on drop.
+///
+/// Closing a handle only drops this process' reference to the buffer. The
+/// buffer itself lives on as long as it is referenced elsewhere, e.g. by a
+/// framebuffer or a dma-buf.
+#[derive(Debug)]
+pub struct OwnedGemHandle<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    dev: D,
+    handle: buffer::Handle,
+}
+
+impl<D> OwnedGemHandle<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    /// Opens a buffer by its global name. See `Device::open_buffer`.
+    pub fn open(dev: D, name: buffer::Name) -> Result<OwnedGemHandle<D>, SystemError> {
+        let handle = dev.open_buffer(name)?;
+        Ok(OwnedGemHandle::from_raw(dev, handle))
+    }
+
+    /// Imports a dma-buf. See `Device::prime_fd_to_buffer`.
+    ///
+    /// Importing the same dma-buf twice returns the same handle, which must
+    /// only be owned once.
+    pub fn from_prime_fd(dev: D, fd: RawFd) -> Result<OwnedGemHandle<D>, SystemError> {
+        let handle = dev.prime_fd_to_buffer(fd)?;
+        Ok(OwnedGemHandle::from_raw(dev, handle))
+    }
+
+    /// Takes ownership of a GEM handle of the device.
+    pub fn from_raw(dev: D, handle: buffer::Handle) -> OwnedGemHandle<D> {
+        OwnedGemHandle {
+            dev: dev,
+            handle: handle,
+        }
+    }
+
+    /// Returns the GEM handle.
+    pub fn handle(&self) -> buffer::Handle {
+        self.handle
+    }
+
+    /// Returns the device the handle belongs to.
+    pub fn device(&self) -> &D {
+        &self.dev
+    }
+
+    /// Releases ownership of the handle.
+    pub fn into_raw(self) -> buffer::Handle {
+        release!(self, handle)
+    }
+
+    /// Closes the handle.
+    pub fn close(self) -> Result<(), SystemError> {
+        let result = self.dev.close_buffer(self.handle);
+        self.into_raw();
+        result
+    }
+}
+
+impl<D> Drop for OwnedGemHandle<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    fn drop(&mut self) {
+        let _ = self.dev.close_buffer(self.handle);
+    }
+}
+
+/// A property blob that is destroyed on drop.
+///
+/// Properties set to the blob keep it alive in the kernel, so it may be
+/// dropped once it was committed.
+#[derive(Debug)]
+pub struct OwnedBlob<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    dev: D,
+    id: u64,
+}
+
+impl<D> OwnedBlob<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    /// Creates a blob holding a value. See `Device::create_property_blob`.
+    pub fn create<T: property::BlobData + ?Sized>(dev: D, data: &T) -> Result<OwnedBlob<D>, SystemError> {
+        let id = dev.create_property_blob(data)?.into();
+        Ok(OwnedBlob::from_raw(dev, id))
+    }
+
+    /// Takes ownership of a blob created on the device.
+    pub fn from_raw(dev: D, id: u64) -> OwnedBlob<D> {
+        OwnedBlob { dev: dev, id: id }
+    }
+
+    /// Returns the id of the blob, the value of properties set to it.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the device the blob was created on.
+    pub fn device(&self) -> &D {
+        &self.dev
+    }
+
+    /// Releases ownership of the blob, returning its id.
+    pub fn into_raw(self) -> u64 {
+        release!(self, id)
+    }
+
+    /// Destroys the blob.
+    pub fn destroy(self) -> Result<(), SystemError> {
+        let result = self.dev.destroy_property_blob(self.id);
+        self.into_raw();
+        result
+    }
+}
+
+impl<D> Drop for OwnedBlob<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    fn drop(&mut self) {
+        let _ = self.dev.destroy_property_blob(self.id);
+    }
+}
@@ -21,6 +21,7 @@ use drm_ffi as ffi;
 /// A handle to a plane
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Handle(control::RawResourceHandle);
 
 impl Into<control::RawResourceHandle> for Handle {
@@ -55,6 +56,7 @@ impl std::fmt::Debug for Handle {
 
 /// Information about a plane
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Info {
     pub(crate) handle: Handle,
     pub(crate) crtc: Option<control::crtc::Handle>,
@@ -65,6 +67,32 @@ pub struct Info {
 }
 
 impl Info {
+    /// Creates information about a plane without querying a device.
+    ///
+    /// This is mostly useful to build `Topology` snapshots in tests.
+    /// `possible_crtcs` is a bitmask of indices into the CRTCs of the device.
+    /// Only the first eight formats are kept, as with `Device::get_plane`.
+    pub fn new(
+        handle: Handle,
+        crtc: Option<control::crtc::Handle>,
+        fb: Option<control::framebuffer::Handle>,
+        possible_crtcs: u32,
+        formats: &[u32],
+    ) -> Info {
+        let mut buf = [0; 8];
+        let len = std::cmp::min(buf.len(), formats.len());
+        buf[..len].copy_from_slice(&formats[..len]);
+
+        Info {
+            handle: handle,
+            crtc: crtc,
+            fb: fb,
+            pos_crtcs: possible_crtcs,
+            formats: buf,
+            fmt_len: formats.len(),
+        }
+    }
+
     /// Returns the handle to this plane.
     pub fn handle(&self) -> Handle {
         self.handle
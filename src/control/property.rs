@@ -20,6 +20,7 @@ pub type RawValue = u64;
 /// A handle to a property
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Handle(control::RawResourceHandle);
 
 impl Into<control::RawResourceHandle> for Handle {
@@ -54,15 +55,22 @@ impl std::fmt::Debug for Handle {
 
 /// Information about a property
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Info {
     pub(crate) handle: Handle,
     pub(crate) val_type: ValueType,
     pub(crate) mutable: bool,
     pub(crate) atomic: bool,
+    #[cfg_attr(feature = "serde", serde(rename = "raw", with = "raw_info"))]
     pub(crate) info: ffi::drm_mode_get_property
 }
 
 impl Info {
+    /// Returns the handle to this property.
+    pub fn handle(&self) -> Handle {
+        self.handle
+    }
+
     /// Returns the name of this property.
     pub fn name(&self) -> &std::ffi::CStr {
         unsafe { std::ffi::CStr::from_ptr(&self.info.name[0] as _) }
@@ -86,6 +94,7 @@ impl Info {
 
 /// A `ValueType` describes the types of value that a property uses.
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ValueType {
     /// A catch-all for any unknown types
     Unknown,
@@ -127,7 +136,7 @@ impl ValueType {
             ValueType::Boolean => Value::Boolean(value != 0),
             ValueType::UnsignedRange(_, _) => Value::UnsignedRange(value),
             ValueType::SignedRange(_, _) => Value::SignedRange(value as i64),
-            ValueType::Enum(values) => Value::Enum(values.get_value_from_raw_value(value)),
+            ValueType::Enum(values) => Value::Enum(*values.get_value_from_raw_value(value)),
             ValueType::Bitmask => Value::Bitmask(value),
             ValueType::Blob => Value::Blob(value),
             ValueType::Object => Value::Object(unsafe { tm(value as u32) }),
@@ -143,12 +152,13 @@ impl ValueType {
 
 /// The value of a property, in a typed format
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
-pub enum Value<'a> {
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Value {
     Unknown(RawValue),
     Boolean(bool),
     UnsignedRange(u64),
     SignedRange(i64),
-    Enum(&'a EnumValue),
+    Enum(EnumValue),
     Bitmask(u64),
     Blob(u64),
     Object(Option<super::RawResourceHandle>),
@@ -160,7 +170,7 @@ pub enum Value<'a> {
     Property(Option<Handle>),
 }
 
-impl<'a> Into<RawValue> for Value<'a> {
+impl Into<RawValue> for Value {
     fn into(self) -> RawValue {
         use std::mem::transmute as tm;
 
@@ -227,6 +237,121 @@ impl EnumValues {
         let (_, enums) = self.values();
         &enums[value as usize]
     }
+
+    /// Returns the `EnumValue` with the given name, if there is one.
+    pub fn get_value_from_name(&self, name: &str) -> Option<&EnumValue> {
+        let (_, enums) = self.values();
+        enums.iter().find(|e| e.name().to_bytes() == name.as_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for EnumValue {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("EnumValue", 2)?;
+        state.serialize_field("value", &self.value())?;
+        state.serialize_field("name", &::util::c_name(&self.0.name))?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for EnumValue {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, Unexpected};
+
+        #[derive(Deserialize)]
+        struct Raw {
+            value: RawValue,
+            name: String,
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
+        let name = ::util::to_c_name(&raw.name)
+            .ok_or_else(|| D::Error::invalid_value(Unexpected::Str(&raw.name), &"an enum name"))?;
+
+        Ok(EnumValue(ffi::drm_mode_property_enum {
+            value: raw.value,
+            name: name,
+        }))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for EnumValues {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (_, enums) = self.values();
+        serializer.collect_seq(enums)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for EnumValues {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let list = Vec::<EnumValue>::deserialize(deserializer)?;
+        if list.len() > 24 {
+            return Err(D::Error::invalid_length(list.len(), &"at most 24 enum values"));
+        }
+
+        let mut values = EnumValues {
+            values: [0; 24],
+            enums: [EnumValue(ffi::drm_mode_property_enum::default()); 24],
+            length: list.len(),
+        };
+
+        for (i, e) in list.into_iter().enumerate() {
+            values.values[i] = e.value();
+            values.enums[i] = e;
+        }
+
+        Ok(values)
+    }
+}
+
+/// Serializes the raw info of a property, without the pointers of the ioctl.
+#[cfg(feature = "serde")]
+mod raw_info {
+    use drm_ffi as ffi;
+    use serde::de::{Error, Unexpected};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "RawInfo")]
+    struct Raw {
+        prop_id: u32,
+        flags: u32,
+        name: String,
+        count_values: u32,
+        count_enum_blobs: u32,
+    }
+
+    pub fn serialize<S: Serializer>(info: &ffi::drm_mode_get_property, serializer: S) -> Result<S::Ok, S::Error> {
+        Raw {
+            prop_id: info.prop_id,
+            flags: info.flags,
+            name: ::util::c_name(&info.name).into_owned(),
+            count_values: info.count_values,
+            count_enum_blobs: info.count_enum_blobs,
+        }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ffi::drm_mode_get_property, D::Error> {
+        let raw = Raw::deserialize(deserializer)?;
+
+        Ok(ffi::drm_mode_get_property {
+            prop_id: raw.prop_id,
+            flags: raw.flags,
+            name: ::util::to_c_name(&raw.name)
+                .ok_or_else(|| D::Error::invalid_value(Unexpected::Str(&raw.name), &"a property name"))?,
+            count_values: raw.count_values,
+            count_enum_blobs: raw.count_enum_blobs,
+            ..Default::default()
+        })
+    }
 }
 
 impl std::fmt::Debug for EnumValues {
@@ -240,3 +365,76 @@ impl std::fmt::Debug for EnumValues {
     }
 }
 
+
+/// A type that can be stored in a property blob.
+///
+/// # Safety
+///
+/// The value is passed to the kernel as its raw bytes, so implementors must
+/// guarantee that the type contains no padding bytes. Structs with padding
+/// have to be copied into a byte buffer field by field instead.
+pub unsafe trait BlobData {}
+
+unsafe impl BlobData for u8 {}
+unsafe impl<T: BlobData> BlobData for [T] {}
+unsafe impl BlobData for control::Mode {}
+unsafe impl BlobData for ffi::drm_mode_modeinfo {}
+unsafe impl BlobData for ffi::drm_mode_rect {}
+unsafe impl BlobData for ffi::drm_color_lut {}
+unsafe impl BlobData for ffi::drm_color_ctm {}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn enum_value(value: RawValue, name: &str) -> EnumValue {
+        EnumValue(ffi::drm_mode_property_enum {
+            value: value,
+            name: ::util::to_c_name(name).unwrap(),
+        })
+    }
+
+    #[test]
+    fn enum_value_round_trips_through_serde() {
+        let value = Value::Enum(enum_value(2, "Full aspect"));
+
+        let json = serde_json::to_string(&value).unwrap();
+        let round_trip: Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(round_trip, value);
+        assert_eq!(Into::<RawValue>::into(round_trip), 2);
+    }
+
+    #[test]
+    fn info_round_trips_through_serde() {
+        let mut values = EnumValues {
+            values: [0; 24],
+            enums: [enum_value(0, ""); 24],
+            length: 2,
+        };
+        values.values[1] = 1;
+        values.enums[0] = enum_value(0, "Off");
+        values.enums[1] = enum_value(1, "On");
+
+        let info = Info {
+            handle: Handle::from(control::RawResourceHandle::new(31).unwrap()),
+            val_type: ValueType::Enum(values),
+            mutable: true,
+            atomic: false,
+            info: ffi::drm_mode_get_property {
+                prop_id: 31,
+                flags: ffi::DRM_MODE_PROP_ENUM,
+                name: ::util::to_c_name("vrr_capable").unwrap(),
+                count_values: 2,
+                count_enum_blobs: 2,
+                ..Default::default()
+            },
+        };
+
+        let json = serde_json::to_string(&info).unwrap();
+        let round_trip: Info = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(round_trip, info);
+    }
+}
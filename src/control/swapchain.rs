@@ -0,0 +1,619 @@
+//! # Swapchain
+//!
+//! A set of buffers that are drawn into by the CPU and shown in turn on a
+//! CRTC.
+//!
+//! A frame is drawn by acquiring a free buffer, drawing into it, and
+//! presenting it. Presenting schedules a page flip, either through the legacy
+//! page flip ioctl or by committing the `FB_ID` of a plane. Once the flip
+//! completed, the presented buffer is in front and the buffer shown before
+//! becomes free again.
+//!
+//! The age of a buffer tells how many frames ago its contents were shown, so
+//! only the damage of the frames since then has to be redrawn. See
+//! `control::damage::DamageTracker`.
+
+use buffer::format::PixelFormat;
+use control::atomic::AtomicModeReq;
+use control::event::Event;
+use control::owned::{OwnedDumbBuffer, OwnedFramebuffer};
+use control::{crtc, framebuffer, plane, property, AtomicCommitFlags, Device, PageFlipFlags};
+use drm_ffi::result::SystemError;
+
+use std::ops::Deref;
+
+/// The state of a buffer of a swapchain.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum BufferState {
+    /// The buffer can be acquired
+    Free,
+    /// The buffer was acquired and is being drawn into
+    Acquired,
+    /// A page flip to the buffer is pending
+    Queued,
+    /// The buffer is shown
+    Front,
+}
+
+/// The way frames are presented.
+#[derive(Debug, Copy, Clone)]
+enum Presenter {
+    Legacy,
+    Atomic { plane: plane::Handle, fb_id: property::Handle },
+}
+
+#[derive(Debug)]
+struct Slot<D>
+where
+    D: Deref,
+    D::Target: Device,
+{
+    framebuffer: OwnedFramebuffer<D>,
+    buffer: OwnedDumbBuffer<D>,
+    state: BufferState,
+    /// The number of the frame the buffer was last presented as, `0` if it
+    /// was never presented.
+    frame: u64,
+}
+
+/// Dumb buffers that are flipped in turn on a CRTC.
+///
+/// The CRTC must already be enabled, as page flips do not set a mode. With
+/// atomic presentation, the plane must already be set up to scan out
+/// buffers of the swapchain's size.
+///
+/// Dropping the swapchain removes the framebuffers of all its buffers,
+/// including the one on screen. The kernel turns off a CRTC or plane whose
+/// framebuffer is removed, so show another framebuffer first to keep the
+/// output on.
+#[derive(Debug)]
+pub struct Swapchain<D>
+where
+    D: Deref + Clone,
+    D::Target: Device,
+{
+    dev: D,
+    crtc: crtc::Handle,
+    presenter: Presenter,
+    slots: Vec<Slot<D>>,
+    frame: u64,
+}
+
+impl<D> Swapchain<D>
+where
+    D: Deref + Clone,
+    D::Target: Device,
+{
+    /// Allocates `count` buffers that are presented through legacy page
+    /// flips.
+    pub fn new(
+        dev: D,
+        crtc: crtc::Handle,
+        size: (u32, u32),
+        format: PixelFormat,
+        count: usize,
+    ) -> Result<Swapchain<D>, SystemError> {
+        Swapchain::with_presenter(dev, crtc, Presenter::Legacy, size, format, count)
+    }
+
+    /// Allocates `count` buffers that are presented by committing the
+    /// `FB_ID` property of a plane.
+    ///
+    /// Requires the atomic client capability.
+    pub fn new_atomic(
+        dev: D,
+        crtc: crtc::Handle,
+        plane: plane::Handle,
+        size: (u32, u32),
+        format: PixelFormat,
+        count: usize,
+    ) -> Result<Swapchain<D>, SystemError> {
+        let fb_id = match dev.find_property(plane, "FB_ID")? {
+            Some((fb_id, _)) => fb_id,
+            None => return Err(SystemError::InvalidArgument),
+        };
+
+        let presenter = Presenter::Atomic {
+            plane: plane,
+            fb_id: fb_id,
+        };
+        Swapchain::with_presenter(dev, crtc, presenter, size, format, count)
+    }
+
+    fn with_presenter(
+        dev: D,
+        crtc: crtc::Handle,
+        presenter: Presenter,
+        size: (u32, u32),
+        format: PixelFormat,
+        count: usize,
+    ) -> Result<Swapchain<D>, SystemError> {
+        if count == 0 {
+            return Err(SystemError::InvalidArgument);
+        }
+
+        let mut slots = Vec::with_capacity(count);
+        for _ in 0..count {
+            let buffer = OwnedDumbBuffer::create(dev.clone(), size, format)?;
+            let framebuffer = OwnedFramebuffer::add_planar(dev.clone(), &buffer, &[0; 4], 0)?;
+
+            slots.push(Slot {
+                framebuffer: framebuffer,
+                buffer: buffer,
+                state: BufferState::Free,
+                frame: 0,
+            });
+        }
+
+        Ok(Swapchain {
+            dev: dev,
+            crtc: crtc,
+            presenter: presenter,
+            slots: slots,
+            frame: 0,
+        })
+    }
+
+    /// Returns the CRTC the buffers are shown on.
+    pub fn crtc(&self) -> crtc::Handle {
+        self.crtc
+    }
+
+    /// Returns the number of buffers.
+    pub fn buffer_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the state of a buffer.
+    pub fn state(&self, index: usize) -> BufferState {
+        self.slots[index].state
+    }
+
+    /// Returns the buffer at an index, e.g. to map it.
+    pub fn buffer(&self, index: usize) -> &OwnedDumbBuffer<D> {
+        &self.slots[index].buffer
+    }
+
+    /// Returns the buffer at an index, e.g. to map it.
+    pub fn buffer_mut(&mut self, index: usize) -> &mut OwnedDumbBuffer<D> {
+        &mut self.slots[index].buffer
+    }
+
+    /// Returns the framebuffer of the buffer at an index.
+    pub fn framebuffer(&self, index: usize) -> framebuffer::Handle {
+        self.slots[index].framebuffer.handle()
+    }
+
+    /// Returns the index of the buffer that is shown.
+    pub fn front(&self) -> Option<usize> {
+        self.find(BufferState::Front)
+    }
+
+    /// Returns whether a page flip is pending.
+    pub fn is_pending(&self) -> bool {
+        self.find(BufferState::Queued).is_some()
+    }
+
+    /// Returns the age of a buffer's contents: `1` if they are the frame
+    /// shown last, `2` for the frame before, and so on. `0` means the
+    /// contents are undefined and everything has to be drawn.
+    pub fn age(&self, index: usize) -> usize {
+        match self.slots[index].frame {
+            0 => 0,
+            frame => (self.frame - frame + 1) as usize,
+        }
+    }
+
+    /// Acquires a free buffer to draw into, returning its index.
+    ///
+    /// Of the free buffers, the one with the most recent contents is picked.
+    /// Returns `None` if every buffer is in use, in which case a pending
+    /// page flip has to complete first.
+    pub fn acquire(&mut self) -> Option<usize> {
+        let index = (0..self.slots.len())
+            .filter(|&i| self.slots[i].state == BufferState::Free)
+            .max_by_key(|&i| self.slots[i].frame)?;
+
+        self.slots[index].state = BufferState::Acquired;
+        Some(index)
+    }
+
+    /// Returns an acquired buffer without presenting it.
+    pub fn release(&mut self, index: usize) {
+        if self.slots[index].state == BufferState::Acquired {
+            self.slots[index].state = BufferState::Free;
+        }
+    }
+
+    /// Schedules a page flip to an acquired buffer.
+    ///
+    /// Only one flip may be pending at a time. Fails with
+    /// `SystemError::InvalidArgument` if the buffer was not acquired or a
+    /// flip is still pending.
+    pub fn present(&mut self, index: usize) -> Result<(), SystemError> {
+        if self.slots[index].state != BufferState::Acquired || self.is_pending() {
+            return Err(SystemError::InvalidArgument);
+        }
+
+        let fb = self.slots[index].framebuffer.handle();
+        match self.presenter {
+            Presenter::Legacy => self.dev.page_flip(self.crtc, fb, &[PageFlipFlags::PageFlipEvent])?,
+            Presenter::Atomic { plane, fb_id } => {
+                let mut req = AtomicModeReq::new();
+                req.add_raw_property(plane.into(), fb_id, Into::<u32>::into(fb).into());
+
+                let flags = [AtomicCommitFlags::PageFlipEvent, AtomicCommitFlags::Nonblock];
+                self.dev.atomic_commit(&flags, req)?;
+            }
+        }
+
+        self.frame += 1;
+        let slot = &mut self.slots[index];
+        slot.state = BufferState::Queued;
+        slot.frame = self.frame;
+        Ok(())
+    }
+
+    /// Handles an event read from the device.
+    ///
+    /// Returns `true` if the event completed the pending flip, which moves
+    /// the presented buffer to the front and frees the buffer shown before.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        let flip = match *event {
+            Event::PageFlip(ref flip) => flip,
+            _ => return false,
+        };
+
+        if flip.crtc().is_some_and(|crtc| crtc != self.crtc) {
+            return false;
+        }
+
+        let queued = match self.find(BufferState::Queued) {
+            Some(queued) => queued,
+            None => return false,
+        };
+
+        if let Some(front) = self.front() {
+            self.slots[front].state = BufferState::Free;
+        }
+        self.slots[queued].state = BufferState::Front;
+        true
+    }
+
+    /// Reads events from the device until the pending flip completed.
+    ///
+    /// Returns the events that were read but not handled by the swapchain.
+    /// Returns immediately if no flip is pending.
+    pub fn wait(&mut self) -> Result<Vec<Event>, SystemError> {
+        let mut unhandled = Vec::new();
+
+        while self.is_pending() {
+            for event in self.dev.receive_events()? {
+                if !self.handle_event(&event) {
+                    unhandled.push(event);
+                }
+            }
+        }
+
+        Ok(unhandled)
+    }
+
+    fn find(&self, state: BufferState) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.state == state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use buffer::{self, Buffer, PlanarBuffer};
+    use control::dumbbuffer::DumbBuffer;
+    use control::event::{PageFlipEvent, VblankEvent};
+    use control::{from_u32, RawResourceHandle};
+    use std::cell::{Cell, RefCell};
+    use std::os::unix::io::{AsRawFd, RawFd};
+    use std::time::Duration;
+
+    /// A device that hands out increasing handles, records flips, commits
+    /// and destroyed objects, and fails flips with the queued errors.
+    #[derive(Debug, Default)]
+    struct Mock {
+        next: Cell<u32>,
+        errors: RefCell<Vec<SystemError>>,
+        flips: RefCell<Vec<framebuffer::Handle>>,
+        commits: RefCell<Vec<Vec<(u32, u64)>>>,
+        removed: RefCell<Vec<framebuffer::Handle>>,
+        destroyed: RefCell<Vec<buffer::Handle>>,
+    }
+
+    impl Mock {
+        fn next(&self) -> u32 {
+            self.next.set(self.next.get() + 1);
+            self.next.get()
+        }
+    }
+
+    impl AsRawFd for Mock {
+        fn as_raw_fd(&self) -> RawFd {
+            -1
+        }
+    }
+
+    impl ::Device for Mock {}
+
+    impl Device for Mock {
+        fn create_dumb_buffer(&self, size: (u32, u32), format: PixelFormat) -> Result<DumbBuffer, SystemError> {
+            Ok(DumbBuffer {
+                size: size,
+                length: (size.0 * size.1 * 4) as usize,
+                format: format,
+                pitch: size.0 * 4,
+                handle: buffer::Handle::from(RawResourceHandle::new(self.next()).unwrap()),
+            })
+        }
+
+        fn add_planar_framebuffer<B>(
+            &self,
+            _buffer: &B,
+            _modifiers: &[u64; 4],
+            _flags: u32,
+        ) -> Result<framebuffer::Handle, SystemError>
+        where
+            B: PlanarBuffer + ?Sized,
+        {
+            Ok(from_u32(self.next()).unwrap())
+        }
+
+        fn destroy_framebuffer(&self, handle: framebuffer::Handle) -> Result<(), SystemError> {
+            self.removed.borrow_mut().push(handle);
+            Ok(())
+        }
+
+        fn destroy_dumb_buffer(&self, buffer: DumbBuffer) -> Result<(), SystemError> {
+            self.destroyed.borrow_mut().push(buffer.handle);
+            Ok(())
+        }
+
+        fn page_flip(
+            &self,
+            _crtc: crtc::Handle,
+            framebuffer: framebuffer::Handle,
+            _flags: &[PageFlipFlags],
+        ) -> Result<(), SystemError> {
+            if !self.errors.borrow().is_empty() {
+                return Err(self.errors.borrow_mut().remove(0));
+            }
+
+            self.flips.borrow_mut().push(framebuffer);
+            Ok(())
+        }
+
+        fn atomic_commit(&self, _flags: &[AtomicCommitFlags], req: AtomicModeReq) -> Result<(), SystemError> {
+            let props = req.props.iter().map(|&p| p.into()).zip(req.values).collect();
+            self.commits.borrow_mut().push(props);
+            Ok(())
+        }
+    }
+
+    const CRTC: u32 = 36;
+
+    fn swapchain(dev: &Mock, count: usize) -> Swapchain<&Mock> {
+        Swapchain::new(dev, from_u32(CRTC).unwrap(), (16, 16), PixelFormat::XRGB8888, count).unwrap()
+    }
+
+    fn flip(crtc: Option<u32>) -> Event {
+        Event::PageFlip(PageFlipEvent {
+            frame: 0,
+            time: Duration::new(0, 0),
+            crtc: crtc.map(|id| from_u32(id).unwrap()),
+        })
+    }
+
+    /// Acquires a buffer, presents it and completes the flip.
+    fn show(chain: &mut Swapchain<&Mock>) -> usize {
+        let index = chain.acquire().unwrap();
+        chain.present(index).unwrap();
+        assert!(chain.handle_event(&flip(Some(CRTC))));
+        index
+    }
+
+    #[test]
+    fn zero_buffers() {
+        let dev = Mock::default();
+        let result = Swapchain::new(&dev, from_u32(CRTC).unwrap(), (16, 16), PixelFormat::XRGB8888, 0);
+
+        match result {
+            Err(SystemError::InvalidArgument) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn present_and_flip() {
+        let dev = Mock::default();
+        let mut chain = swapchain(&dev, 2);
+        assert!(!chain.is_pending());
+        assert_eq!(chain.front(), None);
+
+        let index = chain.acquire().unwrap();
+        assert_eq!(chain.state(index), BufferState::Acquired);
+
+        chain.present(index).unwrap();
+        assert_eq!(chain.state(index), BufferState::Queued);
+        assert!(chain.is_pending());
+        assert_eq!(*dev.flips.borrow(), vec![chain.framebuffer(index)]);
+
+        assert!(chain.handle_event(&flip(Some(CRTC))));
+        assert_eq!(chain.state(index), BufferState::Front);
+        assert_eq!(chain.front(), Some(index));
+        assert!(!chain.is_pending());
+
+        // The next flip frees the buffer shown before.
+        let next = show(&mut chain);
+        assert_ne!(next, index);
+        assert_eq!(chain.front(), Some(next));
+        assert_eq!(chain.state(index), BufferState::Free);
+    }
+
+    #[test]
+    fn acquire_until_exhausted() {
+        let dev = Mock::default();
+        let mut chain = swapchain(&dev, 2);
+
+        let first = chain.acquire().unwrap();
+        let second = chain.acquire().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(chain.acquire(), None);
+
+        chain.release(first);
+        assert_eq!(chain.state(first), BufferState::Free);
+        assert_eq!(chain.acquire(), Some(first));
+
+        // Releasing a buffer that is not acquired does nothing.
+        chain.present(first).unwrap();
+        chain.release(first);
+        assert_eq!(chain.state(first), BufferState::Queued);
+    }
+
+    #[test]
+    fn acquire_prefers_recent_contents() {
+        let dev = Mock::default();
+        let mut chain = swapchain(&dev, 3);
+
+        let a = show(&mut chain);
+        let b = show(&mut chain);
+        assert_eq!(chain.front(), Some(b));
+
+        // `a` holds a frame, the third buffer was never drawn into.
+        assert_eq!(chain.acquire(), Some(a));
+        let unused = chain.acquire().unwrap();
+        assert_eq!(chain.age(unused), 0);
+        assert_eq!(chain.acquire(), None);
+    }
+
+    #[test]
+    fn present_rejects_invalid_states() {
+        let dev = Mock::default();
+        let mut chain = swapchain(&dev, 3);
+
+        // Not acquired.
+        match chain.present(0) {
+            Err(SystemError::InvalidArgument) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        // A flip is still pending.
+        let first = chain.acquire().unwrap();
+        let second = chain.acquire().unwrap();
+        chain.present(first).unwrap();
+        match chain.present(second) {
+            Err(SystemError::InvalidArgument) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(chain.state(second), BufferState::Acquired);
+        assert_eq!(dev.flips.borrow().len(), 1);
+    }
+
+    #[test]
+    fn failed_flip_keeps_buffer_acquired() {
+        let dev = Mock::default();
+        let mut chain = swapchain(&dev, 2);
+        dev.errors.borrow_mut().push(SystemError::PermissionDenied);
+
+        let index = chain.acquire().unwrap();
+        match chain.present(index) {
+            Err(SystemError::PermissionDenied) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(chain.state(index), BufferState::Acquired);
+        assert_eq!(chain.age(index), 0);
+        assert!(!chain.is_pending());
+
+        chain.present(index).unwrap();
+        assert!(chain.handle_event(&flip(Some(CRTC))));
+        assert_eq!(chain.age(index), 1);
+    }
+
+    #[test]
+    fn handle_event_filters() {
+        let dev = Mock::default();
+        let mut chain = swapchain(&dev, 2);
+
+        // Nothing is pending.
+        assert!(!chain.handle_event(&flip(Some(CRTC))));
+
+        let index = chain.acquire().unwrap();
+        chain.present(index).unwrap();
+
+        let vblank = Event::Vblank(VblankEvent {
+            frame: 0,
+            time: Duration::new(0, 0),
+            crtc: Some(from_u32(CRTC).unwrap()),
+            user_data: 0,
+        });
+        assert!(!chain.handle_event(&vblank));
+        assert!(!chain.handle_event(&Event::Unknown(vec![0; 8])));
+        assert!(!chain.handle_event(&flip(Some(CRTC + 1))));
+        assert_eq!(chain.state(index), BufferState::Queued);
+
+        // Drivers without the CRTC in the event are trusted.
+        assert!(chain.handle_event(&flip(None)));
+        assert_eq!(chain.state(index), BufferState::Front);
+    }
+
+    #[test]
+    fn ages() {
+        let dev = Mock::default();
+        let mut chain = swapchain(&dev, 3);
+        assert!((0..3).all(|i| chain.age(i) == 0));
+
+        let a = show(&mut chain);
+        assert_eq!(chain.age(a), 1);
+
+        let b = show(&mut chain);
+        assert_eq!((chain.age(a), chain.age(b)), (2, 1));
+
+        // The free buffer with contents is reused before the undrawn one.
+        assert_eq!(show(&mut chain), a);
+        assert_eq!((chain.age(a), chain.age(b)), (1, 2));
+
+        // An acquired buffer keeps its age until it is presented.
+        assert_eq!(chain.acquire(), Some(b));
+        assert_eq!(chain.age(b), 2);
+        chain.present(b).unwrap();
+        assert_eq!((chain.age(a), chain.age(b)), (2, 1));
+    }
+
+    #[test]
+    fn atomic_commits_fb_id() {
+        let dev = Mock::default();
+        let presenter = Presenter::Atomic {
+            plane: from_u32(31).unwrap(),
+            fb_id: from_u32(17).unwrap(),
+        };
+        let mut chain =
+            Swapchain::with_presenter(&dev, from_u32(CRTC).unwrap(), presenter, (16, 16), PixelFormat::XRGB8888, 2)
+                .unwrap();
+
+        let index = chain.acquire().unwrap();
+        chain.present(index).unwrap();
+
+        let fb: u32 = chain.framebuffer(index).into();
+        assert_eq!(*dev.commits.borrow(), vec![vec![(17, fb as u64)]]);
+        assert!(dev.flips.borrow().is_empty());
+    }
+
+    #[test]
+    fn drop_destroys_everything() {
+        let dev = Mock::default();
+        let mut chain = swapchain(&dev, 2);
+        show(&mut chain);
+
+        let framebuffers = vec![chain.framebuffer(0), chain.framebuffer(1)];
+        let buffers = vec![chain.buffer(0).handle(), chain.buffer(1).handle()];
+        drop(chain);
+
+        assert_eq!(*dev.removed.borrow(), framebuffers);
+        assert_eq!(*dev.destroyed.borrow(), buffers);
+    }
+}
@@ -0,0 +1,482 @@
+//! # Topology
+//!
+//! Snapshots of the modesetting resources of a device.
+//!
+//! A `Topology` captures the state of every connector, encoder, CRTC and
+//! plane at one point in time. Comparing the snapshots taken before and after
+//! a hotplug event tells which outputs actually changed.
+
+use control::{self, connector, crtc, encoder, plane, Device, PlaneResourceHandles, ResourceHandles};
+use drm_ffi::result::SystemError;
+use nix::errno::Errno;
+
+/// The state of all modesetting resources of a device.
+///
+/// All fields are public, so snapshots can also be assembled by hand.
+#[derive(Debug, Clone, Default)]
+pub struct Topology {
+    /// The resource handles the snapshot was gathered from
+    pub resources: Option<ResourceHandles>,
+    /// The plane handles the snapshot was gathered from
+    pub plane_resources: Option<PlaneResourceHandles>,
+    /// Information about every connector
+    pub connectors: Vec<connector::Info>,
+    /// Information about every encoder
+    pub encoders: Vec<encoder::Info>,
+    /// Information about every CRTC
+    pub crtcs: Vec<crtc::Info>,
+    /// Information about every plane
+    pub planes: Vec<plane::Info>,
+    /// The EDID of every connector that has one
+    pub edids: Vec<(connector::Handle, Vec<u8>)>,
+}
+
+/// A difference between two `Topology` snapshots.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Change {
+    /// A connector appeared, e.g. behind a DisplayPort MST hub
+    ConnectorAdded(connector::Handle),
+    /// A connector disappeared
+    ConnectorRemoved(connector::Handle),
+    /// The connection state of a connector changed
+    ConnectionChanged {
+        /// The connector that changed
+        connector: connector::Handle,
+        /// The previous state
+        old: connector::State,
+        /// The current state
+        new: connector::State,
+    },
+    /// The list of modes of a connector changed
+    ModesChanged(connector::Handle),
+    /// The EDID of a connector changed, e.g. a different display was plugged
+    /// in
+    EdidChanged(connector::Handle),
+    /// The CRTC an encoder is attached to changed
+    EncoderChanged(encoder::Handle),
+    /// The mode, framebuffer or position of a CRTC changed
+    CrtcChanged(crtc::Handle),
+    /// The CRTC or framebuffer of a plane changed
+    PlaneChanged(plane::Handle),
+}
+
+impl Topology {
+    /// Creates an empty snapshot.
+    pub fn new() -> Topology {
+        Topology::default()
+    }
+
+    /// Gathers the state of every modesetting resource of a device.
+    ///
+    /// Planes are only gathered if the device exposes them. With
+    /// `force_probe` set, every connector is probed, which is needed to pick
+    /// up new mode lists after a hotplug. See `Device::get_connector`.
+    pub fn gather<D: Device>(dev: &D, force_probe: bool) -> Result<Topology, SystemError> {
+        let resources = dev.resource_handles()?;
+        let plane_resources = dev.plane_handles().ok();
+
+        let mut topology = Topology {
+            resources: Some(resources),
+            plane_resources: plane_resources,
+            ..Topology::default()
+        };
+
+        for &handle in resources.connectors() {
+            let info = match dev.get_connector(handle, force_probe) {
+                Ok(info) => info,
+                Err(ref e) if is_gone(e) => continue,
+                Err(e) => return Err(e),
+            };
+
+            match control::connector_blob(dev, &info, "EDID") {
+                Ok(Some(edid)) => topology.edids.push((handle, edid)),
+                Ok(None) => (),
+                Err(ref e) if is_gone(e) => continue,
+                Err(e) => return Err(e),
+            }
+
+            topology.connectors.push(info);
+        }
+
+        for &handle in resources.encoders() {
+            topology.encoders.push(dev.get_encoder(handle)?);
+        }
+
+        for &handle in resources.crtcs() {
+            topology.crtcs.push(dev.get_crtc(handle)?);
+        }
+
+        if let Some(planes) = plane_resources {
+            for &handle in planes.planes() {
+                topology.planes.push(dev.get_plane(handle)?);
+            }
+        }
+
+        Ok(topology)
+    }
+
+    /// Returns information about a connector in this snapshot.
+    pub fn connector(&self, handle: connector::Handle) -> Option<&connector::Info> {
+        self.connectors.iter().find(|c| c.handle() == handle)
+    }
+
+    /// Returns the EDID of a connector in this snapshot.
+    pub fn edid(&self, handle: connector::Handle) -> Option<&[u8]> {
+        self.edids
+            .iter()
+            .find(|(h, _)| *h == handle)
+            .map(|(_, edid)| edid.as_slice())
+    }
+
+    /// Returns the changes that lead from this snapshot to a newer one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use drm::control::connector::{self, Interface, State};
+    /// use drm::control::topology::{Change, Topology};
+    /// # let handle = |id| drm::control::from_u32::<connector::Handle>(id).unwrap();
+    ///
+    /// let mut old = Topology::new();
+    /// old.connectors.push(connector::Info::new(handle(40), Interface::DisplayPort, 1, State::Disconnected, vec![]));
+    /// old.connectors.push(connector::Info::new(handle(41), Interface::DisplayPort, 2, State::Connected, vec![]));
+    ///
+    /// let mut new = Topology::new();
+    /// new.connectors.push(connector::Info::new(handle(40), Interface::DisplayPort, 1, State::Connected, vec![]));
+    /// new.connectors.push(connector::Info::new(handle(57), Interface::DisplayPort, 3, State::Connected, vec![]));
+    /// new.edids.push((handle(40), vec![0x00, 0xff]));
+    ///
+    /// assert_eq!(old.diff(&new), vec![
+    ///     Change::ConnectorRemoved(handle(41)),
+    ///     Change::ConnectorAdded(handle(57)),
+    ///     Change::ConnectionChanged {
+    ///         connector: handle(40),
+    ///         old: State::Disconnected,
+    ///         new: State::Connected,
+    ///     },
+    ///     Change::EdidChanged(handle(40)),
+    /// ]);
+    /// ```
+    pub fn diff(&self, new: &Topology) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        for old in &self.connectors {
+            if new.connector(old.handle()).is_none() {
+                changes.push(Change::ConnectorRemoved(old.handle()));
+            }
+        }
+
+        for conn in &new.connectors {
+            if self.connector(conn.handle()).is_none() {
+                changes.push(Change::ConnectorAdded(conn.handle()));
+            }
+        }
+
+        for conn in &new.connectors {
+            let handle = conn.handle();
+            let old = match self.connector(handle) {
+                Some(old) => old,
+                None => continue,
+            };
+
+            if old.state() != conn.state() {
+                changes.push(Change::ConnectionChanged {
+                    connector: handle,
+                    old: old.state(),
+                    new: conn.state(),
+                });
+            }
+
+            if old.modes() != conn.modes() {
+                changes.push(Change::ModesChanged(handle));
+            }
+
+            if self.edid(handle) != new.edid(handle) {
+                changes.push(Change::EdidChanged(handle));
+            }
+        }
+
+        for enc in &new.encoders {
+            match self.encoders.iter().find(|e| e.handle() == enc.handle()) {
+                Some(old) if old.crtc() != enc.crtc() => {
+                    changes.push(Change::EncoderChanged(enc.handle()))
+                }
+                _ => (),
+            }
+        }
+
+        for crtc in &new.crtcs {
+            match self.crtcs.iter().find(|c| c.handle() == crtc.handle()) {
+                Some(old) if old != crtc => changes.push(Change::CrtcChanged(crtc.handle())),
+                _ => (),
+            }
+        }
+
+        for plane in &new.planes {
+            match self.planes.iter().find(|p| p.handle() == plane.handle()) {
+                Some(old) if old.crtc() != plane.crtc() || old.framebuffer() != plane.framebuffer() => {
+                    changes.push(Change::PlaneChanged(plane.handle()))
+                }
+                _ => (),
+            }
+        }
+
+        changes
+    }
+}
+
+/// Whether an error means that the object was destroyed in the meantime.
+fn is_gone(err: &SystemError) -> bool {
+    matches!(*err, SystemError::Unknown { errno: Errno::ENOENT })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use control::connector::{Interface, State};
+    use control::{encoder::Kind, from_u32, framebuffer, property, Mode, ResourceHandle};
+    use drm_ffi as ffi;
+    use std::os::unix::io::{AsRawFd, RawFd};
+
+    fn handle<T: ResourceHandle>(id: u32) -> T {
+        from_u32(id).unwrap()
+    }
+
+    fn mode(hdisplay: u16, vdisplay: u16) -> Mode {
+        Mode::from(ffi::drm_mode_modeinfo {
+            hdisplay: hdisplay,
+            vdisplay: vdisplay,
+            ..Default::default()
+        })
+    }
+
+    fn dp(id: u32, interface_id: u32, state: State, modes: Vec<Mode>) -> connector::Info {
+        connector::Info::new(handle(id), Interface::DisplayPort, interface_id, state, modes)
+    }
+
+    /// A device with three connectors, of which 57 has been unplugged and
+    /// 58 loses its EDID blob while being read.
+    struct Mock {
+        error: Errno,
+    }
+
+    impl AsRawFd for Mock {
+        fn as_raw_fd(&self) -> RawFd {
+            -1
+        }
+    }
+
+    impl ::Device for Mock {}
+
+    impl Device for Mock {
+        fn resource_handles(&self) -> Result<ResourceHandles, SystemError> {
+            let mut connectors = [None; 32];
+            connectors[0] = Some(handle(40));
+            connectors[1] = Some(handle(57));
+            connectors[2] = Some(handle(58));
+
+            Ok(ResourceHandles {
+                fbs: [None; 32],
+                fb_len: 0,
+                crtcs: [None; 32],
+                crtc_len: 0,
+                connectors: connectors,
+                conn_len: 3,
+                encoders: [None; 32],
+                enc_len: 0,
+                width: (0, 4096),
+                height: (0, 4096),
+            })
+        }
+
+        fn plane_handles(&self) -> Result<PlaneResourceHandles, SystemError> {
+            Err(SystemError::InvalidArgument)
+        }
+
+        fn get_connector(&self, handle: connector::Handle, _force_probe: bool) -> Result<connector::Info, SystemError> {
+            let id: u32 = handle.into();
+            let mut info = dp(id, id, State::Connected, vec![]);
+            match id {
+                57 => return Err(SystemError::Unknown { errno: self.error }),
+                58 => info.props.push((from_u32(5).unwrap(), 7)),
+                _ => (),
+            }
+            Ok(info)
+        }
+
+        fn get_property(&self, _handle: property::Handle) -> Result<property::Info, SystemError> {
+            Err(SystemError::Unknown { errno: self.error })
+        }
+    }
+
+    #[test]
+    fn gather_skips_vanished_connectors() {
+        let topology = Topology::gather(&Mock { error: Errno::ENOENT }, false).unwrap();
+
+        let connectors: Vec<connector::Handle> = topology.connectors.iter().map(|c| c.handle()).collect();
+        assert_eq!(connectors, vec![handle(40)]);
+        assert!(topology.edids.is_empty());
+        assert!(topology.plane_resources.is_none());
+    }
+
+    #[test]
+    fn gather_fails_on_other_errors() {
+        match Topology::gather(&Mock { error: Errno::EIO }, false) {
+            Err(SystemError::Unknown { errno: Errno::EIO }) => (),
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_changes() {
+        let mut topology = Topology::new();
+        topology.connectors.push(dp(40, 1, State::Connected, vec![mode(1920, 1080)]));
+        topology.edids.push((handle(40), vec![0x00, 0xff]));
+        topology.encoders.push(encoder::Info::new(handle(38), Kind::TMDS, Some(handle(36)), 0b1, 0));
+        topology.crtcs.push(crtc::Info::new(handle(36), (0, 0), Some(mode(1920, 1080)), Some(handle(90)), 256));
+        topology.planes.push(plane::Info::new(handle(31), Some(handle(36)), Some(handle(90)), 0b1, &[]));
+
+        assert_eq!(topology.diff(&topology.clone()), vec![]);
+    }
+
+    #[test]
+    fn mst_connectors_added_and_removed() {
+        let mut old = Topology::new();
+        old.connectors.push(dp(40, 1, State::Connected, vec![]));
+        old.connectors.push(dp(57, 2, State::Connected, vec![]));
+        old.connectors.push(dp(58, 3, State::Connected, vec![]));
+
+        let mut new = Topology::new();
+        new.connectors.push(dp(40, 1, State::Connected, vec![]));
+        new.connectors.push(dp(58, 3, State::Connected, vec![]));
+        new.connectors.push(dp(61, 4, State::Connected, vec![]));
+        new.connectors.push(dp(62, 5, State::Connected, vec![]));
+
+        assert_eq!(old.diff(&new), vec![
+            Change::ConnectorRemoved(handle(57)),
+            Change::ConnectorAdded(handle(61)),
+            Change::ConnectorAdded(handle(62)),
+        ]);
+        assert_eq!(new.diff(&old), vec![
+            Change::ConnectorRemoved(handle(61)),
+            Change::ConnectorRemoved(handle(62)),
+            Change::ConnectorAdded(handle(57)),
+        ]);
+    }
+
+    #[test]
+    fn connection_changes() {
+        let mut old = Topology::new();
+        old.connectors.push(dp(40, 1, State::Connected, vec![]));
+        old.connectors.push(dp(41, 2, State::Disconnected, vec![]));
+        old.connectors.push(dp(42, 3, State::Unknown, vec![]));
+
+        let mut new = Topology::new();
+        new.connectors.push(dp(40, 1, State::Disconnected, vec![]));
+        new.connectors.push(dp(41, 2, State::Connected, vec![]));
+        new.connectors.push(dp(42, 3, State::Unknown, vec![]));
+
+        assert_eq!(old.diff(&new), vec![
+            Change::ConnectionChanged {
+                connector: handle(40),
+                old: State::Connected,
+                new: State::Disconnected,
+            },
+            Change::ConnectionChanged {
+                connector: handle(41),
+                old: State::Disconnected,
+                new: State::Connected,
+            },
+        ]);
+    }
+
+    #[test]
+    fn mode_list_changes() {
+        let mut old = Topology::new();
+        old.connectors.push(dp(40, 1, State::Connected, vec![mode(1920, 1080), mode(1280, 720)]));
+        old.connectors.push(dp(41, 2, State::Connected, vec![mode(1920, 1080)]));
+        old.connectors.push(dp(42, 3, State::Connected, vec![mode(1920, 1080)]));
+
+        let mut new = Topology::new();
+        // Reordered, shrunk and unchanged mode lists.
+        new.connectors.push(dp(40, 1, State::Connected, vec![mode(1280, 720), mode(1920, 1080)]));
+        new.connectors.push(dp(41, 2, State::Connected, vec![]));
+        new.connectors.push(dp(42, 3, State::Connected, vec![mode(1920, 1080)]));
+
+        assert_eq!(old.diff(&new), vec![
+            Change::ModesChanged(handle(40)),
+            Change::ModesChanged(handle(41)),
+        ]);
+    }
+
+    #[test]
+    fn edid_changes() {
+        let mut old = Topology::new();
+        old.connectors.push(dp(40, 1, State::Connected, vec![]));
+        old.connectors.push(dp(41, 2, State::Connected, vec![]));
+        old.connectors.push(dp(42, 3, State::Connected, vec![]));
+        old.connectors.push(dp(43, 4, State::Connected, vec![]));
+        old.edids.push((handle(40), vec![0x00, 0xff, 0x01]));
+        old.edids.push((handle(41), vec![0x00, 0xff, 0x01]));
+        old.edids.push((handle(43), vec![0x00, 0xff, 0x01]));
+
+        let mut new = old.clone();
+        new.edids.clear();
+        // A different display, a display that lost its EDID, one that gained
+        // an EDID and an unchanged one.
+        new.edids.push((handle(40), vec![0x00, 0xff, 0x02]));
+        new.edids.push((handle(42), vec![0x00, 0xff, 0x01]));
+        new.edids.push((handle(43), vec![0x00, 0xff, 0x01]));
+
+        assert_eq!(old.diff(&new), vec![
+            Change::EdidChanged(handle(40)),
+            Change::EdidChanged(handle(41)),
+            Change::EdidChanged(handle(42)),
+        ]);
+    }
+
+    #[test]
+    fn removed_connectors_report_no_other_changes() {
+        let mut old = Topology::new();
+        old.connectors.push(dp(57, 2, State::Connected, vec![mode(1920, 1080)]));
+        old.edids.push((handle(57), vec![0x00, 0xff]));
+
+        assert_eq!(old.diff(&Topology::new()), vec![Change::ConnectorRemoved(handle(57))]);
+    }
+
+    #[test]
+    fn encoder_crtc_and_plane_changes() {
+        let fb: framebuffer::Handle = handle(90);
+        let other_fb: framebuffer::Handle = handle(91);
+
+        let mut old = Topology::new();
+        old.encoders.push(encoder::Info::new(handle(38), Kind::TMDS, Some(handle(36)), 0b11, 0));
+        old.encoders.push(encoder::Info::new(handle(39), Kind::DPMST, None, 0b11, 0));
+        old.crtcs.push(crtc::Info::new(handle(36), (0, 0), Some(mode(1920, 1080)), Some(fb), 256));
+        old.crtcs.push(crtc::Info::new(handle(37), (0, 0), None, None, 256));
+        old.planes.push(plane::Info::new(handle(31), Some(handle(36)), Some(fb), 0b01, &[]));
+        old.planes.push(plane::Info::new(handle(32), None, None, 0b10, &[]));
+
+        let mut new = Topology::new();
+        new.encoders.push(encoder::Info::new(handle(38), Kind::TMDS, None, 0b11, 0));
+        new.encoders.push(encoder::Info::new(handle(39), Kind::DPMST, None, 0b11, 0));
+        new.crtcs.push(crtc::Info::new(handle(36), (0, 0), Some(mode(1920, 1080)), Some(other_fb), 256));
+        new.crtcs.push(crtc::Info::new(handle(37), (0, 0), None, None, 256));
+        new.planes.push(plane::Info::new(handle(31), Some(handle(36)), Some(other_fb), 0b01, &[]));
+        new.planes.push(plane::Info::new(handle(32), None, None, 0b10, &[]));
+
+        assert_eq!(old.diff(&new), vec![
+            Change::EncoderChanged(handle(38)),
+            Change::CrtcChanged(handle(36)),
+            Change::PlaneChanged(handle(31)),
+        ]);
+    }
+
+    #[test]
+    fn plane_keeps_eight_formats() {
+        let formats: Vec<u32> = (1..=10).collect();
+        let plane = plane::Info::new(handle(31), None, None, 0b1, &formats);
+
+        assert_eq!(plane.formats(), &formats[..8]);
+    }
+}
@@ -25,6 +25,12 @@
 //! implemented. See the trait's [example](Device.t.html#example) section for
 //! details on how to implement it.
 //!
+//! ## Features
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize` for the
+//! handles, resource information, modes and property values, e.g. to dump the
+//! state of a device as JSON.
+//!
 
 #![warn(missing_docs)]
 #![feature(nll)]
@@ -34,6 +40,13 @@ extern crate drm_ffi;
 
 extern crate nix;
 
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
 pub(crate) mod util;
 
 pub mod control;
@@ -181,7 +194,7 @@ pub trait Device: AsRawFd {
         let date_len;
         let desc_len;
 
-        let _version = {
+        let version = {
             let mut name_slice = &mut name[..];
             let mut date_slice = &mut date[..];
             let mut desc_slice = &mut desc[..];
@@ -205,6 +218,7 @@ pub trait Device: AsRawFd {
         let desc = SmallOsString::from_i8_buffer(desc, desc_len);
 
         let driver = Driver {
+            version: (version.version_major, version.version_minor, version.version_patchlevel),
             name: name,
             date: date,
             desc: desc,
@@ -240,13 +254,20 @@ impl AsRef<OsStr> for BusID {
 
 /// Driver version of a device.
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Driver {
+    version: (i32, i32, i32),
     name: SmallOsString,
     date: SmallOsString,
     desc: SmallOsString,
 }
 
 impl Driver {
+    /// Major, minor and patch level version of driver
+    pub fn version(&self) -> (i32, i32, i32) {
+        self.version
+    }
+
     /// Name of driver
     pub fn name(&self) -> &OsStr {
         self.name.as_ref()
@@ -266,6 +287,7 @@ impl Driver {
 /// Used to check which capabilities your graphics driver has.
 #[repr(u64)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DriverCapability {
     /// DumbBuffer support for scanout
     DumbBuffer = drm_ffi::DRM_CAP_DUMB_BUFFER as u64,
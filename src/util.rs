@@ -5,7 +5,9 @@
 
 pub use std::ffi::OsStr;
 
+use std::borrow::Cow;
 use std::fmt;
+use std::os::raw::c_char;
 
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
 pub struct SmallOsString {
@@ -79,3 +81,48 @@ impl fmt::Display for SmallOsString {
         Ok(())
     }
 }
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for SmallOsString {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for SmallOsString {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let s = String::deserialize(deserializer)?;
+        if s.len() > 32 {
+            return Err(D::Error::invalid_length(s.len(), &"at most 32 bytes"));
+        }
+
+        let mut data = [0u8; 32];
+        data[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(Self::from_u8_buffer(data, s.len()))
+    }
+}
+
+/// Returns the string in a NUL-terminated name filled in by the kernel.
+pub fn c_name(name: &[c_char]) -> Cow<'_, str> {
+    let bytes: &[u8] = unsafe { std::slice::from_raw_parts(name.as_ptr() as *const u8, name.len()) };
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len])
+}
+
+/// Copies a string into a NUL-padded name, as the kernel fills them in.
+///
+/// Returns `None` if the string contains a NUL byte or does not fit.
+pub fn to_c_name(s: &str) -> Option<[c_char; 32]> {
+    if s.len() >= 32 || s.bytes().any(|b| b == 0) {
+        return None;
+    }
+
+    let mut name = [0 as c_char; 32];
+    for (c, &b) in name.iter_mut().zip(s.as_bytes()) {
+        *c = b as c_char;
+    }
+    Some(name)
+}